@@ -0,0 +1,522 @@
+//! Antenna phase center models (ANTEX)
+use std::str::FromStr;
+
+use gnss::prelude::COSPAR;
+
+use crate::{
+    parsing::parse_values,
+    prelude::{Carrier, Constellation, Epoch, Error, SV, TimeScale},
+};
+
+/// Phase center model of an [Antenna], on a single frequency
+#[derive(Debug, Clone, PartialEq)]
+pub struct AntennaFrequency {
+    /// [Constellation] of this frequency
+    pub constellation: Constellation,
+
+    /// [Carrier] frequency
+    pub carrier: Carrier,
+
+    /// Phase center offset (in meters): (north, east, up) for receiver antennas,
+    /// (x, y, z) in the body frame for satellite antennas.
+    pub pco_m: (f64, f64, f64),
+
+    /// Non azimuth dependent phase center variations (in meters),
+    /// for each zenith (or nadir) angle of the grid.
+    pub pcv_noazi_m: Vec<f64>,
+
+    /// Azimuth dependent phase center variations (in meters), one row for each
+    /// azimuth angle (from 0 to 360°), for each zenith angle of the grid.
+    pub pcv_m: Vec<Vec<f64>>,
+}
+
+/// Receiver or satellite [AntennaKind]
+#[derive(Debug, Clone)]
+pub enum AntennaKind {
+    /// Receiver antenna
+    Receiver {
+        /// Antenna and radome type, as named by the IGS
+        model: String,
+
+        /// Possible serial number, for individually calibrated antennas
+        serial: String,
+    },
+
+    /// Satellite antenna
+    Satellite {
+        /// [SV] (PRN) this antenna is allocated to, during its validity period
+        sv: SV,
+
+        /// Satellite vehicle number
+        svn: String,
+
+        /// [COSPAR] identification number
+        cospar: Option<COSPAR>,
+
+        /// Block (or type) of satellite
+        block: String,
+    },
+}
+
+/// [Antenna] phase center model
+#[derive(Debug, Clone)]
+pub struct Antenna {
+    /// [AntennaKind]
+    pub kind: AntennaKind,
+
+    /// Zenith (nadir for satellites) angle grid: (first, last, step) in degrees
+    pub zenith_grid_deg: (f64, f64, f64),
+
+    /// Azimuth step of the grid, in degrees. Null when only
+    /// non azimuth dependent variations are described.
+    pub azimuth_step_deg: f64,
+
+    /// Start of the validity period
+    pub valid_from: Option<Epoch>,
+
+    /// End of the validity period
+    pub valid_until: Option<Epoch>,
+
+    /// [AntennaFrequency] models
+    pub frequencies: Vec<AntennaFrequency>,
+}
+
+impl Antenna {
+    fn new(kind: AntennaKind) -> Self {
+        Self {
+            kind,
+            zenith_grid_deg: (0.0, 90.0, 5.0),
+            azimuth_step_deg: 0.0,
+            valid_from: None,
+            valid_until: None,
+            frequencies: Vec::with_capacity(4),
+        }
+    }
+
+    /// Returns true if this [Antenna] is valid at this [Epoch]
+    pub fn is_valid(&self, epoch: Epoch) -> bool {
+        self.valid_from.is_none_or(|t| epoch >= t) && self.valid_until.is_none_or(|t| epoch <= t)
+    }
+
+    /// Returns the [AntennaFrequency] model of this signal. When it is not described,
+    /// the model of the closest frequency is used (IGS convention).
+    pub fn frequency(
+        &self,
+        constellation: Constellation,
+        carrier: Carrier,
+    ) -> Option<&AntennaFrequency> {
+        self.frequencies
+            .iter()
+            .find(|freq| {
+                freq.constellation == constellation
+                    && freq.carrier.same_signal(&carrier, constellation)
+            })
+            .or_else(|| {
+                self.frequencies.iter().min_by(|a, b| {
+                    let df_a = (a.carrier.frequency_hz() - carrier.frequency_hz()).abs();
+                    let df_b = (b.carrier.frequency_hz() - carrier.frequency_hz()).abs();
+                    df_a.total_cmp(&df_b)
+                })
+            })
+    }
+
+    /// Interpolates the phase center variation (in meters) of this [AntennaFrequency].
+    ///
+    /// ## Input
+    /// - freq: [AntennaFrequency]
+    /// - zenith_deg: zenith angle (nadir angle for satellites), in degrees
+    /// - azimuth_deg: azimuth angle, in degrees
+    pub fn pcv_m(&self, freq: &AntennaFrequency, zenith_deg: f64, azimuth_deg: f64) -> f64 {
+        let (zen1, zen2, dzen) = self.zenith_grid_deg;
+
+        if dzen <= 0.0 {
+            return 0.0;
+        }
+
+        let interp = |values: &[f64]| {
+            if values.is_empty() {
+                return 0.0;
+            }
+
+            let x = ((zenith_deg.clamp(zen1, zen2) - zen1) / dzen).max(0.0);
+            let i = (x.floor() as usize).min(values.len() - 1);
+            let j = (i + 1).min(values.len() - 1);
+            let a = x - i as f64;
+
+            values[i] * (1.0 - a) + values[j] * a
+        };
+
+        if self.azimuth_step_deg > 0.0 && freq.pcv_m.len() > 1 {
+            let x = azimuth_deg.rem_euclid(360.0) / self.azimuth_step_deg;
+            let i = (x.floor() as usize).min(freq.pcv_m.len() - 1);
+            let j = (i + 1).min(freq.pcv_m.len() - 1);
+            let a = x - i as f64;
+
+            interp(&freq.pcv_m[i]) * (1.0 - a) + interp(&freq.pcv_m[j]) * a
+        } else {
+            interp(&freq.pcv_noazi_m)
+        }
+    }
+}
+
+/// Antenna phase center models, parsed from ANTEX (.atx) content.
+#[derive(Debug, Clone, Default)]
+pub struct Antex {
+    /// [Antenna] models
+    pub antennas: Vec<Antenna>,
+}
+
+impl Antex {
+    /// Returns the receiver [Antenna] of this model (antenna and radome types,
+    /// for example "TRM59800.00     NONE"). When the radome is omitted, "NONE" is assumed.
+    pub fn receiver_antenna(&self, model: &str) -> Option<&Antenna> {
+        let mut model = model.split_ascii_whitespace().collect::<Vec<_>>();
+
+        if model.len() == 1 {
+            model.push("NONE");
+        }
+
+        self.antennas.iter().find(|antenna| match &antenna.kind {
+            AntennaKind::Receiver { model: name, .. } => {
+                name.split_ascii_whitespace().eq(model.iter().copied())
+            },
+            _ => false,
+        })
+    }
+
+    /// Returns the [Antenna] allocated to this [SV] (PRN) at this [Epoch]:
+    /// the validity periods follow the PRN reassignments between satellites.
+    pub fn satellite_antenna(&self, sv: SV, epoch: Epoch) -> Option<&Antenna> {
+        self.antennas.iter().find(|antenna| match &antenna.kind {
+            AntennaKind::Satellite { sv: antenna_sv, .. } => {
+                *antenna_sv == sv && antenna.is_valid(epoch)
+            },
+            _ => false,
+        })
+    }
+}
+
+/// Returns the [Constellation] of this ANTEX system code
+fn antex_constellation(code: char) -> Option<Constellation> {
+    match code {
+        'G' => Some(Constellation::GPS),
+        'R' => Some(Constellation::Glonass),
+        'E' => Some(Constellation::Galileo),
+        'C' => Some(Constellation::BeiDou),
+        'J' => Some(Constellation::QZSS),
+        'S' => Some(Constellation::SBAS),
+        'I' => Some(Constellation::IRNSS),
+        _ => None,
+    }
+}
+
+/// Returns the ([Constellation], [Carrier]) of this ANTEX frequency code (for example "G01").
+fn antex_frequency(code: &str) -> Option<(Constellation, Carrier)> {
+    let constellation = antex_constellation(code.chars().next()?)?;
+    let band = code.get(1..)?.parse::<u8>().ok()?;
+
+    let carrier = match (constellation, band) {
+        (Constellation::Glonass, 1) => Carrier::G1(0),
+        (Constellation::Glonass, 2) => Carrier::G2(0),
+        (Constellation::Glonass, 3) => Carrier::G3,
+        (Constellation::Glonass, 4) => Carrier::G1a,
+        (Constellation::Glonass, 6) => Carrier::G2a,
+        (Constellation::Galileo, 1) => Carrier::L1,
+        (Constellation::Galileo, 5) => Carrier::E5a,
+        (Constellation::Galileo, 6) => Carrier::E6,
+        (Constellation::Galileo, 7) => Carrier::E5b,
+        (Constellation::Galileo, 8) => Carrier::E5a5b,
+        (Constellation::BeiDou, 1) => Carrier::B1C,
+        (Constellation::BeiDou, 2) => Carrier::B1,
+        (Constellation::BeiDou, 5) => Carrier::B2a,
+        (Constellation::BeiDou, 6) => Carrier::B3,
+        (Constellation::BeiDou, 7) => Carrier::B2b,
+        (Constellation::BeiDou, 8) => Carrier::E5a5b,
+        (Constellation::IRNSS, 9) => Carrier::S,
+        (Constellation::QZSS, 6) => Carrier::E6,
+        (_, 1) => Carrier::L1,
+        (_, 2) => Carrier::L2,
+        (_, 5) => Carrier::L5,
+        _ => return None,
+    };
+
+    Some((constellation, carrier))
+}
+
+/// Parses an ANTEX [Epoch] (expressed in GPST)
+fn parse_epoch(content: &str) -> Result<Epoch, Error> {
+    let values = parse_values(content, Error::AntexParsing)?;
+
+    if values.len() < 6 {
+        return Err(Error::AntexParsing);
+    }
+
+    let nanos = (values[5].fract() * 1.0E9).round() as u32;
+
+    Ok(Epoch::from_gregorian(
+        values[0] as i32,
+        values[1] as u8,
+        values[2] as u8,
+        values[3] as u8,
+        values[4] as u8,
+        values[5].trunc() as u8,
+        nanos,
+        TimeScale::GPST,
+    ))
+}
+
+impl FromStr for Antex {
+    type Err = Error;
+
+    /// Parses ANTEX (1.4) content. Frequencies that this library
+    /// does not support are ignored, as well as the RMS values.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut antex = Self::default();
+
+        let mut header = true;
+        let mut rms = false;
+
+        let mut antenna: Option<Antenna> = None;
+        let mut frequency: Option<AntennaFrequency> = None;
+        let mut skipped_frequency = false;
+
+        for line in s.lines() {
+            let label = line.get(60..).map(|label| label.trim()).unwrap_or_default();
+            let content = line.get(..60).unwrap_or(line);
+
+            if header {
+                if label == "END OF HEADER" {
+                    header = false;
+                }
+                continue;
+            }
+
+            match label {
+                "START OF ANTENNA" => {
+                    antenna = None;
+                    continue;
+                },
+                "TYPE / SERIAL NO" => {
+                    let model = content.get(..20).unwrap_or_default().trim();
+                    let serial = content.get(20..40).unwrap_or_default().trim();
+                    let svn = content.get(40..50).unwrap_or_default().trim();
+                    let cospar = content.get(50..60).unwrap_or_default().trim();
+
+                    let kind = if svn.is_empty() && cospar.is_empty() {
+                        AntennaKind::Receiver {
+                            model: model.to_string(),
+                            serial: serial.to_string(),
+                        }
+                    } else {
+                        AntennaKind::Satellite {
+                            sv: SV::from_str(serial).map_err(|_| Error::AntexParsing)?,
+                            svn: svn.to_string(),
+                            cospar: COSPAR::from_str(cospar).ok(),
+                            block: model.to_string(),
+                        }
+                    };
+
+                    antenna = Some(Antenna::new(kind));
+                    continue;
+                },
+                "END OF ANTENNA" => {
+                    if let Some(antenna) = antenna.take() {
+                        antex.antennas.push(antenna);
+                    }
+                    continue;
+                },
+                _ => {},
+            }
+
+            let antenna = match antenna.as_mut() {
+                Some(antenna) => antenna,
+                None => continue,
+            };
+
+            match label {
+                "DAZI" => {
+                    antenna.azimuth_step_deg = *parse_values(content, Error::AntexParsing)?
+                        .first()
+                        .ok_or(Error::AntexParsing)?;
+                },
+                "ZEN1 / ZEN2 / DZEN" => {
+                    let values = parse_values(content, Error::AntexParsing)?;
+
+                    if values.len() < 3 {
+                        return Err(Error::AntexParsing);
+                    }
+
+                    antenna.zenith_grid_deg = (values[0], values[1], values[2]);
+                },
+                "VALID FROM" => {
+                    antenna.valid_from = Some(parse_epoch(content)?);
+                },
+                "VALID UNTIL" => {
+                    antenna.valid_until = Some(parse_epoch(content)?);
+                },
+                "START OF FREQUENCY" => {
+                    frequency = None;
+                    skipped_frequency = true;
+
+                    if let Some((constellation, carrier)) = antex_frequency(content.trim()) {
+                        skipped_frequency = false;
+
+                        frequency = Some(AntennaFrequency {
+                            constellation,
+                            carrier,
+                            pco_m: (0.0, 0.0, 0.0),
+                            pcv_noazi_m: Vec::new(),
+                            pcv_m: Vec::new(),
+                        });
+                    }
+                },
+                "NORTH / EAST / UP" => {
+                    if let Some(frequency) = frequency.as_mut() {
+                        let values = parse_values(content, Error::AntexParsing)?;
+
+                        if values.len() < 3 {
+                            return Err(Error::AntexParsing);
+                        }
+
+                        frequency.pco_m = (values[0] / 1.0E3, values[1] / 1.0E3, values[2] / 1.0E3);
+                    }
+                },
+                "END OF FREQUENCY" => {
+                    if let Some(frequency) = frequency.take() {
+                        antenna.frequencies.push(frequency);
+                    }
+                    skipped_frequency = false;
+                },
+                "START OF FREQ RMS" => rms = true,
+                "END OF FREQ RMS" => rms = false,
+                _ => {
+                    // RMS values, unsupported frequencies and other records (comments..)
+                    if rms || skipped_frequency || label.chars().any(|c| c.is_ascii_alphabetic()) {
+                        continue;
+                    }
+
+                    let frequency = match frequency.as_mut() {
+                        Some(frequency) => frequency,
+                        None => continue,
+                    };
+
+                    let line = line.trim();
+
+                    if let Some(values) = line.strip_prefix("NOAZI") {
+                        frequency.pcv_noazi_m = parse_values(values, Error::AntexParsing)?
+                            .iter()
+                            .map(|v| v / 1.0E3)
+                            .collect();
+                    } else if !line.is_empty() {
+                        // azimuth dependent row: azimuth, then values
+                        let values = parse_values(line, Error::AntexParsing)?;
+
+                        frequency
+                            .pcv_m
+                            .push(values.iter().skip(1).map(|v| v / 1.0E3).collect());
+                    }
+                },
+            }
+        }
+
+        Ok(antex)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AntennaKind, Antex};
+    use crate::prelude::{Carrier, Constellation, Epoch, SV};
+
+    use std::str::FromStr;
+
+    /// Receiver and satellite antennas, with arbitrary values
+    const ANTEX: &str =
+        "     1.4            M                                       ANTEX VERSION / SYST
+A                                                           PCV TYPE / REFANT
+                                                            END OF HEADER
+                                                            START OF ANTENNA
+TEST00000.00    NONE                                        TYPE / SERIAL NO
+   180.0                                                    DAZI
+     0.0  90.0  45.0                                        ZEN1 / ZEN2 / DZEN
+     2                                                      # OF FREQUENCIES
+   G01                                                      START OF FREQUENCY
+      1.00      2.00     90.00                              NORTH / EAST / UP
+arbitrary values                                            COMMENT
+   NOAZI    0.00   -2.00   -4.00
+     0.0    0.00   -2.00   -4.00
+   180.0    0.00   -4.00   -8.00
+   360.0    0.00   -2.00   -4.00
+   G01                                                      END OF FREQUENCY
+   G02                                                      START OF FREQUENCY
+      0.00      0.00    120.00                              NORTH / EAST / UP
+   NOAZI    0.00    1.00    2.00
+     0.0    0.00    1.00    2.00
+   180.0    0.00    1.00    2.00
+   360.0    0.00    1.00    2.00
+   G02                                                      END OF FREQUENCY
+   G01                                                      START OF FREQ RMS
+      0.10      0.10      0.10                              NORTH / EAST / UP
+   NOAZI    0.10    0.10    0.10
+   G01                                                      END OF FREQ RMS
+                                                            END OF ANTENNA
+                                                            START OF ANTENNA
+BLOCK IIF           G01                 G063      2011-036A TYPE / SERIAL NO
+     0.0                                                    DAZI
+     0.0  14.0   7.0                                        ZEN1 / ZEN2 / DZEN
+  2011     7    16     0     0    0.0000000                 VALID FROM
+   G01                                                      START OF FREQUENCY
+    394.00      0.00   1600.00                              NORTH / EAST / UP
+   NOAZI    1.00    0.00   -1.00
+   G01                                                      END OF FREQUENCY
+                                                            END OF ANTENNA
+";
+
+    #[test]
+    fn antex_parsing() {
+        let antex = Antex::from_str(ANTEX).unwrap();
+        assert_eq!(antex.antennas.len(), 2);
+
+        let receiver = antex.receiver_antenna("TEST00000.00").unwrap();
+
+        assert!(matches!(receiver.kind, AntennaKind::Receiver { .. }));
+        assert_eq!(receiver.zenith_grid_deg, (0.0, 90.0, 45.0));
+        assert_eq!(receiver.azimuth_step_deg, 180.0);
+        assert_eq!(receiver.frequencies.len(), 2);
+
+        let l1 = receiver.frequency(Constellation::GPS, Carrier::L1).unwrap();
+
+        assert_eq!(l1.pco_m, (0.001, 0.002, 0.09));
+        assert_eq!(l1.pcv_noazi_m, vec![0.0, -0.002, -0.004]);
+        assert_eq!(l1.pcv_m.len(), 3);
+
+        // closest frequency
+        let e5 = receiver
+            .frequency(Constellation::Galileo, Carrier::E5a)
+            .unwrap();
+
+        assert_eq!(e5.carrier, Carrier::L2);
+
+        // interpolations
+        assert!((receiver.pcv_m(l1, 0.0, 0.0)).abs() < 1.0E-9);
+        assert!((receiver.pcv_m(l1, 22.5, 0.0) + 0.001).abs() < 1.0E-9);
+        assert!((receiver.pcv_m(l1, 45.0, 90.0) + 0.003).abs() < 1.0E-9);
+        assert!((receiver.pcv_m(l1, 45.0, 180.0) + 0.004).abs() < 1.0E-9);
+        assert!((receiver.pcv_m(l1, 120.0, 180.0) + 0.008).abs() < 1.0E-9);
+
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let sv = SV::from_str("G01").unwrap();
+
+        let satellite = antex.satellite_antenna(sv, t0).unwrap();
+        let l1 = satellite
+            .frequency(Constellation::GPS, Carrier::L1)
+            .unwrap();
+
+        assert_eq!(l1.pco_m, (0.394, 0.0, 1.6));
+        assert!((satellite.pcv_m(l1, 3.5, 0.0) - 0.0005).abs() < 1.0E-9);
+
+        // not valid yet
+        let t0 = Epoch::from_str("2010-06-25T00:00:00 GPST").unwrap();
+        assert!(antex.satellite_antenna(sv, t0).is_none());
+    }
+}
@@ -0,0 +1,446 @@
+//! Multi-antenna attitude determination
+use log::debug;
+
+use nalgebra::{DMatrix, DVector, Matrix3, Vector3};
+
+use crate::{
+    navigation::dop::DilutionOfPrecision,
+    prelude::{Epoch, Error},
+};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// [AttitudeSolution] of a rigid body equipped with several antennas,
+/// resolved by [Solver::attitude](crate::prelude::Solver::attitude).
+/// Angles describe the rotation from the local (north, east, down)
+/// frame to the body (forward, right, down) frame.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct AttitudeSolution {
+    /// Measurement [Epoch] that led to this solution.
+    pub epoch: Epoch,
+
+    /// Heading (yaw) angle, in degrees, clockwise from north (0..360°).
+    pub heading_deg: f64,
+
+    /// Heading uncertainty (1σ, in degrees).
+    pub heading_sigma_deg: f64,
+
+    /// Pitch angle, in degrees, positive when the forward axis points up.
+    pub pitch_deg: f64,
+
+    /// Pitch uncertainty (1σ, in degrees).
+    pub pitch_sigma_deg: f64,
+
+    /// Roll angle, in degrees, positive when the right side points down.
+    /// Only resolved when two secondary antennas with non colinear lever arms are used.
+    pub roll_deg: Option<f64>,
+
+    /// Roll uncertainty (1σ, in degrees).
+    pub roll_sigma_deg: Option<f64>,
+
+    /// Baseline vector from the primary antenna to each secondary antenna,
+    /// expressed in meters in the local (east, north, up) frame.
+    pub baselines_enu_m: Vec<(f64, f64, f64)>,
+}
+
+/// Short [Baseline] between two antennas of the same rigid body,
+/// resolved from double differenced phase observations.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Baseline {
+    /// Baseline vector (m)
+    pub vector_m: Vector3<f64>,
+
+    /// Baseline covariance (m²)
+    pub cov_m2: Matrix3<f64>,
+}
+
+impl Baseline {
+    /// DD phase standard deviation (m), lower bound of the a posteriori estimate.
+    const MIN_PHASE_SIGMA_M: f64 = 5.0E-3;
+
+    /// Resolves a [Baseline] of known length, with the ambiguity function method:
+    /// the sphere of known radius is searched for the direction that best explains
+    /// the fractional part of the DD phase observations. Ambiguities are then fixed
+    /// and the baseline is refined by least squares. The search step is adapted to the
+    /// baseline length, so this is intended for short baselines (a few meters).
+    ///
+    /// ## Input
+    /// - g: one row per DD, that is the pivot minus satellite line of sight.
+    /// - phase_m: DD phase observations (m)
+    /// - lambda_m: wavelength of each DD (m)
+    /// - length_m: known baseline length (m)
+    /// - max_length_error_m: tolerated error on the resolved baseline length (m)
+    pub fn resolve(
+        g: &[Vector3<f64>],
+        phase_m: &[f64],
+        lambda_m: &[f64],
+        length_m: f64,
+        max_length_error_m: f64,
+    ) -> Result<Self, Error> {
+        let nb_dd = g.len();
+
+        assert_eq!(nb_dd, phase_m.len(), "invalid DD dimensions");
+        assert_eq!(nb_dd, lambda_m.len(), "invalid DD dimensions");
+
+        if length_m <= 0.0 {
+            return Err(Error::InvalidLeverArms);
+        }
+
+        if nb_dd < 4 {
+            return Err(Error::NotEnoughPostFitCandidates);
+        }
+
+        let min_lambda_m = lambda_m.iter().fold(f64::INFINITY, |min, l| min.min(*l));
+
+        let step_rad = (min_lambda_m / 16.0 / length_m).min(1.0_f64.to_radians());
+        let nb_lat = (std::f64::consts::PI / step_rad).ceil() as usize;
+
+        // ambiguity function search
+        let mut best = (f64::NEG_INFINITY, Vector3::zeros());
+
+        for i in 0..=nb_lat {
+            let lat_rad = -std::f64::consts::FRAC_PI_2 + i as f64 * step_rad;
+            let (sin_lat, cos_lat) = lat_rad.sin_cos();
+
+            // preserve surface resolution towards the poles
+            let nb_long =
+                ((2.0 * std::f64::consts::PI * cos_lat / step_rad).ceil() as usize).max(1);
+            let long_step_rad = 2.0 * std::f64::consts::PI / nb_long as f64;
+
+            for j in 0..nb_long {
+                let (sin_long, cos_long) = (j as f64 * long_step_rad).sin_cos();
+
+                let b = Vector3::new(cos_lat * cos_long, cos_lat * sin_long, sin_lat) * length_m;
+
+                let af = g
+                    .iter()
+                    .zip(phase_m.iter().zip(lambda_m.iter()))
+                    .map(|(g_i, (phi_i, lambda_i))| {
+                        (2.0 * std::f64::consts::PI * (phi_i - g_i.dot(&b)) / lambda_i).cos()
+                    })
+                    .sum::<f64>();
+
+                if af > best.0 {
+                    best = (af, b);
+                }
+            }
+        }
+
+        let (af, b_0) = best;
+
+        debug!(
+            "attitude baseline search - AF={:.3}/{} b0={}",
+            af, nb_dd, b_0
+        );
+
+        // fix ambiguities, then least squares
+        let mut g_mat = DMatrix::<f64>::zeros(nb_dd, 3);
+        let mut y = DVector::<f64>::zeros(nb_dd);
+
+        for i in 0..nb_dd {
+            let n_i = ((phase_m[i] - g[i].dot(&b_0)) / lambda_m[i]).round();
+
+            y[i] = phase_m[i] - n_i * lambda_m[i];
+
+            for j in 0..3 {
+                g_mat[(i, j)] = g[i][j];
+            }
+        }
+
+        let g_t = g_mat.transpose();
+
+        let q = (g_t.clone() * g_mat.clone())
+            .try_inverse()
+            .ok_or(Error::MatrixInversion)?;
+
+        let x = q.clone() * g_t * y.clone();
+        let residuals = y - g_mat * x.clone();
+
+        for (i, residual_m) in residuals.iter().enumerate() {
+            if residual_m.abs() > 0.25 * lambda_m[i] {
+                debug!("attitude baseline - rejected fix: residual={residual_m:.3}m");
+                return Err(Error::AttitudeAmbiguityFixing);
+            }
+        }
+
+        let vector_m = Vector3::new(x[0], x[1], x[2]);
+
+        if (vector_m.norm() - length_m).abs() > max_length_error_m {
+            debug!(
+                "attitude baseline - |b|={:.3}m expecting {:.3}m",
+                vector_m.norm(),
+                length_m
+            );
+            return Err(Error::AttitudeBaselineLength);
+        }
+
+        let sigma0_m2 = if nb_dd > 3 {
+            (residuals.norm_squared() / (nb_dd - 3) as f64).max(Self::MIN_PHASE_SIGMA_M.powi(2))
+        } else {
+            Self::MIN_PHASE_SIGMA_M.powi(2)
+        };
+
+        let q = q * sigma0_m2;
+
+        Ok(Self {
+            vector_m,
+            cov_m2: q.fixed_view::<3, 3>(0, 0).into_owned(),
+        })
+    }
+
+    /// Converts this ECEF [Baseline] to local (east, north, up) frame.
+    pub fn to_enu(self, lat_rad: f64, long_rad: f64) -> Self {
+        let r = DilutionOfPrecision::enu_rotation(lat_rad, long_rad);
+
+        Self {
+            vector_m: r.transpose() * self.vector_m,
+            cov_m2: r.transpose() * self.cov_m2 * r,
+        }
+    }
+}
+
+/// Rotation matrix from body (forward, right, down) frame to local (east, north, up) frame.
+pub(crate) fn body_to_enu(heading_rad: f64, pitch_rad: f64, roll_rad: f64) -> Matrix3<f64> {
+    let (sin_h, cos_h) = heading_rad.sin_cos();
+    let (sin_p, cos_p) = pitch_rad.sin_cos();
+    let (sin_r, cos_r) = roll_rad.sin_cos();
+
+    let r_z = Matrix3::new(cos_h, -sin_h, 0.0, sin_h, cos_h, 0.0, 0.0, 0.0, 1.0);
+    let r_y = Matrix3::new(cos_p, 0.0, sin_p, 0.0, 1.0, 0.0, -sin_p, 0.0, cos_p);
+    let r_x = Matrix3::new(1.0, 0.0, 0.0, 0.0, cos_r, -sin_r, 0.0, sin_r, cos_r);
+
+    // (north, east, down) to (east, north, up)
+    let ned_enu = Matrix3::new(0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, -1.0);
+
+    ned_enu * r_z * r_y * r_x
+}
+
+/// Resolves the attitude angles (heading, pitch, roll) in radians, and their covariance,
+/// from local (east, north, up) [Baseline]s and the body frame lever arms, by iterative
+/// weighted least squares. Roll is only resolved (third angle) when at least two lever arms
+/// are not colinear, otherwise it is fixed to zero and the covariance is 2x2.
+pub(crate) fn attitude_fit(
+    baselines_enu: &[Baseline],
+    lever_arms_m: &[Vector3<f64>],
+) -> Result<(Vector3<f64>, DMatrix<f64>), Error> {
+    const NB_ITER: usize = 20;
+    const DELTA_RAD: f64 = 1.0E-7;
+
+    assert_eq!(
+        baselines_enu.len(),
+        lever_arms_m.len(),
+        "one lever arm per baseline"
+    );
+
+    if baselines_enu.is_empty() {
+        return Err(Error::InvalidLeverArms);
+    }
+
+    let resolves_roll = lever_arms_m.iter().skip(1).any(|lever_arm| {
+        lever_arm.cross(&lever_arms_m[0]).norm()
+            > 1.0E-3 * lever_arm.norm() * lever_arms_m[0].norm()
+    });
+
+    let ndf = if resolves_roll { 3 } else { 2 };
+    let nb_obs = 3 * baselines_enu.len();
+
+    // initial guess, from the first baseline
+    let (b_0, l_0) = (baselines_enu[0].vector_m, lever_arms_m[0]);
+
+    let mut x = Vector3::new(
+        b_0[0].atan2(b_0[1]) - l_0[1].atan2(l_0[0]),
+        (b_0[2] / b_0.norm()).asin() - (-l_0[2] / l_0.norm()).asin(),
+        0.0,
+    );
+
+    let mut w = DMatrix::<f64>::zeros(nb_obs, nb_obs);
+
+    for (i, baseline) in baselines_enu.iter().enumerate() {
+        let w_i = baseline
+            .cov_m2
+            .try_inverse()
+            .ok_or(Error::MatrixInversion)?;
+
+        w.view_mut((3 * i, 3 * i), (3, 3)).copy_from(&w_i);
+    }
+
+    let residuals = |x: &Vector3<f64>| {
+        let rot = body_to_enu(x[0], x[1], x[2]);
+        let mut dy = DVector::<f64>::zeros(nb_obs);
+
+        for (i, (baseline, lever_arm)) in baselines_enu.iter().zip(lever_arms_m.iter()).enumerate()
+        {
+            let dy_i = baseline.vector_m - rot * lever_arm;
+            dy.rows_mut(3 * i, 3).copy_from(&dy_i);
+        }
+
+        dy
+    };
+
+    let mut q = DMatrix::<f64>::zeros(ndf, ndf);
+
+    for _ in 0..NB_ITER {
+        let dy = residuals(&x);
+        let mut j = DMatrix::<f64>::zeros(nb_obs, ndf);
+
+        for k in 0..ndf {
+            let mut x_k = x;
+            x_k[k] += DELTA_RAD;
+
+            // residuals decrease as the model increases
+            let col = (dy.clone() - residuals(&x_k)) / DELTA_RAD;
+            j.set_column(k, &col);
+        }
+
+        let j_t = j.transpose();
+
+        q = (j_t.clone() * w.clone() * j.clone())
+            .try_inverse()
+            .ok_or(Error::MatrixInversion)?;
+
+        let dx = q.clone() * j_t * w.clone() * dy;
+
+        for k in 0..ndf {
+            x[k] += dx[k];
+        }
+
+        if dx.norm() < 1.0E-10 {
+            break;
+        }
+    }
+
+    x[0] = x[0].rem_euclid(2.0 * std::f64::consts::PI);
+
+    // wrap roll to ]-180, 180]
+    if x[2] > std::f64::consts::PI {
+        x[2] -= 2.0 * std::f64::consts::PI;
+    } else if x[2] <= -std::f64::consts::PI {
+        x[2] += 2.0 * std::f64::consts::PI;
+    }
+
+    Ok((x, q))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Baseline, attitude_fit, body_to_enu};
+    use crate::prelude::Error;
+    use nalgebra::{Matrix3, Vector3};
+
+    /// Synthetic DD geometry: pivot at zenith, satellites spread in azimuth.
+    fn dd_geometry() -> Vec<Vector3<f64>> {
+        let pivot = Vector3::new(0.0, 0.0, 1.0);
+
+        [
+            (0.0_f64, 30.0_f64),
+            (70.0, 45.0),
+            (150.0, 20.0),
+            (220.0, 60.0),
+            (300.0, 35.0),
+            (330.0, 15.0),
+        ]
+        .iter()
+        .map(|(az_deg, el_deg)| {
+            let (az, el) = (az_deg.to_radians(), el_deg.to_radians());
+            let los = Vector3::new(el.cos() * az.sin(), el.cos() * az.cos(), el.sin());
+            pivot - los
+        })
+        .collect()
+    }
+
+    #[test]
+    fn baseline_resolution() {
+        let lambda_m = 0.19;
+        let baseline_m = Vector3::new(1.2, -0.7, 0.1);
+        let length_m = baseline_m.norm();
+
+        let g = dd_geometry();
+
+        let ambiguities = [3.0, -5.0, 12.0, 0.0, -1.0, 7.0];
+
+        let phase_m = g
+            .iter()
+            .zip(ambiguities.iter())
+            .map(|(g_i, n_i)| g_i.dot(&baseline_m) + n_i * lambda_m)
+            .collect::<Vec<_>>();
+
+        let lambdas = vec![lambda_m; g.len()];
+
+        let baseline = Baseline::resolve(&g, &phase_m, &lambdas, length_m, 0.05).unwrap();
+
+        assert!(
+            (baseline.vector_m - baseline_m).norm() < 1.0E-6,
+            "resolved {} expecting {}",
+            baseline.vector_m,
+            baseline_m
+        );
+
+        assert_eq!(
+            Baseline::resolve(&g, &phase_m, &lambdas, 0.0, 0.05).err(),
+            Some(Error::InvalidLeverArms),
+        );
+
+        assert_eq!(
+            Baseline::resolve(&g[..3], &phase_m[..3], &lambdas[..3], length_m, 0.05).err(),
+            Some(Error::NotEnoughPostFitCandidates),
+        );
+    }
+
+    #[test]
+    fn attitude_rotation() {
+        // heading 90°: forward points east
+        let rot = body_to_enu(90.0_f64.to_radians(), 0.0, 0.0);
+        let forward = rot * Vector3::new(1.0, 0.0, 0.0);
+        assert!((forward - Vector3::new(1.0, 0.0, 0.0)).norm() < 1.0E-9);
+
+        // pitch up: forward points up
+        let rot = body_to_enu(0.0, 30.0_f64.to_radians(), 0.0);
+        let forward = rot * Vector3::new(1.0, 0.0, 0.0);
+        assert!((forward[2] - 0.5).abs() < 1.0E-9);
+
+        // roll: right side goes down
+        let rot = body_to_enu(0.0, 0.0, 30.0_f64.to_radians());
+        let right = rot * Vector3::new(0.0, 1.0, 0.0);
+        assert!((right[2] + 0.5).abs() < 1.0E-9);
+    }
+
+    #[test]
+    fn attitude_fitting() {
+        let (heading, pitch, roll) = (
+            123.0_f64.to_radians(),
+            4.0_f64.to_radians(),
+            -7.0_f64.to_radians(),
+        );
+
+        let rot = body_to_enu(heading, pitch, roll);
+
+        let lever_arms_m = [Vector3::new(2.0, 0.0, 0.0), Vector3::new(0.5, 1.5, 0.0)];
+
+        let baselines = lever_arms_m
+            .iter()
+            .map(|lever_arm| Baseline {
+                vector_m: rot * lever_arm,
+                cov_m2: Matrix3::identity() * 1.0E-4,
+            })
+            .collect::<Vec<_>>();
+
+        let (x, q) = attitude_fit(&baselines, &lever_arms_m).unwrap();
+
+        assert!((x[0] - heading).abs() < 1.0E-6);
+        assert!((x[1] - pitch).abs() < 1.0E-6);
+        assert!((x[2] - roll).abs() < 1.0E-6);
+        assert_eq!(q.nrows(), 3);
+
+        // single baseline: roll is not resolved
+        let (x, q) = attitude_fit(&baselines[..1], &lever_arms_m[..1]).unwrap();
+
+        let rot = body_to_enu(heading, pitch, 0.0);
+        let expected = rot * lever_arms_m[0];
+
+        assert!((body_to_enu(x[0], x[1], x[2]) * lever_arms_m[0] - expected).norm() < 1.0E-6);
+        assert_eq!(x[2], 0.0);
+        assert_eq!(q.nrows(), 2);
+    }
+}
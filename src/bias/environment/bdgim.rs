@@ -0,0 +1,323 @@
+//! BeiDou Global Ionospheric delay correction Model (BDGIM),
+//! as specified by the BDS-SIS-ICD (B1C, B2a), section 7.
+use crate::{
+    bias::environment::iono::PiercePoint,
+    prelude::{BiasRuntime, Rc, TimeScale},
+};
+
+use std::f64::consts::{PI, TAU};
+
+/// Earth radius (km) of the BDGIM geometry
+const EARTH_RADIUS_KM: f64 = 6378.0;
+
+/// Height (km) of the ionosphere single layer
+const LAYER_HEIGHT_KM: f64 = 400.0;
+
+/// Latitude (degrees) of the geomagnetic north pole
+const POLE_LATITUDE_DEG: f64 = 80.27;
+
+/// Longitude (degrees) of the geomagnetic north pole
+const POLE_LONGITUDE_DEG: f64 = -72.58;
+
+/// Degree and order of the 9 broadcast spherical harmonics
+const BROADCAST_TERMS: [(usize, isize); 9] = [
+    (0, 0),
+    (1, 0),
+    (1, 1),
+    (1, -1),
+    (2, 0),
+    (2, 1),
+    (2, -1),
+    (2, 2),
+    (2, -2),
+];
+
+/// Degree and order of the 17 non broadcast (background) spherical harmonics
+const BACKGROUND_TERMS: [(usize, isize); 17] = [
+    (3, 0),
+    (3, 1),
+    (3, -1),
+    (3, 2),
+    (3, -2),
+    (3, 3),
+    (3, -3),
+    (4, 0),
+    (4, 1),
+    (4, -1),
+    (4, 2),
+    (4, -2),
+    (5, 0),
+    (5, 1),
+    (5, -1),
+    (5, 2),
+    (5, -2),
+];
+
+/// Periods (days) of the background coefficients variations
+const BACKGROUND_PERIODS_DAYS: [f64; 12] = [
+    1.0, 0.5, 0.33, 14.6, 27.0, 121.6, 182.62, 365.25, 4028.71, 2014.35, 1342.90, 1007.18,
+];
+
+/// Returns the normalized associated Legendre function P̄nm(x),
+/// without the Condon-Shortley phase.
+fn normalized_legendre(n: usize, m: usize, x: f64) -> f64 {
+    if m > n {
+        return 0.0;
+    }
+
+    // P(m, m)
+    let mut p_mm = 1.0;
+    let s = (1.0 - x * x).max(0.0).sqrt();
+
+    for i in 1..=m {
+        p_mm *= (2 * i - 1) as f64 * s;
+    }
+
+    let p_nm = if n == m {
+        p_mm
+    } else {
+        let mut p_prev = p_mm;
+        let mut p = x * (2 * m + 1) as f64 * p_mm;
+
+        for k in m + 2..=n {
+            let next = ((2 * k - 1) as f64 * x * p - (k + m - 1) as f64 * p_prev) / (k - m) as f64;
+            p_prev = p;
+            p = next;
+        }
+
+        p
+    };
+
+    // (n-m)! / (n+m)!
+    let ratio = (n - m + 1..=n + m).fold(1.0, |ratio, k| ratio / k as f64);
+    let delta = if m == 0 { 1.0 } else { 2.0 };
+
+    ((2 * n + 1) as f64 * ratio * delta).sqrt() * p_nm
+}
+
+/// Returns the spherical harmonic A(n, m) at this geomagnetic latitude
+/// and sun fixed longitude (radians).
+fn spherical_harmonic((n, m): (usize, isize), lat_rad: f64, lon_rad: f64) -> f64 {
+    let p_nm = normalized_legendre(n, m.unsigned_abs(), lat_rad.sin());
+
+    if m >= 0 {
+        p_nm * (m as f64 * lon_rad).cos()
+    } else {
+        p_nm * (-m as f64 * lon_rad).sin()
+    }
+}
+
+/// BDGIM non broadcast (background) coefficients, as tabulated in the BDS-SIS-ICD.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BdgimBackground {
+    /// For each of the 17 background terms, in ICD order
+    /// ((3,0), (3,1), (3,-1), .. (5,-2)): the constant coefficient,
+    /// followed by the (cosine, sine) coefficients of each of the 12 periods
+    /// (1, 0.5, 0.33, 14.6, 27, 121.6, 182.62, 365.25, 4028.71, 2014.35, 1342.90, 1007.18 days).
+    pub coefficients: [[f64; 25]; 17],
+}
+
+impl BdgimBackground {
+    /// Returns the background VTEC (TECu)
+    fn vtec_tecu(&self, mjd: f64, lat_rad: f64, lon_rad: f64) -> f64 {
+        self.coefficients
+            .iter()
+            .zip(BACKGROUND_TERMS.iter())
+            .map(|(coefs, term)| {
+                let b = BACKGROUND_PERIODS_DAYS.iter().enumerate().fold(
+                    coefs[0],
+                    |b, (k, period_days)| {
+                        let (sin, cos) = (TAU / period_days * mjd).sin_cos();
+                        b + coefs[2 * k + 1] * cos + coefs[2 * k + 2] * sin
+                    },
+                );
+
+                b * spherical_harmonic(*term, lat_rad, lon_rad)
+            })
+            .sum()
+    }
+}
+
+/// BDGIM Model, driven by the BeiDou broadcast ionospheric coefficients.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BdModel {
+    /// Broadcast coefficients (TECu), in ICD order: α1 to α9.
+    pub alpha: [f64; 9],
+
+    /// Possible [BdgimBackground] coefficients. When missing, the
+    /// non broadcast contribution (A0) is neglected.
+    pub background: Option<Rc<BdgimBackground>>,
+}
+
+/// Converts geographic coordinates (radians) to geomagnetic coordinates (radians).
+fn geomagnetic_coordinates(lat_rad: f64, lon_rad: f64) -> (f64, f64) {
+    let (pole_lat_rad, pole_lon_rad) = (
+        POLE_LATITUDE_DEG.to_radians(),
+        POLE_LONGITUDE_DEG.to_radians(),
+    );
+
+    let dlon_rad = lon_rad - pole_lon_rad;
+
+    let geomag_lat_rad = (pole_lat_rad.sin() * lat_rad.sin()
+        + pole_lat_rad.cos() * lat_rad.cos() * dlon_rad.cos())
+    .clamp(-1.0, 1.0)
+    .asin();
+
+    let geomag_lon_rad = (lat_rad.cos() * dlon_rad.sin()).atan2(
+        pole_lat_rad.sin() * lat_rad.cos() * dlon_rad.cos() - pole_lat_rad.cos() * lat_rad.sin(),
+    );
+
+    (geomag_lat_rad, geomag_lon_rad)
+}
+
+/// Returns the sun fixed longitude (radians), from this geomagnetic
+/// longitude (radians) and time of day (s).
+fn sun_fixed_longitude(geomag_lon_rad: f64, t_s: f64) -> f64 {
+    let pole_lat_rad = POLE_LATITUDE_DEG.to_radians();
+    let pole_lon_rad = POLE_LONGITUDE_DEG.to_radians();
+
+    // geographic longitude of the subsolar point (on the equator)
+    let s_rad = PI * (1.0 - 2.0 * t_s / 86400.0);
+
+    // Geomagnetic longitude of the subsolar point. The ICD prints an arctangent:
+    // the 4 quadrants version is the one that matches [geomagnetic_coordinates]
+    // (at zero latitude), so the subsolar point always lies at 0.
+    let sun_lon_rad = (s_rad - pole_lon_rad)
+        .sin()
+        .atan2(pole_lat_rad.sin() * (s_rad - pole_lon_rad).cos());
+
+    geomag_lon_rad - sun_lon_rad
+}
+
+impl BdModel {
+    /// Returns the vertical total electron content (TECu) at this ionospheric
+    /// pierce point (radians), for this time of day (s) and MJD (days).
+    fn vtec_tecu(&self, ipp_lat_rad: f64, ipp_lon_rad: f64, t_s: f64, mjd: f64) -> f64 {
+        let (lat_rad, lon_rad) = geomagnetic_coordinates(ipp_lat_rad, ipp_lon_rad);
+        let lon_rad = sun_fixed_longitude(lon_rad, t_s);
+
+        let broadcast = self
+            .alpha
+            .iter()
+            .zip(BROADCAST_TERMS.iter())
+            .map(|(alpha, term)| alpha * spherical_harmonic(*term, lat_rad, lon_rad))
+            .sum::<f64>();
+
+        let background = match &self.background {
+            Some(background) => background.vtec_tecu(mjd, lat_rad, lon_rad),
+            None => 0.0,
+        };
+
+        (broadcast + background).max(0.0)
+    }
+
+    pub(crate) fn bias_m(&self, rtm: &BiasRuntime) -> f64 {
+        let ipp = PiercePoint::new(rtm, EARTH_RADIUS_KM, LAYER_HEIGHT_KM);
+
+        let t_s = rtm
+            .epoch
+            .to_duration_in_time_scale(TimeScale::BDT)
+            .to_seconds()
+            .rem_euclid(86400.0);
+
+        let mjd = rtm.epoch.to_mjd_utc_days();
+
+        let vtec_tecu = self.vtec_tecu(ipp.lat_rad, ipp.lon_rad, t_s, mjd);
+
+        40.28E16 / rtm.frequency_hz.powi(2) * ipp.mapping * vtec_tecu
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        BdModel, POLE_LATITUDE_DEG, POLE_LONGITUDE_DEG, geomagnetic_coordinates,
+        normalized_legendre, sun_fixed_longitude,
+    };
+    use crate::prelude::{BiasRuntime, Epoch, SV, Vector3};
+
+    use std::{f64::consts::PI, str::FromStr};
+
+    const L1_FREQ_HZ: f64 = 1575.42E6;
+
+    fn runtime(lat_long_deg: (f64, f64), elevation_deg: f64) -> BiasRuntime {
+        BiasRuntime {
+            sv: SV::default(),
+            epoch: Epoch::from_str("2020-06-25T12:00:00 BDT").unwrap(),
+            sv_position_ecef_m: (0.0, 0.0, 0.0),
+            sv_elevation_azimuth_deg_deg: (elevation_deg, 45.0),
+            rx_position_m: Vector3::zeros(),
+            rcvr_lat_long_alt_deg_deg_km: (lat_long_deg.0, lat_long_deg.1, 0.0),
+            frequency_hz: L1_FREQ_HZ,
+        }
+    }
+
+    #[test]
+    fn legendre_normalization() {
+        let x = 0.3_f64;
+
+        assert_eq!(normalized_legendre(0, 0, x), 1.0);
+        assert!((normalized_legendre(1, 0, x) - 3.0_f64.sqrt() * x).abs() < 1.0E-12);
+
+        let p11 = (1.0 - x * x).sqrt();
+        assert!((normalized_legendre(1, 1, x) - 3.0_f64.sqrt() * p11).abs() < 1.0E-12);
+
+        let p20 = (3.0 * x * x - 1.0) / 2.0;
+        assert!((normalized_legendre(2, 0, x) - 5.0_f64.sqrt() * p20).abs() < 1.0E-12);
+
+        let p22 = 3.0 * (1.0 - x * x);
+        let n22 = (5.0_f64 * 2.0 / 24.0).sqrt();
+        assert!((normalized_legendre(2, 2, x) - n22 * p22).abs() < 1.0E-12);
+    }
+
+    #[test]
+    fn subsolar_sun_fixed_longitude() {
+        for t_s in [0.0, 10800.0, 21600.0, 43200.0, 50000.0, 64800.0, 86399.0] {
+            // subsolar point, on the equator
+            let s_rad = PI * (1.0 - 2.0 * t_s / 86400.0);
+
+            let (_, geomag_lon_rad) = geomagnetic_coordinates(0.0, s_rad);
+            let lon_rad = sun_fixed_longitude(geomag_lon_rad, t_s);
+            assert!(lon_rad.sin().abs() < 1.0E-12, "t={t_s} lon={lon_rad}");
+            assert!(lon_rad.cos() > 0.0, "t={t_s} lon={lon_rad}");
+
+            // antisolar point
+            let (_, geomag_lon_rad) = geomagnetic_coordinates(0.0, s_rad + PI);
+            let lon_rad = sun_fixed_longitude(geomag_lon_rad, t_s);
+            assert!(lon_rad.sin().abs() < 1.0E-12, "t={t_s} lon={lon_rad}");
+            assert!(lon_rad.cos() < 0.0, "t={t_s} lon={lon_rad}");
+        }
+    }
+
+    #[test]
+    fn bdgim_delay() {
+        let mut model = BdModel::default();
+        model.alpha[0] = 10.0;
+
+        // zenith: 10 TECu
+        let zenith_m = model.bias_m(&runtime((45.0, 10.0), 90.0));
+        let expected_m = 40.28E16 / L1_FREQ_HZ.powi(2) * 10.0;
+        assert!((zenith_m - expected_m).abs() < 1.0E-9, "{zenith_m}");
+
+        // slant path: mapping function
+        let slant_m = model.bias_m(&runtime((45.0, 10.0), 30.0));
+        let fract = 6378.0 / 6778.0 * 30.0_f64.to_radians().cos();
+        let mapping = 1.0 / (1.0 - fract.powi(2)).sqrt();
+        assert!((slant_m - expected_m * mapping).abs() < 1.0E-9, "{slant_m}");
+
+        // (1, 0) term, at the geomagnetic pole
+        let mut model = BdModel::default();
+        model.alpha[1] = 10.0;
+
+        let pole_m = model.bias_m(&runtime((POLE_LATITUDE_DEG, POLE_LONGITUDE_DEG), 90.0));
+        let expected_m = 40.28E16 / L1_FREQ_HZ.powi(2) * 10.0 * 3.0_f64.sqrt();
+        assert!((pole_m - expected_m).abs() < 1.0E-6, "{pole_m}");
+
+        // negative VTEC is rejected
+        model.alpha[1] = -10.0;
+        assert_eq!(
+            model.bias_m(&runtime((POLE_LATITUDE_DEG, POLE_LONGITUDE_DEG), 90.0)),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,448 @@
+//! Global ionosphere maps (IONEX)
+use std::str::FromStr;
+
+use crate::{
+    bias::environment::iono::PiercePoint,
+    parsing::parse_values,
+    prelude::{BiasRuntime, Epoch, Error},
+};
+
+use log::warn;
+
+/// Missing TEC value
+const MISSING_VALUE: i64 = 9999;
+
+/// Vertical TEC map, at a single [Epoch]
+#[derive(Debug, Clone, PartialEq)]
+struct TecMap {
+    /// [Epoch] of this map
+    epoch: Epoch,
+
+    /// Vertical TEC (TECu), latitude major, in the grid order.
+    /// Missing values are set to None.
+    tec_tecu: Vec<Option<f64>>,
+}
+
+/// [Ionex] global ionosphere maps, as published by the IGS analysis centers,
+/// sampled on a (latitude, longitude) grid at regular time intervals.
+/// Only the TEC maps of 2D (single layer) files are supported; RMS and
+/// height maps are ignored. The slant delays are mapped with the single layer
+/// (1/cos z) mapping function: files that use another mapping function (Q-factor)
+/// or none at all are rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ionex {
+    /// Mean Earth radius (km)
+    pub base_radius_km: f64,
+
+    /// Height (km) of the single layer
+    pub height_km: f64,
+
+    /// Latitude grid (first, last, step), in degrees
+    pub latitude_grid_deg: (f64, f64, f64),
+
+    /// Longitude grid (first, last, step), in degrees
+    pub longitude_grid_deg: (f64, f64, f64),
+
+    /// TEC maps, in chronological order
+    maps: Vec<TecMap>,
+}
+
+impl Default for Ionex {
+    fn default() -> Self {
+        Self {
+            base_radius_km: 6371.0,
+            height_km: 450.0,
+            latitude_grid_deg: (87.5, -87.5, -2.5),
+            longitude_grid_deg: (-180.0, 180.0, 5.0),
+            maps: Vec::new(),
+        }
+    }
+}
+
+/// Number of points of this grid axis
+fn grid_size((first, last, step): (f64, f64, f64)) -> usize {
+    ((last - first) / step).round() as usize + 1
+}
+
+/// Parses an IONEX [Epoch] (expressed in UT)
+fn parse_epoch(content: &str) -> Result<Epoch, Error> {
+    let values = parse_values(content, Error::IonexParsing)?;
+
+    if values.len() < 6 {
+        return Err(Error::IonexParsing);
+    }
+
+    Ok(Epoch::from_gregorian_utc(
+        values[0] as i32,
+        values[1] as u8,
+        values[2] as u8,
+        values[3] as u8,
+        values[4] as u8,
+        values[5] as u8,
+        0,
+    ))
+}
+
+/// Parses a grid description (first, last, step)
+fn parse_grid(content: &str) -> Result<(f64, f64, f64), Error> {
+    let values = parse_values(content, Error::IonexParsing)?;
+
+    if values.len() < 3 || values[2] == 0.0 {
+        return Err(Error::IonexParsing);
+    }
+
+    Ok((values[0], values[1], values[2]))
+}
+
+impl FromStr for Ionex {
+    type Err = Error;
+
+    /// Parses IONEX (1.0) content.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ionex = Self::default();
+
+        let mut header = true;
+        let mut header_exponent = -1;
+        let mut exponent = -1;
+
+        // ongoing TEC map (epoch, raw values)
+        let mut map: Option<(Option<Epoch>, Vec<i64>)> = None;
+
+        for line in s.lines() {
+            let label = line.get(60..).map(|label| label.trim()).unwrap_or_default();
+            let content = line.get(..60).unwrap_or(line);
+
+            if header {
+                match label {
+                    "MAP DIMENSION"
+                        if parse_values(content, Error::IonexParsing)?.first() != Some(&2.0) =>
+                    {
+                        return Err(Error::IonexParsing);
+                    },
+                    "MAPPING FUNCTION" if !matches!(content.trim(), "COSZ" | "") => {
+                        return Err(Error::IonexParsing);
+                    },
+                    "BASE RADIUS" => {
+                        ionex.base_radius_km = *parse_values(content, Error::IonexParsing)?
+                            .first()
+                            .ok_or(Error::IonexParsing)?;
+                    },
+                    "HGT1 / HGT2 / DHGT" => {
+                        ionex.height_km = *parse_values(content, Error::IonexParsing)?
+                            .first()
+                            .ok_or(Error::IonexParsing)?;
+                    },
+                    "LAT1 / LAT2 / DLAT" => ionex.latitude_grid_deg = parse_grid(content)?,
+                    "LON1 / LON2 / DLON" => ionex.longitude_grid_deg = parse_grid(content)?,
+                    "EXPONENT" => {
+                        header_exponent = *parse_values(content, Error::IonexParsing)?
+                            .first()
+                            .ok_or(Error::IonexParsing)?
+                            as i32;
+                    },
+                    "END OF HEADER" => header = false,
+                    _ => {},
+                }
+                continue;
+            }
+
+            match label {
+                "START OF TEC MAP" => {
+                    map = Some((None, Vec::new()));
+                    exponent = header_exponent;
+                },
+                "END OF TEC MAP" => {
+                    let (epoch, values) = map.take().ok_or(Error::IonexParsing)?;
+                    let epoch = epoch.ok_or(Error::IonexParsing)?;
+
+                    let size =
+                        grid_size(ionex.latitude_grid_deg) * grid_size(ionex.longitude_grid_deg);
+
+                    if values.len() != size {
+                        return Err(Error::IonexParsing);
+                    }
+
+                    let scaling = 10.0_f64.powi(exponent);
+
+                    ionex.maps.push(TecMap {
+                        epoch,
+                        tec_tecu: values
+                            .iter()
+                            .map(|value| {
+                                if *value == MISSING_VALUE {
+                                    None
+                                } else {
+                                    Some(*value as f64 * scaling)
+                                }
+                            })
+                            .collect(),
+                    });
+                },
+                "EPOCH OF CURRENT MAP" => {
+                    if let Some((epoch, _)) = map.as_mut() {
+                        *epoch = Some(parse_epoch(content)?);
+                    }
+                },
+                "EXPONENT" => {
+                    exponent = *parse_values(content, Error::IonexParsing)?
+                        .first()
+                        .ok_or(Error::IonexParsing)? as i32;
+                },
+                "LAT/LON1/LON2/DLON/H" => {},
+                _ => {
+                    // TEC values (16I5), RMS and height maps, other records
+                    if label.chars().any(|c| c.is_ascii_alphabetic()) {
+                        continue;
+                    }
+
+                    if let Some((_, values)) = map.as_mut() {
+                        let line = line.trim_end();
+
+                        for start in (0..line.len()).step_by(5) {
+                            let item = line.get(start..(start + 5).min(line.len()));
+                            let item = item.ok_or(Error::IonexParsing)?.trim();
+
+                            if !item.is_empty() {
+                                values.push(item.parse::<i64>().map_err(|_| Error::IonexParsing)?);
+                            }
+                        }
+                    }
+                },
+            }
+        }
+
+        if ionex.maps.is_empty() {
+            return Err(Error::IonexParsing);
+        }
+
+        ionex.maps.sort_by_key(|map| map.epoch);
+
+        Ok(ionex)
+    }
+}
+
+impl Ionex {
+    /// Returns the first and last [Epoch] covered by these maps,
+    /// or None when no map is defined.
+    pub fn time_frame(&self) -> Option<(Epoch, Epoch)> {
+        let first = self.maps.first()?;
+        let last = self.maps.last()?;
+        Some((first.epoch, last.epoch))
+    }
+
+    /// Interpolates the vertical TEC (TECu) of this map, at this location (degrees).
+    /// Returns None outside of the map or when values are missing.
+    fn map_vtec_tecu(&self, map: &TecMap, lat_deg: f64, lon_deg: f64) -> Option<f64> {
+        let (lat1, _, dlat) = self.latitude_grid_deg;
+        let (lon1, _, dlon) = self.longitude_grid_deg;
+
+        let n_lat = grid_size(self.latitude_grid_deg);
+        let n_lon = grid_size(self.longitude_grid_deg);
+
+        // global grids wrap around
+        let global = ((n_lon - 1) as f64 * dlon).abs() >= 360.0 - 1.0E-6;
+
+        let y = ((lat_deg - lat1) / dlat).clamp(0.0, (n_lat - 1) as f64);
+
+        let mut x = (lon_deg - lon1) / dlon;
+
+        if global {
+            x = x.rem_euclid((n_lon - 1) as f64);
+        } else if x < 0.0 || x > (n_lon - 1) as f64 {
+            return None;
+        }
+
+        let (i, j) = (
+            (y.floor() as usize).min(n_lat.saturating_sub(2)),
+            (x.floor() as usize).min(n_lon.saturating_sub(2)),
+        );
+
+        let (p, q) = (x - j as f64, y - i as f64);
+
+        let value = |i: usize, j: usize| map.tec_tecu[i.min(n_lat - 1) * n_lon + j.min(n_lon - 1)];
+
+        Some(
+            (1.0 - p) * (1.0 - q) * value(i, j)?
+                + p * (1.0 - q) * value(i, j + 1)?
+                + q * (1.0 - p) * value(i + 1, j)?
+                + p * q * value(i + 1, j + 1)?,
+        )
+    }
+
+    /// Interpolates the vertical TEC (TECu) at this location (degrees) and [Epoch],
+    /// between the two surrounding maps, that are rotated around the Earth axis
+    /// to follow the Sun (IONEX recommendation).
+    pub fn vtec_tecu(&self, epoch: Epoch, lat_deg: f64, lon_deg: f64) -> Option<f64> {
+        let index = self.maps.iter().position(|map| map.epoch >= epoch)?;
+
+        let next = &self.maps[index];
+
+        if next.epoch == epoch {
+            return self.map_vtec_tecu(next, lat_deg, lon_deg);
+        }
+
+        let prev = &self.maps[index.checked_sub(1)?];
+
+        let dt_prev_s = (epoch - prev.epoch).to_seconds();
+        let dt_next_s = (next.epoch - epoch).to_seconds();
+        let span_s = dt_prev_s + dt_next_s;
+
+        let vtec_prev = self.map_vtec_tecu(prev, lat_deg, lon_deg + 360.0 * dt_prev_s / 86400.0)?;
+        let vtec_next = self.map_vtec_tecu(next, lat_deg, lon_deg - 360.0 * dt_next_s / 86400.0)?;
+
+        Some((dt_next_s * vtec_prev + dt_prev_s * vtec_next) / span_s)
+    }
+
+    /// Returns the ionospheric delay (m), at the pierce point of the single layer,
+    /// or 0 when the maps do not cover this [BiasRuntime].
+    pub(crate) fn bias_m(&self, rtm: &BiasRuntime) -> f64 {
+        let ipp = PiercePoint::new(rtm, self.base_radius_km, self.height_km);
+
+        let (ipp_lat_deg, ipp_lon_deg) = (ipp.lat_rad.to_degrees(), ipp.lon_rad.to_degrees());
+
+        let vtec_tecu = match self.vtec_tecu(rtm.epoch, ipp_lat_deg, ipp_lon_deg) {
+            Some(vtec_tecu) => vtec_tecu,
+            None => {
+                warn!(
+                    "{}({}) - ionosphere maps do not cover ({:.2}°, {:.2}°)",
+                    rtm.epoch, rtm.sv, ipp_lat_deg, ipp_lon_deg
+                );
+                return 0.0;
+            },
+        };
+
+        40.3E16 / rtm.frequency_hz.powi(2) * ipp.mapping * vtec_tecu
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Ionex;
+    use crate::prelude::{BiasRuntime, Epoch};
+
+    use std::str::FromStr;
+
+    const L1_FREQ_HZ: f64 = 1575.42E6;
+
+    /// Builds IONEX content on a coarse global grid (latitudes from 80° to -80°,
+    /// longitudes from -180° to 180°, 40° steps), with a constant value per map.
+    fn ionex_content(maps: &[(u8, i64)]) -> String {
+        let mut content = String::new();
+
+        let record = |content: &str, label: &str| format!("{content:<60}{label}\n");
+
+        content.push_str(&record(
+            "     1.0            IONOSPHERE MAPS     GPS",
+            "IONEX VERSION / TYPE",
+        ));
+        content.push_str(&record("  COSZ", "MAPPING FUNCTION"));
+        content.push_str(&record("  6371.0", "BASE RADIUS"));
+        content.push_str(&record("     2", "MAP DIMENSION"));
+        content.push_str(&record("   450.0 450.0   0.0", "HGT1 / HGT2 / DHGT"));
+        content.push_str(&record("    80.0 -80.0 -40.0", "LAT1 / LAT2 / DLAT"));
+        content.push_str(&record("  -180.0 180.0  40.0", "LON1 / LON2 / DLON"));
+        content.push_str(&record("    -1", "EXPONENT"));
+        content.push_str(&record("", "END OF HEADER"));
+
+        for (index, (hour, value)) in maps.iter().enumerate() {
+            content.push_str(&record(&format!("{:>6}", index + 1), "START OF TEC MAP"));
+            content.push_str(&record(
+                &format!("  2020     6    25{hour:>6}     0     0"),
+                "EPOCH OF CURRENT MAP",
+            ));
+
+            for lat in [80.0, 40.0, 0.0, -40.0, -80.0] {
+                content.push_str(&record(
+                    &format!("  {lat:>6.1}-180.0 180.0  40.0 450.0"),
+                    "LAT/LON1/LON2/DLON/H",
+                ));
+
+                let row = vec![format!("{value:>5}"); 10].join("");
+                content.push_str(&format!("{row}\n"));
+            }
+
+            content.push_str(&record(&format!("{:>6}", index + 1), "END OF TEC MAP"));
+        }
+
+        content.push_str(&record("", "END OF FILE"));
+        content
+    }
+
+    #[test]
+    fn ionex_parsing() {
+        let ionex = Ionex::from_str(&ionex_content(&[(0, 200), (2, 400)])).unwrap();
+
+        assert_eq!(ionex.base_radius_km, 6371.0);
+        assert_eq!(ionex.height_km, 450.0);
+        assert_eq!(ionex.latitude_grid_deg, (80.0, -80.0, -40.0));
+        assert_eq!(ionex.longitude_grid_deg, (-180.0, 180.0, 40.0));
+
+        let (first, last) = ionex.time_frame().unwrap();
+        assert_eq!(first, Epoch::from_gregorian_utc_hms(2020, 6, 25, 0, 0, 0));
+        assert_eq!(last, Epoch::from_gregorian_utc_hms(2020, 6, 25, 2, 0, 0));
+
+        // incomplete map
+        let content = ionex_content(&[(0, 200)]);
+        let truncated = content.replacen(&["  200"; 10].join(""), "  200", 1);
+        assert!(Ionex::from_str(&truncated).is_err());
+
+        // unsupported mapping functions
+        for mapping in ["QFAC", "NONE"] {
+            let content = ionex_content(&[(0, 200)]).replace("  COSZ", &format!("  {mapping}"));
+            assert!(Ionex::from_str(&content).is_err());
+        }
+
+        assert!(Ionex::from_str("").is_err());
+        assert!(Ionex::default().time_frame().is_none());
+    }
+
+    #[test]
+    fn ionex_interpolation() {
+        let ionex = Ionex::from_str(&ionex_content(&[(0, 200), (2, 400), (4, 9999)])).unwrap();
+
+        let t0 = Epoch::from_gregorian_utc_hms(2020, 6, 25, 0, 0, 0);
+        let t1 = Epoch::from_gregorian_utc_hms(2020, 6, 25, 1, 30, 0);
+
+        // 10^-1 scaling
+        assert_eq!(ionex.vtec_tecu(t0, 45.0, 10.0), Some(20.0));
+
+        // time interpolation, across the longitude wrap
+        let vtec = ionex.vtec_tecu(t1, -10.0, 175.0).unwrap();
+        assert!((vtec - 35.0).abs() < 1.0E-9, "{vtec}");
+
+        // missing values, out of time frame
+        let t2 = Epoch::from_gregorian_utc_hms(2020, 6, 25, 3, 0, 0);
+        assert!(ionex.vtec_tecu(t2, 0.0, 0.0).is_none());
+        assert!(
+            ionex
+                .vtec_tecu(t0 - hifitime::Unit::Hour, 0.0, 0.0)
+                .is_none()
+        );
+
+        // zenith delay and mapping
+        let rtm = |elevation_deg: f64| BiasRuntime {
+            epoch: t0,
+            sv_elevation_azimuth_deg_deg: (elevation_deg, 30.0),
+            rcvr_lat_long_alt_deg_deg_km: (45.0, 10.0, 0.0),
+            frequency_hz: L1_FREQ_HZ,
+            ..Default::default()
+        };
+
+        let zenith_m = ionex.bias_m(&rtm(90.0));
+        let expected_m = 40.3E16 / L1_FREQ_HZ.powi(2) * 20.0;
+        assert!((zenith_m - expected_m).abs() < 1.0E-9, "{zenith_m}");
+
+        let slant_m = ionex.bias_m(&rtm(20.0));
+        let fract = 6371.0 / 6821.0 * 20.0_f64.to_radians().cos();
+        let mapping = 1.0 / (1.0 - fract.powi(2)).sqrt();
+        assert!((slant_m - expected_m * mapping).abs() < 1.0E-9, "{slant_m}");
+
+        assert_eq!(
+            ionex.bias_m(&BiasRuntime {
+                epoch: t2,
+                ..rtm(90.0)
+            }),
+            0.0
+        );
+    }
+}
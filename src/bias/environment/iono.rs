@@ -1,4 +1,10 @@
-use crate::{bias::BiasRuntime, prelude::TimeScale};
+use crate::{
+    bias::{
+        BiasRuntime,
+        environment::{bdgim::BdModel, nequick::NgModel},
+    },
+    prelude::{Ionex, Rc, TimeScale},
+};
 
 use std::f64::consts::PI;
 
@@ -6,15 +12,20 @@ use std::f64::consts::PI;
 use serde::{Deserialize, Serialize};
 
 /// Ionopheric delay components that we propose.
-#[derive(Clone, Copy)]
+/// This is not [Copy]: the models may share large (reference counted) tables.
+#[derive(Clone)]
 pub enum IonosphereModel {
     /// Provide a [KbModel]
     KbModel(KbModel),
-    // /// Provide a [NgModel]
-    // NgModel(NgModel),
 
-    // /// Provide a [BdModel]
-    // BdModel(BdModel),
+    /// Provide a [NgModel]
+    NgModel(NgModel),
+
+    /// Provide a [BdModel]
+    BdModel(BdModel),
+
+    /// Provide global ionosphere maps ([Ionex])
+    Ionex(Rc<Ionex>),
 }
 
 impl IonosphereModel {
@@ -23,8 +34,57 @@ impl IonosphereModel {
     pub fn bias_m(&self, rtm: &BiasRuntime) -> f64 {
         match self {
             Self::KbModel(kb) => kb.bias_m(rtm),
-            // Self::BdModel(bd) => bd.bias_m(rtm),
-            // Self::NgModel(ng) => ng.bias_m(rtm),
+            Self::NgModel(ng) => ng.bias_m(rtm),
+            Self::BdModel(bd) => bd.bias_m(rtm),
+            Self::Ionex(ionex) => ionex.bias_m(rtm),
+        }
+    }
+}
+
+/// Ionospheric pierce point of a single layer model, for this [BiasRuntime]
+pub(crate) struct PiercePoint {
+    /// Latitude (radians)
+    pub lat_rad: f64,
+
+    /// Longitude (radians)
+    pub lon_rad: f64,
+
+    /// Single layer mapping function (slant to vertical TEC ratio)
+    pub mapping: f64,
+}
+
+impl PiercePoint {
+    /// Resolves the [PiercePoint] of a thin layer at this height (km),
+    /// above a spherical Earth of this radius (km).
+    pub fn new(rtm: &BiasRuntime, radius_km: f64, height_km: f64) -> Self {
+        let (rx_lat_rad, rx_lon_rad) = (
+            rtm.rcvr_lat_long_alt_deg_deg_km.0.to_radians(),
+            rtm.rcvr_lat_long_alt_deg_deg_km.1.to_radians(),
+        );
+
+        let (elev_rad, azim_rad) = (
+            rtm.sv_elevation_azimuth_deg_deg.0.to_radians(),
+            rtm.sv_elevation_azimuth_deg_deg.1.to_radians(),
+        );
+
+        let fract = radius_km / (radius_km + height_km) * elev_rad.cos();
+
+        // Earth central angle
+        let psi = PI / 2.0 - elev_rad - fract.asin();
+
+        let lat_rad = (rx_lat_rad.sin() * psi.cos()
+            + rx_lat_rad.cos() * psi.sin() * azim_rad.cos())
+        .clamp(-1.0, 1.0)
+        .asin();
+
+        let lon_rad = rx_lon_rad
+            + (psi.sin() * azim_rad.sin() * rx_lat_rad.cos())
+                .atan2(psi.cos() - rx_lat_rad.sin() * lat_rad.sin());
+
+        Self {
+            lat_rad,
+            lon_rad,
+            mapping: 1.0 / (1.0 - fract.powi(2)).sqrt(),
         }
     }
 }
@@ -108,36 +168,6 @@ impl KbModel {
     }
 }
 
-// /// Nequick-G Model: is not supported yet.
-// #[derive(Clone, Copy, Default, Debug)]
-// pub struct NgModel {
-//     /// alpha coefficients
-//     pub a: (f64, f64, f64),
-// }
-
-// impl NgModel {
-//     pub(crate) fn bias_m(&self, _rtm: &RuntimeParams) -> f64 {
-//         //let phi = deg2rad(rtm.apriori_geo.0);
-//         //let mu = inclination / phi.cos().sqrt();
-//         0.0
-//     }
-// }
-
-// /// BDGIM Model: is not supported yet.
-// #[derive(Clone, Copy, Default, Debug)]
-// pub struct BdModel {
-//     /// Alpha coefficients in TECu
-//     pub alpha: (f64, f64, f64, f64, f64, f64, f64, f64, f64),
-// }
-
-// impl BdModel {
-//     pub(crate) fn value(&self, _rtm: &RuntimeParams) -> f64 {
-//         //let phi = deg2rad(rtm.apriori_geo.0);
-//         //let mu = inclination / phi.cos().sqrt();
-//         0.0
-//     }
-// }
-
 /// Modeled (estimated) or measured bias
 #[derive(Debug, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
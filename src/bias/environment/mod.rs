@@ -6,6 +6,15 @@ pub use tropo::TroposphereModel;
 pub(crate) mod iono;
 pub use iono::{IonosphereBias, IonosphereModel, KbModel};
 
+pub(crate) mod nequick;
+pub use nequick::{NeQuickData, NgModel};
+
+pub(crate) mod bdgim;
+pub use bdgim::{BdModel, BdgimBackground};
+
+pub(crate) mod ionex;
+pub use ionex::Ionex;
+
 #[cfg(doc)]
 use crate::prelude::{Config, Method, SV};
 
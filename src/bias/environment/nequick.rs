@@ -0,0 +1,847 @@
+//! NeQuick-G ionosphere model, as specified by the "Ionospheric Correction Algorithm
+//! for Galileo Single Frequency Users" (European GNSS (Galileo) Open Service, issue 1.2).
+use crate::{
+    parsing::parse_values,
+    prelude::{BiasRuntime, Epoch, Error, Rc},
+};
+
+/// Earth radius (km) of the NeQuick-G geometry
+const EARTH_RADIUS_KM: f64 = 6371.2;
+
+/// Size of the (wrapped) MODIP grid: 39 latitudes, 39 longitudes
+const MODIP_GRID_SIZE: usize = 39;
+
+/// Number of spatial and time coefficients of the foF2 maps
+const F2_COEFFICIENTS: (usize, usize) = (76, 13);
+
+/// Number of spatial and time coefficients of the M(3000)F2 maps
+const FM3_COEFFICIENTS: (usize, usize) = (49, 9);
+
+/// Maximal order (in sin(MODIP)) of the spatial functions of the foF2 maps,
+/// for each longitude harmonic.
+const F2_ORDERS: [usize; 9] = [11, 11, 8, 4, 1, 0, 0, 0, 0];
+
+/// Maximal order (in sin(MODIP)) of the spatial functions of the M(3000)F2 maps,
+/// for each longitude harmonic.
+const FM3_ORDERS: [usize; 7] = [6, 7, 5, 2, 1, 0, 0];
+
+/// Altitude (km) that splits the integration
+const INTEGRATION_SPLIT_KM: f64 = 1000.0;
+
+/// Maximal recursion level of the integration
+const MAX_INTEGRATION_LEVEL: usize = 50;
+
+/// Kronrod nodes (G7-K15)
+const KRONROD_NODES: [f64; 8] = [
+    0.991_455_371_120_812_6,
+    0.949_107_912_342_758_5,
+    0.864_864_423_359_769_1,
+    0.741_531_185_599_394_4,
+    0.586_087_235_467_691_1,
+    0.405_845_151_377_397_2,
+    0.207_784_955_007_898_5,
+    0.0,
+];
+
+/// Kronrod weights (G7-K15)
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022_935_322_010_529_2,
+    0.063_092_092_629_978_6,
+    0.104_790_010_322_250_2,
+    0.140_653_259_715_525_9,
+    0.169_004_726_639_267_9,
+    0.190_350_578_064_785_4,
+    0.204_432_940_075_298_9,
+    0.209_482_141_084_727_8,
+];
+
+/// Gauss weights (G7-K15), on every other Kronrod node
+const GAUSS_WEIGHTS: [f64; 4] = [
+    0.129_484_966_168_869_7,
+    0.279_705_391_489_276_7,
+    0.381_830_050_505_118_9,
+    0.417_959_183_673_469_4,
+];
+
+/// Exponential, clipped to prevent overflows
+fn clip_exp(x: f64) -> f64 {
+    if x > 80.0 {
+        5.5406E34
+    } else if x < -80.0 {
+        1.8049E-35
+    } else {
+        x.exp()
+    }
+}
+
+/// Smooth transition from f2 (x << 0) to f1 (x >> 0)
+fn join(f1: f64, f2: f64, alpha: f64, x: f64) -> f64 {
+    let ee = clip_exp(alpha * x);
+    (f1 * ee + f2) / (ee + 1.0)
+}
+
+/// Epstein function of peak amplitude x, thickness y and peak height z, at height w
+fn epstein(x: f64, y: f64, z: f64, w: f64) -> f64 {
+    let ee = clip_exp((w - z) / y);
+    x * ee / (1.0 + ee).powi(2)
+}
+
+/// Third order interpolation of (z(-1), z(0), z(1), z(2)) at 0 <= dx < 1
+fn interpolate(z: [f64; 4], dx: f64) -> f64 {
+    if dx.abs() < 1.0E-10 {
+        return z[1];
+    }
+
+    let g1 = z[2] + z[1];
+    let g2 = z[2] - z[1];
+    let g3 = z[3] + z[0];
+    let g4 = (z[3] - z[0]) / 3.0;
+
+    let a0 = 9.0 * g1 - g3;
+    let a1 = 9.0 * g2 - g4;
+    let a2 = g3 - g1;
+    let a3 = g4 - g2;
+
+    let x = 2.0 * dx - 1.0;
+
+    (a0 + a1 * x + a2 * x.powi(2) + a3 * x.powi(3)) / 16.0
+}
+
+/// WGS84 geodetic (latitude, longitude, altitude) coordinates, in degrees and kilometers,
+/// of this ECEF position (km)
+fn geodetic_coordinates(x_km: f64, y_km: f64, z_km: f64) -> (f64, f64, f64) {
+    const A_KM: f64 = 6378.137;
+    const F: f64 = 1.0 / 298.257_223_563;
+
+    let e2 = F * (2.0 - F);
+    let p_km = (x_km.powi(2) + y_km.powi(2)).sqrt();
+
+    let mut lat_rad = z_km.atan2(p_km * (1.0 - e2));
+    let mut alt_km = 0.0;
+
+    for _ in 0..5 {
+        let n_km = A_KM / (1.0 - e2 * lat_rad.sin().powi(2)).sqrt();
+        alt_km = (p_km.powi(2) + (z_km + e2 * n_km * lat_rad.sin()).powi(2)).sqrt() - n_km;
+        lat_rad = z_km.atan2(p_km * (1.0 - e2 * n_km / (n_km + alt_km)));
+    }
+
+    (lat_rad.to_degrees(), y_km.atan2(x_km).to_degrees(), alt_km)
+}
+
+/// Position (km) in the spherical frame of the model
+fn spherical_position_km(lat_deg: f64, lon_deg: f64, alt_km: f64) -> [f64; 3] {
+    let (sin_lat, cos_lat) = lat_deg.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = lon_deg.to_radians().sin_cos();
+    let r_km = EARTH_RADIUS_KM + alt_km;
+
+    [
+        r_km * cos_lat * cos_lon,
+        r_km * cos_lat * sin_lon,
+        r_km * sin_lat,
+    ]
+}
+
+/// [NeQuickData] tables, that are distributed along the NeQuick-G specifications:
+/// the modified dip latitude (MODIP) grid and the monthly ITU-R (CCIR) maps
+/// of the F2 layer critical frequency (foF2) and propagation factor (M(3000)F2).
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeQuickData {
+    /// MODIP grid (in degrees), latitude major: latitudes from -95° to 95° (5° step),
+    /// longitudes from -190° to 190° (10° step).
+    modip_deg: Vec<f64>,
+
+    /// foF2 coefficients of each month, for both solar activity levels (R12 = 0 and 100)
+    f2: Vec<Vec<f64>>,
+
+    /// M(3000)F2 coefficients of each month, for both solar activity levels (R12 = 0 and 100)
+    fm3: Vec<Vec<f64>>,
+}
+
+impl NeQuickData {
+    /// Builds [NeQuickData] from the content of the MODIP grid file (modipNeQG_wrapped.asc)
+    /// and the 12 CCIR files (ccir11.asc to ccir22.asc), from January to December.
+    pub fn new(modip: &str, ccir: &[&str]) -> Result<Self, Error> {
+        let modip_deg = parse_values(modip, Error::InvalidNeQuickData)?;
+
+        if modip_deg.len() != MODIP_GRID_SIZE * MODIP_GRID_SIZE || ccir.len() != 12 {
+            return Err(Error::InvalidNeQuickData);
+        }
+
+        let f2_len = 2 * F2_COEFFICIENTS.0 * F2_COEFFICIENTS.1;
+        let fm3_len = 2 * FM3_COEFFICIENTS.0 * FM3_COEFFICIENTS.1;
+
+        let mut f2 = Vec::with_capacity(12);
+        let mut fm3 = Vec::with_capacity(12);
+
+        for content in ccir.iter() {
+            let mut values = parse_values(content, Error::InvalidNeQuickData)?;
+
+            if values.len() != f2_len + fm3_len {
+                return Err(Error::InvalidNeQuickData);
+            }
+
+            fm3.push(values.split_off(f2_len));
+            f2.push(values);
+        }
+
+        Ok(Self { modip_deg, f2, fm3 })
+    }
+
+    /// Returns the modified dip latitude (in degrees) at this location.
+    fn modip_deg(&self, lat_deg: f64, lon_deg: f64) -> f64 {
+        if lat_deg <= -90.0 {
+            return -90.0;
+        } else if lat_deg >= 90.0 {
+            return 90.0;
+        }
+
+        let lon_deg = (lon_deg + 180.0).rem_euclid(360.0) - 180.0;
+
+        let x = (lat_deg + 95.0) / 5.0;
+        let y = (lon_deg + 190.0) / 10.0;
+
+        let (i, j) = (x.floor() as usize - 1, y.floor() as usize - 1);
+        let (dx, dy) = (x.fract(), y.fract());
+
+        let mut z = [0.0; 4];
+
+        for (k, z) in z.iter_mut().enumerate() {
+            let column = [0, 1, 2, 3].map(|l| self.modip_deg[(i + l) * MODIP_GRID_SIZE + j + k]);
+            *z = interpolate(column, dx);
+        }
+
+        interpolate(z, dy)
+    }
+}
+
+/// Time dependent foF2 and M(3000)F2 map coefficients
+struct MapCoefficients {
+    f2: [f64; F2_COEFFICIENTS.0],
+    fm3: [f64; FM3_COEFFICIENTS.0],
+}
+
+impl MapCoefficients {
+    fn new(data: &NeQuickData, month: usize, ut_h: f64, azr: f64) -> Self {
+        let t_rad = (15.0 * ut_h - 180.0).to_radians();
+
+        let fourier = |maps: &[f64], (n_space, n_time): (usize, usize), i: usize| {
+            let coef = |k: usize| {
+                maps[i * n_time + k] * (1.0 - azr / 100.0)
+                    + maps[(n_space + i) * n_time + k] * azr / 100.0
+            };
+
+            (1..=(n_time - 1) / 2).fold(coef(0), |sum, k| {
+                let (sin, cos) = (k as f64 * t_rad).sin_cos();
+                sum + coef(2 * k - 1) * sin + coef(2 * k) * cos
+            })
+        };
+
+        Self {
+            f2: std::array::from_fn(|i| fourier(&data.f2[month - 1], F2_COEFFICIENTS, i)),
+            fm3: std::array::from_fn(|i| fourier(&data.fm3[month - 1], FM3_COEFFICIENTS, i)),
+        }
+    }
+
+    /// Evaluates the spatial functions of this map (ITU-R)
+    fn evaluate(
+        coefs: &[f64],
+        orders: &[usize],
+        modip_deg: f64,
+        lat_deg: f64,
+        lon_deg: f64,
+    ) -> f64 {
+        let sin_modip = modip_deg.to_radians().sin();
+        let cos_lat = lat_deg.to_radians().cos();
+
+        let max_order = orders.iter().copied().max().unwrap_or_default();
+
+        let powers = (0..=max_order)
+            .map(|k| sin_modip.powi(k as i32))
+            .collect::<Vec<_>>();
+
+        let mut sum = (0..=orders[0]).map(|k| coefs[k] * powers[k]).sum::<f64>();
+        let mut index = orders[0] + 1;
+
+        for (n, order) in orders.iter().enumerate().skip(1) {
+            let (sin, cos) = (n as f64 * lon_deg.to_radians()).sin_cos();
+            let cos_lat_n = cos_lat.powi(n as i32);
+
+            for power in powers.iter().take(order + 1) {
+                sum += (coefs[index] * cos + coefs[index + 1] * sin) * power * cos_lat_n;
+                index += 2;
+            }
+        }
+
+        sum
+    }
+}
+
+/// Solar declination, from the month and universal time (hours)
+struct SolarDeclination {
+    sin: f64,
+    cos: f64,
+}
+
+impl SolarDeclination {
+    fn new(month: usize, ut_h: f64) -> Self {
+        let doy = 30.5 * month as f64 - 15.0;
+        let t = doy + (18.0 - ut_h) / 24.0;
+
+        let am_rad = (0.9856 * t - 3.289).to_radians();
+        let al_rad =
+            am_rad + (1.916 * am_rad.sin() + 0.020 * (2.0 * am_rad).sin() + 282.634).to_radians();
+
+        let sin = 0.39782 * al_rad.sin();
+
+        Self {
+            sin,
+            cos: (1.0 - sin.powi(2)).sqrt(),
+        }
+    }
+}
+
+/// Electron density profile parameters, at a given location.
+/// Peak densities are expressed in 10¹¹ el.m⁻³, heights and thicknesses in km.
+struct Profile {
+    nm_f2: f64,
+    hm_e: f64,
+    hm_f1: f64,
+    hm_f2: f64,
+    b_e_top: f64,
+    b_e_bot: f64,
+    b_f1_top: f64,
+    b_f1_bot: f64,
+    b_f2_bot: f64,
+    h0: f64,
+    amplitudes: [f64; 3],
+}
+
+impl Profile {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        data: &NeQuickData,
+        maps: &MapCoefficients,
+        declination: &SolarDeclination,
+        month: usize,
+        ut_h: f64,
+        az: f64,
+        azr: f64,
+        lat_deg: f64,
+        lon_deg: f64,
+    ) -> Self {
+        let modip_deg = data.modip_deg(lat_deg, lon_deg);
+        let (sin_lat, cos_lat) = lat_deg.to_radians().sin_cos();
+
+        // solar zenith angle
+        let lt_h = ut_h + lon_deg / 15.0;
+
+        let cos_chi = sin_lat * declination.sin
+            + cos_lat * declination.cos * (std::f64::consts::PI / 12.0 * (12.0 - lt_h)).cos();
+
+        let chi_deg = (1.0 - cos_chi.powi(2))
+            .max(0.0)
+            .sqrt()
+            .atan2(cos_chi)
+            .to_degrees();
+
+        let chi_eff_deg = join(
+            90.0 - 0.24 * clip_exp(20.0 - 0.2 * chi_deg),
+            chi_deg,
+            12.0,
+            chi_deg - 86.232_927_962_116_15,
+        );
+
+        // E layer
+        let season = match month {
+            1 | 2 | 11 | 12 => -1.0,
+            3 | 4 | 9 | 10 => 0.0,
+            _ => 1.0,
+        };
+
+        let ee = clip_exp(0.3 * lat_deg);
+        let season = season * (ee - 1.0) / (ee + 1.0);
+
+        let fo_e = ((1.112 - 0.019 * season).powi(2)
+            * az.sqrt()
+            * chi_eff_deg.to_radians().cos().max(0.0).powf(0.6)
+            + 0.49)
+            .sqrt();
+
+        let nm_e = 0.124 * fo_e.powi(2);
+
+        // F2 layer
+        let fo_f2 = MapCoefficients::evaluate(&maps.f2, &F2_ORDERS, modip_deg, lat_deg, lon_deg);
+        let m3000 = MapCoefficients::evaluate(&maps.fm3, &FM3_ORDERS, modip_deg, lat_deg, lon_deg);
+
+        let nm_f2 = 0.124 * fo_f2.powi(2);
+
+        let ratio = fo_f2 / fo_e;
+        let ratio = join(ratio, 1.75, 20.0, ratio - 1.75);
+        let delta_m = 0.253 / (ratio - 1.215) - 0.012;
+
+        let hm_f2 = 1490.0
+            * m3000
+            * ((0.0196 * m3000.powi(2) + 1.0) / (1.2967 * m3000.powi(2) - 1.0)).sqrt()
+            / (m3000 + delta_m)
+            - 176.0;
+
+        // F1 layer
+        let fo_f1 = join(1.4 * fo_e, 0.0, 1000.0, fo_e - 2.0);
+        let fo_f1 = join(0.0, fo_f1, 1000.0, fo_e - fo_f1);
+        let fo_f1 = join(fo_f1, 0.85 * fo_f1, 60.0, 0.85 * fo_f2 - fo_f1);
+        let fo_f1 = if fo_f1 < 1.0E-6 { 0.0 } else { fo_f1 };
+
+        let nm_f1 = if fo_f1 <= 0.0 && fo_e > 2.0 {
+            0.124 * (fo_e + 0.5).powi(2)
+        } else {
+            0.124 * fo_f1.powi(2)
+        };
+
+        // heights and thicknesses
+        let hm_e = 120.0;
+        let hm_f1 = (hm_f2 + hm_e) / 2.0;
+
+        let b_f2_bot = 0.385 * nm_f2
+            / (0.01 * (-3.467 + 0.857 * fo_f2.powi(2).ln() + 2.02 * m3000.ln()).exp());
+
+        let b_f1_top = 0.3 * (hm_f2 - hm_f1);
+        let b_f1_bot = 0.5 * (hm_f1 - hm_e);
+        let b_e_top = b_f1_bot.max(7.0);
+        let b_e_bot = 5.0;
+
+        // Epstein amplitudes
+        let a1 = 4.0 * nm_f2;
+
+        let (a2, a3) = if fo_f1 < 0.5 {
+            (0.0, 4.0 * (nm_e - epstein(a1, b_f2_bot, hm_f2, hm_e)))
+        } else {
+            let mut a2 = 0.0;
+            let mut a3 = 4.0 * nm_e;
+
+            for _ in 0..5 {
+                a2 = 4.0
+                    * (nm_f1
+                        - epstein(a1, b_f2_bot, hm_f2, hm_f1)
+                        - epstein(a3, b_e_top, hm_e, hm_f1));
+
+                a2 = join(a2, 0.8 * nm_f1, 1.0, a2 - 0.8 * nm_f1);
+
+                a3 = 4.0
+                    * (nm_e
+                        - epstein(a2, b_f1_bot, hm_f1, hm_e)
+                        - epstein(a1, b_f2_bot, hm_f2, hm_e));
+            }
+
+            (a2, a3)
+        };
+
+        let a3 = join(a3, 0.05, 60.0, a3 - 0.005);
+
+        // topside thickness
+        let k = if (4..=9).contains(&month) {
+            6.705 - 0.014 * azr - 0.008 * hm_f2
+        } else {
+            -7.77 + 0.097 * (hm_f2 / b_f2_bot).powi(2) + 0.153 * nm_f2
+        };
+
+        let k = join(k, 2.0, 1.0, k - 2.0);
+        let k = join(8.0, k, 1.0, k - 8.0);
+
+        Self {
+            nm_f2,
+            hm_e,
+            hm_f1,
+            hm_f2,
+            b_e_top,
+            b_e_bot,
+            b_f1_top,
+            b_f1_bot,
+            b_f2_bot,
+            h0: k * b_f2_bot,
+            amplitudes: [a1, a2, a3],
+        }
+    }
+
+    /// Returns the electron density (el.m⁻³) at this altitude (km)
+    fn electron_density(&self, h_km: f64) -> f64 {
+        if h_km > self.hm_f2 {
+            self.topside_density(h_km)
+        } else {
+            self.bottomside_density(h_km)
+        }
+    }
+
+    fn topside_density(&self, h_km: f64) -> f64 {
+        const G: f64 = 0.125;
+        const R: f64 = 100.0;
+
+        let dh_km = h_km - self.hm_f2;
+        let z = dh_km / (self.h0 * (1.0 + R * G * dh_km / (R * self.h0 + G * dh_km)));
+        let ea = clip_exp(z);
+
+        if ea > 1.0E11 {
+            4.0 * self.nm_f2 / ea * 1.0E11
+        } else {
+            4.0 * self.nm_f2 * ea / (1.0 + ea).powi(2) * 1.0E11
+        }
+    }
+
+    fn bottomside_density(&self, h_km: f64) -> f64 {
+        let h = h_km.max(100.0);
+
+        let b_e = if h > self.hm_e {
+            self.b_e_top
+        } else {
+            self.b_e_bot
+        };
+
+        let b_f1 = if h > self.hm_f1 {
+            self.b_f1_top
+        } else {
+            self.b_f1_bot
+        };
+
+        let thicknesses = [self.b_f2_bot, b_f1, b_e];
+
+        // E and F1 layers fade out near the F2 peak
+        let fading = (10.0 / (1.0 + (h - self.hm_f2).abs())).exp();
+
+        let alpha = [
+            (h - self.hm_f2) / self.b_f2_bot,
+            (h - self.hm_f1) / b_f1 * fading,
+            (h - self.hm_e) / b_e * fading,
+        ];
+
+        let mut s = [0.0; 3];
+        let mut ds = [0.0; 3];
+
+        for i in 0..3 {
+            if alpha[i].abs() <= 25.0 {
+                let ee = alpha[i].exp();
+                s[i] = self.amplitudes[i] * ee / (1.0 + ee).powi(2);
+                ds[i] = (1.0 - ee) / (1.0 + ee) / thicknesses[i];
+            }
+        }
+
+        let n = s.iter().sum::<f64>();
+
+        if h_km >= 100.0 {
+            return n * 1.0E11;
+        }
+
+        // below 100 km: Chapman layer
+        let dn = s.iter().zip(ds.iter()).map(|(s, ds)| s * ds).sum::<f64>();
+        let bf = 1.0 - 10.0 * dn / n;
+        let z = (h_km - 100.0) / 10.0;
+
+        n * clip_exp(1.0 - bf * z - clip_exp(-z)) * 1.0E11
+    }
+}
+
+/// Integrates f over [a, b], with the adaptive Kronrod G7-K15 method.
+fn integrate<F: Fn(f64) -> f64>(f: &F, a: f64, b: f64, tolerance: f64, level: usize) -> f64 {
+    let center = (a + b) / 2.0;
+    let half = (b - a) / 2.0;
+
+    let f_center = f(center);
+
+    let mut kronrod = KRONROD_WEIGHTS[7] * f_center;
+    let mut gauss = GAUSS_WEIGHTS[3] * f_center;
+
+    for i in 0..7 {
+        let dx = half * KRONROD_NODES[i];
+        let sum = f(center - dx) + f(center + dx);
+
+        kronrod += KRONROD_WEIGHTS[i] * sum;
+
+        if i % 2 == 1 {
+            gauss += GAUSS_WEIGHTS[i / 2] * sum;
+        }
+    }
+
+    kronrod *= half;
+    gauss *= half;
+
+    if (kronrod - gauss).abs() <= tolerance * kronrod.abs() || level >= MAX_INTEGRATION_LEVEL {
+        kronrod
+    } else {
+        integrate(f, a, center, tolerance, level + 1)
+            + integrate(f, center, b, tolerance, level + 1)
+    }
+}
+
+/// NeQuick-G Model, driven by the Galileo broadcast ionospheric coefficients.
+/// The model is evaluated along the signal path, from the receiver to the satellite.
+#[derive(Debug, Clone)]
+pub struct NgModel {
+    /// Effective ionisation level coefficients (ai0, ai1, ai2)
+    pub a: (f64, f64, f64),
+
+    /// [NeQuickData] tables
+    pub data: Rc<NeQuickData>,
+}
+
+impl NgModel {
+    /// Returns the effective ionisation level (Az) and the effective sunspot number (Azr),
+    /// at this MODIP (degrees).
+    fn effective_ionisation(&self, modip_deg: f64) -> (f64, f64) {
+        let (a0, a1, a2) = self.a;
+
+        let az = if a0 == 0.0 && a1 == 0.0 && a2 == 0.0 {
+            63.7
+        } else {
+            (a0 + a1 * modip_deg + a2 * modip_deg.powi(2)).clamp(0.0, 400.0)
+        };
+
+        let azr = (167_273.0 + (az - 63.7) * 1123.6).sqrt() - 408.99;
+
+        (az, azr)
+    }
+
+    /// Returns the slant total electron content (TECu), between the receiver
+    /// and the satellite, both described by their geodetic coordinates
+    /// (latitude, longitude in degrees, altitude in km).
+    pub(crate) fn stec_tecu(
+        &self,
+        epoch: Epoch,
+        rx_lat_long_alt: (f64, f64, f64),
+        sv_lat_long_alt: (f64, f64, f64),
+    ) -> f64 {
+        let (_, month, _, hours, minutes, seconds, nanos) = epoch.to_gregorian_utc();
+
+        let month = month as usize;
+        let ut_h = hours as f64
+            + minutes as f64 / 60.0
+            + (seconds as f64 + nanos as f64 * 1.0E-9) / 3600.0;
+
+        let (rx_lat_deg, rx_lon_deg, rx_alt_km) = rx_lat_long_alt;
+        let (sv_lat_deg, sv_lon_deg, sv_alt_km) = sv_lat_long_alt;
+
+        let (az, azr) = self.effective_ionisation(self.data.modip_deg(rx_lat_deg, rx_lon_deg));
+
+        let maps = MapCoefficients::new(&self.data, month, ut_h, azr);
+        let declination = SolarDeclination::new(month, ut_h);
+
+        let p1 = spherical_position_km(rx_lat_deg, rx_lon_deg, rx_alt_km);
+        let p2 = spherical_position_km(sv_lat_deg, sv_lon_deg, sv_alt_km);
+
+        let d = [p2[0] - p1[0], p2[1] - p1[1], p2[2] - p1[2]];
+        let length_km = (d[0].powi(2) + d[1].powi(2) + d[2].powi(2)).sqrt();
+
+        if length_km == 0.0 {
+            return 0.0;
+        }
+
+        let u = d.map(|d| d / length_km);
+
+        let electron_density = |s_km: f64| {
+            let p = [0, 1, 2].map(|i| p1[i] + s_km * u[i]);
+            let r_km = (p[0].powi(2) + p[1].powi(2) + p[2].powi(2)).sqrt();
+
+            let lat_deg = (p[2] / r_km).asin().to_degrees();
+            let lon_deg = p[1].atan2(p[0]).to_degrees();
+
+            let profile = Profile::new(
+                &self.data,
+                &maps,
+                &declination,
+                month,
+                ut_h,
+                az,
+                azr,
+                lat_deg,
+                lon_deg,
+            );
+
+            profile.electron_density(r_km - EARTH_RADIUS_KM)
+        };
+
+        // distance along the path, to the integration split altitude
+        let r1_km = EARTH_RADIUS_KM + rx_alt_km;
+        let r_split_km = EARTH_RADIUS_KM + INTEGRATION_SPLIT_KM;
+        let p1_u = p1[0] * u[0] + p1[1] * u[1] + p1[2] * u[2];
+
+        let s_split_km = -p1_u + (p1_u.powi(2) - r1_km.powi(2) + r_split_km.powi(2)).sqrt();
+
+        // integration in km, density in el.m⁻³
+        let integral = if sv_alt_km <= INTEGRATION_SPLIT_KM {
+            integrate(&electron_density, 0.0, length_km, 1.0E-3, 0)
+        } else if rx_alt_km >= INTEGRATION_SPLIT_KM {
+            integrate(&electron_density, 0.0, length_km, 1.0E-2, 0)
+        } else {
+            integrate(&electron_density, 0.0, s_split_km, 1.0E-3, 0)
+                + integrate(&electron_density, s_split_km, length_km, 1.0E-2, 0)
+        };
+
+        integral * 1.0E-13
+    }
+
+    pub(crate) fn bias_m(&self, rtm: &BiasRuntime) -> f64 {
+        let (x_m, y_m, z_m) = rtm.sv_position_ecef_m;
+        let sv_lat_long_alt = geodetic_coordinates(x_m / 1.0E3, y_m / 1.0E3, z_m / 1.0E3);
+
+        let stec_tecu =
+            self.stec_tecu(rtm.epoch, rtm.rcvr_lat_long_alt_deg_deg_km, sv_lat_long_alt);
+
+        40.3E16 * stec_tecu / rtm.frequency_hz.powi(2)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        F2_COEFFICIENTS, FM3_COEFFICIENTS, FM3_ORDERS, MODIP_GRID_SIZE, MapCoefficients,
+        NeQuickData, NgModel, Profile, SolarDeclination, interpolate,
+    };
+
+    use crate::prelude::{Epoch, Rc};
+
+    use std::str::FromStr;
+
+    /// Synthetic [NeQuickData]: uniform 30° MODIP and uniform maps,
+    /// where foF2 is 8 MHz and M(3000)F2 is 3.0.
+    fn synthetic_data() -> NeQuickData {
+        synthetic_f2_data(8.0)
+    }
+
+    /// Synthetic [NeQuickData] like [synthetic_data], with this foF2 (MHz)
+    fn synthetic_f2_data(fo_f2_mhz: f64) -> NeQuickData {
+        let modip = vec!["30.0"; MODIP_GRID_SIZE * MODIP_GRID_SIZE].join(" ");
+
+        let mut ccir = Vec::new();
+
+        for _ in 0..2 {
+            for i in 0..F2_COEFFICIENTS.0 {
+                for k in 0..F2_COEFFICIENTS.1 {
+                    let value = if i == 0 && k == 0 { fo_f2_mhz } else { 0.0 };
+                    ccir.push(format!("{value:.8E}"));
+                }
+            }
+        }
+
+        for _ in 0..2 {
+            for i in 0..FM3_COEFFICIENTS.0 {
+                for k in 0..FM3_COEFFICIENTS.1 {
+                    let value = if i == 0 && k == 0 { 3.0 } else { 0.0 };
+                    ccir.push(format!("{value:.8E}"));
+                }
+            }
+        }
+
+        let ccir = ccir.join(" ");
+        let months = vec![ccir.as_str(); 12];
+
+        NeQuickData::new(&modip, &months).unwrap()
+    }
+
+    #[test]
+    fn third_order_interpolation() {
+        // cubic polynomials are reproduced
+        let p = |x: f64| 2.0 * x.powi(3) - x.powi(2) + 3.0 * x - 1.0;
+        let z = [p(-1.0), p(0.0), p(1.0), p(2.0)];
+
+        for dx in [0.0, 0.25, 0.5, 0.9] {
+            assert!((interpolate(z, dx) - p(dx)).abs() < 1.0E-9, "{dx}");
+        }
+    }
+
+    #[test]
+    fn nequick_data() {
+        let data = synthetic_data();
+
+        assert!((data.modip_deg(45.0, 7.0) - 30.0).abs() < 1.0E-9);
+        assert!((data.modip_deg(-89.0, 179.0) - 30.0).abs() < 1.0E-9);
+        assert_eq!(data.modip_deg(90.0, 0.0), 90.0);
+
+        assert!(NeQuickData::new("30.0", &[]).is_err());
+    }
+
+    #[test]
+    fn nequick_stec() {
+        let model = NgModel {
+            a: (0.0, 0.0, 0.0),
+            data: Rc::new(synthetic_data()),
+        };
+
+        // default effective ionisation level
+        assert_eq!(model.effective_ionisation(30.0).0, 63.7);
+
+        let t = Epoch::from_str("2020-06-25T12:00:00 UTC").unwrap();
+        let rx = (40.0, 0.0, 0.0);
+
+        // vertical: foF2 = 8 MHz, that is NmF2 = 7.9E11 el.m⁻³
+        let vertical_tecu = model.stec_tecu(t, rx, (40.0, 0.0, 20_000.0));
+        assert!(
+            vertical_tecu > 10.0 && vertical_tecu < 60.0,
+            "{vertical_tecu}"
+        );
+
+        // slant path (~39° elevation)
+        let slant_tecu = model.stec_tecu(t, rx, (0.0, 0.0, 20_000.0));
+        assert!(slant_tecu > 1.3 * vertical_tecu, "{slant_tecu}");
+
+        // receiver above the ionosphere
+        let leo_tecu = model.stec_tecu(t, (40.0, 0.0, 1_500.0), (40.0, 0.0, 20_000.0));
+        assert!(
+            leo_tecu > 0.0 && leo_tecu < 0.2 * vertical_tecu,
+            "{leo_tecu}"
+        );
+    }
+
+    #[test]
+    fn nequick_profile() {
+        let data = synthetic_data();
+        let maps = MapCoefficients::new(&data, 6, 12.0, 0.0);
+        let declination = SolarDeclination::new(6, 12.0);
+
+        let profile = Profile::new(&data, &maps, &declination, 6, 12.0, 63.7, 0.0, 40.0, 0.0);
+
+        // F2 peak
+        let nm_f2 = 0.124 * 8.0_f64.powi(2);
+        assert!((profile.nm_f2 - nm_f2).abs() < 1.0E-9);
+
+        let peak = profile.electron_density(profile.hm_f2);
+        assert!((peak / 1.0E11 - nm_f2).abs() < 1.0E-6, "{peak}");
+        assert!(profile.hm_f2 > 200.0 && profile.hm_f2 < 500.0);
+
+        // decreasing density, away from the peak
+        assert!(profile.electron_density(profile.hm_f2 + 100.0) < peak);
+        assert!(profile.electron_density(profile.hm_f2 + 1000.0) < 0.1 * peak);
+        assert!(profile.electron_density(90.0) < 0.1 * peak);
+
+        // night time, without F1 layer: the E layer amplitude is bounded
+        // below, even though the F2 layer dominates the E layer peak
+        let data = synthetic_f2_data(14.0);
+        let maps = MapCoefficients::new(&data, 6, 0.0, 0.0);
+        let declination = SolarDeclination::new(6, 0.0);
+        let profile = Profile::new(&data, &maps, &declination, 6, 0.0, 63.7, 0.0, 40.0, 0.0);
+
+        assert_eq!(profile.amplitudes[1], 0.0);
+        assert!(profile.amplitudes[2] > 0.04, "{}", profile.amplitudes[2]);
+    }
+
+    #[test]
+    fn map_spatial_functions() {
+        let (modip_deg, lat_deg, lon_deg) = (40.0_f64, 30.0_f64, 20.0_f64);
+
+        let sin_modip = modip_deg.to_radians().sin();
+        let cos_lat = lat_deg.to_radians().cos();
+
+        // M(3000)F2 orders are not uniform: the first longitude harmonic
+        // goes up to the 7th power of sin(modip), one more than the first block
+        let mut coefs = [0.0; FM3_COEFFICIENTS.0];
+
+        // first harmonic, 7th power (cosine term)
+        coefs[7 + 2 * 7] = 1.0;
+
+        // second harmonic, first term (sine term)
+        coefs[7 + 2 * 8 + 1] = 2.0;
+
+        let expected = sin_modip.powi(7) * lon_deg.to_radians().cos() * cos_lat
+            + 2.0 * (2.0 * lon_deg.to_radians()).sin() * cos_lat.powi(2);
+
+        let value = MapCoefficients::evaluate(&coefs, &FM3_ORDERS, modip_deg, lat_deg, lon_deg);
+
+        assert!((value - expected).abs() < 1.0E-12, "{value} {expected}");
+    }
+}
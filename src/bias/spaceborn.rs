@@ -10,6 +10,9 @@ pub struct SatelliteClockCorrection {
 
     /// True when relativistic effect has not been corrected.
     pub(crate) needs_relativistic_correction: bool,
+
+    /// Clock drift (s.s⁻¹)
+    pub(crate) drift_s_s: f64,
 }
 
 impl SatelliteClockCorrection {
@@ -18,6 +21,7 @@ impl SatelliteClockCorrection {
         Self {
             duration,
             needs_relativistic_correction: false,
+            drift_s_s: 0.0,
         }
     }
 
@@ -26,8 +30,16 @@ impl SatelliteClockCorrection {
         Self {
             duration,
             needs_relativistic_correction: true,
+            drift_s_s: 0.0,
         }
     }
+
+    /// Copies and returns [SatelliteClockCorrection] with the clock drift (s.s⁻¹),
+    /// which is compensated on the Doppler observations.
+    pub fn with_drift(mut self, drift_s_s: f64) -> Self {
+        self.drift_s_s = drift_s_s;
+        self
+    }
 }
 
 /// [SpacebornBias] must be implemented to determine all on-board biases.
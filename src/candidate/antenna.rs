@@ -0,0 +1,240 @@
+//! Antenna phase center corrections
+use crate::{
+    candidate::{Candidate, windup::SatelliteAttitude},
+    navigation::state::State,
+    prelude::{Antenna, Vector3},
+};
+
+impl Candidate {
+    /// Compensates the antenna phase center offsets (PCO) and variations (PCV),
+    /// on each signal. Pseudo and phase ranges are corrected so they refer to the
+    /// receiver position (marker) and the satellite center of mass.
+    ///
+    /// ## Input
+    /// - rx_state: receiver [State]
+    /// - arp_enu: possible Antenna Reference Point (ENU, m), from the marker
+    /// - rx_antenna: possible receiver [Antenna] model
+    /// - sv_antenna: possible satellite [Antenna] model. It only applies to
+    ///   orbital states that refer to the center of mass (precise orbits),
+    ///   because broadcast orbits refer to the antenna phase center.
+    /// - attitude: [SatelliteAttitude], required by the satellite PCO
+    pub(crate) fn antenna_correction(
+        &mut self,
+        rx_state: &State,
+        arp_enu: Option<(f64, f64, f64)>,
+        rx_antenna: Option<&Antenna>,
+        sv_antenna: Option<&Antenna>,
+        attitude: Option<SatelliteAttitude>,
+    ) {
+        let sv_state = self.orbit.unwrap_or_else(|| {
+            panic!("internal error: antenna correction while state is not fully resolved");
+        });
+
+        let pos_vel_m = sv_state.to_cartesian_pos_vel() * 1.0E3;
+        let r_sv = Vector3::new(pos_vel_m[0], pos_vel_m[1], pos_vel_m[2]);
+
+        let r_rx = rx_state.to_position_ecef_m();
+
+        // line of sight, from the receiver to the satellite
+        let los = (r_sv - r_rx).normalize();
+
+        let (lat_deg, long_deg, _) = rx_state.lat_long_alt_deg_deg_km;
+        let (sin_lat, cos_lat) = lat_deg.to_radians().sin_cos();
+        let (sin_long, cos_long) = long_deg.to_radians().sin_cos();
+
+        // local axes
+        let east = Vector3::new(-sin_long, cos_long, 0.0);
+        let north = Vector3::new(-sin_lat * cos_long, -sin_lat * sin_long, cos_lat);
+        let up = Vector3::new(cos_lat * cos_long, cos_lat * sin_long, sin_lat);
+
+        let zenith_deg = 90.0 - self.elevation_deg.unwrap_or(90.0);
+        let azimuth_deg = self.azimuth_deg.unwrap_or_default();
+
+        let arp_enu_m = arp_enu.unwrap_or_default();
+
+        // satellite antenna boresight (Z axis) and nadir angle
+        let e_z = -r_sv.normalize();
+        let nadir_deg = e_z.dot(&-los).clamp(-1.0, 1.0).acos().to_degrees();
+
+        let sv_antenna = sv_antenna.filter(|_| self.precise_orbit);
+
+        for ob in self.observations.iter_mut() {
+            // range excess (m) due to the antenna phase centers
+            let mut correction_m = 0.0;
+
+            let (mut east_m, mut north_m, mut up_m) = arp_enu_m;
+
+            if let Some((antenna, freq)) = rx_antenna.and_then(|antenna| {
+                let freq = antenna.frequency(self.sv.constellation, ob.carrier)?;
+                Some((antenna, freq))
+            }) {
+                north_m += freq.pco_m.0;
+                east_m += freq.pco_m.1;
+                up_m += freq.pco_m.2;
+                correction_m += antenna.pcv_m(freq, zenith_deg, azimuth_deg);
+            }
+
+            correction_m -= los.dot(&(east * east_m + north * north_m + up * up_m));
+
+            if let Some((antenna, freq)) = sv_antenna.and_then(|antenna| {
+                let freq = antenna.frequency(self.sv.constellation, ob.carrier)?;
+                Some((antenna, freq))
+            }) {
+                if let Some(attitude) = attitude {
+                    let (x_m, y_m, z_m) = freq.pco_m;
+                    let offset_m = attitude.x * x_m + attitude.y * y_m + e_z * z_m;
+                    correction_m += los.dot(&offset_m);
+                }
+
+                correction_m += antenna.pcv_m(freq, nadir_deg, 0.0);
+            }
+
+            if let Some(pseudo_range_m) = ob.pseudo_range_m.as_mut() {
+                *pseudo_range_m -= correction_m;
+            }
+
+            if let Some(phase_range_m) = ob.phase_range_m.as_mut() {
+                *phase_range_m -= correction_m;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        candidate::windup::SatelliteAttitude,
+        navigation::state::State,
+        prelude::{
+            Antenna, AntennaFrequency, AntennaKind, Candidate, Carrier, Constellation, Epoch,
+            Observation, Orbit, SV, Vector3,
+        },
+        tests::earth_frame,
+    };
+
+    use anise::math::Vector6;
+
+    use std::str::FromStr;
+
+    const EARTH_RADIUS_KM: f64 = 6378.137;
+
+    const ORBIT_RADIUS_KM: f64 = 26_560.0;
+
+    /// Receiver located at (0°, 0°), observing a satellite at its zenith
+    fn zenith_geometry() -> (State, Candidate) {
+        let t = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let frame = earth_frame();
+
+        let rx_orbit = Orbit::from_cartesian_pos_vel(
+            Vector6::new(EARTH_RADIUS_KM, 0.0, 0.0, 0.0, 0.0, 0.0),
+            t,
+            frame,
+        );
+
+        let sv_orbit = Orbit::from_cartesian_pos_vel(
+            Vector6::new(ORBIT_RADIUS_KM, 0.0, 0.0, 0.0, 3.874, 0.0),
+            t,
+            frame,
+        );
+
+        let range_m = (ORBIT_RADIUS_KM - EARTH_RADIUS_KM) * 1.0E3;
+
+        let mut cd = Candidate::new(
+            SV::new(Constellation::GPS, 1),
+            t,
+            vec![
+                Observation::pseudo_range(Carrier::L1, range_m, None)
+                    .with_ambiguous_phase_range_m(range_m),
+            ],
+        );
+
+        cd.orbit = Some(sv_orbit);
+        cd.precise_orbit = true;
+        cd.elevation_deg = Some(90.0);
+        cd.azimuth_deg = Some(0.0);
+
+        (State::from_orbit(&rx_orbit).unwrap(), cd)
+    }
+
+    /// [Antenna] with this phase center offset on L1, without variations
+    fn antenna(kind: AntennaKind, pco_m: (f64, f64, f64)) -> Antenna {
+        Antenna {
+            kind,
+            zenith_grid_deg: (0.0, 90.0, 5.0),
+            azimuth_step_deg: 0.0,
+            valid_from: None,
+            valid_until: None,
+            frequencies: vec![AntennaFrequency {
+                constellation: Constellation::GPS,
+                carrier: Carrier::L1,
+                pco_m,
+                pcv_noazi_m: vec![0.0; 19],
+                pcv_m: Vec::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn receiver_phase_center_offset() {
+        let (state, mut cd) = zenith_geometry();
+        let range_m = cd.observations[0].pseudo_range_m.unwrap();
+
+        let rx_antenna = antenna(
+            AntennaKind::Receiver {
+                model: "TEST NONE".to_string(),
+                serial: String::default(),
+            },
+            (0.0, 0.0, 0.09),
+        );
+
+        cd.antenna_correction(&state, None, Some(&rx_antenna), None, None);
+
+        // the phase center is 9cm above the marker: the measured range is 9cm shorter
+        let ob = &cd.observations[0];
+        assert!((ob.pseudo_range_m.unwrap() - range_m - 0.09).abs() < 1.0E-6);
+        assert!((ob.phase_range_m.unwrap() - range_m - 0.09).abs() < 1.0E-6);
+
+        // horizontal offsets are orthogonal to the line of sight
+        let (_, mut cd) = zenith_geometry();
+        let rx_antenna = antenna(rx_antenna.kind, (0.1, 0.2, 0.0));
+
+        cd.antenna_correction(&state, Some((0.3, 0.4, 0.0)), Some(&rx_antenna), None, None);
+        assert!((cd.observations[0].pseudo_range_m.unwrap() - range_m).abs() < 1.0E-6);
+    }
+
+    #[test]
+    fn satellite_phase_center_offset() {
+        let (state, mut cd) = zenith_geometry();
+        let range_m = cd.observations[0].pseudo_range_m.unwrap();
+
+        let sv_antenna = antenna(
+            AntennaKind::Satellite {
+                sv: cd.sv,
+                svn: "G063".to_string(),
+                cospar: None,
+                block: "BLOCK IIF".to_string(),
+            },
+            (0.0, 0.0, 1.5),
+        );
+
+        let attitude = SatelliteAttitude {
+            yaw_rad: Some(0.0),
+            x: Vector3::new(0.0, 0.0, 1.0),
+            y: Vector3::new(0.0, 1.0, 0.0),
+        };
+
+        cd.antenna_correction(&state, None, None, Some(&sv_antenna), Some(attitude));
+
+        // the z offset points to the Earth center, along the line of sight:
+        // the phase center is 1.5m closer to the receiver than the center of mass
+        let ob = &cd.observations[0];
+        assert!((ob.pseudo_range_m.unwrap() - range_m - 1.5).abs() < 1.0E-6);
+
+        // broadcast orbits refer to the phase center: no correction
+        let (_, mut cd) = zenith_geometry();
+        cd.precise_orbit = false;
+
+        cd.antenna_correction(&state, None, None, Some(&sv_antenna), Some(attitude));
+        assert!((cd.observations[0].pseudo_range_m.unwrap() - range_m).abs() < 1.0E-6);
+    }
+}
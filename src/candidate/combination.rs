@@ -16,7 +16,50 @@ pub(crate) struct Combination {
     pub value: f64,
 }
 
+/// Triple frequency [TripleCombination]
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) struct TripleCombination {
+    /// Reference, subsidary and third [Carrier] signals
+    pub carriers: (Carrier, Carrier, Carrier),
+
+    /// Value
+    pub value: f64,
+}
+
+/// Returns the coefficients (a_1, a_j, a_k) of the minimal noise, first order ionosphere free,
+/// triple frequency combination. They preserve the geometry (a_1 + a_j + a_k = 1),
+/// cancel the ionosphere (sum of a_i * f_1² / f_i² = 0) and minimize a_1² + a_j² + a_k².
+pub(crate) fn triple_if_coefficients(c_1: Carrier, c_j: Carrier, c_k: Carrier) -> (f64, f64, f64) {
+    let f_1 = c_1.frequency_hz();
+
+    let gamma = [
+        1.0,
+        (f_1 / c_j.frequency_hz()).powi(2),
+        (f_1 / c_k.frequency_hz()).powi(2),
+    ];
+
+    let s_1 = gamma.iter().sum::<f64>();
+    let s_2 = gamma.iter().map(|g| g.powi(2)).sum::<f64>();
+    let det = 3.0 * s_2 - s_1.powi(2);
+
+    // Lagrange multipliers
+    let (mu_1, mu_2) = (s_2 / det, -s_1 / det);
+
+    (
+        mu_1 + mu_2 * gamma[0],
+        mu_1 + mu_2 * gamma[1],
+        mu_1 + mu_2 * gamma[2],
+    )
+}
+
 impl Candidate {
+    /// Returns the designation of this [Carrier] within the signal plan of this
+    /// satellite, so combinations of signals sharing a frequency (like L5 and E5a)
+    /// are described identically, whatever the receiver designation.
+    fn plan_carrier(&self, carrier: Carrier) -> Carrier {
+        carrier.for_constellation(self.sv.constellation)
+    }
+
     /// Returns an IF code range [Combination] using prefered available frequencies
     pub(crate) fn code_if_combination(&self) -> Option<Combination> {
         let (c1, p1) = self.l1_pseudo_range()?;
@@ -30,8 +73,8 @@ impl Candidate {
         let freq = f1_hz * fj_hz / (f1pow + fjpow).sqrt();
 
         Some(Combination {
-            lhs: cj,
-            rhs: c1,
+            lhs: self.plan_carrier(cj),
+            rhs: self.plan_carrier(c1),
             lambda: SPEED_OF_LIGHT_M_S / freq,
             value: (f1pow * p1 - fjpow * pj) / (f1pow - fjpow),
         })
@@ -50,8 +93,8 @@ impl Candidate {
         let freq = f1_hz * fj_hz / (f1pow + fjpow).sqrt();
 
         Some(Combination {
-            lhs: cj,
-            rhs: c1,
+            lhs: self.plan_carrier(cj),
+            rhs: self.plan_carrier(c1),
             lambda: SPEED_OF_LIGHT_M_S / freq,
             value: (f1pow * l1 - fjpow * lj) / (f1pow - fjpow),
         })
@@ -66,8 +109,8 @@ impl Candidate {
         let fj = cj.frequency_hz();
 
         Some(Combination {
-            lhs: cj,
-            rhs: c1,
+            lhs: self.plan_carrier(cj),
+            rhs: self.plan_carrier(c1),
             lambda: SPEED_OF_LIGHT_M_S / (f1 - fj),
             value: (f1 * l1 - fj * lj) / (f1 - fj),
         })
@@ -82,25 +125,63 @@ impl Candidate {
         let fj = cj.frequency_hz();
 
         Some(Combination {
-            lhs: cj,
-            rhs: c1,
+            lhs: self.plan_carrier(cj),
+            rhs: self.plan_carrier(c1),
             lambda: SPEED_OF_LIGHT_M_S / (f1 + fj),
             value: (f1 * p1 + fj * pj) / (f1 + fj),
         })
     }
 
-    // /// Returns MW [Combination]
-    // pub(crate) fn mw_combination(&self) -> Option<Combination> {
-    //     let ph_w = self.phase_wl_combination()?;
-    //     let pr_n = self.code_nl_combination()?;
+    /// Returns the triple frequency IF code range [TripleCombination],
+    /// see [triple_if_coefficients].
+    pub(crate) fn code_tf_if_combination(&self) -> Option<TripleCombination> {
+        let (c_1, p_1) = self.l1_pseudo_range()?;
+        let (c_j, p_j) = self.subsidary_pseudo_range()?;
+        let (c_k, p_k) = self.third_pseudo_range()?;
+
+        let (a_1, a_j, a_k) = triple_if_coefficients(c_1, c_j, c_k);
+
+        Some(TripleCombination {
+            carriers: (
+                self.plan_carrier(c_1),
+                self.plan_carrier(c_j),
+                self.plan_carrier(c_k),
+            ),
+            value: a_1 * p_1 + a_j * p_j + a_k * p_k,
+        })
+    }
+
+    /// Returns the triple frequency IF phase range [TripleCombination],
+    /// see [triple_if_coefficients].
+    pub(crate) fn phase_tf_if_combination(&self) -> Option<TripleCombination> {
+        let (c_1, l_1) = self.l1_phase_range()?;
+        let (c_j, l_j) = self.subsidary_phase_range()?;
+        let (c_k, l_k) = self.third_phase_range()?;
+
+        let (a_1, a_j, a_k) = triple_if_coefficients(c_1, c_j, c_k);
+
+        Some(TripleCombination {
+            carriers: (
+                self.plan_carrier(c_1),
+                self.plan_carrier(c_j),
+                self.plan_carrier(c_k),
+            ),
+            value: a_1 * l_1 + a_j * l_j + a_k * l_k,
+        })
+    }
 
-    //     Some(Combination {
-    //         lhs: ph_w.lhs,
-    //         rhs: ph_w.rhs,
-    //         value: ph_w.value - pr_n.value,
-    //         lambda: ph_w.lambda,
-    //     })
-    // }
+    /// Returns MW [Combination]
+    pub(crate) fn mw_combination(&self) -> Option<Combination> {
+        let ph_w = self.phase_wl_combination()?;
+        let pr_n = self.code_nl_combination()?;
+
+        Some(Combination {
+            lhs: ph_w.lhs,
+            rhs: ph_w.rhs,
+            value: ph_w.value - pr_n.value,
+            lambda: ph_w.lambda,
+        })
+    }
 
     /// Form GF [Combination]
     #[cfg(test)]
@@ -108,21 +189,20 @@ impl Candidate {
         let (c1, p1) = self.l1_pseudo_range()?;
         let (c2, p2) = self.subsidary_pseudo_range()?;
         Some(Combination {
-            lhs: c2,
-            rhs: c1,
+            lhs: self.plan_carrier(c2),
+            rhs: self.plan_carrier(c1),
             value: p2 - p1,
             lambda: SPEED_OF_LIGHT_M_S / c1.frequency_hz(),
         })
     }
 
     /// Returns GF [Combination]
-    #[cfg(test)]
     pub(crate) fn phase_gf_combination(&self) -> Option<Combination> {
         let (c1, l1) = self.l1_phase_range()?;
         let (c2, l2) = self.subsidary_phase_range()?;
         Some(Combination {
-            lhs: c2,
-            rhs: c1,
+            lhs: self.plan_carrier(c2),
+            rhs: self.plan_carrier(c1),
             value: l1 - l2,
             lambda: SPEED_OF_LIGHT_M_S / c1.frequency_hz(),
         })
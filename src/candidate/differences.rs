@@ -3,10 +3,36 @@ use log::debug;
 use std::collections::HashMap;
 
 use crate::{
+    candidate::combination::triple_if_coefficients,
     constants::SPEED_OF_LIGHT_M_S,
     prelude::{Carrier, SV},
 };
 
+/// Integer ambiguities (in cycles) resolved by the triple frequency cascade.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct CascadedAmbiguities {
+    /// Extra wide lane (subsidary - third) ambiguity
+    pub n_ewl: f64,
+
+    /// Wide lane (reference - subsidary) ambiguity
+    pub n_wl: f64,
+
+    /// Reference signal ambiguity
+    pub n_1: f64,
+}
+
+impl CascadedAmbiguities {
+    /// Returns the subsidary signal ambiguity
+    pub fn n_j(&self) -> f64 {
+        self.n_1 - self.n_wl
+    }
+
+    /// Returns the third signal ambiguity
+    pub fn n_k(&self) -> f64 {
+        self.n_j() - self.n_ewl
+    }
+}
+
 #[derive(Default, Debug)]
 pub(crate) struct Difference {
     /// Code difference
@@ -15,6 +41,9 @@ pub(crate) struct Difference {
     /// Extra code difference
     pub code_j: Option<(Carrier, f64)>,
 
+    /// Third code difference
+    pub code_k: Option<(Carrier, f64)>,
+
     /// Code IF difference
     pub code_if: Option<(Carrier, f64)>,
 
@@ -24,6 +53,9 @@ pub(crate) struct Difference {
     /// Extra phase difference
     pub phase_j: Option<(Carrier, f64)>,
 
+    /// Third phase difference
+    pub phase_k: Option<(Carrier, f64)>,
+
     /// Phase IF difference
     pub phase_if: Option<(Carrier, f64, f64)>,
 
@@ -32,6 +64,9 @@ pub(crate) struct Difference {
 
     /// Cn difference
     pub cn: Option<(Carrier, f64, f64)>,
+
+    /// Glonass FDMA frequency channel difference, to the pivot satellite
+    pub channel_delta: i8,
 }
 
 impl std::fmt::Display for Difference {
@@ -48,6 +83,10 @@ impl std::fmt::Display for Difference {
             writeln!(f, "code({carrier})={code}")?;
         }
 
+        if let Some((carrier, code)) = &self.code_k {
+            writeln!(f, "code({carrier})={code}")?;
+        }
+
         if let Some((carrier, code)) = &self.code_if {
             writeln!(f, "code_if({carrier})={code}")?;
         }
@@ -60,6 +99,10 @@ impl std::fmt::Display for Difference {
             writeln!(f, "phase({carrier})={phase}")?;
         }
 
+        if let Some((carrier, phase)) = &self.phase_k {
+            writeln!(f, "phase({carrier})={phase}")?;
+        }
+
         if let Some((carrier, _, phase_if)) = &self.phase_if {
             writeln!(f, "phase_if({carrier})={phase_if} ")?;
         }
@@ -80,43 +123,67 @@ impl Difference {
     pub fn is_some(&self) -> bool {
         self.code.is_some()
             || self.code_j.is_some()
+            || self.code_k.is_some()
             || self.code_if.is_some()
             || self.phase.is_some()
             || self.phase_j.is_some()
+            || self.phase_k.is_some()
             || self.phase_if.is_some()
             || self.cn.is_some()
             || self.lw.is_some()
     }
 
+    /// True if this [Difference] contains phase observations, which
+    /// carry the ambiguities of the reference site it was formed against.
+    pub fn has_phase(&self) -> bool {
+        self.phase.is_some()
+            || self.phase_j.is_some()
+            || self.phase_k.is_some()
+            || self.phase_if.is_some()
+            || self.lw.is_some()
+    }
+
+    /// True if this [Difference] contains the three phase observations
+    /// of the triple frequency cascade.
+    pub fn has_triple_phase(&self) -> bool {
+        self.phase.is_some() && self.phase_j.is_some() && self.phase_k.is_some()
+    }
+
     pub fn with_code(mut self, value: (Carrier, f64)) -> Self {
-        assert!(value.0.is_l1(), "internal error: this is not L1 frequency!");
         self.code = Some(value);
         self
     }
 
     pub fn with_code_j(mut self, value: (Carrier, f64)) -> Self {
-        assert!(!value.0.is_l1(), "internal error: invalid frequency!");
         self.code_j = Some(value);
         self
     }
 
+    pub fn with_code_k(mut self, value: (Carrier, f64)) -> Self {
+        self.code_k = Some(value);
+        self
+    }
+
     pub fn with_code_if(mut self, value: (Carrier, f64)) -> Self {
         self.code_if = Some(value);
         self
     }
 
     pub fn with_phase(mut self, value: (Carrier, f64)) -> Self {
-        assert!(value.0.is_l1(), "internal issue: this is not L1 frequency!");
         self.phase = Some(value);
         self
     }
 
     pub fn with_phase_j(mut self, value: (Carrier, f64)) -> Self {
-        assert!(!value.0.is_l1(), "internal issue: this is L1 signal");
         self.phase_j = Some(value);
         self
     }
 
+    pub fn with_phase_k(mut self, value: (Carrier, f64)) -> Self {
+        self.phase_k = Some(value);
+        self
+    }
+
     pub fn with_phase_if(mut self, value: (Carrier, f64, f64)) -> Self {
         self.phase_if = Some(value);
         self
@@ -132,6 +199,51 @@ impl Difference {
         self
     }
 
+    /// Applies a geometric range offset (in meters) to all differenced observations.
+    /// This is used to translate a [Difference] formed against one reference site,
+    /// to another reference position.
+    pub fn geometric_shift_mut(&mut self, offset_m: f64) {
+        for (_, value) in [
+            &mut self.code,
+            &mut self.code_j,
+            &mut self.code_k,
+            &mut self.code_if,
+            &mut self.phase,
+            &mut self.phase_j,
+            &mut self.phase_k,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *value += offset_m;
+        }
+
+        for (_, _, value) in [&mut self.phase_if, &mut self.lw, &mut self.cn]
+            .into_iter()
+            .flatten()
+        {
+            *value += offset_m;
+        }
+    }
+
+    /// Compensates the Glonass inter-frequency code bias between both receivers,
+    /// modeled as linear with the frequency channel number (m per channel).
+    pub fn ifb_correction_mut(&mut self, ifb_m_per_channel: f64) {
+        let offset_m = ifb_m_per_channel * self.channel_delta as f64;
+
+        for (_, value) in [
+            &mut self.code,
+            &mut self.code_j,
+            &mut self.code_k,
+            &mut self.code_if,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *value -= offset_m;
+        }
+    }
+
     /// Obtain precise phase IF from purely differenced measurements.
     pub fn phase_if(&self, sv: SV) -> Option<f64> {
         let (c_1, phase_1) = self.phase?;
@@ -160,6 +272,97 @@ impl Difference {
         debug!("{sv} - Nif={nif}");
         Some(phase_if - nif)
     }
+
+    /// Obtain the triple frequency IF code from purely differenced measurements.
+    pub fn code_tf_if(&self) -> Option<f64> {
+        let (c_1, code_1) = self.code?;
+        let (c_j, code_j) = self.code_j?;
+        let (c_k, code_k) = self.code_k?;
+
+        let (a_1, a_j, a_k) = triple_if_coefficients(c_1, c_j, c_k);
+
+        Some(a_1 * code_1 + a_j * code_j + a_k * code_k)
+    }
+
+    /// Resolves the phase ambiguities of purely differenced measurements, in cascade:
+    /// - the extra wide lane (subsidary - third) ambiguity, from the
+    ///   Melbourne-Wübbena combination of the subsidary and third signals
+    /// - the wide lane (reference - subsidary) ambiguity, using the
+    ///   ambiguity fixed extra wide lane as geometric range
+    /// - the reference signal ambiguity, from the geometry free phase combination.
+    ///
+    /// Each step is only fixed when its float ambiguity lies within `max_fraction_cycles`
+    /// of the nearest integer, otherwise the ambiguities remain unresolved.
+    /// Residual ionospheric delay is considered cancelled (short baseline),
+    /// the geometry free step is the first one to fail when it is not.
+    pub fn cascaded_ambiguities(&self, max_fraction_cycles: f64) -> Option<CascadedAmbiguities> {
+        let (c_1, phase_1) = self.phase?;
+        let (c_j, phase_j) = self.phase_j?;
+        let (c_k, phase_k) = self.phase_k?;
+        let (_, code_j) = self.code_j?;
+        let (_, code_k) = self.code_k?;
+
+        let (f_1, f_j, f_k) = (c_1.frequency_hz(), c_j.frequency_hz(), c_k.frequency_hz());
+
+        let fix = |step: &str, float_cycles: f64| {
+            let fixed = float_cycles.round();
+            let fraction = (float_cycles - fixed).abs();
+
+            if fraction > max_fraction_cycles {
+                debug!("{step} ambiguity not fixed: {float_cycles:.3} cycles");
+                None
+            } else {
+                Some(fixed)
+            }
+        };
+
+        // extra wide lane
+        let lambda_ewl = SPEED_OF_LIGHT_M_S / (f_j - f_k);
+        let phase_ewl = (f_j * phase_j - f_k * phase_k) / (f_j - f_k);
+        let code_nl = (f_j * code_j + f_k * code_k) / (f_j + f_k);
+        let n_ewl = fix("extra wide lane", (phase_ewl - code_nl) / lambda_ewl)?;
+
+        // wide lane
+        let lambda_wl = SPEED_OF_LIGHT_M_S / (f_1 - f_j);
+        let phase_wl = (f_1 * phase_1 - f_j * phase_j) / (f_1 - f_j);
+        let range_ewl = phase_ewl - lambda_ewl * n_ewl;
+        let n_wl = fix("wide lane", (phase_wl - range_ewl) / lambda_wl)?;
+
+        // reference signal
+        let (lambda_1, lambda_j) = (SPEED_OF_LIGHT_M_S / f_1, SPEED_OF_LIGHT_M_S / f_j);
+        let n_1 = fix(
+            "reference signal",
+            (phase_1 - phase_j - lambda_j * n_wl) / (lambda_1 - lambda_j),
+        )?;
+
+        Some(CascadedAmbiguities { n_ewl, n_wl, n_1 })
+    }
+
+    /// Obtain the ambiguity fixed, triple frequency IF phase from purely differenced
+    /// measurements. Ambiguities are resolved with [Self::cascaded_ambiguities].
+    pub fn phase_tf_if(&self, sv: SV, max_fraction_cycles: f64) -> Option<f64> {
+        let (c_1, phase_1) = self.phase?;
+        let (c_j, phase_j) = self.phase_j?;
+        let (c_k, phase_k) = self.phase_k?;
+
+        let ambiguities = self.cascaded_ambiguities(max_fraction_cycles)?;
+
+        let (a_1, a_j, a_k) = triple_if_coefficients(c_1, c_j, c_k);
+
+        let phase_tf_if = a_1 * phase_1 + a_j * phase_j + a_k * phase_k;
+
+        let ntf_if = a_1 * c_1.wavelength() * ambiguities.n_1
+            + a_j * c_j.wavelength() * ambiguities.n_j()
+            + a_k * c_k.wavelength() * ambiguities.n_k();
+
+        debug!(
+            "{sv} - Newl={}, Nw={}, N1={}",
+            ambiguities.n_ewl, ambiguities.n_wl, ambiguities.n_1
+        );
+
+        debug!("{sv} - Ntf_if={ntf_if}");
+        Some(phase_tf_if - ntf_if)
+    }
 }
 
 /// [Differences] is a set to manage Single or Double [Difference]s.
@@ -179,12 +382,20 @@ impl Differences {
         let mut ret = Self::default();
 
         for (sat, difference) in self.inner.iter() {
-            let mut diff = Difference::default();
+            let mut diff = Difference {
+                channel_delta: difference.channel_delta,
+                ..Default::default()
+            };
+
+            // both sites may designate a shared frequency differently (like L5 and E5a)
+            let same_signal = |c_1: Carrier, c_2: Carrier| {
+                c_1.for_constellation(sat.constellation) == c_2.for_constellation(sat.constellation)
+            };
 
             if let Some((_, rhs_diff)) = rhs.inner.iter().find(|(rhs_sat, _)| *rhs_sat == sat) {
                 if let Some((c_1, p_1)) = difference.code {
                     if let Some((c_2, p_2)) = rhs_diff.code {
-                        if c_1 == c_2 {
+                        if same_signal(c_1, c_2) {
                             diff.code = Some((c_1, p_1 - p_2));
                         }
                     }
@@ -192,15 +403,23 @@ impl Differences {
 
                 if let Some((c_1, p_1)) = difference.code_j {
                     if let Some((c_2, p_2)) = rhs_diff.code_j {
-                        if c_1 == c_2 {
+                        if same_signal(c_1, c_2) {
                             diff.code_j = Some((c_1, p_1 - p_2));
                         }
                     }
                 }
 
+                if let Some((c_1, p_1)) = difference.code_k {
+                    if let Some((c_2, p_2)) = rhs_diff.code_k {
+                        if same_signal(c_1, c_2) {
+                            diff.code_k = Some((c_1, p_1 - p_2));
+                        }
+                    }
+                }
+
                 if let Some((c_1, p_1)) = difference.code_if {
                     if let Some((c_2, p_2)) = rhs_diff.code_if {
-                        if c_1 == c_2 {
+                        if same_signal(c_1, c_2) {
                             diff.code_if = Some((c_1, p_1 - p_2));
                         }
                     }
@@ -208,7 +427,7 @@ impl Differences {
 
                 if let Some((c_1, p_1)) = difference.phase {
                     if let Some((c_2, p_2)) = rhs_diff.phase {
-                        if c_1 == c_2 {
+                        if same_signal(c_1, c_2) {
                             diff.phase = Some((c_1, p_1 - p_2));
                         }
                     }
@@ -216,15 +435,23 @@ impl Differences {
 
                 if let Some((c_1, p_1)) = difference.phase_j {
                     if let Some((c_2, p_2)) = rhs_diff.phase_j {
-                        if c_1 == c_2 {
+                        if same_signal(c_1, c_2) {
                             diff.phase_j = Some((c_1, p_1 - p_2));
                         }
                     }
                 }
 
+                if let Some((c_1, p_1)) = difference.phase_k {
+                    if let Some((c_2, p_2)) = rhs_diff.phase_k {
+                        if same_signal(c_1, c_2) {
+                            diff.phase_k = Some((c_1, p_1 - p_2));
+                        }
+                    }
+                }
+
                 if let Some((c_1, lambda_1, p_1)) = difference.phase_if {
                     if let Some((c_2, _, p_2)) = rhs_diff.phase_if {
-                        if c_1 == c_2 {
+                        if same_signal(c_1, c_2) {
                             diff.phase_if = Some((c_1, lambda_1, p_1 - p_2));
                         }
                     }
@@ -232,7 +459,7 @@ impl Differences {
 
                 if let Some((c_1, lambda_1, p_1)) = difference.lw {
                     if let Some((c_2, _, p_2)) = rhs_diff.lw {
-                        if c_1 == c_2 {
+                        if same_signal(c_1, c_2) {
                             diff.lw = Some((c_1, lambda_1, p_1 - p_2));
                         }
                     }
@@ -240,7 +467,7 @@ impl Differences {
 
                 if let Some((c_1, lambda_1, p_1)) = difference.cn {
                     if let Some((c_2, _, p_2)) = rhs_diff.cn {
-                        if c_1 == c_2 {
+                        if same_signal(c_1, c_2) {
                             diff.cn = Some((c_1, lambda_1, p_1 - p_2));
                         }
                     }
@@ -255,6 +482,82 @@ impl Differences {
         ret
     }
 
+    /// Forms the weighted mean of several [Differences] (for example, formed against
+    /// several reference sites that share the same reference position).
+    /// Code observations are averaged over all sets that provide them, on the same signal.
+    /// Each site carries its own integer ambiguities, so phase observations are never averaged:
+    /// they are picked from the heaviest set that provides phase for this [SV].
+    pub fn weighted_mean(sets: &[(Self, f64)]) -> Self {
+        let mut ret = Self::default();
+
+        for sat in sets.iter().flat_map(|(set, _)| set.inner.keys()) {
+            if ret.inner.contains_key(sat) {
+                continue;
+            }
+
+            let diffs = sets
+                .iter()
+                .filter_map(|(set, weight)| Some((set.inner.get(sat)?, *weight)))
+                .collect::<Vec<_>>();
+
+            let phase_ref = diffs
+                .iter()
+                .filter(|(d, _)| d.has_phase())
+                .max_by(|(_, w_a), (_, w_b)| w_a.total_cmp(w_b))
+                .map(|(d, _)| *d);
+
+            let diff = Difference {
+                code: Self::weighted_mean_2(*sat, diffs.iter().map(|(d, w)| (d.code, *w))),
+                code_j: Self::weighted_mean_2(*sat, diffs.iter().map(|(d, w)| (d.code_j, *w))),
+                code_k: Self::weighted_mean_2(*sat, diffs.iter().map(|(d, w)| (d.code_k, *w))),
+                code_if: Self::weighted_mean_2(*sat, diffs.iter().map(|(d, w)| (d.code_if, *w))),
+                phase: phase_ref.and_then(|d| d.phase),
+                phase_j: phase_ref.and_then(|d| d.phase_j),
+                phase_k: phase_ref.and_then(|d| d.phase_k),
+                phase_if: phase_ref.and_then(|d| d.phase_if),
+                lw: phase_ref.and_then(|d| d.lw),
+                cn: phase_ref.and_then(|d| d.cn),
+                channel_delta: diffs
+                    .first()
+                    .map(|(d, _)| d.channel_delta)
+                    .unwrap_or_default(),
+            };
+
+            if diff.is_some() {
+                ret.insert(*sat, diff);
+            }
+        }
+
+        ret
+    }
+
+    fn weighted_mean_2(
+        sv: SV,
+        values: impl Iterator<Item = (Option<(Carrier, f64)>, f64)>,
+    ) -> Option<(Carrier, f64)> {
+        let (mut carrier, mut sum, mut sum_w) = (None, 0.0, 0.0);
+
+        for (value, weight) in values {
+            if let Some((c, value)) = value {
+                let c = c.for_constellation(sv.constellation);
+
+                if *carrier.get_or_insert(c) == c {
+                    sum += value * weight;
+                    sum_w += weight;
+                }
+            }
+        }
+
+        Some((carrier?, sum / sum_w))
+    }
+
+    /// Compensates the Glonass inter-frequency code bias, see [Difference::ifb_correction_mut].
+    pub fn ifb_correction_mut(&mut self, ifb_m_per_channel: f64) {
+        for diff in self.inner.values_mut() {
+            diff.ifb_correction_mut(ifb_m_per_channel);
+        }
+    }
+
     /// Obtain [Difference] for this [SV] if it exists.
     pub fn difference(&self, sv: SV) -> Option<&Difference> {
         self.inner.get(&sv)
@@ -264,3 +567,85 @@ impl Differences {
         self.inner.len()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        candidate::differences::{Difference, Differences},
+        prelude::Carrier,
+        tests::{E01, E03, E05, E09},
+    };
+
+    #[test]
+    fn difference_geometric_shift() {
+        let mut diff = Difference::default()
+            .with_code((Carrier::L1, 10.0))
+            .with_phase((Carrier::L1, 20.0))
+            .with_lw((Carrier::L1, 0.86, 30.0));
+
+        diff.geometric_shift_mut(-2.5);
+
+        assert_eq!(diff.code, Some((Carrier::L1, 7.5)));
+        assert_eq!(diff.phase, Some((Carrier::L1, 17.5)));
+        assert_eq!(diff.lw, Some((Carrier::L1, 0.86, 27.5)));
+
+        // missing observations are not created
+        assert!(diff.code_j.is_none());
+        assert!(diff.phase_if.is_none());
+    }
+
+    #[test]
+    fn network_weighted_mean() {
+        let mut close = Differences::default();
+        let mut far = Differences::default();
+
+        close.insert(
+            E01,
+            Difference::default()
+                .with_code((Carrier::L1, 1.0))
+                .with_phase((Carrier::L1, 0.1)),
+        );
+
+        far.insert(
+            E01,
+            Difference::default()
+                .with_code((Carrier::L1, 4.0))
+                .with_phase((Carrier::L1, 0.4)),
+        );
+
+        // only observed by the far site
+        far.insert(E03, Difference::default().with_code((Carrier::L1, 5.0)));
+
+        // phase only tracked by the far site
+        close.insert(E05, Difference::default().with_code((Carrier::L1, 1.0)));
+
+        far.insert(
+            E05,
+            Difference::default()
+                .with_code((Carrier::L1, 4.0))
+                .with_phase((Carrier::L1, 0.4))
+                .with_lw((Carrier::L1, 0.86, 0.5)),
+        );
+
+        let mean = Differences::weighted_mean(&[(close, 2.0), (far, 1.0)]);
+
+        assert_eq!(mean.ndf(), 3);
+
+        let e01 = mean.difference(E01).unwrap();
+        assert_eq!(e01.code, Some((Carrier::L1, 2.0)));
+
+        // phase is not averaged: picked from the heaviest site
+        assert_eq!(e01.phase, Some((Carrier::L1, 0.1)));
+
+        let e03 = mean.difference(E03).unwrap();
+        assert_eq!(e03.code, Some((Carrier::L1, 5.0)));
+        assert!(!e03.has_phase());
+
+        let e05 = mean.difference(E05).unwrap();
+        assert_eq!(e05.code, Some((Carrier::L1, 2.0)));
+        assert_eq!(e05.phase, Some((Carrier::L1, 0.4)));
+        assert_eq!(e05.lw, Some((Carrier::L1, 0.86, 0.5)));
+
+        assert!(mean.difference(E09).is_none());
+    }
+}
@@ -0,0 +1,281 @@
+//! Doppler (range rate) contribution
+use std::cmp::Ordering;
+
+use crate::{
+    constants::SPEED_OF_LIGHT_M_S,
+    prelude::{Candidate, Carrier, Epoch, Error, Vector3},
+};
+
+impl Candidate {
+    /// Returns Doppler [Observation](crate::prelude::Observation) with best SNR value,
+    /// converted to range rate (in m.s⁻¹), amongst all frequencies.
+    /// A positive Doppler shift means the satellite is approaching,
+    /// therefore the range is decreasing.
+    pub(crate) fn best_snr_range_rate_m_s(&self) -> Option<(Carrier, f64)> {
+        let obs = self
+            .observations
+            .iter()
+            .filter(|ob| ob.doppler.is_some())
+            .max_by(|ob_a, ob_b| {
+                if let Some(snr_a) = ob_a.snr_dbhz {
+                    if let Some(snr_b) = ob_b.snr_dbhz {
+                        snr_a.total_cmp(&snr_b)
+                    } else {
+                        Ordering::Greater
+                    }
+                } else {
+                    Ordering::Less
+                }
+            })?;
+
+        let doppler_hz = obs.doppler?;
+
+        Some((obs.carrier, -doppler_hz * obs.carrier.wavelength()))
+    }
+
+    /// Range rate measurement contribution, used by the velocity filter.
+    /// The observed range rate is corrected for the satellite motion projected
+    /// on the line of sight and for the satellite clock drift, which leaves
+    /// the receiver velocity and clock drift (in m.s⁻¹) to be estimated.
+    ///
+    /// ## Input
+    /// - x0_y0_z0_m: current position estimate (ECEF, meters)
+    ///
+    /// ## Returns
+    /// - [Carrier] of the Doppler observation and range rate residual (m.s⁻¹)
+    pub(crate) fn doppler_vector_contribution(
+        &self,
+        x0_y0_z0_m: Vector3<f64>,
+    ) -> Result<(Carrier, f64), Error> {
+        let (carrier, range_rate_m_s) = self
+            .best_snr_range_rate_m_s()
+            .ok_or(Error::MissingDoppler)?;
+
+        let orbit = self.orbit.ok_or(Error::UnresolvedState)?;
+        let pos_vel_m = orbit.to_cartesian_pos_vel() * 1.0E3;
+
+        let sv_vel_m_s = Vector3::new(pos_vel_m[3], pos_vel_m[4], pos_vel_m[5]);
+
+        if sv_vel_m_s.norm() == 0.0 {
+            return Err(Error::UnresolvedVelocity);
+        }
+
+        let los = Vector3::new(pos_vel_m[0], pos_vel_m[1], pos_vel_m[2]) - x0_y0_z0_m;
+        let los = los / los.norm();
+
+        let sv_clock_drift_m_s = self.clock_corr.drift_s_s * SPEED_OF_LIGHT_M_S;
+
+        Ok((
+            carrier,
+            range_rate_m_s - los.dot(&sv_vel_m_s) + sv_clock_drift_m_s,
+        ))
+    }
+
+    /// Extrapolates this [Candidate] to a new sampling [Epoch], using the
+    /// range rate to propagate each pseudo range and phase range observation.
+    /// Observations that do not provide a Doppler shift are propagated with the
+    /// best SNR range rate, because the geometric variation does not depend on
+    /// the frequency (the ionosphere variation is neglected).
+    /// This is used to synchronize moving base observations to the rover.
+    pub(crate) fn doppler_extrapolation_mut(&mut self, epoch: Epoch) -> Result<(), Error> {
+        let dt_s = (epoch - self.epoch).to_seconds();
+
+        if dt_s == 0.0 {
+            return Ok(());
+        }
+
+        let (_, best_range_rate_m_s) = self
+            .best_snr_range_rate_m_s()
+            .ok_or(Error::MissingDoppler)?;
+
+        for obs in self.observations.iter_mut() {
+            let range_rate_m_s = match obs.doppler {
+                Some(doppler_hz) => -doppler_hz * obs.carrier.wavelength(),
+                None => best_range_rate_m_s,
+            };
+
+            if let Some(pseudo_range_m) = &mut obs.pseudo_range_m {
+                *pseudo_range_m += range_rate_m_s * dt_s;
+            }
+
+            if let Some(phase_range_m) = &mut obs.phase_range_m {
+                *phase_range_m += range_rate_m_s * dt_s;
+            }
+        }
+
+        self.epoch = epoch;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        prelude::{
+            Candidate, Carrier, Epoch, Error, Frame, Observation, Orbit, SPEED_OF_LIGHT_M_S, SV,
+            SatelliteClockCorrection, Vector3, Vector6,
+        },
+        tests::ROVER_REFERENCE_COORDS_ECEF_M,
+    };
+
+    use rstest::*;
+
+    use std::str::FromStr;
+
+    #[fixture]
+    fn build_earth_frame() -> Frame {
+        use crate::tests::earth_frame;
+        earth_frame()
+    }
+
+    #[test]
+    fn doppler_range_rate() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let g01 = SV::from_str("G01").unwrap();
+
+        let cd = Candidate::new(
+            g01,
+            t0,
+            vec![Observation::pseudo_range(Carrier::L1, 20.0E6, Some(45.0))],
+        );
+
+        assert!(cd.best_snr_range_rate_m_s().is_none());
+
+        let cd = Candidate::new(
+            g01,
+            t0,
+            vec![
+                Observation::doppler(Carrier::L1, 1000.0, Some(40.0)),
+                Observation::doppler(Carrier::L5, 1000.0, Some(45.0)),
+            ],
+        );
+
+        let (carrier, range_rate) = cd.best_snr_range_rate_m_s().unwrap();
+
+        assert_eq!(carrier, Carrier::L5);
+        assert_eq!(range_rate, -1000.0 * Carrier::L5.wavelength());
+
+        // invalid SNR does not panic
+        let cd = Candidate::new(
+            g01,
+            t0,
+            vec![
+                Observation::doppler(Carrier::L1, 1000.0, Some(f64::NAN)),
+                Observation::doppler(Carrier::L5, 1000.0, Some(45.0)),
+            ],
+        );
+
+        assert!(cd.best_snr_range_rate_m_s().is_some());
+    }
+
+    #[test]
+    fn doppler_extrapolation() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let t1 = Epoch::from_str("2020-06-25T00:00:00.5 GPST").unwrap();
+        let g01 = SV::from_str("G01").unwrap();
+
+        let lambda = Carrier::L1.wavelength();
+
+        let mut cd = Candidate::new(
+            g01,
+            t0,
+            vec![Observation::pseudo_range(Carrier::L1, 20.0E6, Some(45.0))],
+        );
+
+        assert_eq!(cd.doppler_extrapolation_mut(t0), Ok(()));
+        assert_eq!(cd.doppler_extrapolation_mut(t1), Err(Error::MissingDoppler));
+
+        let mut cd = Candidate::new(
+            g01,
+            t0,
+            vec![
+                Observation::pseudo_range(Carrier::L1, 20.0E6, Some(45.0))
+                    .with_doppler(-100.0 / lambda),
+                Observation::ambiguous_phase_range(Carrier::L5, 20.0E6, Some(40.0)),
+            ],
+        );
+
+        cd.doppler_extrapolation_mut(t1).unwrap();
+
+        assert_eq!(cd.epoch, t1);
+
+        for obs in cd.observations.iter() {
+            match obs.carrier {
+                Carrier::L1 => {
+                    let pr = obs.pseudo_range_m.unwrap();
+                    assert!((pr - 20.0E6 - 50.0).abs() < 1.0E-6);
+                },
+                Carrier::L5 => {
+                    let ph = obs.phase_range_m.unwrap();
+                    assert!((ph - 20.0E6 - 50.0).abs() < 1.0E-6);
+                },
+                _ => unreachable!("unexpected carrier"),
+            }
+        }
+    }
+
+    #[test]
+    fn doppler_vector_contribution() {
+        let earth_frame = build_earth_frame();
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let g01 = SV::from_str("G01").unwrap();
+
+        let x0_y0_z0_m = Vector3::new(
+            ROVER_REFERENCE_COORDS_ECEF_M.0,
+            ROVER_REFERENCE_COORDS_ECEF_M.1,
+            ROVER_REFERENCE_COORDS_ECEF_M.2,
+        );
+
+        // satellite moving along the line of sight, receiver is static
+        let sv_pos_m = x0_y0_z0_m * 5.0;
+        let los = (sv_pos_m - x0_y0_z0_m).normalize();
+        let sv_vel_m_s = los * 1000.0;
+
+        let lambda = Carrier::L1.wavelength();
+
+        let mut cd = Candidate::new(
+            g01,
+            t0,
+            vec![Observation::doppler(Carrier::L1, -1000.0 / lambda, None)],
+        );
+
+        assert_eq!(
+            cd.doppler_vector_contribution(x0_y0_z0_m),
+            Err(Error::UnresolvedState),
+        );
+
+        cd.set_orbit(Orbit::from_cartesian_pos_vel(
+            Vector6::new(
+                sv_pos_m[0] / 1.0E3,
+                sv_pos_m[1] / 1.0E3,
+                sv_pos_m[2] / 1.0E3,
+                sv_vel_m_s[0] / 1.0E3,
+                sv_vel_m_s[1] / 1.0E3,
+                sv_vel_m_s[2] / 1.0E3,
+            ),
+            t0,
+            earth_frame,
+        ));
+
+        let (carrier, residual) = cd.doppler_vector_contribution(x0_y0_z0_m).unwrap();
+
+        assert_eq!(carrier, Carrier::L1);
+
+        assert!(
+            residual.abs() < 1.0E-6,
+            "residual error too large: {residual}"
+        );
+
+        // satellite clock drift is compensated
+        let drift_s_s = 1.0E-11;
+        cd.clock_corr = SatelliteClockCorrection::default().with_drift(drift_s_s);
+
+        let (_, residual) = cd.doppler_vector_contribution(x0_y0_z0_m).unwrap();
+        let expected = drift_s_s * SPEED_OF_LIGHT_M_S;
+
+        assert!(
+            (residual - expected).abs() < 1.0E-6,
+            "residual error too large: {residual}"
+        );
+    }
+}
@@ -6,6 +6,7 @@ use log::debug;
 
 use crate::{
     bias::spaceborn::SatelliteClockCorrection,
+    candidate::windup::{SatelliteAttitude, windup_cycles},
     constants::SPEED_OF_LIGHT_M_S,
     navigation::state::State,
     prelude::{Almanac, Config, Duration, Epoch, Error, Orbit, SV, Vector3},
@@ -13,10 +14,13 @@ use crate::{
 
 use anise::errors::AlmanacResult;
 
+mod antenna;
 mod bias;
+mod doppler;
 mod ppp;
 mod rtk;
 mod signal;
+mod windup;
 
 pub(crate) mod combination;
 pub(crate) mod differences;
@@ -57,8 +61,12 @@ pub struct Candidate {
     /// Ionosphere delay (meters)
     pub(crate) ionod: f64,
 
-    // /// Phase wind up in cycles
-    // pub(crate) windup: f64,
+    /// Phase wind up in cycles
+    pub(crate) windup: f64,
+
+    /// Satellite yaw angle (in radians), when the phase wind up is modeled
+    pub(crate) yaw_rad: Option<f64>,
+
     /// [SatelliteClockCorrection]
     pub(crate) clock_corr: SatelliteClockCorrection,
 
@@ -70,6 +78,26 @@ pub struct Candidate {
 
     /// Estimated relativistic path range
     pub(crate) relativistic_path_range: f64,
+
+    /// Receiver site displacement (ECEF, m), due to the solid Earth tides
+    /// and the ocean tide loading
+    pub(crate) site_displacement_m: Vector3<f64>,
+
+    /// Position of the pivot satellite (ECEF, m) this [Candidate] was differenced against,
+    /// when it differs from the main pivot (Glonass FDMA).
+    pub(crate) pivot_position_ecef_m: Option<(f64, f64, f64)>,
+
+    /// True when the orbital state was provided by the [OrbitSource](crate::prelude::OrbitSource)
+    /// rather than the broadcast ephemeris.
+    pub(crate) precise_orbit: bool,
+
+    /// True when a cycle slip was detected at this epoch,
+    /// which started a new phase arc.
+    pub(crate) cycle_slip: bool,
+
+    /// Pivot satellite, when the ambiguities of this [Candidate] were resolved
+    /// against it (absolute triple frequency navigation). Both share a float ambiguity.
+    pub(crate) ambiguity_pivot: Option<SV>,
 }
 
 impl Candidate {
@@ -93,12 +121,18 @@ impl Candidate {
             tx_epoch: epoch,
             tgd: Duration::ZERO,
             orbit: Default::default(),
-            // windup: Default::default(),
+            windup: Default::default(),
+            yaw_rad: Default::default(),
             azimuth_deg: Default::default(),
             clock_corr: Default::default(),
             elevation_deg: Default::default(),
             system_correction: Default::default(),
             relativistic_path_range: Default::default(),
+            pivot_position_ecef_m: Default::default(),
+            site_displacement_m: Vector3::zeros(),
+            precise_orbit: false,
+            cycle_slip: false,
+            ambiguity_pivot: None,
         }
     }
 
@@ -109,11 +143,14 @@ impl Candidate {
 
     /// Update pseudo range observation (in meters) for this frequency.
     pub fn set_pseudo_range_m(&mut self, carrier: Carrier, pr_m: f64) {
+        let constellation = self.sv.constellation;
+
         if let Some(observation) = self
             .observations
             .iter_mut()
             .filter_map(|obs| {
-                if obs.carrier == carrier && obs.pseudo_range_m.is_some() {
+                if obs.carrier.same_signal(&carrier, constellation) && obs.pseudo_range_m.is_some()
+                {
                     Some(obs)
                 } else {
                     None
@@ -130,11 +167,13 @@ impl Candidate {
 
     /// Update with ambiguous range observation (in meters) for this frequency.
     pub fn set_ambiguous_phase_range_m(&mut self, carrier: Carrier, pr_m: f64) {
+        let constellation = self.sv.constellation;
+
         if let Some(observation) = self
             .observations
             .iter_mut()
             .filter_map(|obs| {
-                if obs.carrier == carrier && obs.phase_range_m.is_some() {
+                if obs.carrier.same_signal(&carrier, constellation) && obs.phase_range_m.is_some() {
                     Some(obs)
                 } else {
                     None
@@ -149,27 +188,65 @@ impl Candidate {
         }
     }
 
-    /// Computes phase windup correction term.
+    /// Computes phase windup correction term, from the satellite attitude
+    /// and the receiver antenna orientation (North, West).
+    /// The correction is kept continuous with the past [Candidate], if any.
     pub(crate) fn phase_windup_correction(
         &mut self,
-        _rx_state: &State,
-        _r_sun: Vector3<f64>,
-        _past_correction: Option<f64>,
+        rx_state: &State,
+        r_sun: Vector3<f64>,
+        past: Option<&Candidate>,
     ) {
-        // let sv_state = self.orbit.unwrap_or_else(|| {
-        //     panic!("internal error: phase windup while state is not fully resolved");
-        // });
-
-        // let r_sv = sv_state.to_cartesian_pos_vel() * 1.0E3;
+        let sv_state = self.orbit.unwrap_or_else(|| {
+            panic!("internal error: phase windup while state is not fully resolved");
+        });
+
+        let pos_vel_m = sv_state.to_cartesian_pos_vel() * 1.0E3;
+        let r_sv = Vector3::new(pos_vel_m[0], pos_vel_m[1], pos_vel_m[2]);
+
+        let attitude = match self.satellite_attitude(r_sun, past) {
+            Some(attitude) => attitude,
+            None => {
+                debug!("{}({}) - singular attitude", self.epoch, self.sv);
+                return;
+            },
+        };
+
+        let r_rx = rx_state.to_position_ecef_m();
+        let k = (r_rx - r_sv).normalize();
+
+        let (lat_deg, long_deg, _) = rx_state.lat_long_alt_deg_deg_km;
+        let (lat_rad, long_rad) = (lat_deg.to_radians(), long_deg.to_radians());
+
+        let north = Vector3::new(
+            -lat_rad.sin() * long_rad.cos(),
+            -lat_rad.sin() * long_rad.sin(),
+            lat_rad.cos(),
+        );
 
-        // // todo self.yaw_attitude();
+        let west = Vector3::new(long_rad.sin(), -long_rad.cos(), 0.0);
 
-        // let r_rx = rx_state.to_position_ecef_m();
-        // let r_sv = Vector3::new(r_sv[0], r_sv[1], r_sv[2]);
-        // let r_rr_rs = r_rx - r_sv;
-        // let e_r_rs = r_rr_rs.norm();
+        self.yaw_rad = attitude.yaw_rad;
+        self.windup = windup_cycles(k, &attitude, north, west, past.map(|past| past.windup));
+    }
 
-        // self.windup = 0.0; // TODO
+    /// Resolves the [SatelliteAttitude], with the yaw rate limited
+    /// with respect to the past [Candidate], if any.
+    pub(crate) fn satellite_attitude(
+        &self,
+        r_sun: Vector3<f64>,
+        past: Option<&Candidate>,
+    ) -> Option<SatelliteAttitude> {
+        let pos_vel_m = self.orbit?.to_cartesian_pos_vel() * 1.0E3;
+        let r_sv = Vector3::new(pos_vel_m[0], pos_vel_m[1], pos_vel_m[2]);
+        let v_sv = Vector3::new(pos_vel_m[3], pos_vel_m[4], pos_vel_m[5]);
+
+        let past_yaw = past.and_then(|past| {
+            let dt_s = (self.epoch - past.epoch).to_seconds();
+            Some((past.yaw_rad?, dt_s))
+        });
+
+        SatelliteAttitude::new(self.sv, r_sv, v_sv, r_sun, past_yaw)
     }
 
     /// Computes signal transmission instant, as [Epoch].
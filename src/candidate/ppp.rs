@@ -2,9 +2,13 @@
 use log::debug;
 
 use crate::{
+    candidate::{
+        combination::triple_if_coefficients,
+        differences::{CascadedAmbiguities, Difference},
+    },
     constants::SPEED_OF_LIGHT_M_S,
     navigation::{sv::SVContribution, vector::VectorContribution},
-    prelude::{Candidate, Config, Duration, Error, Method, Signal, Vector3},
+    prelude::{Candidate, Config, Duration, Error, Method, SV, Signal, Vector3},
 };
 
 impl Candidate {
@@ -33,6 +37,9 @@ impl Candidate {
         let mut bias_m = 0.0;
         let mut vec = VectorContribution::default();
 
+        // site displacement
+        let x0_y0_z0_m = x0_y0_z0_m + self.site_displacement_m;
+
         let (x0_m, y0_m, z0_m) = (x0_y0_z0_m[0], x0_y0_z0_m[1], x0_y0_z0_m[2]);
 
         let orbit = self.orbit.ok_or(Error::UnresolvedState)?;
@@ -42,6 +49,7 @@ impl Candidate {
 
         contribution.elevation_deg = elev_deg;
         contribution.azimuth_deg = azim_deg;
+        contribution.cycle_slip = self.cycle_slip;
 
         let (sv_x_m, sv_y_m, sv_z_m) = (pos_vel_m[0], pos_vel_m[1], pos_vel_m[2]);
 
@@ -51,12 +59,28 @@ impl Candidate {
         rho += self.relativistic_path_range;
         contribution.relativistic_path_range_m = self.relativistic_path_range;
 
-        let (_, range_m) = match cfg.method {
+        let (range_m, int_delay_s) = match cfg.method {
             Method::SPP => {
                 let (carrier, pr) = self.best_snr_range_m().ok_or(Error::MissingPseudoRange)?;
                 contribution.signal = Signal::Single(carrier);
 
-                (carrier.wavelength(), pr)
+                (pr, cfg.internal_delay_s(carrier))
+            },
+            Method::PPP_TF => {
+                let comb = self
+                    .code_tf_if_combination()
+                    .ok_or(Error::PseudoRangeCombination)?;
+
+                contribution.signal = Signal::Triple(comb.carriers);
+
+                let (c_1, c_j, c_k) = comb.carriers;
+                let (a_1, a_j, a_k) = triple_if_coefficients(c_1, c_j, c_k);
+
+                let int_delay_s = a_1 * cfg.internal_delay_s(c_1)
+                    + a_j * cfg.internal_delay_s(c_j)
+                    + a_k * cfg.internal_delay_s(c_k);
+
+                (comb.value, int_delay_s)
             },
             _ => {
                 let comb = self
@@ -65,12 +89,16 @@ impl Candidate {
 
                 contribution.signal = Signal::Dual((comb.lhs, comb.rhs));
 
-                let (f1, f2) = (
+                let (f1, fj) = (
                     comb.rhs.frequency_hz().powi(2),
                     comb.lhs.frequency_hz().powi(2),
                 );
 
-                (SPEED_OF_LIGHT_M_S * (f1 - f2) / f1 / f2, comb.value)
+                let int_delay_s = (f1 * cfg.internal_delay_s(comb.rhs)
+                    - fj * cfg.internal_delay_s(comb.lhs))
+                    / (f1 - fj);
+
+                (comb.value, int_delay_s)
             },
         };
 
@@ -82,6 +110,13 @@ impl Candidate {
 
                 Some(comb.value)
             },
+            Method::PPP_TF => {
+                let comb = self
+                    .phase_tf_if_combination()
+                    .ok_or(Error::PhaseRangeCombination)?;
+
+                Some(comb.value)
+            },
             _ => None,
         };
 
@@ -104,37 +139,105 @@ impl Candidate {
             bias_m -= delay_s * SPEED_OF_LIGHT_M_S;
         }
 
-        for _ in cfg.int_delay.iter() {
-            // TODO
-        }
-
         bias_m += self.ionod;
 
         bias_m += self.tropod;
         contribution.tropo_bias = Some(self.tropod);
 
-        vec.sigma = 1.0; // TODO
-
-        let pr = range_m - rho - bias_m;
+        // receiver internal delays only affect the code
+        let pr = range_m - rho - bias_m - int_delay_s * SPEED_OF_LIGHT_M_S;
 
         let cp = cp.map(|cp| cp - rho - bias_m);
 
-        if (two_rows || cfg.method == Method::PPP) && cp.is_none() {
+        if (two_rows || cfg.method.uses_phase()) && cp.is_none() {
             return Err(Error::MissingPhaseRange)?;
         }
 
+        let code_sigma_m = self.measurement_sigma_m(cfg, &contribution.signal, false);
+        let phase_sigma_m = cp.map(|_| self.measurement_sigma_m(cfg, &contribution.signal, true));
+
+        // unit weights are not reported
+        if cfg.stochastic.models_uncertainties() {
+            contribution.code_sigma_m = Some(code_sigma_m);
+            contribution.phase_sigma_m = phase_sigma_m;
+        }
+
         if two_rows {
             vec.row_1 = pr;
             vec.row_2 = cp.unwrap_or_default();
-        } else if cfg.method == Method::PPP {
+            vec.sigma_1 = code_sigma_m;
+            vec.sigma_2 = phase_sigma_m.unwrap_or_default();
+        } else if cfg.method.uses_phase() {
             vec.row_1 = cp.unwrap_or_default();
+            vec.sigma_1 = phase_sigma_m.unwrap_or_default();
         } else {
             vec.row_1 = pr;
+            vec.sigma_1 = code_sigma_m;
         }
 
         Ok(vec)
     }
 
+    /// Returns the float ambiguity (in meters) of the phase combination used by
+    /// this navigation [Method], leveled to the code combination. None when this combination
+    /// cannot be formed, or when its phase ambiguities are known (or resolved against a pivot).
+    pub(crate) fn ppp_float_ambiguity_m(&self, cfg: &Config) -> Option<f64> {
+        if self.ambiguity_pivot.is_some() {
+            return None;
+        }
+
+        let known = self
+            .observations
+            .iter()
+            .filter(|ob| ob.phase_range_m.is_some())
+            .all(|ob| ob.ambiguity.is_some());
+
+        if known {
+            return None;
+        }
+
+        match cfg.method {
+            Method::PPP => {
+                Some(self.phase_if_combination()?.value - self.code_if_combination()?.value)
+            },
+            Method::PPP_TF => {
+                Some(self.phase_tf_if_combination()?.value - self.code_tf_if_combination()?.value)
+            },
+            _ => None,
+        }
+    }
+
+    /// Applies the ambiguities (in cycles) resolved against this pivot satellite,
+    /// from their between satellite [Difference].
+    pub(crate) fn resolved_ambiguities_mut(
+        &mut self,
+        pivot: SV,
+        sd: &Difference,
+        ambiguities: &CascadedAmbiguities,
+    ) {
+        let resolved = [
+            (sd.phase, ambiguities.n_1),
+            (sd.phase_j, ambiguities.n_j()),
+            (sd.phase_k, ambiguities.n_k()),
+        ];
+
+        for (phase, n) in resolved {
+            let carrier = match phase {
+                Some((carrier, _)) => carrier,
+                None => continue,
+            };
+
+            // the range is phase + ambiguity × λ
+            for ob in self.observations.iter_mut() {
+                if ob.carrier == carrier && ob.phase_range_m.is_some() {
+                    ob.ambiguity = Some(ob.ambiguity.unwrap_or_default() - n);
+                }
+            }
+        }
+
+        self.ambiguity_pivot = Some(pivot);
+    }
+
     /// Matrix contribution.
     ///
     /// ## Input
@@ -178,7 +281,10 @@ impl Candidate {
 #[cfg(test)]
 mod test {
     use crate::{
-        prelude::{Config, Epoch, Frame, Method, Orbit},
+        cfg::InternalDelay,
+        constants::SPEED_OF_LIGHT_M_S,
+        navigation::sv::SVContribution,
+        prelude::{Carrier, Config, Epoch, Frame, Method, Orbit},
         tests::{CandidatesBuilder, E05, ROVER_REFERENCE_COORDS_ECEF_M},
     };
 
@@ -339,4 +445,77 @@ mod test {
         assert!((dy - e_i.1).abs() < 1E-6, "y error too large");
         assert!((dz - e_i.2).abs() < 1E-6, "z error too large");
     }
+
+    #[test]
+    fn internal_delay_compensation() {
+        let earth_frame = build_earth_frame();
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+
+        let mut rover = CandidatesBuilder::build_rover_sv_at(E05, t0);
+
+        rover.orbit = Some(Orbit::from_position(
+            -11562.163582,
+            14053.114306,
+            23345.128269,
+            t0,
+            earth_frame,
+        ));
+
+        rover.elevation_deg = Some(45.0);
+        rover.azimuth_deg = Some(90.0);
+
+        // L1 is the best SNR signal
+        rover.observations[0].snr_dbhz = Some(45.0);
+        rover.observations[1].snr_dbhz = Some(40.0);
+
+        let x0_y0_z0_m = Vector3::new(
+            ROVER_REFERENCE_COORDS_ECEF_M.0,
+            ROVER_REFERENCE_COORDS_ECEF_M.1,
+            ROVER_REFERENCE_COORDS_ECEF_M.2,
+        );
+
+        let delays = vec![
+            InternalDelay {
+                delay: 10.0E-9,
+                frequency: Carrier::L1.frequency_hz(),
+            },
+            InternalDelay {
+                delay: 20.0E-9,
+                frequency: Carrier::E5b.frequency_hz(),
+            },
+            InternalDelay {
+                delay: 1.0E-6,
+                frequency: Carrier::L2.frequency_hz(),
+            },
+        ];
+
+        let (f1, fj) = (
+            Carrier::L1.frequency_hz().powi(2),
+            Carrier::E5b.frequency_hz().powi(2),
+        );
+
+        for (method, expected_s) in [
+            (Method::SPP, 10.0E-9),
+            (Method::CPP, (f1 * 10.0E-9 - fj * 20.0E-9) / (f1 - fj)),
+        ] {
+            let mut cfg = Config::default().with_navigation_method(method);
+
+            let reference = rover
+                .ppp_vector_contribution(&cfg, false, x0_y0_z0_m, &mut SVContribution::default())
+                .unwrap();
+
+            cfg.int_delay = delays.clone();
+
+            let compensated = rover
+                .ppp_vector_contribution(&cfg, false, x0_y0_z0_m, &mut SVContribution::default())
+                .unwrap();
+
+            let delay_m = reference.row_1 - compensated.row_1;
+
+            assert!(
+                (delay_m - expected_s * SPEED_OF_LIGHT_M_S).abs() < 1.0E-6,
+                "{method}: {delay_m}"
+            );
+        }
+    }
 }
@@ -1,11 +1,15 @@
-use log::error;
+use log::{debug, error};
 
 use crate::{
     candidate::differences::Differences,
     navigation::{sv::SVContribution, vector::VectorContribution},
-    prelude::{Candidate, Config, Epoch, Error, Method, Vector3},
+    prelude::{Candidate, Config, Epoch, Error, Method, Signal, Vector3},
 };
 
+/// Double differences combine four independent measurements of similar uncertainty,
+/// which doubles the uncertainty of the raw measurement.
+const DOUBLE_DIFFERENCE_NOISE_FACTOR: f64 = 2.0;
+
 impl Candidate {
     /// Measurement vector contribution.
     pub(crate) fn rtk_vector_contribution(
@@ -22,6 +26,8 @@ impl Candidate {
 
         let mut vec = VectorContribution::default();
 
+        contribution.cycle_slip = self.cycle_slip;
+
         // row #1
         match cfg.method {
             Method::SPP => {
@@ -32,6 +38,14 @@ impl Candidate {
                     return Err(Error::MissingPseudoRange);
                 }
             },
+            Method::PPP_TF => {
+                if let Some(code) = dd.code_tf_if() {
+                    vec.row_1 = code;
+                } else {
+                    error!("{}({}) - missing pseudo range", epoch, self.sv);
+                    return Err(Error::MissingPseudoRange);
+                }
+            },
             _ => {
                 if let Some((_, code)) = dd.code_if {
                     vec.row_1 = code;
@@ -52,6 +66,29 @@ impl Candidate {
             }
         }
 
+        // row #1, or row #2 (special case)
+        if cfg.method == Method::PPP_TF {
+            let max_fraction_cycles = cfg.solver.max_ambiguity_fraction_cycles;
+
+            let phase_tf_if = match dd.phase_tf_if(self.sv, max_fraction_cycles) {
+                Some(phase_tf_if) => phase_tf_if,
+                None if dd.has_triple_phase() => {
+                    debug!("{}({}) - unresolved ambiguities", epoch, self.sv);
+                    return Err(Error::UnresolvedAmbiguity);
+                },
+                None => {
+                    error!("{}({}) - missing phase data", epoch, self.sv);
+                    return Err(Error::MissingPhaseRange);
+                },
+            };
+
+            if two_rows {
+                vec.row_2 = phase_tf_if;
+            } else {
+                vec.row_1 = phase_tf_if;
+            }
+        }
+
         // row #2 (special case)
         if two_rows && cfg.method == Method::PPP {
             // special case
@@ -63,6 +100,50 @@ impl Candidate {
             }
         }
 
+        // uncertainties
+        let signal = match cfg.method {
+            Method::SPP => dd.code.map(|(carrier, _)| Signal::Single(carrier)),
+            Method::PPP_TF => self
+                .code_tf_if_combination()
+                .map(|comb| Signal::Triple(comb.carriers)),
+            _ => self
+                .code_if_combination()
+                .map(|comb| Signal::Dual((comb.lhs, comb.rhs))),
+        };
+
+        if let Some(signal) = signal {
+            contribution.signal = signal;
+        }
+
+        let models_uncertainties = cfg.stochastic.models_uncertainties();
+
+        let dd_noise_factor = if models_uncertainties {
+            DOUBLE_DIFFERENCE_NOISE_FACTOR
+        } else {
+            1.0
+        };
+
+        let code_sigma_m =
+            dd_noise_factor * self.measurement_sigma_m(cfg, &contribution.signal, false);
+
+        contribution.code_sigma_m = Some(code_sigma_m).filter(|_| models_uncertainties);
+
+        if cfg.method.uses_phase() {
+            let phase_sigma_m =
+                dd_noise_factor * self.measurement_sigma_m(cfg, &contribution.signal, true);
+
+            contribution.phase_sigma_m = Some(phase_sigma_m).filter(|_| models_uncertainties);
+
+            if two_rows {
+                vec.sigma_1 = code_sigma_m;
+                vec.sigma_2 = phase_sigma_m;
+            } else {
+                vec.sigma_1 = phase_sigma_m;
+            }
+        } else {
+            vec.sigma_1 = code_sigma_m;
+        }
+
         Ok(vec)
     }
 
@@ -72,13 +153,16 @@ impl Candidate {
     ///  - i: matrix row
     ///  - cfg: [Config] preset
     ///  - x0_y0_z0: position coordinates as ECEF (m)
+    ///  - pivot_position_ecef_m: main pivot position as ECEF (m),
+    ///    unless this [Candidate] was differenced against a Glonass pivot.
     pub(crate) fn rtk_matrix_contribution(
         &self,
         x0_y0_z0_m: Vector3<f64>,
         pivot_position_ecef_m: (f64, f64, f64),
     ) -> (f64, f64, f64) {
         let (x0_m, y0_m, z0_m) = (x0_y0_z0_m[0], x0_y0_z0_m[1], x0_y0_z0_m[2]);
-        let (pivot_x_m, pivot_y_m, pivot_z_m) = pivot_position_ecef_m;
+        let (pivot_x_m, pivot_y_m, pivot_z_m) =
+            self.pivot_position_ecef_m.unwrap_or(pivot_position_ecef_m);
 
         let orbit = self.orbit.unwrap_or_else(|| {
             panic!(
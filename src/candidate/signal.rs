@@ -2,7 +2,15 @@
 use itertools::Itertools;
 use std::cmp::Ordering;
 
-use crate::prelude::{Candidate, Carrier};
+use crate::prelude::{Candidate, Carrier, Config, Signal};
+
+/// Minimal frequency separation between reference and subsidary signals (MHz).
+/// Signals of the same band (like B1I and B1C) do not form usable combinations.
+const MIN_SUBSIDARY_SEPARATION_MHZ: f64 = 50.0;
+
+/// Minimal frequency separation between subsidary and third signals (MHz).
+/// E5a and E5b are only 30 MHz apart, the E5 (AltBOC) signal sits in between.
+const MIN_THIRD_SEPARATION_MHZ: f64 = 20.0;
 
 #[derive(Debug, Copy, Clone, Default, PartialEq)]
 pub struct Observation {
@@ -15,7 +23,7 @@ pub struct Observation {
     /// Ambiguous phase range observation, in meters.
     pub phase_range_m: Option<f64>,
 
-    /// Possible doppler observation (in Hz/Hz).
+    /// Possible Doppler shift observation (in Hz).
     pub doppler: Option<f64>,
 
     /// Possible SNR indication (in dB/Hz).
@@ -52,7 +60,7 @@ impl Observation {
         }
     }
 
-    /// Creates new Doppler [Observation], with possible SNR in dB/Hz.
+    /// Creates new Doppler shift [Observation] (in Hz), with possible SNR in dB/Hz.
     pub fn doppler(carrier: Carrier, doppler: f64, snr_dbhz: Option<f64>) -> Self {
         Self {
             snr_dbhz,
@@ -79,10 +87,19 @@ impl Observation {
         self
     }
 
-    /// Copies and returns new [Observation] with defined doppler shift (in Hz/Hz),
+    /// Copies and returns new [Observation] with defined Doppler shift (in Hz),
     /// for that frequency.
-    pub fn with_doppler(mut self, doppler_hz_hz: f64) -> Self {
-        self.doppler = Some(doppler_hz_hz);
+    pub fn with_doppler(mut self, doppler_hz: f64) -> Self {
+        self.doppler = Some(doppler_hz);
+        self
+    }
+
+    /// Copies and returns new [Observation] with known phase ambiguity (in cycles),
+    /// for that frequency: the phase range is then compensated by ambiguity × λ.
+    /// Known ambiguities are preserved by the phase tracking, otherwise
+    /// the navigation filter estimates one float ambiguity per phase arc.
+    pub fn with_ambiguity(mut self, ambiguity: f64) -> Self {
+        self.ambiguity = Some(ambiguity);
         self
     }
 }
@@ -141,6 +158,11 @@ impl Candidate {
         self.has_dual_pseudo_range() && self.has_dual_phase_range()
     }
 
+    /// True if Self is [Method::PPP_TF] compatible
+    pub(crate) fn ppp_tf_compatible(&self) -> bool {
+        self.has_triple_pseudo_range() && self.has_triple_phase_range()
+    }
+
     /// True if dual pseudo range measurement is present
     pub(crate) fn has_dual_pseudo_range(&self) -> bool {
         self.pseudo_range_iter()
@@ -150,14 +172,11 @@ impl Candidate {
             > 1
     }
 
-    // /// True if dual pseudo range measurement is present
-    // pub(crate) fn has_triple_pseudo_range(&self) -> bool {
-    //     self.pseudo_range_iter()
-    //         .map(|(signal, _)| (signal.frequency_mega_hz() * 100.0) as u32)
-    //         .unique()
-    //         .count()
-    //         > 2
-    // }
+    /// True if triple pseudo range measurement is present:
+    /// reference, subsidary and third signals all exist.
+    pub(crate) fn has_triple_pseudo_range(&self) -> bool {
+        self.third_pseudo_range().is_some()
+    }
 
     /// True if dual phase range measurement exist.
     pub(crate) fn has_dual_phase_range(&self) -> bool {
@@ -168,23 +187,86 @@ impl Candidate {
             > 1
     }
 
-    // /// True if dual phase range measurement exist.
-    // pub(crate) fn has_triple_phase_range(&self) -> bool {
-    //     self.phase_range_iter()
-    //         .map(|(signal, _)| (signal.frequency_mega_hz() * 100.0) as u32)
-    //         .unique()
-    //         .count()
-    //         > 2
-    // }
+    /// True if triple phase range measurement exist:
+    /// reference, subsidary and third signals all exist.
+    pub(crate) fn has_triple_phase_range(&self) -> bool {
+        self.third_phase_range().is_some()
+    }
 
-    /// Returns the L1 Pseudo Range observation [m] if it exists
-    pub(crate) fn l1_pseudo_range(&self) -> Option<(Carrier, f64)> {
-        let l1 = self
-            .observations
+    /// Returns the reference [Observation] (L1 or equivalent) that passes the filter,
+    /// prefering the first signal of the constellation signal plan.
+    fn reference_observation<F: Fn(&Observation) -> bool>(
+        &self,
+        filter: F,
+    ) -> Option<&Observation> {
+        let constellation = self.sv.constellation;
+
+        self.observations
             .iter()
-            .filter(|ob| ob.carrier.is_l1() && ob.pseudo_range_m.is_some())
-            .reduce(|k, _| k)?;
+            .filter(|ob| ob.carrier.is_reference(constellation) && filter(ob))
+            .min_by_key(|ob| ob.carrier.signal_rank(constellation))
+    }
+
+    /// Returns the subsidary [Observation] that passes the filter, prefering the
+    /// first signal of the constellation signal plan. It needs to be sufficiently
+    /// far from the reference signal (in frequency) to form meaningful combinations.
+    fn subsidary_observation<F: Fn(&Observation) -> bool>(
+        &self,
+        filter: F,
+    ) -> Option<&Observation> {
+        let constellation = self.sv.constellation;
+
+        let reference_mhz = self
+            .reference_observation(&filter)
+            .map(|ob| ob.carrier.frequency_mega_hz());
+
+        self.observations
+            .iter()
+            .filter(|ob| {
+                let far_enough = match reference_mhz {
+                    Some(reference_mhz) => {
+                        (ob.carrier.frequency_mega_hz() - reference_mhz).abs()
+                            > MIN_SUBSIDARY_SEPARATION_MHZ
+                    },
+                    None => !ob.carrier.is_reference(constellation),
+                };
+
+                far_enough && filter(ob)
+            })
+            .min_by_key(|ob| ob.carrier.signal_rank(constellation))
+    }
+
+    /// Returns the third [Observation] that passes the filter, prefering the
+    /// first signal of the constellation signal plan. It only exists when both
+    /// reference and subsidary signals exist, and differs from both of them.
+    fn third_observation<F: Fn(&Observation) -> bool>(&self, filter: F) -> Option<&Observation> {
+        let constellation = self.sv.constellation;
+
+        let reference_mhz = self
+            .reference_observation(&filter)?
+            .carrier
+            .frequency_mega_hz();
 
+        let subsidary_mhz = self
+            .subsidary_observation(&filter)?
+            .carrier
+            .frequency_mega_hz();
+
+        self.observations
+            .iter()
+            .filter(|ob| {
+                let freq_mhz = ob.carrier.frequency_mega_hz();
+
+                (freq_mhz - reference_mhz).abs() > MIN_SUBSIDARY_SEPARATION_MHZ
+                    && (freq_mhz - subsidary_mhz).abs() > MIN_THIRD_SEPARATION_MHZ
+                    && filter(ob)
+            })
+            .min_by_key(|ob| ob.carrier.signal_rank(constellation))
+    }
+
+    /// Returns the L1 (or reference signal) Pseudo Range observation [m] if it exists
+    pub(crate) fn l1_pseudo_range(&self) -> Option<(Carrier, f64)> {
+        let l1 = self.reference_observation(|ob| ob.pseudo_range_m.is_some())?;
         Some((l1.carrier, l1.pseudo_range_m.unwrap()))
     }
 
@@ -193,47 +275,94 @@ impl Candidate {
         Some(carrier)
     }
 
-    /// Returns the L1 Phase Range observation [m] if it exists
+    /// Returns the L1 (or reference signal) Phase Range observation [m] if it exists
     pub(crate) fn l1_phase_range(&self) -> Option<(Carrier, f64)> {
-        let l1 = self
-            .observations
-            .iter()
-            .filter(|ob| ob.carrier.is_l1() && ob.phase_range_m.is_some())
-            .reduce(|k, _| k)?;
+        let l1 = self.reference_observation(|ob| ob.phase_range_m.is_some())?;
 
         let mut l_1 = l1.phase_range_m.unwrap();
         let lambda_1 = l1.carrier.wavelength();
         l_1 += l1.ambiguity.unwrap_or_default() * lambda_1;
+        l_1 -= self.windup * lambda_1;
 
         Some((l1.carrier, l_1))
     }
 
     /// Returns the a Pseudo Range observation [m] if it exists
     pub(crate) fn subsidary_pseudo_range(&self) -> Option<(Carrier, f64)> {
-        let lj = self
-            .observations
-            .iter()
-            .filter(|ob| !ob.carrier.is_l1() && ob.pseudo_range_m.is_some())
-            .reduce(|k, _| k)?;
-
+        let lj = self.subsidary_observation(|ob| ob.pseudo_range_m.is_some())?;
         Some((lj.carrier, lj.pseudo_range_m.unwrap()))
     }
 
-    /// Returns the L2 Phase Range observation [m] if it exists
+    /// Returns the L2 (or subsidary signal) Phase Range observation [m] if it exists
     pub(crate) fn subsidary_phase_range(&self) -> Option<(Carrier, f64)> {
-        let lj = self
-            .observations
-            .iter()
-            .filter(|ob| !ob.carrier.is_l1() && ob.phase_range_m.is_some())
-            .reduce(|k, _| k)?;
+        let lj = self.subsidary_observation(|ob| ob.phase_range_m.is_some())?;
 
         let mut l_j = lj.phase_range_m.unwrap();
         let lambda_j = lj.carrier.wavelength();
         l_j += lj.ambiguity.unwrap_or_default() * lambda_j;
+        l_j -= self.windup * lambda_j;
 
         Some((lj.carrier, l_j))
     }
 
+    /// Returns the third Pseudo Range observation [m] if it exists
+    pub(crate) fn third_pseudo_range(&self) -> Option<(Carrier, f64)> {
+        let lk = self.third_observation(|ob| ob.pseudo_range_m.is_some())?;
+        Some((lk.carrier, lk.pseudo_range_m.unwrap()))
+    }
+
+    /// Returns the third Phase Range observation [m] if it exists
+    pub(crate) fn third_phase_range(&self) -> Option<(Carrier, f64)> {
+        let lk = self.third_observation(|ob| ob.phase_range_m.is_some())?;
+
+        let mut l_k = lk.phase_range_m.unwrap();
+        let lambda_k = lk.carrier.wavelength();
+        l_k += lk.ambiguity.unwrap_or_default() * lambda_k;
+        l_k -= self.windup * lambda_k;
+
+        Some((lk.carrier, l_k))
+    }
+
+    /// Returns the lowest SNR (in dB/Hz) of the observations forming this [Signal],
+    /// when it is known.
+    fn signal_snr_dbhz(&self, signal: &Signal) -> Option<f64> {
+        let snr_dbhz = |carrier: &Carrier| {
+            self.observations
+                .iter()
+                .find(|ob| ob.carrier.same_signal(carrier, self.sv.constellation))
+                .and_then(|ob| ob.snr_dbhz)
+        };
+
+        match signal {
+            Signal::Single(c_1) => snr_dbhz(c_1),
+            Signal::Dual((c_j, c_1)) => [c_1, c_j]
+                .iter()
+                .filter_map(|c| snr_dbhz(c))
+                .reduce(f64::min),
+            Signal::Triple((c_1, c_j, c_k)) => [c_1, c_j, c_k]
+                .iter()
+                .filter_map(|c| snr_dbhz(c))
+                .reduce(f64::min),
+        }
+    }
+
+    /// Returns the uncertainty (1σ, in meters) of the measurement formed with this [Signal],
+    /// according to the [Config] stochastic model. Unit when uncertainties are not modeled.
+    pub(crate) fn measurement_sigma_m(&self, cfg: &Config, signal: &Signal, phase: bool) -> f64 {
+        if !cfg.stochastic.models_uncertainties() {
+            return 1.0;
+        }
+
+        let elevation_deg = self.elevation_deg.unwrap_or(90.0);
+        let snr_dbhz = self.signal_snr_dbhz(signal);
+
+        let sigma_m = cfg
+            .stochastic
+            .sigma_m(self.sv.constellation, phase, elevation_deg, snr_dbhz);
+
+        sigma_m * signal.noise_factor()
+    }
+
     /// Discards all observations below given SNR mask (>)
     pub(crate) fn min_c_n0_mask(&mut self, min_snr_dbhz: f64) {
         self.observations.retain(|ob| {
@@ -251,7 +380,7 @@ impl Candidate {
 
 #[cfg(test)]
 mod test {
-    use crate::prelude::{Candidate, Carrier, Epoch, Observation, SV};
+    use crate::prelude::{Candidate, Carrier, Constellation, Epoch, Observation, SV};
     use std::str::FromStr;
 
     #[test]
@@ -321,4 +450,75 @@ mod test {
         assert!(cd.code_if_combination().is_none());
         assert!(cd.phase_if_combination().is_some());
     }
+
+    #[test]
+    fn constellation_signal_plans() {
+        let t0 = Epoch::from_str("2000-01-01T00:00:00 UTC").unwrap();
+
+        // Galileo: E5a prefered to E6
+        let e01 = SV::new(Constellation::Galileo, 1);
+
+        let cd = Candidate::new(
+            e01,
+            t0,
+            vec![
+                Observation::pseudo_range(Carrier::E6, 0.6, None),
+                Observation::pseudo_range(Carrier::E5a, 0.5, None),
+                Observation::pseudo_range(Carrier::L1, 0.1, None),
+            ],
+        );
+
+        assert_eq!(cd.l1_pseudo_range(), Some((Carrier::L1, 0.1)));
+        assert_eq!(cd.subsidary_pseudo_range(), Some((Carrier::E5a, 0.5)));
+
+        // BeiDou: B1C does not form a combination with B1I
+        let c01 = SV::new(Constellation::BeiDou, 1);
+
+        let cd = Candidate::new(
+            c01,
+            t0,
+            vec![
+                Observation::pseudo_range(Carrier::B1C, 0.2, None),
+                Observation::pseudo_range(Carrier::B2a, 0.3, None),
+                Observation::pseudo_range(Carrier::B1, 0.1, None),
+            ],
+        );
+
+        assert_eq!(cd.l1_pseudo_range(), Some((Carrier::B1, 0.1)));
+        assert_eq!(cd.subsidary_pseudo_range(), Some((Carrier::B2a, 0.3)));
+        assert_eq!(cd.prefered_carrier(), Some(Carrier::B1));
+
+        // BeiDou-3: B1C/B2a
+        let cd = Candidate::new(
+            c01,
+            t0,
+            vec![
+                Observation::pseudo_range(Carrier::B2a, 0.3, None),
+                Observation::pseudo_range(Carrier::B1C, 0.2, None),
+            ],
+        );
+
+        assert_eq!(cd.l1_pseudo_range(), Some((Carrier::B1C, 0.2)));
+        assert_eq!(cd.subsidary_pseudo_range(), Some((Carrier::B2a, 0.3)));
+        assert!(cd.code_if_combination().is_some());
+
+        // IRNSS: L5/S
+        let i01 = SV::new(Constellation::IRNSS, 1);
+
+        let cd = Candidate::new(
+            i01,
+            t0,
+            vec![
+                Observation::pseudo_range(Carrier::S, 0.2, None),
+                Observation::pseudo_range(Carrier::L5, 0.1, None),
+            ],
+        );
+
+        assert_eq!(cd.l1_pseudo_range(), Some((Carrier::L5, 0.1)));
+        assert_eq!(cd.subsidary_pseudo_range(), Some((Carrier::S, 0.2)));
+
+        let combination = cd.code_if_combination().unwrap();
+        assert_eq!(combination.rhs, Carrier::L5);
+        assert_eq!(combination.lhs, Carrier::S);
+    }
 }
@@ -3,7 +3,7 @@ use log::debug;
 use crate::{
     candidate::differences::Difference,
     // constants::SPEED_OF_LIGHT_M_S,
-    prelude::Candidate,
+    prelude::{Candidate, Carrier},
 };
 
 impl Candidate {
@@ -11,25 +11,51 @@ impl Candidate {
     pub(crate) fn single_difference(&self, pivot: &Self) -> Difference {
         let mut sd = Difference::default();
 
+        // signals are compared within the signal plan of this satellite,
+        // so shared frequencies (like L5 and E5a) are differenced
+        let constellation = self.sv.constellation;
+        let same_frequency = |c_1: Carrier, c_2: Carrier| {
+            c_1.for_constellation(constellation) == c_2.for_constellation(constellation)
+        };
+
+        // Glonass FDMA code observations are differenced across frequency channels,
+        // which introduces an inter-frequency bias. Phase observations are not:
+        // their ambiguities would no longer be integer.
         if let Some((c_1, p_1)) = self.l1_pseudo_range() {
             if let Some((c_2, p_2)) = pivot.l1_pseudo_range() {
-                if c_1 == c_2 {
+                if c_1.same_signal(&c_2, constellation) {
                     sd = sd.with_code((c_1, p_1 - p_2));
+
+                    if let (Some(k_1), Some(k_2)) =
+                        (c_1.frequency_channel(), c_2.frequency_channel())
+                    {
+                        sd.channel_delta = k_1 - k_2;
+                    }
                 }
             }
         }
 
         if let Some((c_1, p_1)) = self.subsidary_pseudo_range() {
             if let Some((c_2, p_2)) = pivot.subsidary_pseudo_range() {
-                if c_1 == c_2 {
+                if c_1.same_signal(&c_2, constellation) {
                     sd = sd.with_code_j((c_1, p_1 - p_2));
                 }
             }
         }
 
+        if let Some((c_1, p_1)) = self.third_pseudo_range() {
+            if let Some((c_2, p_2)) = pivot.third_pseudo_range() {
+                if c_1.same_signal(&c_2, constellation) {
+                    sd = sd.with_code_k((c_1, p_1 - p_2));
+                }
+            }
+        }
+
         if let Some(l_1) = self.code_if_combination() {
             if let Some(l_2) = pivot.code_if_combination() {
-                if l_1.lhs == l_2.lhs && l_1.rhs == l_2.rhs {
+                if l_1.lhs.same_signal(&l_2.lhs, constellation)
+                    && l_1.rhs.same_signal(&l_2.rhs, constellation)
+                {
                     sd = sd.with_code_if((l_1.rhs, l_1.value - l_2.value));
                 }
             }
@@ -37,7 +63,7 @@ impl Candidate {
 
         if let Some((c_1, l_1)) = self.l1_phase_range() {
             if let Some((c_2, l_2)) = pivot.l1_phase_range() {
-                if c_1 == c_2 {
+                if same_frequency(c_1, c_2) {
                     sd = sd.with_phase((c_1, l_1 - l_2));
                 }
             }
@@ -45,15 +71,23 @@ impl Candidate {
 
         if let Some((c_1, l_1)) = self.subsidary_phase_range() {
             if let Some((c_2, l_2)) = pivot.subsidary_phase_range() {
-                if c_1 == c_2 {
+                if same_frequency(c_1, c_2) {
                     sd = sd.with_phase_j((c_1, l_1 - l_2));
                 }
             }
         }
 
+        if let Some((c_1, l_1)) = self.third_phase_range() {
+            if let Some((c_2, l_2)) = pivot.third_phase_range() {
+                if same_frequency(c_1, c_2) {
+                    sd = sd.with_phase_k((c_1, l_1 - l_2));
+                }
+            }
+        }
+
         if let Some(l_1) = self.phase_if_combination() {
             if let Some(l_2) = pivot.phase_if_combination() {
-                if l_1.lhs == l_2.lhs && l_1.rhs == l_2.rhs {
+                if same_frequency(l_1.lhs, l_2.lhs) && same_frequency(l_1.rhs, l_2.rhs) {
                     sd = sd.with_phase_if((l_1.rhs, l_1.lambda, l_1.value - l_2.value));
                 }
             }
@@ -61,7 +95,7 @@ impl Candidate {
 
         if let Some(c_1) = self.code_nl_combination() {
             if let Some(c_2) = pivot.code_nl_combination() {
-                if c_1.lhs == c_2.lhs && c_1.rhs == c_2.rhs {
+                if same_frequency(c_1.lhs, c_2.lhs) && same_frequency(c_1.rhs, c_2.rhs) {
                     sd = sd.with_cn((c_1.rhs, c_1.lambda, c_1.value - c_2.value));
                 }
             }
@@ -69,7 +103,7 @@ impl Candidate {
 
         if let Some(l_1) = self.phase_wl_combination() {
             if let Some(l_2) = pivot.phase_wl_combination() {
-                if l_1.lhs == l_2.lhs && l_1.rhs == l_2.rhs {
+                if same_frequency(l_1.lhs, l_2.lhs) && same_frequency(l_1.rhs, l_2.rhs) {
                     sd = sd.with_lw((l_1.rhs, l_1.lambda, l_1.value - l_2.value));
                 }
             }
@@ -86,8 +120,9 @@ mod test {
     use std::str::FromStr;
 
     use crate::{
+        candidate::differences::Differences,
         constants::SPEED_OF_LIGHT_M_S,
-        prelude::{Carrier, Epoch},
+        prelude::{Candidate, Carrier, Epoch, Observation, SV},
         tests::{CandidatesBuilder, E01, E03, E05, init_logger},
     };
 
@@ -317,4 +352,233 @@ mod test {
         assert!(e01_test_passed);
         assert!(e03_test_passed);
     }
+
+    #[test]
+    fn glonass_fdma_single_difference() {
+        let t = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+
+        let (r01, r02) = (SV::from_str("R01").unwrap(), SV::from_str("R02").unwrap());
+        let (g1_1, g1_m2) = (Carrier::G1(1), Carrier::G1(-2));
+
+        let candidate = |sv, carrier, code_m, phase_m| {
+            Candidate::new(
+                sv,
+                t,
+                vec![
+                    Observation::pseudo_range(carrier, code_m, None),
+                    Observation::ambiguous_phase_range(carrier, phase_m, None),
+                ],
+            )
+        };
+
+        let rover = candidate(r01, g1_1, 20.0E6, 20.5E6);
+        let rover_pivot = candidate(r02, g1_m2, 21.0E6, 21.5E6);
+
+        let base = candidate(r01, g1_1, 20.1E6, 20.6E6);
+        let base_pivot = candidate(r02, g1_m2, 21.0E6, 21.5E6);
+
+        let sd = rover.single_difference(&rover_pivot);
+
+        assert_eq!(sd.code, Some((g1_1, -1.0E6)));
+        assert_eq!(sd.channel_delta, 3);
+        assert!(sd.phase.is_none(), "FDMA phase should not be differenced");
+
+        let (mut rover_sd, mut base_sd) = (Differences::default(), Differences::default());
+
+        rover_sd.insert(r01, sd);
+        base_sd.insert(r01, base.single_difference(&base_pivot));
+
+        let mut dd = rover_sd.double_difference(&base_sd);
+        dd.ifb_correction_mut(0.5);
+
+        let dd = dd.difference(r01).unwrap();
+
+        assert_eq!(dd.channel_delta, 3);
+        assert_eq!(dd.code, Some((g1_1, -0.1E6 - 1.5)));
+
+        // different constellations are not differenced
+        let gps = Candidate::new(
+            SV::from_str("G01").unwrap(),
+            t,
+            vec![Observation::pseudo_range(Carrier::L1, 21.0E6, None)],
+        );
+
+        assert!(!rover.single_difference(&gps).is_some());
+    }
+
+    #[test]
+    fn shared_frequency_designations() {
+        let t = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+
+        // the rover receiver designates Galileo E5a as L5
+        let candidate = |sv, carrier, range_m: f64| {
+            Candidate::new(
+                sv,
+                t,
+                vec![
+                    Observation::pseudo_range(Carrier::L1, range_m, None),
+                    Observation::ambiguous_phase_range(Carrier::L1, range_m, None),
+                    Observation::pseudo_range(carrier, range_m + 1.0, None),
+                    Observation::ambiguous_phase_range(carrier, range_m + 1.0, None),
+                ],
+            )
+        };
+
+        let rover = candidate(E01, Carrier::L5, 20.0E6);
+        let rover_pivot = candidate(E03, Carrier::E5a, 21.0E6);
+
+        let base = candidate(E01, Carrier::E5a, 20.0E6 + 100.0);
+        let base_pivot = candidate(E03, Carrier::L5, 21.0E6 + 50.0);
+
+        let sd = rover.single_difference(&rover_pivot);
+
+        assert_eq!(sd.code_j, Some((Carrier::L5, -1.0E6)));
+        assert_eq!(sd.phase_j, Some((Carrier::L5, -1.0E6)));
+
+        // combinations are described within the Galileo signal plan
+        let (_, lambda_w, _) = sd.lw.unwrap();
+        assert_eq!(sd.lw.unwrap().0, Carrier::L1);
+        assert_eq!(
+            lambda_w,
+            SPEED_OF_LIGHT_M_S / (Carrier::L1.frequency_hz() - Carrier::E5a.frequency_hz())
+        );
+
+        let (mut rover_sd, mut base_sd) = (Differences::default(), Differences::default());
+
+        rover_sd.insert(E01, sd);
+        base_sd.insert(E01, base.single_difference(&base_pivot));
+
+        let dd = rover_sd.double_difference(&base_sd);
+        let dd = dd.difference(E01).unwrap();
+
+        assert_eq!(dd.code_j, Some((Carrier::L5, -50.0)));
+        assert_eq!(dd.phase_j, Some((Carrier::L5, -50.0)));
+        assert!(dd.phase_if.is_some());
+        assert!(dd.lw.is_some());
+    }
+
+    #[test]
+    fn triple_frequency_cascade() {
+        let t = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let (g01, g02) = (SV::from_str("G01").unwrap(), SV::from_str("G02").unwrap());
+
+        let carriers = [Carrier::L1, Carrier::L2, Carrier::L5];
+
+        // code noise (m) and integer ambiguities (cycles), per frequency
+        let candidate = |sv, rho_m: f64, noise_m: [f64; 3], ambiguities: [f64; 3]| {
+            let mut observations = Vec::new();
+
+            for (i, carrier) in carriers.iter().enumerate() {
+                observations.push(Observation::pseudo_range(
+                    *carrier,
+                    rho_m + noise_m[i],
+                    None,
+                ));
+
+                observations.push(Observation::ambiguous_phase_range(
+                    *carrier,
+                    rho_m + ambiguities[i] * carrier.wavelength(),
+                    None,
+                ));
+            }
+
+            Candidate::new(sv, t, observations)
+        };
+
+        let rover = candidate(g01, 20.0E6, [0.0, 0.3, -0.2], [12.0, 5.0, -3.0]);
+        let rover_pivot = candidate(g02, 21.0E6, [0.0; 3], [0.0; 3]);
+
+        let base = candidate(g01, 20.0E6 + 100.0, [0.0; 3], [0.0; 3]);
+        let base_pivot = candidate(g02, 21.0E6 + 50.0, [0.0; 3], [0.0; 3]);
+
+        assert!(rover.ppp_tf_compatible());
+        assert_eq!(
+            rover.third_pseudo_range(),
+            Some((Carrier::L5, 20.0E6 - 0.2))
+        );
+
+        let (mut rover_sd, mut base_sd) = (Differences::default(), Differences::default());
+
+        rover_sd.insert(g01, rover.single_difference(&rover_pivot));
+        base_sd.insert(g01, base.single_difference(&base_pivot));
+
+        let dd = rover_sd.double_difference(&base_sd);
+        let dd = dd.difference(g01).unwrap();
+
+        let ambiguities = dd.cascaded_ambiguities(0.15).unwrap();
+
+        assert_eq!(ambiguities.n_ewl, 8.0);
+        assert_eq!(ambiguities.n_wl, 7.0);
+        assert_eq!(ambiguities.n_1, 12.0);
+        assert_eq!(ambiguities.n_k(), -3.0);
+
+        // ambiguity fixed, triple frequency IF phase is the DD geometric range
+        let phase_tf_if = dd.phase_tf_if(g01, 0.15).unwrap();
+        assert!((phase_tf_if + 50.0).abs() < 1.0E-6);
+
+        let code_tf_if = dd.code_tf_if().unwrap();
+        assert!((code_tf_if + 50.0).abs() < 1.0);
+
+        // extra wide lane validation: code noise of half a cycle
+        let lambda_ewl =
+            SPEED_OF_LIGHT_M_S / (Carrier::L2.frequency_hz() - Carrier::L5.frequency_hz());
+        let noisy = candidate(
+            g01,
+            20.0E6,
+            [0.0, lambda_ewl / 2.0, lambda_ewl / 2.0],
+            [12.0, 5.0, -3.0],
+        );
+
+        let mut rover_sd = Differences::default();
+        rover_sd.insert(g01, noisy.single_difference(&rover_pivot));
+
+        let dd = rover_sd.double_difference(&base_sd);
+        let dd = dd.difference(g01).unwrap();
+
+        assert!(dd.has_triple_phase());
+        assert!(dd.cascaded_ambiguities(0.15).is_none());
+        assert!(dd.phase_tf_if(g01, 0.15).is_none());
+
+        // geometry free validation: residual ionosphere of 5cm on L1 (phase advance)
+        let mut iono = candidate(g01, 20.0E6, [0.0, 0.3, -0.2], [12.0, 5.0, -3.0]);
+
+        for ob in iono.observations.iter_mut() {
+            let ratio = Carrier::L1.frequency_hz() / ob.carrier.frequency_hz();
+
+            if let Some(phase_range_m) = ob.phase_range_m.as_mut() {
+                *phase_range_m -= 0.05 * ratio.powi(2);
+            }
+        }
+
+        let mut rover_sd = Differences::default();
+        rover_sd.insert(g01, iono.single_difference(&rover_pivot));
+
+        let dd = rover_sd.double_difference(&base_sd);
+        let dd = dd.difference(g01).unwrap();
+
+        assert!(dd.cascaded_ambiguities(0.15).is_none());
+
+        // without validation, the reference signal ambiguity is wrongly rounded
+        let ambiguities = dd.cascaded_ambiguities(0.5).unwrap();
+        assert_eq!(ambiguities.n_wl, 7.0);
+        assert_ne!(ambiguities.n_1, 12.0);
+
+        // absolute navigation: between satellite resolution, against the pivot
+        let mut rover = rover;
+        let sd = rover.single_difference(&rover_pivot);
+        let ambiguities = sd.cascaded_ambiguities(0.15).unwrap();
+
+        assert_eq!(ambiguities.n_1, 12.0);
+
+        rover.resolved_ambiguities_mut(g02, &sd, &ambiguities);
+        assert_eq!(rover.ambiguity_pivot, Some(g02));
+
+        for ob in rover.observations.iter() {
+            if let Some(phase_range_m) = ob.phase_range_m {
+                let ambiguity = ob.ambiguity.unwrap();
+                let range_m = phase_range_m + ambiguity * ob.carrier.wavelength();
+                assert!((range_m - 20.0E6).abs() < 1.0E-6);
+            }
+        }
+    }
 }
@@ -0,0 +1,252 @@
+//! Carrier phase wind up and satellite attitude (yaw) models
+use std::f64::consts::{PI, TAU};
+
+use crate::{
+    constants::EARTH_ANGULAR_VEL_RAD,
+    prelude::{Constellation, SV, Vector3},
+};
+
+/// Maximal yaw rate (in degrees per second) of the satellites that
+/// perform noon and midnight turns, when their nominal yaw rate
+/// becomes too large (low Sun elevation above the orbital plane).
+fn max_yaw_rate_deg_s(sv: SV) -> Option<f64> {
+    match sv.constellation {
+        Constellation::GPS => Some(0.12),
+        Constellation::Galileo => Some(0.2),
+        Constellation::BeiDou => Some(0.1),
+        Constellation::Glonass => Some(0.25),
+        _ => None,
+    }
+}
+
+/// Returns true when this satellite is in orbit normal mode (null yaw angle):
+/// - BeiDou GEO satellites, permanently
+/// - BeiDou-2 IGSO and MEO satellites, when the Sun elevation above
+///   the orbital plane (β) is lower than 4°.
+fn orbit_normal_mode(sv: SV, beta_deg: f64) -> bool {
+    if sv.constellation != Constellation::BeiDou {
+        return false;
+    }
+
+    match sv.prn {
+        1..=5 | 59..=63 => true,
+        6..=18 => beta_deg.abs() < 4.0,
+        _ => false,
+    }
+}
+
+/// Satellite body frame, expressed in ECEF
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SatelliteAttitude {
+    /// Yaw angle (in radians), when the orbital frame is known
+    pub yaw_rad: Option<f64>,
+
+    /// Body frame X axis
+    pub x: Vector3<f64>,
+
+    /// Body frame Y axis
+    pub y: Vector3<f64>,
+}
+
+impl SatelliteAttitude {
+    /// Resolves the satellite attitude. The nominal yaw steering attitude keeps
+    /// the solar panels (Y axis) perpendicular to the Sun, and the antenna (Z axis)
+    /// pointed to the Earth center. The yaw rate is limited to the maximal
+    /// hardware rate, which describes the noon and midnight turns.
+    ///
+    /// ## Input
+    /// - sv: [SV]
+    /// - r_sv_m: satellite position (ECEF, m)
+    /// - v_sv_m_s: satellite velocity (ECEF, m.s⁻¹), null when unknown
+    /// - r_sun_m: Sun position (ECEF, m)
+    /// - past_yaw: previous yaw angle (in radians) and elapsed time (in seconds)
+    pub fn new(
+        sv: SV,
+        r_sv_m: Vector3<f64>,
+        v_sv_m_s: Vector3<f64>,
+        r_sun_m: Vector3<f64>,
+        past_yaw: Option<(f64, f64)>,
+    ) -> Option<Self> {
+        let e_z = -r_sv_m.normalize();
+        let e_sun = (r_sun_m - r_sv_m).normalize();
+
+        let nominal_y = e_z.cross(&e_sun);
+
+        if v_sv_m_s.norm() == 0.0 {
+            // orbital frame is not known: nominal attitude
+            if nominal_y.norm() < 1.0E-9 {
+                return None;
+            }
+
+            let y = nominal_y.normalize();
+
+            return Some(Self {
+                yaw_rad: None,
+                x: y.cross(&e_z),
+                y,
+            });
+        }
+
+        // inertial velocity
+        let v_i = v_sv_m_s + Vector3::new(0.0, 0.0, EARTH_ANGULAR_VEL_RAD).cross(&r_sv_m);
+
+        let e_n = r_sv_m.cross(&v_i).normalize();
+        let beta_deg = e_n.dot(&r_sun_m.normalize()).asin().to_degrees();
+
+        // along track and cross track axes
+        let e_t = (v_i - e_z * v_i.dot(&e_z)).normalize();
+        let e_c = e_z.cross(&e_t);
+
+        let past_yaw_rad = past_yaw.map(|(yaw_rad, _)| yaw_rad);
+
+        let nominal_yaw_rad = if orbit_normal_mode(sv, beta_deg) {
+            0.0
+        } else if nominal_y.norm() < 1.0E-9 {
+            // singular geometry: Sun aligned with the Z axis
+            past_yaw_rad.unwrap_or_default()
+        } else {
+            let nominal_x = nominal_y.normalize().cross(&e_z);
+            nominal_x.dot(&e_c).atan2(nominal_x.dot(&e_t))
+        };
+
+        let mut yaw_rad = nominal_yaw_rad;
+
+        let past_yaw = past_yaw.filter(|(_, dt_s)| *dt_s > 0.0);
+
+        if let (Some((past_yaw_rad, dt_s)), Some(rate_deg_s)) = (past_yaw, max_yaw_rate_deg_s(sv)) {
+            let max_rad = (rate_deg_s * dt_s).to_radians();
+            let delta_rad = (nominal_yaw_rad - past_yaw_rad + PI).rem_euclid(TAU) - PI;
+
+            if delta_rad.abs() > max_rad {
+                yaw_rad = past_yaw_rad + max_rad.copysign(delta_rad);
+            }
+        }
+
+        let x = e_t * yaw_rad.cos() + e_c * yaw_rad.sin();
+
+        Some(Self {
+            yaw_rad: Some(yaw_rad),
+            x,
+            y: e_z.cross(&x),
+        })
+    }
+}
+
+/// Returns the carrier phase wind up (in cycles), from the satellite and receiver
+/// antenna orientations (Wu et al. 1993).
+///
+/// ## Input
+/// - k: line of sight, from the satellite to the receiver
+/// - sat: satellite body frame
+/// - rx_x, rx_y: receiver antenna axes (North, West)
+/// - past_windup: previous wind up (in cycles), to preserve the continuity
+pub(crate) fn windup_cycles(
+    k: Vector3<f64>,
+    sat: &SatelliteAttitude,
+    rx_x: Vector3<f64>,
+    rx_y: Vector3<f64>,
+    past_windup: Option<f64>,
+) -> f64 {
+    let d_s = sat.x - k * k.dot(&sat.x) - k.cross(&sat.y);
+    let d_r = rx_x - k * k.dot(&rx_x) + k.cross(&rx_y);
+
+    let cos_phi = (d_s.dot(&d_r) / d_s.norm() / d_r.norm()).clamp(-1.0, 1.0);
+
+    let mut windup = cos_phi.acos() / TAU;
+
+    if k.dot(&d_s.cross(&d_r)) < 0.0 {
+        windup = -windup;
+    }
+
+    match past_windup {
+        Some(past) => windup + (past - windup).round(),
+        None => windup,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SatelliteAttitude, windup_cycles};
+
+    use crate::{
+        constants::EARTH_ANGULAR_VEL_RAD,
+        prelude::{Constellation, SV, Vector3},
+    };
+
+    const ORBIT_RADIUS_M: f64 = 26_560.0E3;
+
+    /// Satellite above the equator (0°, 0°), with a 30° Sun elevation above its orbital
+    /// plane, at orbit noon.
+    fn geometry() -> (Vector3<f64>, Vector3<f64>, Vector3<f64>) {
+        let r_sv_m = Vector3::new(ORBIT_RADIUS_M, 0.0, 0.0);
+
+        // ECEF velocity of a 3.874 km/s inertial velocity
+        let v_sv_m_s = Vector3::new(0.0, 3874.0 - EARTH_ANGULAR_VEL_RAD * ORBIT_RADIUS_M, 0.0);
+
+        let beta = 30.0_f64.to_radians();
+        let r_sun_m = Vector3::new(beta.cos(), 0.0, beta.sin()) * 1.496E11;
+
+        (r_sv_m, v_sv_m_s, r_sun_m)
+    }
+
+    #[test]
+    fn yaw_attitude() {
+        let (r_sv_m, v_sv_m_s, r_sun_m) = geometry();
+        let gps = SV::new(Constellation::GPS, 1);
+
+        // nominal yaw steering
+        let attitude = SatelliteAttitude::new(gps, r_sv_m, v_sv_m_s, r_sun_m, None).unwrap();
+        let yaw_deg = attitude.yaw_rad.unwrap().to_degrees();
+
+        assert!((yaw_deg + 90.0).abs() < 1.0E-6, "yaw={yaw_deg}");
+        assert!(attitude.y.dot(&(r_sun_m - r_sv_m).normalize()).abs() < 1.0E-6);
+
+        // rate limited turn
+        let attitude =
+            SatelliteAttitude::new(gps, r_sv_m, v_sv_m_s, r_sun_m, Some((0.0, 30.0))).unwrap();
+        let yaw_deg = attitude.yaw_rad.unwrap().to_degrees();
+
+        assert!((yaw_deg + 3.6).abs() < 1.0E-6, "yaw={yaw_deg}");
+
+        // orbit normal mode: X axis along track
+        let geo = SV::new(Constellation::BeiDou, 1);
+
+        let attitude = SatelliteAttitude::new(geo, r_sv_m, v_sv_m_s, r_sun_m, None).unwrap();
+
+        assert_eq!(attitude.yaw_rad, Some(0.0));
+        assert!((attitude.x - Vector3::new(0.0, 1.0, 0.0)).norm() < 1.0E-9);
+    }
+
+    #[test]
+    fn phase_windup() {
+        // satellite at the zenith of a receiver located at (0°, 0°)
+        let k = Vector3::new(-1.0, 0.0, 0.0);
+
+        let (rx_x, rx_y) = (Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, -1.0, 0.0));
+
+        // satellite body frame, rotated by a yaw angle
+        let attitude = |yaw_deg: f64| {
+            let yaw = yaw_deg.to_radians();
+            let x = Vector3::new(0.0, -yaw.sin(), yaw.cos());
+
+            SatelliteAttitude {
+                yaw_rad: Some(yaw),
+                x,
+                y: k.cross(&x),
+            }
+        };
+
+        let windup = windup_cycles(k, &attitude(0.0), rx_x, rx_y, None);
+        assert!(windup.abs() < 1.0E-9);
+
+        let positive = windup_cycles(k, &attitude(60.0), rx_x, rx_y, None);
+        let negative = windup_cycles(k, &attitude(-60.0), rx_x, rx_y, None);
+
+        assert!((positive.abs() - 1.0 / 6.0).abs() < 1.0E-9);
+        assert!((positive + negative).abs() < 1.0E-9);
+
+        // continuity
+        let windup = windup_cycles(k, &attitude(60.0), rx_x, rx_y, Some(3.0));
+        assert!((windup - 3.0 - positive).abs() < 1.0E-9);
+    }
+}
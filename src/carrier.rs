@@ -1,4 +1,8 @@
-use crate::{constants::SPEED_OF_LIGHT_M_S, prelude::Error};
+use crate::{
+    candidate::combination::triple_if_coefficients,
+    constants::SPEED_OF_LIGHT_M_S,
+    prelude::{Constellation, Error},
+};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -16,55 +20,78 @@ pub enum Carrier {
     /// [Carrier::L5] (1176.45) (GPS +QZSS +SBAS +IRNSS)
     L5,
 
+    /// [Carrier::E5a] (1176.45) (Galileo)
+    E5a,
+
     /// [Carrier::E5b] (1207.140) (Galileo)
     E5b,
 
     /// [Carrier::E5a5b] (1191.795) (Galileo +BDS)
     E5a5b,
 
-    // /// [Carrier::E6Lex] (1278.75) (Galileo +QZSS)
-    // E6Lex,
-    /// [Carrier::B1] (1561.098) (BDS)
+    /// [Carrier::E6] (1278.75) (Galileo +QZSS L6)
+    E6,
+
+    /// [Carrier::B1] (1561.098) (BDS B1I)
     B1,
 
+    /// [Carrier::B1C] (1575.42) (BDS)
+    B1C,
+
+    /// [Carrier::B2a] (1176.45) (BDS)
+    B2a,
+
+    /// [Carrier::B2b] (1207.14) (BDS)
+    B2b,
+
     /// [Carrier::B3] (1268.52) (BDS)
     B3,
-    // /// [Carrier::S] (2492.028) (IRNSS)
-    // S,
-    //
-    // /// [Carrier::G1] (1602.00) (Glonass)
-    // G1,
-    //
-    // /// [Carrier::G1a] (1600.995) (Glonass)
-    // G1a,
-    //
-    // /// [Carrier::G2] (1246.00) (Glonass)
-    // G2,
-    //
-    // /// [Carrier::G2a] (1248.06) (Glonass)
-    // G2a,
-    //
-    // /// [Carrier::G3] (1202.025) (Glonass)
-    // G3,
+
+    /// [Carrier::S] (2492.028) (IRNSS)
+    S,
+
+    /// [Carrier::G1] (1602.00 + k*0.5625) (Glonass FDMA),
+    /// parametrized by the frequency channel number k (-7..=6).
+    G1(i8),
+
+    /// [Carrier::G1a] (1600.995) (Glonass CDMA)
+    G1a,
+
+    /// [Carrier::G2] (1246.00 + k*0.4375) (Glonass FDMA),
+    /// parametrized by the frequency channel number k (-7..=6).
+    G2(i8),
+
+    /// [Carrier::G2a] (1248.06) (Glonass CDMA)
+    G2a,
+
+    /// [Carrier::G3] (1202.025) (Glonass CDMA)
+    G3,
 }
 
+/// Glonass FDMA frequency channel numbers
+const GLONASS_FDMA_CHANNELS: std::ops::RangeInclusive<i8> = -7..=6;
+
 impl std::fmt::Display for Carrier {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         match self {
             Self::L1 => write!(f, "L1"),
             Self::L2 => write!(f, "L2"),
             Self::L5 => write!(f, "L5"),
+            Self::E5a => write!(f, "E5a"),
             Self::E5b => write!(f, "E5b"),
             Self::E5a5b => write!(f, "E5a5b"),
-            // Self::E6Lex => write!(f, "E6/LEX"),
+            Self::E6 => write!(f, "E6"),
             Self::B1 => write!(f, "B1"),
+            Self::B1C => write!(f, "B1C"),
+            Self::B2a => write!(f, "B2a"),
+            Self::B2b => write!(f, "B2b"),
             Self::B3 => write!(f, "B3"),
-            // Self::S => write!(f, "S"),
-            // Self::G1 => write!(f, "G1"),
-            // Self::G1a => write!(f, "G1a"),
-            // Self::G2 => write!(f, "G2"),
-            // Self::G2a => write!(f, "G2a"),
-            // Self::G3 => write!(f, "G3"),
+            Self::S => write!(f, "S"),
+            Self::G1(k) => write!(f, "G1({k})"),
+            Self::G1a => write!(f, "G1a"),
+            Self::G2(k) => write!(f, "G2({k})"),
+            Self::G2a => write!(f, "G2a"),
+            Self::G3 => write!(f, "G3"),
         }
     }
 }
@@ -79,18 +106,41 @@ impl std::str::FromStr for Carrier {
             "l1" => Ok(Self::L1),
             "l2" => Ok(Self::L2),
             "l5" => Ok(Self::L5),
+            "e5a" => Ok(Self::E5a),
             "e5b" => Ok(Self::E5b),
             "e5a5b" => Ok(Self::E5a5b),
-            "b1" => Ok(Self::B1),
+            "e6" | "l6" | "lex" | "e6/lex" => Ok(Self::E6),
+            "b1" | "b1i" => Ok(Self::B1),
+            "b1c" => Ok(Self::B1C),
+            "b2a" => Ok(Self::B2a),
+            "b2b" => Ok(Self::B2b),
             "b3" => Ok(Self::B3),
-            // "s" => Ok(Self::S),
-            // "g1" => Ok(Self::G1),
-            // "g1a" => Ok(Self::G1a),
-            // "g2" => Ok(Self::G2),
-            // "g2a" => Ok(Self::G2a),
-            // "g3" => Ok(Self::G3),
-            // "e6" | "lex" | "e6/lex" => Ok(Self::E6Lex),
-            _ => Err(Error::InvalidFrequency),
+            "s" => Ok(Self::S),
+            "g1a" => Ok(Self::G1a),
+            "g2a" => Ok(Self::G2a),
+            "g3" => Ok(Self::G3),
+            _ => {
+                // FDMA: "G1(k)" or "G2(k)"
+                let (band, channel) = lowered
+                    .strip_suffix(')')
+                    .and_then(|s| s.split_once('('))
+                    .ok_or(Error::InvalidFrequency)?;
+
+                let channel = channel
+                    .trim()
+                    .parse::<i8>()
+                    .map_err(|_| Error::InvalidFrequency)?;
+
+                if !GLONASS_FDMA_CHANNELS.contains(&channel) {
+                    return Err(Error::InvalidFrequency);
+                }
+
+                match band.trim() {
+                    "g1" => Ok(Self::G1(channel)),
+                    "g2" => Ok(Self::G2(channel)),
+                    _ => Err(Error::InvalidFrequency),
+                }
+            },
         }
     }
 }
@@ -98,6 +148,8 @@ impl std::str::FromStr for Carrier {
 impl Carrier {
     /// Build a new [Carrier] frequency from value in MHz.
     /// This requires a 1kHz accuracy on the description.
+    /// Signals sharing the same frequency cannot be told apart: the GPS
+    /// (or Galileo) designation is returned, see [Self::for_constellation].
     pub fn from_frequency_mega_hz(freq_mhz: f64) -> Result<Self, Error> {
         if freq_mhz.is_sign_negative() {
             return Err(Error::InvalidFrequency);
@@ -111,21 +163,138 @@ impl Carrier {
             1176_450 => Ok(Self::L5),
             1207_140 => Ok(Self::E5b),
             1191_795 => Ok(Self::E5a5b),
-            // 1278_750 => Ok(Self::E6Lex),
+            1278_750 => Ok(Self::E6),
             1561_098 => Ok(Self::B1),
             1268_520 => Ok(Self::B3),
-            // 2492_028 => Ok(Self::S),
-            // 1602_000 => Ok(Self::G1),
-            // 1600_995 => Ok(Self::G1a),
-            // 1246_000 => Ok(Self::G2),
-            // 1248_060 => Ok(Self::G2a),
-            // 1202_025 => Ok(Self::G3),
-            _ => Err(Error::UnknownCarrierFrequency),
+            2492_028 => Ok(Self::S),
+            1600_995 => Ok(Self::G1a),
+            1248_060 => Ok(Self::G2a),
+            1202_025 => Ok(Self::G3),
+            _ => {
+                // FDMA channels
+                for (base_mhz, spacing_mhz, band) in [
+                    (1602.0, 0.5625, Self::G1 as fn(i8) -> Self),
+                    (1246.0, 0.4375, Self::G2 as fn(i8) -> Self),
+                ] {
+                    let k = (freq_mhz - base_mhz) / spacing_mhz;
+                    let channel = k.round();
+
+                    if (k - channel).abs() * spacing_mhz < 1.0E-3
+                        && GLONASS_FDMA_CHANNELS.contains(&(channel as i8))
+                    {
+                        return Ok(band(channel as i8));
+                    }
+                }
+
+                Err(Error::UnknownCarrierFrequency)
+            },
+        }
+    }
+
+    /// Returns the designation of this [Carrier] frequency, within the signal plan
+    /// of this [Constellation]. For example, [Carrier::L5] is [Carrier::E5a] for Galileo
+    /// and [Carrier::B2a] for BeiDou.
+    pub fn for_constellation(&self, constellation: Constellation) -> Self {
+        match (constellation, self) {
+            (Constellation::Galileo, Self::L5) => Self::E5a,
+            (Constellation::BeiDou, Self::L1) => Self::B1C,
+            (Constellation::BeiDou, Self::L5 | Self::E5a) => Self::B2a,
+            (Constellation::BeiDou, Self::E5b) => Self::B2b,
+            (Constellation::Galileo, Self::B2a) => Self::E5a,
+            (Constellation::Galileo, Self::B2b) => Self::E5b,
+            (
+                Constellation::GPS | Constellation::QZSS | Constellation::IRNSS,
+                Self::E5a | Self::B2a,
+            ) => Self::L5,
+            (_, Self::B1C) if constellation != Constellation::BeiDou => Self::L1,
+            _ => *self,
+        }
+    }
+
+    /// Returns true if this [Carrier] may serve as reference signal
+    /// for this [Constellation]: L1 (or equivalent) for most constellations,
+    /// while IRNSS is primarily an L5 system.
+    pub(crate) fn is_reference(&self, constellation: Constellation) -> bool {
+        match constellation {
+            Constellation::BeiDou => matches!(self, Self::B1 | Self::B1C | Self::L1),
+            Constellation::IRNSS => matches!(self, Self::L5 | Self::L1),
+            Constellation::Glonass => matches!(self, Self::G1(_) | Self::G1a),
+            _ => matches!(
+                self,
+                Self::L1 | Self::B1 | Self::B1C | Self::G1(_) | Self::G1a
+            ),
+        }
+    }
+
+    /// Returns the rank of this [Carrier] within the signal plan of this [Constellation],
+    /// the lower the rank the more prefered the signal. Signals that do not belong
+    /// to this plan come last.
+    pub(crate) fn signal_rank(&self, constellation: Constellation) -> u8 {
+        let plan: &[&[Self]] = match constellation {
+            Constellation::Galileo => &[
+                &[Self::L1],
+                &[Self::E5a, Self::L5],
+                &[Self::E5b],
+                &[Self::E5a5b],
+                &[Self::E6],
+            ],
+            Constellation::BeiDou => &[
+                &[Self::B1],
+                &[Self::B1C, Self::L1],
+                &[Self::B3],
+                &[Self::B2a, Self::L5],
+                &[Self::B2b, Self::E5b],
+                &[Self::E5a5b],
+            ],
+            Constellation::IRNSS => &[&[Self::L5], &[Self::S], &[Self::L1]],
+            Constellation::Glonass => {
+                return match self {
+                    Self::G1(_) => 0,
+                    Self::G1a => 1,
+                    Self::G2(_) => 2,
+                    Self::G2a => 3,
+                    Self::G3 => 4,
+                    _ => u8::MAX,
+                };
+            },
+            _ => &[&[Self::L1], &[Self::L2], &[Self::L5], &[Self::E6]],
+        };
+
+        plan.iter()
+            .position(|signals| signals.contains(self))
+            .map(|rank| rank as u8)
+            .unwrap_or(u8::MAX)
+    }
+
+    /// Returns the Glonass FDMA frequency channel number, if this is a FDMA [Carrier].
+    pub fn frequency_channel(&self) -> Option<i8> {
+        match self {
+            Self::G1(k) | Self::G2(k) => Some(*k),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this is a Glonass FDMA [Carrier].
+    pub fn is_fdma(&self) -> bool {
+        self.frequency_channel().is_some()
+    }
+
+    /// Returns true if both [Carrier]s belong to the same frequency band.
+    /// Glonass FDMA [Carrier]s of the same band match, whatever their frequency channel.
+    pub(crate) fn same_band(&self, rhs: &Self) -> bool {
+        match (self, rhs) {
+            (Self::G1(_), Self::G1(_)) | (Self::G2(_), Self::G2(_)) => true,
+            _ => self == rhs,
         }
     }
 
-    pub(crate) fn is_l1(&self) -> bool {
-        matches!(*self, Self::L1 | Self::B1)
+    /// Returns true if both [Carrier]s describe the same signal within the
+    /// signal plan of this [Constellation], see [Self::for_constellation].
+    /// For example, [Carrier::L5] and [Carrier::E5a] are the same Galileo signal.
+    /// Glonass FDMA [Carrier]s of the same band match, like [Self::same_band].
+    pub(crate) fn same_signal(&self, rhs: &Self, constellation: Constellation) -> bool {
+        self.same_band(rhs)
+            || self.for_constellation(constellation) == rhs.for_constellation(constellation)
     }
 
     // /// Returns unsigned frequency in kHz
@@ -140,17 +309,21 @@ impl Carrier {
             Self::L1 => 1575.420_f64,
             Self::L2 => 1227.600_f64,
             Self::L5 => 1176.450_f64,
+            Self::E5a => 1176.450,
             Self::E5b => 1207.140,
             Self::E5a5b => 1191.795,
-            // Self::E6Lex => 1278.750,
+            Self::E6 => 1278.750,
             Self::B1 => 1561.098,
+            Self::B1C => 1575.420,
+            Self::B2a => 1176.450,
+            Self::B2b => 1207.140,
             Self::B3 => 1268.520,
-            // Self::S => 2492.028,
-            // Self::G1 => 1602.000,
-            // Self::G1a => 1600.995,
-            // Self::G2 => 1246.000,
-            // Self::G2a => 1248.060,
-            // Self::G3 => 1202.025,
+            Self::S => 2492.028,
+            Self::G1(k) => 1602.000 + *k as f64 * 0.5625,
+            Self::G1a => 1600.995,
+            Self::G2(k) => 1246.000 + *k as f64 * 0.4375,
+            Self::G2a => 1248.060,
+            Self::G3 => 1202.025,
         }
     }
 
@@ -169,6 +342,8 @@ impl Carrier {
 pub enum Signal {
     Single(Carrier),
     Dual((Carrier, Carrier)),
+    /// Reference, subsidary and third [Carrier]s
+    Triple((Carrier, Carrier, Carrier)),
 }
 
 impl Default for Signal {
@@ -177,11 +352,30 @@ impl Default for Signal {
     }
 }
 
+impl Signal {
+    /// Returns the noise amplification factor of the ionosphere free combination
+    /// this [Signal] forms, assuming independent measurements of equal uncertainty.
+    pub(crate) fn noise_factor(&self) -> f64 {
+        match self {
+            Self::Single(_) => 1.0,
+            Self::Dual((lhs, rhs)) => {
+                let (f1, fj) = (rhs.frequency_hz().powi(2), lhs.frequency_hz().powi(2));
+                (f1.powi(2) + fj.powi(2)).sqrt() / (f1 - fj).abs()
+            },
+            Self::Triple((c_1, c_j, c_k)) => {
+                let (a_1, a_j, a_k) = triple_if_coefficients(*c_1, *c_j, *c_k);
+                (a_1.powi(2) + a_j.powi(2) + a_k.powi(2)).sqrt()
+            },
+        }
+    }
+}
+
 impl std::fmt::Display for Signal {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         match self {
             Self::Single(carrier) => write!(f, "{carrier}"),
             Self::Dual((lhs, rhs)) => write!(f, "{rhs}/{lhs}"),
+            Self::Triple((c_1, c_j, c_k)) => write!(f, "{c_1}/{c_j}/{c_k}"),
         }
     }
 }
@@ -197,26 +391,35 @@ impl std::fmt::Display for Signal {
 
 #[cfg(test)]
 mod test {
-    use super::Carrier;
+    use super::{Carrier, Signal};
+    use crate::prelude::Constellation;
     use std::str::FromStr;
 
     #[test]
     fn test_frequencies() {
-        for carrier in [
-            Carrier::L1,
-            Carrier::L2,
-            Carrier::L5,
-            Carrier::E5b,
-            Carrier::E5a5b,
-            // Carrier::E6Lex,
-            Carrier::B1,
-            Carrier::B3,
-            // Carrier::S,
-            // Carrier::G1,
-            // Carrier::G1a,
-            // Carrier::G2,
-            // Carrier::G2a,
-            // Carrier::G3,
+        for (carrier, constellation) in [
+            (Carrier::L1, Constellation::GPS),
+            (Carrier::L2, Constellation::GPS),
+            (Carrier::L5, Constellation::GPS),
+            (Carrier::E5a, Constellation::Galileo),
+            (Carrier::E5b, Constellation::Galileo),
+            (Carrier::E5a5b, Constellation::Galileo),
+            (Carrier::E6, Constellation::Galileo),
+            (Carrier::B1, Constellation::BeiDou),
+            (Carrier::B1C, Constellation::BeiDou),
+            (Carrier::B2a, Constellation::BeiDou),
+            (Carrier::B2b, Constellation::BeiDou),
+            (Carrier::B3, Constellation::BeiDou),
+            (Carrier::S, Constellation::IRNSS),
+            (Carrier::G1(-7), Constellation::Glonass),
+            (Carrier::G1(0), Constellation::Glonass),
+            (Carrier::G1(6), Constellation::Glonass),
+            (Carrier::G1a, Constellation::Glonass),
+            (Carrier::G2(-7), Constellation::Glonass),
+            (Carrier::G2(1), Constellation::Glonass),
+            (Carrier::G2(6), Constellation::Glonass),
+            (Carrier::G2a, Constellation::Glonass),
+            (Carrier::G3, Constellation::Glonass),
         ] {
             let freq_mhz = carrier.frequency_mega_hz();
 
@@ -224,11 +427,15 @@ mod test {
                 panic!("{e} - failed to identify carrier from frequency: {freq_mhz} Mhz")
             });
 
+            // shared frequencies are told apart by the constellation
             assert_eq!(
-                identified, carrier,
+                identified.for_constellation(constellation),
+                carrier,
                 "Carrier frequency API: invalid for {freq_mhz} frequency",
             );
 
+            assert!(identified.same_signal(&carrier, constellation));
+
             let formatted = carrier.to_string();
             let parsed = Carrier::from_str(&formatted).unwrap_or_else(|e| {
                 panic!("{e} - failed to identify Carrier from \"{formatted}\"")
@@ -240,4 +447,121 @@ mod test {
             )
         }
     }
+
+    #[test]
+    fn glonass_fdma() {
+        assert_eq!(Carrier::G1(-7).frequency_mega_hz(), 1598.0625);
+        assert_eq!(Carrier::G2(6).frequency_mega_hz(), 1248.625);
+
+        assert_eq!(Carrier::G1(3).frequency_channel(), Some(3));
+        assert_eq!(Carrier::G1a.frequency_channel(), None);
+
+        assert!(Carrier::G1(1).same_band(&Carrier::G1(-2)));
+        assert!(!Carrier::G1(1).same_band(&Carrier::G2(1)));
+        assert!(!Carrier::G1(0).same_band(&Carrier::L1));
+
+        assert!(Carrier::G1(-1).wavelength() > Carrier::G1(1).wavelength());
+
+        assert_eq!(Carrier::from_str("G1(+2)").unwrap(), Carrier::G1(2));
+
+        for invalid in ["G1", "G1(7)", "G1(-8)", "G3(1)"] {
+            assert!(Carrier::from_str(invalid).is_err());
+        }
+
+        assert!(Carrier::from_frequency_mega_hz(1602.25).is_err());
+        assert!(Carrier::from_frequency_mega_hz(1606.5).is_err());
+    }
+
+    #[test]
+    fn constellation_signal_plans() {
+        // shared frequencies
+        for (carrier, generic) in [
+            (Carrier::E5a, Carrier::L5),
+            (Carrier::B1C, Carrier::L1),
+            (Carrier::B2a, Carrier::L5),
+            (Carrier::B2b, Carrier::E5b),
+        ] {
+            assert_eq!(carrier.frequency_mega_hz(), generic.frequency_mega_hz());
+
+            let identified = Carrier::from_frequency_mega_hz(carrier.frequency_mega_hz()).unwrap();
+            assert_eq!(identified, generic);
+
+            let formatted = carrier.to_string();
+            assert_eq!(Carrier::from_str(&formatted).unwrap(), carrier);
+        }
+
+        assert!(Carrier::L5.same_signal(&Carrier::E5a, Constellation::Galileo));
+        assert!(Carrier::E5b.same_signal(&Carrier::B2b, Constellation::BeiDou));
+        assert!(!Carrier::L5.same_signal(&Carrier::E5b, Constellation::Galileo));
+        assert!(Carrier::G1(1).same_signal(&Carrier::G1(-2), Constellation::Glonass));
+
+        assert_eq!(Carrier::from_str("L6").unwrap(), Carrier::E6);
+        assert_eq!(Carrier::from_str("B1I").unwrap(), Carrier::B1);
+
+        assert_eq!(
+            Carrier::L5.for_constellation(Constellation::Galileo),
+            Carrier::E5a
+        );
+        assert_eq!(
+            Carrier::L1.for_constellation(Constellation::BeiDou),
+            Carrier::B1C
+        );
+        assert_eq!(
+            Carrier::L5.for_constellation(Constellation::BeiDou),
+            Carrier::B2a
+        );
+        assert_eq!(
+            Carrier::E5b.for_constellation(Constellation::BeiDou),
+            Carrier::B2b
+        );
+        assert_eq!(
+            Carrier::B2a.for_constellation(Constellation::GPS),
+            Carrier::L5
+        );
+        assert_eq!(
+            Carrier::L1.for_constellation(Constellation::Galileo),
+            Carrier::L1
+        );
+        assert_eq!(
+            Carrier::L5.for_constellation(Constellation::IRNSS),
+            Carrier::L5
+        );
+
+        // reference signals
+        assert!(Carrier::L1.is_reference(Constellation::GPS));
+        assert!(!Carrier::L5.is_reference(Constellation::GPS));
+        assert!(Carrier::L5.is_reference(Constellation::IRNSS));
+        assert!(Carrier::B1C.is_reference(Constellation::BeiDou));
+        assert!(!Carrier::B2a.is_reference(Constellation::BeiDou));
+
+        // signal plans
+        assert!(
+            Carrier::E5a.signal_rank(Constellation::Galileo)
+                < Carrier::E6.signal_rank(Constellation::Galileo)
+        );
+        assert!(
+            Carrier::B1.signal_rank(Constellation::BeiDou)
+                < Carrier::B1C.signal_rank(Constellation::BeiDou)
+        );
+        assert!(
+            Carrier::S.signal_rank(Constellation::IRNSS)
+                < Carrier::L1.signal_rank(Constellation::IRNSS)
+        );
+        assert_eq!(Carrier::S.signal_rank(Constellation::GPS), u8::MAX);
+    }
+
+    #[test]
+    fn signal_noise_factors() {
+        assert_eq!(Signal::Single(Carrier::L1).noise_factor(), 1.0);
+
+        let l1_l2 = Signal::Dual((Carrier::L2, Carrier::L1)).noise_factor();
+        assert!((l1_l2 - 2.978).abs() < 1.0E-3, "L1/L2: {l1_l2}");
+
+        let l1_l5 = Signal::Dual((Carrier::L5, Carrier::L1)).noise_factor();
+        assert!((l1_l5 - 2.588).abs() < 1.0E-3, "L1/L5: {l1_l5}");
+
+        // minimal noise combination: never worse than the dual frequency combinations
+        let triple = Signal::Triple((Carrier::L1, Carrier::L2, Carrier::L5)).noise_factor();
+        assert!(triple > 1.0 && triple < l1_l5, "L1/L2/L5: {triple}");
+    }
 }
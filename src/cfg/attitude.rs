@@ -0,0 +1,35 @@
+//! Attitude determination preset
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const fn default_max_length_error() -> f64 {
+    0.05
+}
+
+/// [AttitudeOpts] describes the antennas installed on the rigid body,
+/// for multi-antenna attitude determination.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AttitudeOpts {
+    /// Lever arm of each secondary antenna, from the primary antenna, in meters,
+    /// expressed in the body frame (forward, right, down).
+    /// A single secondary antenna, installed along the forward axis, resolves heading and pitch.
+    /// Two secondary antennas with non colinear lever arms also resolve roll.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub lever_arms_m: Vec<(f64, f64, f64)>,
+
+    /// Maximal error tolerated between the resolved baseline length
+    /// and the lever arm length, in meters.
+    #[cfg_attr(feature = "serde", serde(default = "default_max_length_error"))]
+    pub max_length_error_m: f64,
+}
+
+impl Default for AttitudeOpts {
+    fn default() -> Self {
+        Self {
+            lever_arms_m: Default::default(),
+            max_length_error_m: default_max_length_error(),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Cycle slip detection preset
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const fn default_detection() -> bool {
+    false
+}
+
+const fn default_gf_threshold() -> f64 {
+    0.05
+}
+
+const fn default_mw_threshold() -> f64 {
+    3.0
+}
+
+const fn default_doppler_threshold() -> f64 {
+    5.0
+}
+
+const fn default_max_gap() -> f64 {
+    60.0
+}
+
+const fn default_code_carrier_threshold() -> f64 {
+    10.0
+}
+
+/// [CycleSlipOpts] to parametrize the phase arcs tracking. Each satellite is tracked
+/// continuously, and a new phase arc starts on each cycle slip or data gap,
+/// which resets the phase ambiguities of that satellite.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CycleSlipOpts {
+    /// Enables the cycle slip detection, which requires phase observations.
+    /// Disabled by default: the float ambiguities of the navigation filter
+    /// then only restart on data gaps.
+    #[cfg_attr(feature = "serde", serde(default = "default_detection"))]
+    pub detection: bool,
+
+    /// Maximal variation of the geometry free phase combination
+    /// between two epochs (in meters).
+    #[cfg_attr(feature = "serde", serde(default = "default_gf_threshold"))]
+    pub gf_threshold_m: f64,
+
+    /// Maximal deviation of the Melbourne-Wübbena combination from its
+    /// average over the current phase arc (in wide lane cycles).
+    #[cfg_attr(feature = "serde", serde(default = "default_mw_threshold"))]
+    pub mw_threshold_cycles: f64,
+
+    /// Maximal deviation of the phase variation, from the
+    /// Doppler predicted variation (in cycles).
+    #[cfg_attr(feature = "serde", serde(default = "default_doppler_threshold"))]
+    pub doppler_threshold_cycles: f64,
+
+    /// Maximal data gap (in seconds) before a new phase arc is started.
+    #[cfg_attr(feature = "serde", serde(default = "default_max_gap"))]
+    pub max_gap_s: f64,
+
+    /// Maximal deviation of the pseudo range from its carrier smoothed prediction
+    /// (in meters), before the code smoothing of that signal restarts.
+    /// This check is always active when code smoothing is enabled,
+    /// even when the cycle slip detection is not.
+    #[cfg_attr(feature = "serde", serde(default = "default_code_carrier_threshold"))]
+    pub code_carrier_threshold_m: f64,
+}
+
+impl Default for CycleSlipOpts {
+    fn default() -> Self {
+        Self {
+            detection: default_detection(),
+            gf_threshold_m: default_gf_threshold(),
+            mw_threshold_cycles: default_mw_threshold(),
+            doppler_threshold_cycles: default_doppler_threshold(),
+            max_gap_s: default_max_gap(),
+            code_carrier_threshold_m: default_code_carrier_threshold(),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Integrity monitoring preset
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const fn default_raim() -> bool {
+    false
+}
+
+const fn default_false_alarm_probability() -> f64 {
+    1.0E-5
+}
+
+const fn default_integrity_risk() -> f64 {
+    1.0E-7
+}
+
+const fn default_max_exclusions() -> usize {
+    1
+}
+
+/// [IntegrityOpts] to parametrize the receiver autonomous integrity monitoring (RAIM):
+/// fault detection and exclusion (FDE), and the protection levels.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IntegrityOpts {
+    /// Enables the chi-square residual test on each solution, the exclusion
+    /// of faulty satellites, and the protection levels.
+    /// Requires a [StochasticModel](crate::prelude::StochasticModel) that models
+    /// the measurement uncertainties: unit weights are not supported.
+    #[cfg_attr(feature = "serde", serde(default = "default_raim"))]
+    pub raim: bool,
+
+    /// Probability of false alarm of the residual test.
+    /// Defines the detection threshold.
+    #[cfg_attr(feature = "serde", serde(default = "default_false_alarm_probability"))]
+    pub false_alarm_probability: f64,
+
+    /// Integrity risk, which is the tolerated probability of
+    /// missed detection. Defines the protection levels.
+    #[cfg_attr(feature = "serde", serde(default = "default_integrity_risk"))]
+    pub integrity_risk: f64,
+
+    /// Maximal number of satellites that may be excluded, per epoch.
+    /// When the fault persists, the solution is rejected.
+    #[cfg_attr(feature = "serde", serde(default = "default_max_exclusions"))]
+    pub max_exclusions: usize,
+}
+
+impl Default for IntegrityOpts {
+    fn default() -> Self {
+        Self {
+            raim: default_raim(),
+            false_alarm_probability: default_false_alarm_probability(),
+            integrity_risk: default_integrity_risk(),
+            max_exclusions: default_max_exclusions(),
+        }
+    }
+}
@@ -31,6 +31,19 @@ pub enum Method {
     /// prefit. This is the most accurate solution, with at least 2 orders of magnitude
     /// improvements from [Method::CPP].
     PPP,
+
+    /// Triple frequency Precise Point Positioning. Same as [Method::PPP],
+    /// but uses the minimal noise ionosphere free combination of three frequencies
+    /// (for example L1/L2/L5 or E1/E5a/E5b). In RTK, the phase ambiguities are resolved
+    /// in cascade: extra wide lane first, then wide lane, then the reference signal.
+    PPP_TF,
+}
+
+impl Method {
+    /// Returns true if this [Method] navigates using phase observations.
+    pub(crate) fn uses_phase(&self) -> bool {
+        matches!(self, Self::PPP | Self::PPP_TF)
+    }
 }
 
 impl std::fmt::Display for Method {
@@ -39,6 +52,7 @@ impl std::fmt::Display for Method {
             Self::SPP => write!(fmt, "SPP"),
             Self::CPP => write!(fmt, "CPP"),
             Self::PPP => write!(fmt, "PPP"),
+            Self::PPP_TF => write!(fmt, "PPP-TF"),
         }
     }
 }
@@ -50,6 +64,7 @@ impl std::str::FromStr for Method {
             "spp" => Ok(Self::SPP),
             "cpp" => Ok(Self::CPP),
             "ppp" => Ok(Self::PPP),
+            "ppp-tf" | "ppp_tf" | "tf-ppp" => Ok(Self::PPP_TF),
             _ => Err(Error::UnknownNavigationMethod),
         }
     }
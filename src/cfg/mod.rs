@@ -3,19 +3,31 @@ use thiserror::Error;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+mod attitude;
+mod cycle_slip;
+mod integrity;
 mod method;
 mod modeling;
+mod ocean_loading;
 mod solver;
+mod stochastic;
 
 pub use crate::{
     carrier::Signal,
+    cfg::attitude::AttitudeOpts,
+    cfg::cycle_slip::CycleSlipOpts,
+    cfg::integrity::IntegrityOpts,
+    cfg::ocean_loading::OceanLoading,
     cfg::solver::SolverOpts,
+    cfg::stochastic::{ConstellationSigmas, StochasticModel, WeightingModel},
     cfg::{method::Method, modeling::Modeling},
     prelude::TimeScale,
 };
 
+use crate::prelude::Carrier;
+
 /// Configuration Error
-#[derive(Debug, Error)]
+#[derive(Debug, PartialEq, Error)]
 pub enum Error {
     #[error("invalid troposphere model")]
     InvalidTroposphereModel,
@@ -25,6 +37,18 @@ pub enum Error {
 
     #[error("invalid clock profile")]
     InvalidClockProfile,
+
+    #[error("invalid BLQ ocean loading coefficients")]
+    InvalidOceanLoading,
+
+    #[error("invalid stochastic model: uncertainties should be positive and finite")]
+    InvalidStochasticModel,
+
+    #[error("integrity monitoring requires a stochastic model: unit weights are not supported")]
+    UnweightedIntegrityMonitoring,
+
+    #[error("ocean loading modeling requires the BLQ coefficients of the site")]
+    MissingOceanLoading,
 }
 
 const fn default_timescale() -> TimeScale {
@@ -68,6 +92,17 @@ pub struct InternalDelay {
     pub frequency: f64,
 }
 
+impl InternalDelay {
+    /// Returns true if this [InternalDelay] applies to this [Carrier] (1kHz accuracy).
+    /// Glonass FDMA delays apply to all the frequency channels of the same band.
+    pub(crate) fn applies_to(&self, carrier: Carrier) -> bool {
+        match Carrier::from_frequency_mega_hz(self.frequency / 1.0E6) {
+            Ok(band) if band.is_fdma() => band.same_band(&carrier),
+            _ => (self.frequency - carrier.frequency_hz()).abs() < 1.0E3,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Config {
@@ -94,24 +129,39 @@ pub struct Config {
     #[cfg_attr(feature = "serde", serde(default))]
     pub fixed_altitude: Option<f64>,
 
-    /// Pseudo Range code smoothing (window length).
-    /// Use phase observatoins to smooth and reduce error in the pseudo range code.
+    /// Pseudo Range code smoothing (window length, in epochs).
+    /// Use phase observations to smooth and reduce error in the pseudo range code (Hatch filter).
+    /// The smoothing is divergence free when dual frequency phase observations are available.
+    /// It restarts on each cycle slip, data gap, and code-carrier jump (see [CycleSlipOpts]).
     /// This has no effect if phase observations are missing.
     /// Set to 0 to disable this feature completely.
     /// When parametrizing, think in terms of window duration versus Ionospheric activity.
     #[cfg_attr(feature = "serde", serde(default = "default_code_smoothing"))]
     pub code_smoothing: usize,
 
-    /// Internal delays to compensate for (total summation, in [s]).
-    /// Compensation is only effective if [Modeling.cable_delay]
-    /// is also turned on.
+    /// Internal delays to compensate for, on each carrier (in [s]).
+    /// Delays that apply to the same carrier are summed up. They are compensated
+    /// on the pseudo ranges of direct navigation, through the signal combination
+    /// (for example, the ionosphere free combination).
     #[cfg_attr(feature = "serde", serde(default))]
     pub int_delay: Vec<InternalDelay>,
 
-    /// Antenna Reference Point (ARP) expressed as ENU offset [m]
+    /// Antenna Reference Point (ARP) expressed as ENU offset [m],
+    /// from the rover position (marker) to the ARP.
     #[cfg_attr(feature = "serde", serde(default))]
     pub arp_enu: Option<(f64, f64, f64)>,
 
+    /// Rover antenna and radome types, as named in the ANTEX
+    /// (for example "TRM59800.00     NONE"). The phase center offset and variations
+    /// of this antenna are compensated when ANTEX is provided to the [Solver](crate::prelude::Solver).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rx_antenna: Option<String>,
+
+    /// [OceanLoading] coefficients of the receiver site, used when
+    /// [Modeling::ocean_loading] is enabled.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ocean_loading: Option<OceanLoading>,
+
     /// Solver customization
     #[cfg_attr(feature = "serde", serde(default))]
     pub solver: SolverOpts,
@@ -166,6 +216,29 @@ pub struct Config {
     /// Atmospherical and Physical [Modeling] used to improve the accuracy of solution.
     #[cfg_attr(feature = "serde", serde(default))]
     pub modeling: Modeling,
+
+    /// Multi-antenna attitude determination preset
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attitude: AttitudeOpts,
+
+    /// Receiver autonomous integrity monitoring preset
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub integrity: IntegrityOpts,
+
+    /// Measurement [StochasticModel], from which the navigation filter weights are built.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stochastic: StochasticModel,
+
+    /// Cycle slip detection and phase arcs tracking preset
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cycle_slip: CycleSlipOpts,
+
+    /// Glonass inter-frequency code bias between the rover and the reference receivers,
+    /// modeled as linear with the FDMA frequency channel number (in [m] per channel).
+    /// It does not cancel in the RTK double differences, as soon as
+    /// both receivers differ. Leave to zero for identical receivers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub glonass_code_ifb_m: f64,
 }
 
 impl Default for Config {
@@ -176,9 +249,16 @@ impl Default for Config {
             solver: SolverOpts::default(),
             int_delay: Default::default(),
             modeling: Modeling::default(),
+            attitude: AttitudeOpts::default(),
+            integrity: IntegrityOpts::default(),
+            stochastic: StochasticModel::default(),
+            cycle_slip: CycleSlipOpts::default(),
+            glonass_code_ifb_m: 0.0,
             fixed_altitude: None,
             prefered_signal: None,
             arp_enu: None,
+            rx_antenna: None,
+            ocean_loading: None,
             min_snr: None, // TODO
             min_sv_azim: None,
             max_sv_azim: None,
@@ -200,6 +280,15 @@ impl Config {
         s
     }
 
+    /// Returns the total [InternalDelay] (in seconds) of this [Carrier].
+    pub(crate) fn internal_delay_s(&self, carrier: Carrier) -> f64 {
+        self.int_delay
+            .iter()
+            .filter(|delay| delay.applies_to(carrier))
+            .map(|delay| delay.delay)
+            .sum()
+    }
+
     /// Copies and returns [Config] with desired [Modeling], that you can
     /// tune to improve the accuracy of your solution, or for learning purposes.
     pub fn with_modeling(&self, modeling: Modeling) -> Self {
@@ -207,16 +296,62 @@ impl Config {
         s.modeling = modeling;
         s
     }
+
+    /// Verifies this [Config] may be used by the [Solver](crate::prelude::Solver).
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        self.stochastic.validate()?;
+
+        // the residual test is only meaningful on properly weighted measurements
+        if self.integrity.raim && !self.stochastic.models_uncertainties() {
+            return Err(Error::UnweightedIntegrityMonitoring);
+        }
+
+        if self.modeling.ocean_loading && self.ocean_loading.is_none() {
+            return Err(Error::MissingOceanLoading);
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
-#[cfg(feature = "serde")]
 mod test {
     use super::*;
-    use std::io::Write;
 
     #[test]
+    fn integrity_monitoring_weights() {
+        let mut cfg = Config::default();
+        cfg.integrity.raim = true;
+
+        assert!(matches!(
+            cfg.validate(),
+            Err(Error::UnweightedIntegrityMonitoring)
+        ));
+
+        cfg.stochastic.weighting = WeightingModel::Elevation;
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn ocean_loading_coefficients() {
+        let mut cfg = Config::default();
+        cfg.modeling.ocean_loading = true;
+
+        assert!(matches!(cfg.validate(), Err(Error::MissingOceanLoading)));
+
+        cfg.ocean_loading = Some(OceanLoading {
+            amplitudes_m: [[0.0; 11]; 3],
+            phases_deg: [[0.0; 11]; 3],
+        });
+
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
     fn generate_default_preset() {
+        use std::io::Write;
+
         let cfg = Config::default();
         let string = serde_json::to_string_pretty(&cfg).unwrap();
         let mut fd = std::fs::File::create("default.json").unwrap();
@@ -37,6 +37,14 @@ fn default_solid_tides() -> bool {
     false
 }
 
+fn default_mean_tide() -> bool {
+    false
+}
+
+fn default_ocean_loading() -> bool {
+    false
+}
+
 /// Atmospherical, Physical and Environmental modeling
 #[derive(Copy, Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -75,13 +83,29 @@ pub struct Modeling {
 
     /// Compensate for signal phase windup. This only impacts
     /// strategies that use raw phase like [Method::PPP].
+    /// Requires the Sun position, from the Almanac.
     #[cfg_attr(feature = "serde", serde(default = "default_phase_windup"))]
     pub phase_windup: bool,
 
     /// Compensate for crust (solid body) deformation due to moon and star
-    /// gravitational effect.
+    /// gravitational effect (IERS 2010, up to +/- 40cm vertical).
+    /// Requires the Sun and Moon positions, from the Almanac.
+    /// This only impacts [Method::PPP] and similar strategies.
     #[cfg_attr(feature = "serde", serde(default = "default_solid_tides"))]
     pub solid_tides: bool,
+
+    /// Positions are expressed in the mean tide system, when [Modeling::solid_tides]
+    /// is enabled: the permanent tide is not part of the modeled displacement.
+    /// By default, positions are expressed in the conventional tide free system (ITRF).
+    #[cfg_attr(feature = "serde", serde(default = "default_mean_tide"))]
+    pub mean_tide: bool,
+
+    /// Compensate for the site displacement due to ocean tide loading (up to +/- 10cm),
+    /// which requires the site coefficients, see [Config::ocean_loading]:
+    /// the solver cannot be deployed without them.
+    /// This only impacts [Method::PPP] and similar strategies.
+    #[cfg_attr(feature = "serde", serde(default = "default_ocean_loading"))]
+    pub ocean_loading: bool,
 }
 
 impl Default for Modeling {
@@ -89,6 +113,8 @@ impl Default for Modeling {
         Self {
             sv_clock_bias: default_sv_clock(),
             solid_tides: default_solid_tides(),
+            mean_tide: default_mean_tide(),
+            ocean_loading: default_ocean_loading(),
             earth_rotation: default_earth_rot(),
             phase_windup: default_phase_windup(),
             sv_total_group_delay: default_group_delay(),
@@ -117,6 +143,8 @@ impl Modeling {
             earth_rotation: false,
             phase_windup: false,
             solid_tides: false,
+            mean_tide: false,
+            ocean_loading: false,
         }
     }
 }
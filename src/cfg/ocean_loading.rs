@@ -0,0 +1,222 @@
+//! Ocean tide loading
+use crate::{cfg::Error, prelude::Epoch};
+
+use std::f64::consts::TAU;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Number of tidal constituents
+const NB_CONSTITUENTS: usize = 11;
+
+/// MJD of 1975-01-01 (UTC), reference of the astronomical arguments
+const MJD_1975: f64 = 42413.0;
+
+/// Angular velocity (rad.s⁻¹) and Doodson multipliers
+/// (h0, s0, p0, quarter cycles) of the tidal constituents,
+/// in BLQ order: M2, S2, N2, K2, K1, O1, P1, Q1, Mf, Mm, Ssa.
+const CONSTITUENTS: [(f64, f64, f64, f64, f64); NB_CONSTITUENTS] = [
+    (1.40519E-4, 2.0, -2.0, 0.0, 0.0),
+    (1.45444E-4, 0.0, 0.0, 0.0, 0.0),
+    (1.37880E-4, 2.0, -3.0, 1.0, 0.0),
+    (1.45842E-4, 2.0, 0.0, 0.0, 0.0),
+    (0.72921E-4, 1.0, 0.0, 0.0, 0.25),
+    (0.67598E-4, 1.0, -2.0, 0.0, -0.25),
+    (0.72523E-4, -1.0, 0.0, 0.0, -0.25),
+    (0.64959E-4, 1.0, -3.0, 1.0, -0.25),
+    (0.53234E-5, 0.0, 2.0, 0.0, 0.0),
+    (0.26392E-5, 0.0, 1.0, -1.0, 0.0),
+    (0.03982E-5, 2.0, 0.0, 0.0, 0.0),
+];
+
+/// [OceanLoading] coefficients of the receiver site, in the BLQ format
+/// (as provided by the Onsala Space Observatory ocean loading service),
+/// for the 11 main tidal constituents: M2, S2, N2, K2, K1, O1, P1, Q1, Mf, Mm, Ssa.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OceanLoading {
+    /// Amplitudes (in meters) of the radial, west and south displacements
+    pub amplitudes_m: [[f64; NB_CONSTITUENTS]; 3],
+
+    /// Greenwich phase lags (in degrees) of the radial, west and south displacements
+    pub phases_deg: [[f64; NB_CONSTITUENTS]; 3],
+}
+
+impl std::str::FromStr for OceanLoading {
+    type Err = Error;
+
+    /// Parses the first site described in BLQ content: comments (`$$`) and the
+    /// site name are followed by 3 lines of amplitudes and 3 lines of phases.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rows = Vec::with_capacity(6);
+
+        for line in s.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with("$$") {
+                continue;
+            }
+
+            let values = line
+                .split_ascii_whitespace()
+                .map(|item| item.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>();
+
+            match values {
+                Ok(values) if values.len() == NB_CONSTITUENTS => {
+                    let mut row = [0.0; NB_CONSTITUENTS];
+                    row.copy_from_slice(&values);
+                    rows.push(row);
+
+                    if rows.len() == 6 {
+                        break;
+                    }
+                },
+                // site name
+                _ if rows.is_empty() => continue,
+                _ => return Err(Error::InvalidOceanLoading),
+            }
+        }
+
+        if rows.len() < 6 {
+            return Err(Error::InvalidOceanLoading);
+        }
+
+        Ok(Self {
+            amplitudes_m: [rows[0], rows[1], rows[2]],
+            phases_deg: [rows[3], rows[4], rows[5]],
+        })
+    }
+}
+
+impl OceanLoading {
+    /// Returns the site displacement (ENU, m) at this [Epoch].
+    pub(crate) fn displacement_enu_m(&self, epoch: Epoch) -> (f64, f64, f64) {
+        let mjd = epoch.to_mjd_utc_days();
+
+        // seconds of day, days since 1975 (included)
+        let fday_s = mjd.fract() * 86400.0;
+        let days = mjd.floor() - MJD_1975 + 1.0;
+
+        let t = (27392.500528 + 1.000000035 * days) / 36525.0;
+        let (t2, t3) = (t * t, t * t * t);
+
+        // mean longitudes of the Sun, the Moon and the lunar perigee
+        let h0 = (279.69668 + 36000.768930485 * t + 3.03E-4 * t2).to_radians();
+        let s0 = (270.434358 + 481267.88314137 * t - 0.001133 * t2 + 1.9E-6 * t3).to_radians();
+        let p0 = (334.329653 + 4069.0340329577 * t - 0.010325 * t2 - 1.2E-5 * t3).to_radians();
+
+        let mut dp = [0.0; 3];
+
+        for (i, (omega, k_h0, k_s0, k_p0, k_quarter)) in CONSTITUENTS.iter().enumerate() {
+            let arg = omega * fday_s + k_h0 * h0 + k_s0 * s0 + k_p0 * p0 + k_quarter * TAU;
+
+            for (j, dp) in dp.iter_mut().enumerate() {
+                *dp += self.amplitudes_m[j][i] * (arg - self.phases_deg[j][i].to_radians()).cos();
+            }
+        }
+
+        // (radial, west, south) to ENU
+        (-dp[1], -dp[2], dp[0])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::OceanLoading;
+    use crate::prelude::Epoch;
+
+    use hifitime::Unit;
+
+    use std::str::FromStr;
+
+    /// BLQ coefficients of a test site
+    const TEST_SITE: &str = "$$ Ocean loading displacement
+$$
+$$ OLFG, FES2004
+$$ END HEADER
+$$
+  TEST
+$$ FES2004
+$$ TEST,                                 lon/lat:   11.9264   57.3958    0.000
+  .00384 .00091 .00084 .00019 .00265 .00134 .00086 .00018 .00060 .00029 .00026
+  .00102 .00031 .00024 .00009 .00053 .00036 .00017 .00007 .00019 .00014 .00013
+  .00071 .00016 .00018 .00005 .00086 .00035 .00028 .00006 .00032 .00014 .00012
+   -64.7  -31.6  -80.9  -29.7  -54.4 -146.1  -56.8  169.9    2.4    6.4    1.6
+    84.9  121.7   63.3  120.3  104.9   35.2  102.9  -30.4 -178.6  179.3  178.2
+    78.3  115.8   63.8  114.3   80.1 -125.6   81.7   97.8 -174.4   -6.1    1.9
+";
+
+    #[test]
+    fn blq_parsing() {
+        let blq = OceanLoading::from_str(TEST_SITE).unwrap();
+
+        assert_eq!(blq.amplitudes_m[0][0], 0.00384);
+        assert_eq!(blq.amplitudes_m[2][10], 0.00012);
+        assert_eq!(blq.phases_deg[0][0], -64.7);
+        assert_eq!(blq.phases_deg[2][10], 1.9);
+
+        assert!(OceanLoading::from_str("$$ empty\n  TEST\n").is_err());
+        assert!(OceanLoading::from_str(&TEST_SITE.replace("-64.7", "bad")).is_err());
+    }
+
+    #[test]
+    fn ocean_loading_displacement() {
+        let blq = OceanLoading::from_str(TEST_SITE).unwrap();
+
+        let sum = |row: &[f64; 11]| row.iter().sum::<f64>();
+        let bounds = (
+            sum(&blq.amplitudes_m[1]),
+            sum(&blq.amplitudes_m[2]),
+            sum(&blq.amplitudes_m[0]),
+        );
+
+        let t0 = Epoch::from_gregorian_utc_at_midnight(2020, 6, 25);
+
+        let mut up_min = f64::MAX;
+        let mut up_max = f64::MIN;
+
+        for hour in 0..48 {
+            let epoch = t0 + hour as f64 * Unit::Hour;
+            let (east, north, up) = blq.displacement_enu_m(epoch);
+
+            assert!(east.abs() <= bounds.0);
+            assert!(north.abs() <= bounds.1);
+            assert!(up.abs() <= bounds.2);
+
+            up_min = up_min.min(up);
+            up_max = up_max.max(up);
+        }
+
+        // centimetric signal, dominated by the semi-diurnal constituents
+        assert!(up_max - up_min > 0.005, "{up_min} {up_max}");
+    }
+
+    #[test]
+    fn astronomical_arguments() {
+        // the daily update of the astronomical arguments must match the
+        // angular velocity of each constituent: no jump at midnight
+        for constituent in 0..11 {
+            let mut blq = OceanLoading {
+                amplitudes_m: [[0.0; 11]; 3],
+                phases_deg: [[0.0; 11]; 3],
+            };
+
+            blq.amplitudes_m[0][constituent] = 0.01;
+            blq.phases_deg[0][constituent] = 30.0;
+
+            for day in 0..30 {
+                let midnight =
+                    Epoch::from_gregorian_utc_at_midnight(2020, 6, 1) + day as f64 * Unit::Day;
+
+                let (_, _, before) = blq.displacement_enu_m(midnight - 1.0 * Unit::Millisecond);
+                let (_, _, after) = blq.displacement_enu_m(midnight);
+
+                assert!(
+                    (after - before).abs() < 1.0E-6,
+                    "constituent #{constituent} @{midnight}: {before} {after}"
+                );
+            }
+        }
+    }
+}
@@ -1,5 +1,8 @@
 //! Solver configuration preset
 
+#[cfg(doc)]
+use crate::prelude::Method;
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +18,26 @@ const fn default_open_loop() -> bool {
     false
 }
 
+const fn default_rts_smoothing() -> bool {
+    false
+}
+
+const fn default_inter_system_biases() -> bool {
+    false
+}
+
+const fn default_innovation_gate() -> Option<f64> {
+    None
+}
+
+const fn default_max_ambiguity_fraction() -> f64 {
+    0.15
+}
+
+const fn default_ppp_ambiguity_resolution() -> bool {
+    false
+}
+
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SolverOpts {
@@ -32,6 +55,40 @@ pub struct SolverOpts {
     /// 1000 for x1000 improvement attempt.
     #[cfg_attr(feature = "serde", serde(default = "default_postfit_denoising"))]
     pub postfit_denoising: f64,
+
+    /// Records the forward navigation pass, so a backward (Rauch-Tung-Striebel)
+    /// smoothing pass may run at the end of a post-processed session.
+    /// Memory usage grows with the session length: do not use this in real-time applications.
+    #[cfg_attr(feature = "serde", serde(default = "default_rts_smoothing"))]
+    pub rts_smoothing: bool,
+
+    /// Estimates one inter-system bias per constellation, in multi-constellation
+    /// absolute navigation. The most represented constellation, when the filter
+    /// initializes, serves as time reference. Other constellations are then
+    /// no longer dependent on externally supplied time offsets.
+    #[cfg_attr(feature = "serde", serde(default = "default_inter_system_biases"))]
+    pub inter_system_biases: bool,
+
+    /// Innovation gating threshold: each measurement whose normalized innovation
+    /// (innovation over its predicted standard deviation) exceeds this value
+    /// is rejected by the navigation filter. For example, 5.0 for 5σ.
+    /// Rejected satellites are reported in the solution. Disabled by default.
+    #[cfg_attr(feature = "serde", serde(default = "default_innovation_gate"))]
+    pub innovation_gate: Option<f64>,
+
+    /// Validation of the triple frequency cascaded ambiguity resolution: each step
+    /// (extra wide lane, wide lane, then reference signal) is only fixed when its float
+    /// ambiguity lies within this distance (in cycles) of the nearest integer.
+    /// Otherwise, the ambiguities of that satellite remain unresolved.
+    #[cfg_attr(feature = "serde", serde(default = "default_max_ambiguity_fraction"))]
+    pub max_ambiguity_fraction_cycles: f64,
+
+    /// Resolves the between satellite ambiguities in absolute triple frequency navigation
+    /// ([Method::PPP_TF]), with the validated cascade. The phase observations must
+    /// then be corrected for the satellite phase biases. Each fixed satellite shares
+    /// the float ambiguity of its pivot satellite. Disabled by default.
+    #[cfg_attr(feature = "serde", serde(default = "default_ppp_ambiguity_resolution"))]
+    pub ppp_ambiguity_resolution: bool,
 }
 
 impl Default for SolverOpts {
@@ -40,6 +97,11 @@ impl Default for SolverOpts {
             max_gdop: default_max_gdop(),
             open_loop: default_open_loop(),
             postfit_denoising: default_postfit_denoising(),
+            rts_smoothing: default_rts_smoothing(),
+            inter_system_biases: default_inter_system_biases(),
+            innovation_gate: default_innovation_gate(),
+            max_ambiguity_fraction_cycles: default_max_ambiguity_fraction(),
+            ppp_ambiguity_resolution: default_ppp_ambiguity_resolution(),
         }
     }
 }
@@ -51,6 +113,11 @@ impl SolverOpts {
             max_gdop: 3.0,
             open_loop: default_open_loop(),
             postfit_denoising: default_postfit_denoising(),
+            rts_smoothing: default_rts_smoothing(),
+            inter_system_biases: default_inter_system_biases(),
+            innovation_gate: default_innovation_gate(),
+            max_ambiguity_fraction_cycles: default_max_ambiguity_fraction(),
+            ppp_ambiguity_resolution: default_ppp_ambiguity_resolution(),
         }
     }
 }
@@ -0,0 +1,367 @@
+//! Measurement stochastic model
+use crate::{cfg::Error, prelude::Constellation};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Lowest elevation angle of the elevation dependent model,
+/// which avoids diverging weights close to the horizon.
+const MIN_ELEVATION_DEG: f64 = 5.0;
+
+const fn default_code_sigma() -> f64 {
+    1.0
+}
+
+const fn default_phase_sigma() -> f64 {
+    0.01
+}
+
+const fn default_doppler_sigma() -> f64 {
+    0.1
+}
+
+const fn default_elevation_coefficients() -> (f64, f64) {
+    (0.5, 0.5)
+}
+
+const fn default_snr_coefficients() -> (f64, f64) {
+    (1.0E4, 2.24E-3)
+}
+
+/// [WeightingModel] defines how each measurement is weighted,
+/// in the navigation filter.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WeightingModel {
+    /// Unit weights: uncertainties are not modeled,
+    /// and all measurements contribute equally, whatever their type.
+    #[default]
+    Unit,
+
+    /// All measurements share the nominal code (or phase) uncertainty.
+    Equal,
+
+    /// Elevation dependent model: σ = σ0 (a + b / sin(el)),
+    /// where (a, b) are the [StochasticModel] elevation coefficients.
+    Elevation,
+
+    /// SNR dependent model (SIGMA-ε): σ² = C 10^(-(C/N0) / 10),
+    /// where C is the [StochasticModel] SNR coefficient.
+    /// Measurements that do not come with an SNR use the nominal uncertainty.
+    Snr,
+
+    /// User defined code and phase uncertainties, per [Constellation].
+    /// Constellations that are not described use the nominal uncertainty.
+    Constellation,
+}
+
+/// Code and phase uncertainties of a [Constellation]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConstellationSigmas {
+    /// [Constellation]
+    pub constellation: Constellation,
+
+    /// Code uncertainty (1σ, in meters)
+    pub code_sigma_m: f64,
+
+    /// Phase uncertainty (1σ, in meters)
+    pub phase_sigma_m: f64,
+}
+
+/// [StochasticModel] of the raw measurements, from which the
+/// navigation filter weights are built.
+/// Combinations (IF, double differences) propagate these uncertainties.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StochasticModel {
+    /// [WeightingModel] in use
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub weighting: WeightingModel,
+
+    /// Nominal code uncertainty (1σ, in meters)
+    #[cfg_attr(feature = "serde", serde(default = "default_code_sigma"))]
+    pub code_sigma_m: f64,
+
+    /// Nominal phase uncertainty (1σ, in meters)
+    #[cfg_attr(feature = "serde", serde(default = "default_phase_sigma"))]
+    pub phase_sigma_m: f64,
+
+    /// Nominal Doppler (range rate) uncertainty (1σ, in m.s⁻¹),
+    /// which weights the velocity filter.
+    #[cfg_attr(feature = "serde", serde(default = "default_doppler_sigma"))]
+    pub doppler_sigma_m_s: f64,
+
+    /// (a, b) coefficients of the [WeightingModel::Elevation] model
+    #[cfg_attr(feature = "serde", serde(default = "default_elevation_coefficients"))]
+    pub elevation_coefficients: (f64, f64),
+
+    /// Code and phase C coefficients (in m².Hz) of the [WeightingModel::Snr] model
+    #[cfg_attr(feature = "serde", serde(default = "default_snr_coefficients"))]
+    pub snr_coefficients: (f64, f64),
+
+    /// [ConstellationSigmas] of the [WeightingModel::Constellation] model
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub constellation_sigmas: Vec<ConstellationSigmas>,
+}
+
+impl Default for StochasticModel {
+    fn default() -> Self {
+        Self {
+            weighting: WeightingModel::default(),
+            code_sigma_m: default_code_sigma(),
+            phase_sigma_m: default_phase_sigma(),
+            doppler_sigma_m_s: default_doppler_sigma(),
+            elevation_coefficients: default_elevation_coefficients(),
+            snr_coefficients: default_snr_coefficients(),
+            constellation_sigmas: Default::default(),
+        }
+    }
+}
+
+impl StochasticModel {
+    /// Returns true when this [StochasticModel] models the measurement
+    /// uncertainties, which are then reported and used to weight the measurements.
+    pub(crate) fn models_uncertainties(&self) -> bool {
+        self.weighting != WeightingModel::Unit
+    }
+
+    /// Verifies that all uncertainties and coefficients are positive and finite,
+    /// which would otherwise lead to invalid weights.
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        let (a, b) = self.elevation_coefficients;
+        let (c_code, c_phase) = self.snr_coefficients;
+
+        let valid = [
+            self.code_sigma_m,
+            self.phase_sigma_m,
+            self.doppler_sigma_m_s,
+            c_code,
+            c_phase,
+        ]
+        .into_iter()
+        .chain(
+            self.constellation_sigmas
+                .iter()
+                .flat_map(|sigmas| [sigmas.code_sigma_m, sigmas.phase_sigma_m]),
+        )
+        .all(|value| value.is_finite() && value > 0.0);
+
+        // the elevation model only requires a positive combination
+        let valid_elevation = a.is_finite() && b.is_finite() && a >= 0.0 && b >= 0.0 && a + b > 0.0;
+
+        if valid && valid_elevation {
+            Ok(())
+        } else {
+            Err(Error::InvalidStochasticModel)
+        }
+    }
+
+    /// Returns the uncertainty (1σ, in m.s⁻¹) of a Doppler (range rate) measurement.
+    /// Doppler observations are always weighted, because the velocity filter
+    /// balances them against its process noise. Only the elevation dependency applies,
+    /// the other models use the nominal uncertainty.
+    pub(crate) fn doppler_sigma_m_s(&self, elevation_deg: f64) -> f64 {
+        match self.weighting {
+            WeightingModel::Elevation => {
+                let (a, b) = self.elevation_coefficients;
+                let sin_elev = elevation_deg.max(MIN_ELEVATION_DEG).to_radians().sin();
+                self.doppler_sigma_m_s * (a + b / sin_elev)
+            },
+            _ => self.doppler_sigma_m_s,
+        }
+    }
+
+    /// Returns the uncertainty (1σ, in meters) of a raw measurement.
+    ///
+    /// ## Input
+    /// - constellation: [Constellation] of the measurement
+    /// - phase: true for phase measurements, false for code measurements
+    /// - elevation_deg: elevation angle, in degrees
+    /// - snr_dbhz: possible SNR, in dB/Hz
+    pub(crate) fn sigma_m(
+        &self,
+        constellation: Constellation,
+        phase: bool,
+        elevation_deg: f64,
+        snr_dbhz: Option<f64>,
+    ) -> f64 {
+        let nominal_m = if phase {
+            self.phase_sigma_m
+        } else {
+            self.code_sigma_m
+        };
+
+        match self.weighting {
+            WeightingModel::Unit => 1.0,
+            WeightingModel::Equal => nominal_m,
+            WeightingModel::Elevation => {
+                let (a, b) = self.elevation_coefficients;
+                let sin_elev = elevation_deg.max(MIN_ELEVATION_DEG).to_radians().sin();
+                nominal_m * (a + b / sin_elev)
+            },
+            WeightingModel::Snr => match snr_dbhz {
+                Some(snr_dbhz) => {
+                    let c = if phase {
+                        self.snr_coefficients.1
+                    } else {
+                        self.snr_coefficients.0
+                    };
+
+                    (c * 10.0_f64.powf(-snr_dbhz / 10.0)).sqrt()
+                },
+                None => nominal_m,
+            },
+            WeightingModel::Constellation => self
+                .constellation_sigmas
+                .iter()
+                .find(|sigmas| sigmas.constellation == constellation)
+                .map(|sigmas| {
+                    if phase {
+                        sigmas.phase_sigma_m
+                    } else {
+                        sigmas.code_sigma_m
+                    }
+                })
+                .unwrap_or(nominal_m),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConstellationSigmas, StochasticModel, WeightingModel};
+    use crate::prelude::Constellation;
+
+    #[test]
+    fn stochastic_models() {
+        let mut model = StochasticModel::default();
+
+        // unit weights
+        assert!(!model.models_uncertainties());
+        assert_eq!(model.sigma_m(Constellation::GPS, false, 10.0, None), 1.0);
+        assert_eq!(
+            model.sigma_m(Constellation::GPS, true, 10.0, Some(30.0)),
+            1.0
+        );
+
+        // equal weights
+        model.weighting = WeightingModel::Equal;
+        assert!(model.models_uncertainties());
+
+        assert_eq!(model.sigma_m(Constellation::GPS, false, 10.0, None), 1.0);
+        assert_eq!(
+            model.sigma_m(Constellation::GPS, true, 10.0, Some(30.0)),
+            0.01
+        );
+
+        // elevation dependent: nominal at zenith, degraded close to the horizon
+        model.weighting = WeightingModel::Elevation;
+
+        let zenith = model.sigma_m(Constellation::GPS, false, 90.0, None);
+        assert!((zenith - 1.0).abs() < 1.0E-9);
+
+        let low = model.sigma_m(Constellation::GPS, false, 30.0, None);
+        assert!((low - 1.5).abs() < 1.0E-9);
+
+        let horizon = model.sigma_m(Constellation::GPS, false, 0.0, None);
+        assert!(horizon > low);
+        assert!(horizon.is_finite());
+
+        // SNR dependent
+        model.weighting = WeightingModel::Snr;
+
+        let strong = model.sigma_m(Constellation::GPS, false, 30.0, Some(50.0));
+        let weak = model.sigma_m(Constellation::GPS, false, 30.0, Some(30.0));
+
+        assert!((strong - 0.1_f64.sqrt()).abs() < 1.0E-9);
+        assert!((weak - 10.0_f64.sqrt()).abs() < 1.0E-9);
+        assert_eq!(model.sigma_m(Constellation::GPS, false, 30.0, None), 1.0);
+
+        // per constellation
+        model.weighting = WeightingModel::Constellation;
+        model.constellation_sigmas.push(ConstellationSigmas {
+            constellation: Constellation::Galileo,
+            code_sigma_m: 0.5,
+            phase_sigma_m: 0.002,
+        });
+
+        assert_eq!(
+            model.sigma_m(Constellation::Galileo, false, 30.0, None),
+            0.5
+        );
+        assert_eq!(
+            model.sigma_m(Constellation::Galileo, true, 30.0, None),
+            0.002
+        );
+        assert_eq!(model.sigma_m(Constellation::GPS, true, 30.0, None), 0.01);
+    }
+
+    #[test]
+    fn doppler_uncertainty() {
+        let mut model = StochasticModel::default();
+
+        // always weighted, even with unit weights
+        assert_eq!(model.doppler_sigma_m_s(30.0), 0.1);
+
+        model.weighting = WeightingModel::Snr;
+        assert_eq!(model.doppler_sigma_m_s(30.0), 0.1);
+
+        model.weighting = WeightingModel::Elevation;
+        assert!((model.doppler_sigma_m_s(90.0) - 0.1).abs() < 1.0E-9);
+        assert!((model.doppler_sigma_m_s(30.0) - 0.15).abs() < 1.0E-9);
+    }
+
+    #[test]
+    fn stochastic_model_validation() {
+        assert!(StochasticModel::default().validate().is_ok());
+
+        for invalid in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            for model in [
+                StochasticModel {
+                    code_sigma_m: invalid,
+                    ..Default::default()
+                },
+                StochasticModel {
+                    phase_sigma_m: invalid,
+                    ..Default::default()
+                },
+                StochasticModel {
+                    doppler_sigma_m_s: invalid,
+                    ..Default::default()
+                },
+                StochasticModel {
+                    snr_coefficients: (invalid, 2.24E-3),
+                    ..Default::default()
+                },
+                StochasticModel {
+                    constellation_sigmas: vec![ConstellationSigmas {
+                        constellation: Constellation::Galileo,
+                        code_sigma_m: 0.5,
+                        phase_sigma_m: invalid,
+                    }],
+                    ..Default::default()
+                },
+            ] {
+                assert!(model.validate().is_err(), "{model:?}");
+            }
+        }
+
+        // elevation model
+        let mut model = StochasticModel {
+            elevation_coefficients: (0.0, 1.0),
+            ..Default::default()
+        };
+
+        assert!(model.validate().is_ok());
+
+        model.elevation_coefficients = (0.0, 0.0);
+        assert!(model.validate().is_err());
+
+        model.elevation_coefficients = (1.0, -0.5);
+        assert!(model.validate().is_err());
+
+        model.elevation_coefficients = (f64::NAN, 0.5);
+        assert!(model.validate().is_err());
+    }
+}
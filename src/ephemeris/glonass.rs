@@ -0,0 +1,223 @@
+use log::debug;
+use nalgebra::{Vector3, Vector6};
+
+use crate::{
+    ephemeris::GlonassEphemeris,
+    prelude::{Duration, Epoch, Frame, Orbit, SatelliteClockCorrection},
+};
+
+/// PZ-90 Earth gravitational constant (m³.s⁻²)
+const GM_M3_S2: f64 = 3.9860044E14;
+
+/// PZ-90 Earth equatorial radius (m)
+const EARTH_EQUATORIAL_RADIUS_M: f64 = 6378136.0;
+
+/// PZ-90 second zonal harmonic
+const J2: f64 = 1.0826257E-3;
+
+/// PZ-90 Earth angular velocity (rad.s⁻¹)
+const EARTH_ANGULAR_VEL_RAD_S: f64 = 7.292115E-5;
+
+/// Numerical integration step (s)
+const INTEGRATION_STEP_S: f64 = 60.0;
+
+impl GlonassEphemeris {
+    /// Returns True if this [GlonassEphemeris] frame was broadcast as healthy
+    pub fn is_healthy(&self) -> bool {
+        self.health == 0
+    }
+
+    /// Returns the validity period of this frame, around its reference instant.
+    /// Frames are broadcast every 30 minutes, at the center of their validity period.
+    pub fn max_dtoe(&self) -> Duration {
+        Duration::from_seconds(900.0)
+    }
+
+    /// Returns True if this frame may be used at this point in time:
+    /// it is healthy and within its validity period (see [Self::max_dtoe]).
+    pub fn is_usable(&self, now: Epoch) -> bool {
+        self.is_healthy() && (now - self.toe).abs() < self.max_dtoe()
+    }
+
+    /// Elapsed time since the reference instant (s), in the Glonass timescale.
+    fn t_k(&self, epoch: Epoch) -> Option<f64> {
+        let timescale = self.sv.constellation.timescale()?;
+        Some((epoch.to_time_scale(timescale) - self.toe).to_seconds())
+    }
+
+    /// Returns the [SatelliteClockCorrection] at this [Epoch].
+    /// The broadcast clock model integrates the relativistic correction.
+    pub fn clock_correction(&self, epoch: Epoch) -> Option<SatelliteClockCorrection> {
+        let dt = self.t_k(epoch)?;
+        let correction_s = self.clock_bias_s + self.relative_frequency_bias * dt;
+
+        Some(
+            SatelliteClockCorrection::with_relativistic_correction(Duration::from_seconds(
+                correction_s,
+            ))
+            .with_drift(self.relative_frequency_bias),
+        )
+    }
+
+    /// PZ-90 equations of motion, in the rotating frame.
+    /// The state is position (m) then velocity (m/s).
+    fn derivatives(state: &Vector6<f64>, acceleration_m_s2: &Vector3<f64>) -> Vector6<f64> {
+        let (x, y, z) = (state[0], state[1], state[2]);
+        let (vx, vy) = (state[3], state[4]);
+
+        let r2 = x.powi(2) + y.powi(2) + z.powi(2);
+        let r3 = r2 * r2.sqrt();
+        let omega_2 = EARTH_ANGULAR_VEL_RAD_S.powi(2);
+
+        let a = 1.5 * J2 * GM_M3_S2 * EARTH_EQUATORIAL_RADIUS_M.powi(2) / r2 / r3;
+        let b = 5.0 * z.powi(2) / r2;
+        let c = -GM_M3_S2 / r3 - a * (1.0 - b);
+
+        Vector6::new(
+            state[3],
+            state[4],
+            state[5],
+            (c + omega_2) * x + 2.0 * EARTH_ANGULAR_VEL_RAD_S * vy + acceleration_m_s2[0],
+            (c + omega_2) * y - 2.0 * EARTH_ANGULAR_VEL_RAD_S * vx + acceleration_m_s2[1],
+            (c - 2.0 * a) * z + acceleration_m_s2[2],
+        )
+    }
+
+    /// Runs one Runge-Kutta (4th order) integration step.
+    fn runge_kutta_step(
+        state: &Vector6<f64>,
+        acceleration_m_s2: &Vector3<f64>,
+        dt: f64,
+    ) -> Vector6<f64> {
+        let k1 = Self::derivatives(state, acceleration_m_s2);
+        let k2 = Self::derivatives(&(state + k1 * dt / 2.0), acceleration_m_s2);
+        let k3 = Self::derivatives(&(state + k2 * dt / 2.0), acceleration_m_s2);
+        let k4 = Self::derivatives(&(state + k3 * dt), acceleration_m_s2);
+
+        state + (k1 + 2.0 * k2 + 2.0 * k3 + k4) * dt / 6.0
+    }
+
+    /// Propagates the broadcast state vector to this [Epoch], returning
+    /// the position and velocity of the satellite.
+    pub fn resolve_state(&self, epoch: Epoch, frame: Frame) -> Option<Orbit> {
+        let t_k = self.t_k(epoch)?;
+
+        let mut state = Vector6::new(
+            self.position_km.0 * 1.0E3,
+            self.position_km.1 * 1.0E3,
+            self.position_km.2 * 1.0E3,
+            self.velocity_km_s.0 * 1.0E3,
+            self.velocity_km_s.1 * 1.0E3,
+            self.velocity_km_s.2 * 1.0E3,
+        );
+
+        let acceleration_m_s2 = Vector3::new(
+            self.acceleration_km_s2.0 * 1.0E3,
+            self.acceleration_km_s2.1 * 1.0E3,
+            self.acceleration_km_s2.2 * 1.0E3,
+        );
+
+        let mut remaining = t_k;
+
+        while remaining.abs() > 1.0E-9 {
+            let dt = remaining.signum() * remaining.abs().min(INTEGRATION_STEP_S);
+            state = Self::runge_kutta_step(&state, &acceleration_m_s2, dt);
+            remaining -= dt;
+        }
+
+        debug!(
+            "{}({}) - state vector propagation x_km={} y_km={} z_km={} t_k={}",
+            epoch,
+            self.sv,
+            state[0] / 1.0E3,
+            state[1] / 1.0E3,
+            state[2] / 1.0E3,
+            t_k
+        );
+
+        Some(Orbit::from_cartesian_pos_vel(state / 1.0E3, epoch, frame))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        ephemeris::GlonassEphemeris,
+        prelude::{Constellation, Duration, Epoch, IAU_EARTH_FRAME, SV},
+    };
+
+    use std::str::FromStr;
+
+    fn glonass_ephemeris() -> GlonassEphemeris {
+        GlonassEphemeris {
+            sv: SV::new(Constellation::Glonass, 1),
+            toe: Epoch::from_str("2020-06-25T00:15:00 GPST").unwrap(),
+            position_km: (-14038.271, -6476.493, 20306.178),
+            velocity_km_s: (-0.2543, -2.9147, -1.1064),
+            acceleration_km_s2: (0.0, 0.0, -1.86E-9),
+            clock_bias_s: 4.2E-5,
+            relative_frequency_bias: 1.8E-12,
+            frequency_channel: 1,
+            health: 0,
+        }
+    }
+
+    #[test]
+    fn glonass_state_propagation() {
+        let eph = glonass_ephemeris();
+
+        // reference instant
+        let state = eph.resolve_state(eph.toe, IAU_EARTH_FRAME).unwrap();
+        let pos_vel = state.to_cartesian_pos_vel();
+
+        assert_eq!(pos_vel[0], eph.position_km.0);
+        assert_eq!(pos_vel[4], eph.velocity_km_s.1);
+
+        // forward then backward propagation
+        let t = eph.toe + Duration::from_seconds(900.0);
+        let forward = eph.resolve_state(t, IAU_EARTH_FRAME).unwrap();
+
+        let mut reversed = eph;
+        let pos_vel = forward.to_cartesian_pos_vel();
+
+        reversed.toe = t;
+        reversed.position_km = (pos_vel[0], pos_vel[1], pos_vel[2]);
+        reversed.velocity_km_s = (pos_vel[3], pos_vel[4], pos_vel[5]);
+
+        let backward = reversed
+            .resolve_state(eph.toe, IAU_EARTH_FRAME)
+            .unwrap()
+            .to_cartesian_pos_vel();
+
+        assert!((backward[0] - eph.position_km.0).abs() < 1.0E-5);
+        assert!((backward[1] - eph.position_km.1).abs() < 1.0E-5);
+        assert!((backward[2] - eph.position_km.2).abs() < 1.0E-5);
+
+        // orbital radius is preserved (quasi circular orbit)
+        let r0_km =
+            (eph.position_km.0.powi(2) + eph.position_km.1.powi(2) + eph.position_km.2.powi(2))
+                .sqrt();
+
+        assert!((forward.rmag_km() - r0_km).abs() < 50.0);
+    }
+
+    #[test]
+    fn glonass_clock_and_validity() {
+        let mut eph = glonass_ephemeris();
+
+        let t = eph.toe + Duration::from_seconds(100.0);
+        let correction = eph.clock_correction(t).unwrap();
+
+        // nanosecond resolution
+        assert!((correction.duration.to_seconds() - (4.2E-5 + 1.8E-10)).abs() < 1.0E-9);
+        assert!(!correction.needs_relativistic_correction);
+        assert_eq!(correction.drift_s_s, eph.relative_frequency_bias);
+
+        assert!(eph.is_usable(t));
+        assert!(eph.is_usable(eph.toe - Duration::from_seconds(800.0)));
+        assert!(!eph.is_usable(eph.toe + Duration::from_seconds(1000.0)));
+
+        eph.health = 1;
+        assert!(!eph.is_usable(t));
+    }
+}
@@ -1,5 +1,6 @@
-use crate::prelude::{Epoch, SV};
+use crate::prelude::{Duration, Epoch, Frame, Orbit, SV, SatelliteClockCorrection};
 
+pub(crate) mod glonass;
 pub(crate) mod private;
 
 #[derive(Debug, Copy, Clone, Default, PartialEq)]
@@ -48,16 +49,117 @@ pub struct Ephemeris {
 
     /// Sine / Cosine (in meters)
     pub crs_crc_m: (f64, f64),
+
+    /// Clock bias (af0, in seconds)
+    pub clock_bias_s: f64,
+
+    /// Clock drift (af1, in s.s⁻¹)
+    pub clock_drift_s_s: f64,
+
+    /// Clock drift rate (af2, in s.s⁻²)
+    pub clock_drift_rate_s_s2: f64,
+
+    /// Total group delay (in seconds), referenced to the L1 frequency.
+    pub tgd_s: f64,
+
+    /// Health flag, as broadcast: null means healthy.
+    pub health: u32,
 }
 
-/// [EphemerisSource] to provie [Ephemeris] data and contribute to the solving process.
+/// [GlonassEphemeris] describes a Glonass (state vector) navigation frame.
+/// The satellite state is propagated by numerical integration of the
+/// PZ-90 equations of motion.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct GlonassEphemeris {
+    /// [SV]
+    pub sv: SV,
+
+    /// Reference instant (tb) that must be expressed in correct timescale
+    pub toe: Epoch,
+
+    /// Position at reference instant (PZ-90, in km)
+    pub position_km: (f64, f64, f64),
+
+    /// Velocity at reference instant (PZ-90, in km/s)
+    pub velocity_km_s: (f64, f64, f64),
+
+    /// Lunisolar acceleration (PZ-90, in km/s²)
+    pub acceleration_km_s2: (f64, f64, f64),
+
+    /// Clock bias (-TauN, in seconds)
+    pub clock_bias_s: f64,
+
+    /// Relative frequency bias (GammaN)
+    pub relative_frequency_bias: f64,
+
+    /// Frequency channel number
+    pub frequency_channel: i8,
+
+    /// Health flag, as broadcast: null means healthy.
+    pub health: u32,
+}
+
+/// Broadcast navigation frame, buffered for each satellite.
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum BroadcastEphemeris {
+    /// Keplerian [Ephemeris]
+    Kepler(Ephemeris),
+
+    /// [GlonassEphemeris]
+    Glonass(GlonassEphemeris),
+}
+
+impl BroadcastEphemeris {
+    /// Returns True if this frame may be used at this point in time.
+    pub fn is_usable(&self, now: Epoch) -> bool {
+        match self {
+            Self::Kepler(eph) => eph.is_usable(now),
+            Self::Glonass(eph) => eph.is_usable(now),
+        }
+    }
+
+    /// Returns the [SatelliteClockCorrection] at this point in time.
+    pub fn clock_correction(&self, epoch: Epoch) -> Option<SatelliteClockCorrection> {
+        match self {
+            Self::Kepler(eph) => eph.clock_correction(epoch),
+            Self::Glonass(eph) => eph.clock_correction(epoch),
+        }
+    }
+
+    /// Returns the total group delay. Not broadcast by Glonass.
+    pub fn group_delay(&self) -> Duration {
+        match self {
+            Self::Kepler(eph) => Duration::from_seconds(eph.tgd_s),
+            Self::Glonass(_) => Duration::ZERO,
+        }
+    }
+
+    /// Resolves the satellite state at this point in time.
+    pub fn resolve_state(&self, epoch: Epoch, frame: Frame) -> Option<Orbit> {
+        match self {
+            Self::Kepler(eph) => eph.resolve_state(epoch, frame),
+            Self::Glonass(eph) => eph.resolve_state(epoch, frame),
+        }
+    }
+}
+
+/// [EphemerisSource] to provide [Ephemeris] data and contribute to the solving process.
 pub trait EphemerisSource {
-    /// :warning: this is work in progress, it is not possible to navigate using
-    /// ephemeris using current version.
-    ///
     /// Provide [Ephemeris] frame for requested [SV]. Provided [Epoch] is the ongoing
-    /// [Epoch] being processed by the solver.
-    /// It is possible to navigate without proposing any [Orbit]al state, if you maintain
-    /// up-to-date [Ephemeris] data.
+    /// [Epoch] being processed by the solver. You should propose the latest
+    /// frame that was broadcast, outdated or unhealthy frames are disregarded.
+    ///
+    /// It is possible to navigate without proposing any [Orbit](crate::prelude::Orbit)al state,
+    /// if you maintain up-to-date [Ephemeris] data: the satellite states and clock
+    /// corrections are then resolved from the broadcast frames.
+    /// [Ephemeris] are only used for satellites that the
+    /// [OrbitSource](crate::prelude::OrbitSource) cannot describe.
     fn ephemeris_data(&self, epoch: Epoch, sv: SV) -> Option<Ephemeris>;
+
+    /// Provide [GlonassEphemeris] frame for requested Glonass [SV].
+    /// Implement this to navigate with Glonass satellites from the
+    /// broadcast frames, in the same manner as [Self::ephemeris_data].
+    fn glonass_ephemeris_data(&self, _epoch: Epoch, _sv: SV) -> Option<GlonassEphemeris> {
+        None
+    }
 }
@@ -1,7 +1,19 @@
 use log::{debug, error};
-use nalgebra::{Rotation3, Vector3};
+use nalgebra::{Matrix3, Vector3, Vector6};
 
-use crate::prelude::{Duration, Ephemeris, Epoch, Frame, Orbit};
+use crate::{
+    constants::SPEED_OF_LIGHT_M_S,
+    prelude::{Constellation, Duration, Ephemeris, Epoch, Frame, Orbit, SatelliteClockCorrection},
+};
+
+/// Kepler solver convergence criteria (radians)
+const KEPLER_TOLERANCE_RAD: f64 = 1.0E-13;
+
+/// Kepler solver maximal number of iterations
+const KEPLER_MAX_ITER: usize = 30;
+
+/// BeiDou GEO orbital plane inclination, to the reference frame (radians)
+const BDS_GEO_INCLINATION_RAD: f64 = -5.0 * std::f64::consts::PI / 180.0;
 
 impl Ephemeris {
     /// Returns True if this [Ephemeris] frame is still valid
@@ -9,6 +21,28 @@ impl Ephemeris {
         (now - self.toe).abs() < max_dtoe
     }
 
+    /// Returns True if this [Ephemeris] frame was broadcast as healthy
+    pub fn is_healthy(&self) -> bool {
+        self.health == 0
+    }
+
+    /// Returns True if this [Ephemeris] frame may be used at this point in time:
+    /// it is healthy, within its validity period (see [Self::max_dtoe])
+    /// and describes a Keplerian orbit.
+    pub fn is_usable(&self, now: Epoch) -> bool {
+        self.is_keplerian() && self.is_healthy() && self.is_valid(now, self.max_dtoe())
+    }
+
+    /// Returns the validity period of this [Ephemeris] frame, around ToE,
+    /// which depends on the [Constellation].
+    pub fn max_dtoe(&self) -> Duration {
+        match self.sv.constellation {
+            Constellation::Galileo => Duration::from_hours(4.0),
+            Constellation::BeiDou => Duration::from_hours(6.0),
+            _ => Duration::from_hours(2.0),
+        }
+    }
+
     /// Returns ToE in seconds of week
     pub fn weekly_toe_seconds(&self) -> f64 {
         (self.toe.to_time_of_week().1 as f64) / 1.0E9
@@ -19,54 +53,118 @@ impl Ephemeris {
         (self.toc.to_time_of_week().1 as f64) / 1.0E9
     }
 
-    /// Resolves Kepler equations from [Ephemeris]
-    pub fn resolve_state(&self, epoch: Epoch, frame: Frame) -> Option<Orbit> {
-        // constants
-        const GM_M3_S2: f64 = 3.986004418E14;
-        const OMEGA_EARTH: f64 = 7.2921151467E-5;
+    /// Returns True if this frame describes a Keplerian orbit.
+    /// Glonass and SBAS broadcast state vectors, that we do not support.
+    fn is_keplerian(&self) -> bool {
+        self.sv.constellation != Constellation::Glonass && !self.sv.constellation.is_sbas()
+    }
 
-        let e = self.eccentricity;
-        let e_2 = e.powi(2);
-        let a = self.semi_major_axis_m;
-        let a_3 = a.powi(3);
+    /// Returns True for BeiDou GEO satellites, that require a specific treatment.
+    fn is_beidou_geo(&self) -> bool {
+        self.sv.constellation == Constellation::BeiDou && (self.sv.prn <= 5 || self.sv.prn >= 59)
+    }
 
-        let (cus, cuc) = self.cus_cuc_rad;
-        let (cis, cic) = self.cis_cic_rad;
-        let (crs, crc) = self.crs_crc_m;
-        let (i0, idot) = (self.i0_rad, self.idot_rad_s);
-        let (omega0, omega, omega_dot) = (self.omega0_rad, self.omega_rad, self.omega_dot_rad_s);
+    /// Earth gravitational constant (m³.s⁻²), as defined by each ICD.
+    fn gm_m3_s2(&self) -> f64 {
+        match self.sv.constellation {
+            Constellation::Galileo | Constellation::BeiDou => 3.986004418E14,
+            _ => 3.9860050E14,
+        }
+    }
+
+    /// Earth angular velocity (rad.s⁻¹), as defined by each ICD.
+    fn earth_angular_vel_rad_s(&self) -> f64 {
+        match self.sv.constellation {
+            Constellation::BeiDou => 7.2921150E-5,
+            _ => 7.2921151467E-5,
+        }
+    }
 
+    /// Elapsed time since ToE (s), at this [Epoch], in the [Constellation] timescale.
+    fn t_k(&self, epoch: Epoch) -> Option<f64> {
         let timescale = self.sv.constellation.timescale()?;
-        let epoch = epoch.to_time_scale(timescale);
+        Some((epoch.to_time_scale(timescale) - self.toe).to_seconds())
+    }
 
-        let t_k = (epoch - self.toe).to_seconds();
+    /// Resolves the Kepler equation (Newton-Raphson), returning the
+    /// eccentric anomaly (rad) and corrected mean motion (rad.s⁻¹).
+    fn eccentric_anomaly(&self, t_k: f64) -> Option<(f64, f64)> {
+        let (e, a) = (self.eccentricity, self.semi_major_axis_m);
 
-        let n0 = (GM_M3_S2 / a_3).sqrt();
+        let n0 = (self.gm_m3_s2() / a.powi(3)).sqrt();
         let n = n0 + self.dn_rad;
-        let m = self.m0_rad + n * t_k;
+        let m_k = self.m0_rad + n * t_k;
 
-        let mut e_k;
-        let mut i = 0;
+        let mut e_k = m_k;
 
-        let e_k_lst = 0.0_f64;
+        for _ in 0..KEPLER_MAX_ITER {
+            let delta = (e_k - e * e_k.sin() - m_k) / (1.0 - e * e_k.cos());
 
-        loop {
-            e_k = m + e * e_k_lst.sin();
+            e_k -= delta;
 
-            if (e_k - e_k_lst).abs() < 1E-10 {
-                break;
+            if delta.abs() < KEPLER_TOLERANCE_RAD {
+                return Some((e_k, n));
             }
+        }
 
-            i += 1;
-            if i == 10 {
-                error!("{}({}) - kepler solver in failure", epoch, self.sv);
-                return None;
-            }
+        error!("{}({}) - kepler solver in failure", self.toe, self.sv);
+        None
+    }
+
+    /// Returns the [SatelliteClockCorrection] at this [Epoch], from the broadcast
+    /// clock polynomial (af0, af1, af2). The relativistic correction is integrated.
+    /// The total group delay is not: it is signal dependent.
+    pub fn clock_correction(&self, epoch: Epoch) -> Option<SatelliteClockCorrection> {
+        let timescale = self.sv.constellation.timescale()?;
+        let dt = (epoch.to_time_scale(timescale) - self.toc).to_seconds();
+
+        let polynomial_s =
+            self.clock_bias_s + self.clock_drift_s_s * dt + self.clock_drift_rate_s_s2 * dt.powi(2);
+
+        let relativistic_s = self.relativistic_correction_s(epoch)?;
+
+        let drift_s_s = self.clock_drift_s_s + 2.0 * self.clock_drift_rate_s_s2 * dt;
+
+        Some(
+            SatelliteClockCorrection::with_relativistic_correction(Duration::from_seconds(
+                polynomial_s + relativistic_s,
+            ))
+            .with_drift(drift_s_s),
+        )
+    }
+
+    /// Returns the relativistic clock correction (s), due to the orbit eccentricity.
+    pub fn relativistic_correction_s(&self, epoch: Epoch) -> Option<f64> {
+        let t_k = self.t_k(epoch)?;
+        let (e_k, _) = self.eccentric_anomaly(t_k)?;
+
+        let f = -2.0 * self.gm_m3_s2().sqrt() / SPEED_OF_LIGHT_M_S.powi(2);
+
+        Some(f * self.eccentricity * self.semi_major_axis_m.sqrt() * e_k.sin())
+    }
+
+    /// Resolves Kepler equations from [Ephemeris], returning
+    /// the position and velocity of the satellite at this [Epoch].
+    pub fn resolve_state(&self, epoch: Epoch, frame: Frame) -> Option<Orbit> {
+        if !self.is_keplerian() {
+            return None;
         }
 
+        let e = self.eccentricity;
+        let a = self.semi_major_axis_m;
+        let omega_e = self.earth_angular_vel_rad_s();
+
+        let (cus, cuc) = self.cus_cuc_rad;
+        let (cis, cic) = self.cis_cic_rad;
+        let (crs, crc) = self.crs_crc_m;
+        let (i0, idot) = (self.i0_rad, self.idot_rad_s);
+        let (omega0, omega, omega_dot) = (self.omega0_rad, self.omega_rad, self.omega_dot_rad_s);
+
+        let t_k = self.t_k(epoch)?;
+        let (e_k, n) = self.eccentric_anomaly(t_k)?;
+
         let (sin_e_k, cos_e_k) = e_k.sin_cos();
-        let v_k = ((1.0 - e_2).sqrt() * sin_e_k).atan2(cos_e_k - e);
-        // let (sin_v_k, cos_v_k) = v_k.sin_cos();
+        let v_k = ((1.0 - e.powi(2)).sqrt() * sin_e_k).atan2(cos_e_k - e);
 
         let phi = v_k + omega;
         let (sin_2phi, cos_2phi) = (2.0 * phi).sin_cos();
@@ -74,30 +172,241 @@ impl Ephemeris {
         let u_k = phi + cuc * cos_2phi + cus * sin_2phi;
         let r_k = a * (1.0 - e * cos_e_k) + crc * cos_2phi + crs * sin_2phi;
         let i_k = i0 + idot * t_k + cic * cos_2phi + cis * sin_2phi;
-        let omega_k =
-            omega0 + (omega_dot - OMEGA_EARTH) * t_k - OMEGA_EARTH * self.weekly_toe_seconds();
 
-        let (x, y, z) = (r_k * u_k.cos(), r_k * u_k.sin(), 0.0);
+        // time derivatives
+        let e_k_dot = n / (1.0 - e * cos_e_k);
+        let v_k_dot = e_k_dot * (1.0 - e.powi(2)).sqrt() / (1.0 - e * cos_e_k);
+
+        let u_k_dot = v_k_dot * (1.0 + 2.0 * (cus * cos_2phi - cuc * sin_2phi));
+        let r_k_dot = a * e * sin_e_k * e_k_dot + 2.0 * v_k_dot * (crs * cos_2phi - crc * sin_2phi);
+        let i_k_dot = idot + 2.0 * v_k_dot * (cis * cos_2phi - cic * sin_2phi);
+
+        // position and velocity in the orbital plane
+        let (sin_u_k, cos_u_k) = u_k.sin_cos();
+        let (x, y) = (r_k * cos_u_k, r_k * sin_u_k);
 
-        // MEO orbit to ECEF rotation matrix
-        let rot_x3 = Rotation3::from_axis_angle(&Vector3::x_axis(), i_k);
-        let rot_z3 = Rotation3::from_axis_angle(&Vector3::z_axis(), omega_k);
-        let rot3 = rot_z3 * rot_x3;
+        let x_dot = r_k_dot * cos_u_k - r_k * u_k_dot * sin_u_k;
+        let y_dot = r_k_dot * sin_u_k + r_k * u_k_dot * cos_u_k;
 
-        let xyz_vec3 = Vector3::new(x, y, z);
-        let xyz_ecef = rot3 * xyz_vec3;
+        // BeiDou GEO: the ascending node is expressed in an inertial frame
+        let (omega_k, omega_k_dot) = if self.is_beidou_geo() {
+            (
+                omega0 + omega_dot * t_k - omega_e * self.weekly_toe_seconds(),
+                omega_dot,
+            )
+        } else {
+            (
+                omega0 + (omega_dot - omega_e) * t_k - omega_e * self.weekly_toe_seconds(),
+                omega_dot - omega_e,
+            )
+        };
 
-        let (x_km, y_km, z_km) = (
-            xyz_ecef[0] / 1000.0,
-            xyz_ecef[1] / 1000.0,
-            xyz_ecef[2] / 1000.0,
+        let (sin_omega_k, cos_omega_k) = omega_k.sin_cos();
+        let (sin_i_k, cos_i_k) = i_k.sin_cos();
+
+        let position = Vector3::new(
+            x * cos_omega_k - y * cos_i_k * sin_omega_k,
+            x * sin_omega_k + y * cos_i_k * cos_omega_k,
+            y * sin_i_k,
         );
 
+        let y_dot_plane = y_dot * cos_i_k - y * sin_i_k * i_k_dot;
+
+        let velocity = Vector3::new(
+            -omega_k_dot * position[1] + x_dot * cos_omega_k - y_dot_plane * sin_omega_k,
+            omega_k_dot * position[0] + x_dot * sin_omega_k + y_dot_plane * cos_omega_k,
+            y_dot * sin_i_k + y * cos_i_k * i_k_dot,
+        );
+
+        let (position, velocity) = if self.is_beidou_geo() {
+            // rotation to the ECEF frame
+            let (sin_z, cos_z) = (omega_e * t_k).sin_cos();
+            let (sin_x, cos_x) = BDS_GEO_INCLINATION_RAD.sin_cos();
+
+            let rot_x = Matrix3::new(1.0, 0.0, 0.0, 0.0, cos_x, sin_x, 0.0, -sin_x, cos_x);
+            let rot_z = Matrix3::new(cos_z, sin_z, 0.0, -sin_z, cos_z, 0.0, 0.0, 0.0, 1.0);
+
+            let rot_z_dot =
+                omega_e * Matrix3::new(-sin_z, cos_z, 0.0, -cos_z, -sin_z, 0.0, 0.0, 0.0, 0.0);
+
+            (
+                rot_z * rot_x * position,
+                rot_z * rot_x * velocity + rot_z_dot * rot_x * position,
+            )
+        } else {
+            (position, velocity)
+        };
+
         debug!(
-            "{}({}) - kepler solving x_km={}, y_km={} z_km={} t_k={}",
-            epoch, self.sv, x_km, y_km, z_km, t_k
+            "{}({}) - kepler solving x_km={} y_km={} z_km={} t_k={}",
+            epoch,
+            self.sv,
+            position[0] / 1.0E3,
+            position[1] / 1.0E3,
+            position[2] / 1.0E3,
+            t_k
         );
 
-        Some(Orbit::from_position(x_km, y_km, z_km, epoch, frame))
+        Some(Orbit::from_cartesian_pos_vel(
+            Vector6::new(
+                position[0] / 1.0E3,
+                position[1] / 1.0E3,
+                position[2] / 1.0E3,
+                velocity[0] / 1.0E3,
+                velocity[1] / 1.0E3,
+                velocity[2] / 1.0E3,
+            ),
+            epoch,
+            frame,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::{
+        Constellation, Duration, EARTH_J2000, Ephemeris, Epoch, SPEED_OF_LIGHT_M_S, SV,
+    };
+
+    use std::str::FromStr;
+
+    fn gps_ephemeris() -> Ephemeris {
+        let toe = Epoch::from_str("2020-06-25T02:00:00 GPST").unwrap();
+
+        Ephemeris {
+            sv: SV::new(Constellation::GPS, 1),
+            toe,
+            toc: toe,
+            semi_major_axis_m: 5153.6_f64.powi(2),
+            eccentricity: 0.0111,
+            m0_rad: 0.85,
+            i0_rad: 0.97,
+            idot_rad_s: -2.1E-10,
+            dn_rad: 4.5E-9,
+            omega0_rad: -1.92,
+            omega_rad: 0.73,
+            omega_dot_rad_s: -8.1E-9,
+            cus_cuc_rad: (6.2E-6, -1.3E-6),
+            cis_cic_rad: (1.1E-7, -5.2E-8),
+            crs_crc_m: (-24.5, 283.0),
+            clock_bias_s: -1.2E-4,
+            clock_drift_s_s: -9.0E-12,
+            clock_drift_rate_s_s2: 0.0,
+            tgd_s: 5.1E-9,
+            health: 0,
+        }
+    }
+
+    #[test]
+    fn kepler_state() {
+        let eph = gps_ephemeris();
+        let t = eph.toe + Duration::from_seconds(1800.0);
+
+        let orbit = eph.resolve_state(t, EARTH_J2000).unwrap();
+        let state = orbit.to_cartesian_pos_vel() * 1.0E3;
+
+        let r_m = (state[0].powi(2) + state[1].powi(2) + state[2].powi(2)).sqrt();
+        let a = eph.semi_major_axis_m;
+
+        assert!(r_m > a * (1.0 - eph.eccentricity) - 1.0E3);
+        assert!(r_m < a * (1.0 + eph.eccentricity) + 1.0E3);
+
+        // velocity consistency (finite differences)
+        let dt = 0.5;
+        let before = eph
+            .resolve_state(t - Duration::from_seconds(dt), EARTH_J2000)
+            .unwrap()
+            .to_cartesian_pos_vel()
+            * 1.0E3;
+
+        let after = eph
+            .resolve_state(t + Duration::from_seconds(dt), EARTH_J2000)
+            .unwrap()
+            .to_cartesian_pos_vel()
+            * 1.0E3;
+
+        for i in 0..3 {
+            let velocity_m_s = (after[i] - before[i]) / 2.0 / dt;
+            assert!(
+                (velocity_m_s - state[3 + i]).abs() < 1.0E-3,
+                "velocity[{}] {} != {}",
+                i,
+                state[3 + i],
+                velocity_m_s
+            );
+        }
+    }
+
+    #[test]
+    fn beidou_geo_state() {
+        let mut eph = gps_ephemeris();
+
+        eph.sv = SV::new(Constellation::BeiDou, 3);
+        eph.semi_major_axis_m = 6493.4_f64.powi(2);
+        eph.eccentricity = 4.0E-4;
+        eph.i0_rad = 0.1;
+
+        let t = eph.toe + Duration::from_seconds(600.0);
+
+        let state = eph
+            .resolve_state(t, EARTH_J2000)
+            .unwrap()
+            .to_cartesian_pos_vel()
+            * 1.0E3;
+
+        let r_m = (state[0].powi(2) + state[1].powi(2) + state[2].powi(2)).sqrt();
+        assert!((r_m - eph.semi_major_axis_m).abs() < 20.0E3);
+
+        // GEO: quasi static in the ECEF frame
+        let v_m_s = (state[3].powi(2) + state[4].powi(2) + state[5].powi(2)).sqrt();
+        assert!(v_m_s < 400.0, "GEO velocity too large: {} m/s", v_m_s);
+    }
+
+    #[test]
+    fn clock_correction() {
+        let mut eph = gps_ephemeris();
+        let t = eph.toc + Duration::from_seconds(100.0);
+
+        let relativistic_s = eph.relativistic_correction_s(t).unwrap();
+
+        // bounded by the eccentricity (~23ns for 0.01)
+        let bound_s =
+            2.0 * 3.986005E14_f64.sqrt() * eph.eccentricity * eph.semi_major_axis_m.sqrt()
+                / SPEED_OF_LIGHT_M_S.powi(2);
+
+        assert!(relativistic_s.abs() <= bound_s);
+
+        let correction = eph.clock_correction(t).unwrap();
+        let expected_s = -1.2E-4 - 9.0E-12 * 100.0 + relativistic_s;
+
+        // nanosecond resolution
+        assert!((correction.duration.to_seconds() - expected_s).abs() < 1.0E-9);
+        assert!(!correction.needs_relativistic_correction);
+
+        let expected_s_s = eph.clock_drift_s_s + 2.0 * eph.clock_drift_rate_s_s2 * 100.0;
+        assert_eq!(correction.drift_s_s, expected_s_s);
+
+        // circular orbit
+        eph.eccentricity = 0.0;
+        assert_eq!(eph.relativistic_correction_s(t).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn ephemeris_validity() {
+        let mut eph = gps_ephemeris();
+
+        assert!(eph.is_usable(eph.toe + Duration::from_hours(1.0)));
+        assert!(!eph.is_usable(eph.toe + Duration::from_hours(3.0)));
+
+        eph.health = 1;
+        assert!(!eph.is_usable(eph.toe));
+
+        let mut eph = gps_ephemeris();
+        eph.sv = SV::new(Constellation::Galileo, 1);
+        assert!(eph.is_usable(eph.toe + Duration::from_hours(3.0)));
+
+        eph.sv = SV::new(Constellation::Glonass, 1);
+        assert!(!eph.is_usable(eph.toe));
+        assert!(eph.resolve_state(eph.toe, EARTH_J2000).is_none());
     }
 }
@@ -5,7 +5,7 @@ use anise::{
     errors::{AlmanacError, PhysicsError},
 };
 
-use crate::prelude::{Epoch, SV};
+use crate::prelude::{ConfigError, Epoch, SV};
 
 #[derive(Debug, PartialEq, Error)]
 pub enum Error {
@@ -209,7 +209,62 @@ pub enum Error {
     #[error("rtk baseline most likely too long - internal limitation")]
     RtkBaselineTooLong,
 
+    /// None of the network reference sites observes the rover pivot satellite
+    /// (or could form its single differences).
+    #[error("rtk network: no reference site observes the pivot satellite")]
+    NoCommonPivot,
+
     /// RTK resolution non feasible: missing DD measurement postfit
     #[error("missing rtk measurement DD post-fit")]
     RtkDDPostfitMissing,
+
+    /// Velocity estimation requires Doppler observations to be associated
+    /// to the [Candidate]s.
+    #[error("missing doppler observation")]
+    MissingDoppler,
+
+    /// Velocity estimation requires the satellite velocity to be determined,
+    /// either by the [OrbitSource] or by finite differences over past epochs.
+    #[error("unresolved satellite velocity")]
+    UnresolvedVelocity,
+
+    /// Attitude determination requires one lever arm per secondary antenna,
+    /// with non null length.
+    #[error("invalid or missing antenna lever arms")]
+    InvalidLeverArms,
+
+    /// Attitude baseline ambiguities could not be fixed reliably
+    /// (bad phase observations or cycle slips).
+    #[error("attitude baseline ambiguity fixing error")]
+    AttitudeAmbiguityFixing,
+
+    /// Resolved attitude baseline does not match the lever arm length.
+    #[error("attitude baseline length does not match the lever arm")]
+    AttitudeBaselineLength,
+
+    /// Smoothing requires the forward pass to be recorded,
+    /// see `SolverOpts::rts_smoothing`.
+    #[error("rts smoothing is not enabled")]
+    RtsSmoothingDisabled,
+
+    /// Integrity monitoring detected a fault that could not be excluded,
+    /// see `IntegrityOpts`.
+    #[error("rejected solution: integrity fault")]
+    IntegrityFault,
+
+    /// ANTEX content could not be parsed, see `Antex`.
+    #[error("invalid ANTEX content")]
+    AntexParsing,
+
+    /// NeQuick-G tables could not be parsed, see `NeQuickData`.
+    #[error("invalid NeQuick-G data")]
+    InvalidNeQuickData,
+
+    /// Invalid [Config](crate::prelude::Config), rejected on [Solver](crate::prelude::Solver) deployment.
+    #[error("invalid configuration: {0}")]
+    Config(#[from] ConfigError),
+
+    /// IONEX content could not be parsed, see `Ionex`.
+    #[error("invalid IONEX content")]
+    IonexParsing,
 }
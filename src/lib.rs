@@ -10,6 +10,8 @@ pub mod error;
 
 // mod ambiguity;
 // mod averager;
+mod antex;
+mod attitude;
 mod bancroft;
 mod bias;
 mod candidate;
@@ -18,12 +20,15 @@ mod cfg;
 mod ephemeris;
 mod navigation;
 mod orbit;
+mod parsing;
 mod pool;
 mod rtk;
-// mod smoothing;
+mod session;
+mod smoothing;
+mod tides;
 mod time;
+mod tracker;
 mod user;
-// mod tides;
 
 pub(crate) mod constants;
 pub(crate) mod solver;
@@ -34,22 +39,33 @@ mod tests;
 // prelude
 pub mod prelude {
     pub use crate::{
+        antex::{Antenna, AntennaFrequency, AntennaKind, Antex},
+        attitude::AttitudeSolution,
         bias::{
             BiasRuntime,
             environment::{
-                EnvironmentalBias, IonosphereBias, IonosphereModel, KbModel, TroposphereModel,
+                BdModel, BdgimBackground, EnvironmentalBias, Ionex, IonosphereBias,
+                IonosphereModel, KbModel, NeQuickData, NgModel, TroposphereModel,
             },
             spaceborn::{SatelliteClockCorrection, SpacebornBias},
         },
         candidate::{Candidate, Observation},
         carrier::{Carrier, Signal},
-        cfg::{Config, Method},
+        cfg::{
+            AttitudeOpts, Config, ConstellationSigmas, CycleSlipOpts, Error as ConfigError,
+            IntegrityOpts, InternalDelay, Method, OceanLoading, StochasticModel, WeightingModel,
+        },
         constants::SPEED_OF_LIGHT_M_S,
-        ephemeris::{Ephemeris, EphemerisSource},
+        ephemeris::{Ephemeris, EphemerisSource, GlonassEphemeris},
         error::Error,
-        navigation::solutions::{PVTSolution, PVTSolutionType},
-        orbit::OrbitSource,
+        navigation::{
+            accuracy::{ErrorEllipse, ErrorEllipsoid},
+            integrity::ProtectionLevels,
+            solutions::{InterSystemBias, PVTSolution, PVTSolutionType},
+        },
+        orbit::{NullOrbitSource, OrbitSource},
         rtk::RTKBase,
+        session::{SessionReport, SessionSummary},
         solver::Solver,
         time::AbsoluteTime,
         user::{ClockProfile, UserParameters, UserProfile},
@@ -59,7 +75,7 @@ pub mod prelude {
     pub use std::rc::Rc;
 
     // gnss types
-    pub use gnss::prelude::{Constellation, SV};
+    pub use gnss::prelude::{COSPAR, Constellation, SV};
 
     // anise types
     pub use anise::{
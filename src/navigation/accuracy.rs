@@ -0,0 +1,291 @@
+use nalgebra::{DMatrix, DimName, Matrix2, Matrix3, U3};
+
+use crate::{
+    constants::SPEED_OF_LIGHT_M_S,
+    navigation::{DilutionOfPrecision, Navigation, state::State},
+};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// Horizontal [ErrorEllipse] (1σ), expressed in the local (east, north) plane.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ErrorEllipse {
+    /// Semi major axis, in meters.
+    pub semi_major_m: f64,
+
+    /// Semi minor axis, in meters.
+    pub semi_minor_m: f64,
+
+    /// Orientation of the semi major axis, in degrees, clockwise from north (0..180°).
+    pub orientation_deg: f64,
+}
+
+/// 3D [ErrorEllipsoid] (1σ), described by its semi axes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ErrorEllipsoid {
+    /// Semi major axis, in meters.
+    pub semi_major_m: f64,
+
+    /// Semi intermediate axis, in meters.
+    pub semi_intermediate_m: f64,
+
+    /// Semi minor axis, in meters.
+    pub semi_minor_m: f64,
+}
+
+/// [Navigation] filter [Accuracy], derived from the estimation covariance.
+#[derive(Clone, Default, Copy)]
+pub(crate) struct Accuracy {
+    /// Position covariance (ECEF, m²)
+    pub pos_cov_m2: Matrix3<f64>,
+
+    /// Position covariance (ENU, m²)
+    pub enu_cov_m2: Matrix3<f64>,
+
+    /// Clock offset standard deviation (s)
+    pub clock_offset_sigma_s: f64,
+
+    /// Circular error probable (m)
+    pub cep_m: f64,
+
+    /// 95% horizontal radius (m)
+    pub r95_m: f64,
+
+    /// Horizontal [ErrorEllipse]
+    pub error_ellipse: ErrorEllipse,
+
+    /// [ErrorEllipsoid]
+    pub error_ellipsoid: ErrorEllipsoid,
+}
+
+impl Accuracy {
+    /// Creates new [Accuracy].
+    ///
+    /// ## Input
+    /// - new [State]
+    /// - p: state covariance matrix, position first (m²)
+    ///   then possible clock offset (m²)
+    pub fn new(state: &State, p: &DMatrix<f64>) -> Self {
+        let (nrows, ncols) = (p.nrows(), p.ncols());
+
+        assert!(nrows >= U3::DIM, "incorrect P dimensions");
+        assert_eq!(nrows, ncols, "P is not square");
+
+        let (lat_rad, long_rad) = (
+            state.lat_long_alt_deg_deg_km.0.to_radians(),
+            state.lat_long_alt_deg_deg_km.1.to_radians(),
+        );
+
+        let pos_cov_m2 = p.fixed_view::<3, 3>(0, 0).into_owned();
+        let enu_cov_m2 = DilutionOfPrecision::q_enu(p, lat_rad, long_rad);
+
+        let clock_index = Navigation::clock_index();
+
+        let clock_offset_sigma_s = if nrows > clock_index {
+            p[(clock_index, clock_index)].sqrt() / SPEED_OF_LIGHT_M_S
+        } else {
+            0.0
+        };
+
+        let error_ellipse = Self::error_ellipse(&enu_cov_m2);
+
+        Self {
+            pos_cov_m2,
+            enu_cov_m2,
+            clock_offset_sigma_s,
+            error_ellipse,
+            cep_m: Self::horizontal_radius_m(&error_ellipse, 0.5),
+            r95_m: Self::horizontal_radius_m(&error_ellipse, 0.95),
+            error_ellipsoid: Self::error_ellipsoid(&enu_cov_m2),
+        }
+    }
+
+    /// Returns standard deviation per ECEF axis (m)
+    pub fn pos_sigma_m(&self) -> (f64, f64, f64) {
+        (
+            self.pos_cov_m2[(0, 0)].sqrt(),
+            self.pos_cov_m2[(1, 1)].sqrt(),
+            self.pos_cov_m2[(2, 2)].sqrt(),
+        )
+    }
+
+    /// Returns standard deviation per ENU axis (m)
+    pub fn enu_sigma_m(&self) -> (f64, f64, f64) {
+        (
+            self.enu_cov_m2[(0, 0)].sqrt(),
+            self.enu_cov_m2[(1, 1)].sqrt(),
+            self.enu_cov_m2[(2, 2)].sqrt(),
+        )
+    }
+
+    /// Horizontal [ErrorEllipse] from the ENU covariance.
+    fn error_ellipse(enu_cov_m2: &Matrix3<f64>) -> ErrorEllipse {
+        let horizontal = Matrix2::new(
+            enu_cov_m2[(0, 0)],
+            enu_cov_m2[(0, 1)],
+            enu_cov_m2[(1, 0)],
+            enu_cov_m2[(1, 1)],
+        );
+
+        let eigen = horizontal.symmetric_eigen();
+
+        let (major, minor) = if eigen.eigenvalues[0] >= eigen.eigenvalues[1] {
+            (0, 1)
+        } else {
+            (1, 0)
+        };
+
+        let (east, north) = (
+            eigen.eigenvectors[(0, major)],
+            eigen.eigenvectors[(1, major)],
+        );
+
+        let orientation_deg = east.atan2(north).to_degrees().rem_euclid(180.0);
+
+        ErrorEllipse {
+            orientation_deg,
+            semi_major_m: eigen.eigenvalues[major].max(0.0).sqrt(),
+            semi_minor_m: eigen.eigenvalues[minor].max(0.0).sqrt(),
+        }
+    }
+
+    /// [ErrorEllipsoid] from the ENU covariance.
+    fn error_ellipsoid(enu_cov_m2: &Matrix3<f64>) -> ErrorEllipsoid {
+        let eigen = enu_cov_m2.symmetric_eigen();
+
+        let mut axes_m = [
+            eigen.eigenvalues[0].max(0.0).sqrt(),
+            eigen.eigenvalues[1].max(0.0).sqrt(),
+            eigen.eigenvalues[2].max(0.0).sqrt(),
+        ];
+
+        axes_m.sort_by(|a, b| b.partial_cmp(a).unwrap());
+
+        ErrorEllipsoid {
+            semi_major_m: axes_m[0],
+            semi_intermediate_m: axes_m[1],
+            semi_minor_m: axes_m[2],
+        }
+    }
+
+    /// Returns the radius (m) of the horizontal circle, centered on the solution,
+    /// that contains the horizontal error with given probability.
+    /// The bivariate normal distribution is integrated in polar coordinates.
+    fn horizontal_radius_m(ellipse: &ErrorEllipse, probability: f64) -> f64 {
+        const NB_STEPS: usize = 360;
+        const NB_ITER: usize = 64;
+
+        let a = ellipse.semi_major_m;
+
+        if a == 0.0 {
+            return 0.0;
+        }
+
+        // avoid singularity on degenerate ellipses
+        let b = ellipse.semi_minor_m.max(a * 1.0E-3);
+
+        let d_theta = 2.0 * std::f64::consts::PI / NB_STEPS as f64;
+
+        let cdf = |r: f64| {
+            let mut sum = 0.0;
+
+            for i in 0..NB_STEPS {
+                let (sin, cos) = (i as f64 * d_theta).sin_cos();
+                let q = cos.powi(2) / a.powi(2) + sin.powi(2) / b.powi(2);
+                sum += (1.0 - (-r.powi(2) * q / 2.0).exp()) / q;
+            }
+
+            sum * d_theta / 2.0 / std::f64::consts::PI / a / b
+        };
+
+        let (mut low, mut high) = (0.0, 5.0 * a);
+
+        for _ in 0..NB_ITER {
+            let mid = (low + high) / 2.0;
+
+            if cdf(mid) < probability {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        (low + high) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Accuracy, ErrorEllipse};
+    use crate::navigation::State;
+
+    use nalgebra::{DMatrix, DVector};
+
+    #[test]
+    fn circular_accuracy() {
+        let state = State::default();
+        let sigma_m = 2.0_f64;
+
+        let p = DMatrix::from_diagonal(&DVector::from_row_slice(&[
+            sigma_m.powi(2),
+            sigma_m.powi(2),
+            sigma_m.powi(2),
+        ]));
+
+        let accuracy = Accuracy::new(&state, &p);
+
+        assert!((accuracy.cep_m - 1.1774 * sigma_m).abs() < 1.0E-3);
+        assert!((accuracy.r95_m - 2.4477 * sigma_m).abs() < 1.0E-3);
+
+        assert!((accuracy.error_ellipse.semi_major_m - sigma_m).abs() < 1.0E-9);
+        assert!((accuracy.error_ellipse.semi_minor_m - sigma_m).abs() < 1.0E-9);
+
+        assert!((accuracy.error_ellipsoid.semi_major_m - sigma_m).abs() < 1.0E-9);
+        assert!((accuracy.error_ellipsoid.semi_minor_m - sigma_m).abs() < 1.0E-9);
+
+        assert_eq!(accuracy.clock_offset_sigma_s, 0.0, "clock is not resolved");
+    }
+
+    #[test]
+    fn elliptical_accuracy() {
+        // null lat/long: (E, N, U) = (Y, Z, X)
+        let state = State::default();
+
+        let p = DMatrix::from_diagonal(&DVector::from_row_slice(&[9.0, 1.0, 4.0, 1.0]));
+
+        let accuracy = Accuracy::new(&state, &p);
+
+        let (sigma_e, sigma_n, sigma_u) = accuracy.enu_sigma_m();
+
+        assert!((sigma_e - 1.0).abs() < 1.0E-9);
+        assert!((sigma_n - 2.0).abs() < 1.0E-9);
+        assert!((sigma_u - 3.0).abs() < 1.0E-9);
+
+        assert_eq!(accuracy.pos_sigma_m(), (3.0, 1.0, 2.0));
+
+        let ErrorEllipse {
+            semi_major_m,
+            semi_minor_m,
+            orientation_deg,
+        } = accuracy.error_ellipse;
+
+        assert!((semi_major_m - 2.0).abs() < 1.0E-9);
+        assert!((semi_minor_m - 1.0).abs() < 1.0E-9);
+        assert!(
+            orientation_deg.abs() < 1.0E-6,
+            "major axis should point north"
+        );
+
+        assert!((accuracy.error_ellipsoid.semi_major_m - 3.0).abs() < 1.0E-9);
+        assert!((accuracy.error_ellipsoid.semi_intermediate_m - 2.0).abs() < 1.0E-9);
+        assert!((accuracy.error_ellipsoid.semi_minor_m - 1.0).abs() < 1.0E-9);
+
+        assert!(accuracy.cep_m > 1.0 && accuracy.cep_m < 2.0);
+        assert!(accuracy.r95_m > 3.0 && accuracy.r95_m < 2.0 * 2.4477);
+
+        assert!(accuracy.clock_offset_sigma_s > 0.0);
+    }
+}
@@ -1,4 +1,4 @@
-use nalgebra::{DMatrix, DimName, Matrix3, U3};
+use nalgebra::{DMatrix, DimName, Matrix3, U3, U4};
 
 use crate::navigation::state::State;
 
@@ -19,8 +19,10 @@ pub(crate) struct DilutionOfPrecision {
 }
 
 impl DilutionOfPrecision {
-    pub(crate) fn q_enu(mat: &DMatrix<f64>, lat_rad: f64, lon_rad: f64) -> Matrix3<f64> {
-        let r = Matrix3::<f64>::new(
+    /// Returns the rotation matrix whose columns are the local
+    /// (east, north, up) axes, expressed in ECEF.
+    pub(crate) fn enu_rotation(lat_rad: f64, lon_rad: f64) -> Matrix3<f64> {
+        Matrix3::<f64>::new(
             -lon_rad.sin(),
             -lon_rad.cos() * lat_rad.sin(),
             lat_rad.cos() * lon_rad.cos(),
@@ -30,7 +32,11 @@ impl DilutionOfPrecision {
             0.0_f64,
             lat_rad.cos(),
             lat_rad.sin(),
-        );
+        )
+    }
+
+    pub(crate) fn q_enu(mat: &DMatrix<f64>, lat_rad: f64, lon_rad: f64) -> Matrix3<f64> {
+        let r = Self::enu_rotation(lat_rad, lon_rad);
 
         let q_3 = Matrix3::<f64>::new(
             mat[(0, 0)],
@@ -66,7 +72,11 @@ impl DilutionOfPrecision {
         let q_enu = Self::q_enu(&g_gt_inv, lat_rad, long_rad);
 
         Self {
-            gdop: g_gt_inv.trace().sqrt(),
+            // position and clock only (possible inter-system biases excluded)
+            gdop: (0..nrows.min(U4::DIM))
+                .map(|i| g_gt_inv[(i, i)])
+                .sum::<f64>()
+                .sqrt(),
             tdop: if nrows > U3::DIM {
                 g_gt_inv[(3, 3)].sqrt()
             } else {
@@ -0,0 +1,364 @@
+//! Receiver autonomous integrity monitoring (RAIM)
+use nalgebra::{DMatrix, DVector, DimName, U3};
+
+use crate::{navigation::DilutionOfPrecision, prelude::IntegrityOpts};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// Convergence criteria of the numerical methods
+const EPSILON: f64 = 1.0E-14;
+
+/// Maximal number of iterations of the numerical methods
+const MAX_ITER: usize = 500;
+
+/// [ProtectionLevels] bound the position error, with a probability
+/// that is the integrity risk, see [IntegrityOpts].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ProtectionLevels {
+    /// Horizontal protection level, in meters.
+    pub hpl_m: f64,
+
+    /// Vertical protection level, in meters.
+    pub vpl_m: f64,
+}
+
+/// Outcome of the residual test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum IntegrityStatus {
+    /// Not enough redundancy (or singular geometry) to monitor this solution
+    Unavailable,
+
+    /// Consistent measurements, bounded by [ProtectionLevels]
+    Nominal(ProtectionLevels),
+
+    /// Fault detected, with the most likely faulty measurement (row), when it may be identified.
+    Fault(Option<usize>),
+}
+
+/// Natural logarithm of the Gamma function (Lanczos approximation).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179E-2,
+        -0.5395239384953E-5,
+    ];
+
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+
+    let mut y = x;
+    let mut series = 1.000000000190015;
+
+    for coeff in COEFFS {
+        y += 1.0;
+        series += coeff / y;
+    }
+
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Regularized upper incomplete Gamma function Q(a, x).
+fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+
+    let ln_prefix = -x + a * x.ln() - ln_gamma(a);
+
+    if x < a + 1.0 {
+        // series expansion of P(a, x)
+        let (mut ap, mut sum) = (a, 1.0 / a);
+        let mut del = sum;
+
+        for _ in 0..MAX_ITER {
+            ap += 1.0;
+            del *= x / ap;
+            sum += del;
+
+            if del.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+
+        1.0 - sum * ln_prefix.exp()
+    } else {
+        // continued fraction (modified Lentz)
+        let tiny = 1.0E-300;
+
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+
+        for i in 1..MAX_ITER {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+
+            d = 1.0 / d;
+
+            let del = d * c;
+            h *= del;
+
+            if (del - 1.0).abs() < EPSILON {
+                break;
+            }
+        }
+
+        ln_prefix.exp() * h
+    }
+}
+
+/// Returns the chi-square value that is exceeded with this (upper tail) probability,
+/// for this number of degrees of freedom.
+pub(crate) fn chi_square_upper_quantile(probability: f64, dof: usize) -> f64 {
+    let a = dof as f64 / 2.0;
+
+    let (mut low, mut high) = (0.0, dof as f64 + 10.0);
+
+    while gamma_q(a, high / 2.0) > probability {
+        high *= 2.0;
+    }
+
+    for _ in 0..MAX_ITER {
+        let mid = (low + high) / 2.0;
+
+        if gamma_q(a, mid / 2.0) > probability {
+            low = mid;
+        } else {
+            high = mid;
+        }
+
+        if high - low < high * EPSILON {
+            break;
+        }
+    }
+
+    (low + high) / 2.0
+}
+
+/// Returns the standard normal value that is exceeded with this (upper tail) probability.
+pub(crate) fn normal_upper_quantile(probability: f64) -> f64 {
+    chi_square_upper_quantile(2.0 * probability, 1).sqrt()
+}
+
+/// Snapshot residual test, on the measurements of the latest update,
+/// weighted like the navigation filter.
+///
+/// ## Input
+/// - opts: [IntegrityOpts]
+/// - g: G [DMatrix], position first
+/// - w: measurement weights [DMatrix] (inverse variances, m⁻²)
+/// - y: measurement [DVector]
+/// - lat_rad, lon_rad: geodetic position, in radians
+///
+/// ## Output
+/// - [IntegrityStatus]
+pub(crate) fn residual_test(
+    opts: &IntegrityOpts,
+    g: &DMatrix<f64>,
+    w: &DMatrix<f64>,
+    y: &DVector<f64>,
+    lat_rad: f64,
+    lon_rad: f64,
+) -> IntegrityStatus {
+    let (nrows, ndf) = (g.nrows(), g.ncols());
+
+    if nrows <= ndf || ndf < U3::DIM || y.nrows() != nrows || w.shape() != (nrows, nrows) {
+        return IntegrityStatus::Unavailable;
+    }
+
+    let dof = nrows - ndf;
+
+    let gt_w = g.transpose() * w;
+
+    let gt_w_g_inv = match (gt_w.clone() * g).try_inverse() {
+        Some(inv) => inv,
+        None => return IntegrityStatus::Unavailable,
+    };
+
+    let a = gt_w_g_inv * gt_w;
+    let s = DMatrix::<f64>::identity(nrows, nrows) - g * a.clone();
+
+    let residuals = s.clone() * y;
+    let w_residuals = w * residuals.clone();
+
+    // W.S is symmetric: its diagonal is the sensitivity of the statistic to each measurement
+    let w_s = w * s;
+
+    let statistic = residuals.dot(&w_residuals);
+    let threshold = chi_square_upper_quantile(opts.false_alarm_probability, dof);
+
+    if statistic > threshold {
+        if dof < 2 {
+            return IntegrityStatus::Fault(None);
+        }
+
+        // normalized residuals
+        let faulty = (0..nrows)
+            .filter(|i| w_s[(*i, *i)] > EPSILON)
+            .max_by(|i, j| {
+                let r_i = w_residuals[*i].abs() / w_s[(*i, *i)].sqrt();
+                let r_j = w_residuals[*j].abs() / w_s[(*j, *j)].sqrt();
+                r_i.total_cmp(&r_j)
+            });
+
+        return IntegrityStatus::Fault(faulty);
+    }
+
+    // position error induced by a bias on each measurement (slopes)
+    let r = DilutionOfPrecision::enu_rotation(lat_rad, lon_rad);
+    let a_enu = r.transpose() * a.rows(0, U3::DIM);
+
+    let (mut hslope, mut vslope) = (0.0_f64, 0.0_f64);
+
+    for i in 0..nrows {
+        let w_s_ii = w_s[(i, i)];
+
+        // undetectable, with no impact on the position
+        if w_s_ii < EPSILON {
+            continue;
+        }
+
+        let scaling = 1.0 / w_s_ii.sqrt();
+
+        hslope = hslope.max((a_enu[(0, i)].powi(2) + a_enu[(1, i)].powi(2)).sqrt() * scaling);
+        vslope = vslope.max(a_enu[(2, i)].abs() * scaling);
+    }
+
+    let pbias = threshold.sqrt() + normal_upper_quantile(opts.integrity_risk);
+
+    IntegrityStatus::Nominal(ProtectionLevels {
+        hpl_m: hslope * pbias,
+        vpl_m: vslope * pbias,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{IntegrityStatus, chi_square_upper_quantile, normal_upper_quantile, residual_test};
+
+    use crate::prelude::IntegrityOpts;
+
+    use nalgebra::{DMatrix, DVector};
+
+    #[test]
+    fn chi_square_quantiles() {
+        for (probability, dof, expected) in [
+            (0.05, 1, 3.841459),
+            (0.05, 2, 5.991465),
+            (0.05, 10, 18.307038),
+            (0.01, 4, 13.276704),
+            (1.0E-5, 1, 19.511421),
+        ] {
+            let value = chi_square_upper_quantile(probability, dof);
+            assert!(
+                (value - expected).abs() < 1.0E-4,
+                "chi2({probability}, {dof}): {value} expecting {expected}"
+            );
+        }
+
+        assert!((normal_upper_quantile(0.025) - 1.959964).abs() < 1.0E-5);
+        assert!((normal_upper_quantile(1.0E-7) - 5.199338).abs() < 1.0E-4);
+    }
+
+    #[test]
+    fn fault_detection_and_protection_levels() {
+        let opts = IntegrityOpts {
+            raim: true,
+            ..Default::default()
+        };
+
+        // line of sights: (azimuth, elevation)
+        let geometry = [
+            (0.0_f64, 80.0_f64),
+            (45.0, 30.0),
+            (135.0, 45.0),
+            (225.0, 20.0),
+            (315.0, 55.0),
+            (90.0, 35.0),
+            (180.0, 60.0),
+            (270.0, 25.0),
+        ];
+
+        // local frame at the equator, null longitude: (up, east, north) = (x, y, z)
+        let mut g = DMatrix::<f64>::zeros(geometry.len(), 4);
+
+        for (i, (azim_deg, elev_deg)) in geometry.iter().enumerate() {
+            let (azim_rad, elev_rad) = (azim_deg.to_radians(), elev_deg.to_radians());
+
+            g[(i, 0)] = -elev_rad.sin();
+            g[(i, 1)] = -elev_rad.cos() * azim_rad.sin();
+            g[(i, 2)] = -elev_rad.cos() * azim_rad.cos();
+            g[(i, 3)] = 1.0;
+        }
+
+        // 5m uncertainty
+        let w = DMatrix::<f64>::identity(geometry.len(), geometry.len()) / 25.0;
+
+        // consistent measurements
+        let x = DVector::from_row_slice(&[1.0, -2.0, 3.0, 100.0]);
+        let mut y = g.clone() * x;
+
+        match residual_test(&opts, &g, &w, &y, 0.0, 0.0) {
+            IntegrityStatus::Nominal(levels) => {
+                assert!(levels.hpl_m > 0.0);
+                assert!(levels.vpl_m > 0.0);
+
+                // vertical geometry is always weaker
+                assert!(levels.vpl_m > levels.hpl_m);
+            },
+            status => panic!("unexpected status: {status:?}"),
+        }
+
+        // faulty measurement
+        y[3] += 100.0;
+
+        assert_eq!(
+            residual_test(&opts, &g, &w, &y, 0.0, 0.0),
+            IntegrityStatus::Fault(Some(3))
+        );
+
+        // same fault, on a measurement that is given little weight
+        let mut w_low = w.clone();
+        w_low[(3, 3)] = 1.0 / 100.0_f64.powi(2);
+
+        assert!(matches!(
+            residual_test(&opts, &g, &w_low, &y, 0.0, 0.0),
+            IntegrityStatus::Nominal(_)
+        ));
+
+        // singular geometry: unmonitored
+        let mut g_singular = g.clone();
+        g_singular.column_mut(3).fill(0.0);
+
+        assert_eq!(
+            residual_test(&opts, &g_singular, &w, &y, 0.0, 0.0),
+            IntegrityStatus::Unavailable
+        );
+
+        // no redundancy
+        let w = w.view((0, 0), (4, 4)).into_owned();
+        let g = g.rows(0, 4).into_owned();
+        let y = y.rows(0, 4).into_owned();
+
+        assert_eq!(
+            residual_test(&opts, &g, &w, &y, 0.0, 0.0),
+            IntegrityStatus::Unavailable
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use log::debug;
+
 use crate::error::Error;
 
 use nalgebra::{
@@ -163,7 +165,89 @@ impl Kalman {
         self.predicted.reset();
     }
 
-    /// Run this [Kalman] filter, returning new [KfEstimate].
+    /// Innovation gating: returns the measurements (rows) whose normalized innovation
+    /// exceeds this threshold. Each innovation is formed against the prediction updated
+    /// with all other measurements, so common errors (like the receiver clock) do not hide
+    /// outliers. The worst outlier is rejected first, until all innovations are consistent.
+    /// When most measurements would be rejected, this is a common mode jump rather
+    /// than outliers: nothing is rejected.
+    fn innovation_gating(
+        &self,
+        g_k: &DMatrix<f64>,
+        w_k: &DMatrix<f64>,
+        y_k: &DVector<f64>,
+        threshold: f64,
+    ) -> Result<Vec<usize>, Error> {
+        let (nrows, ndf) = (g_k.nrows(), g_k.ncols());
+
+        let p_inv = self
+            .predicted
+            .p
+            .clone()
+            .try_inverse()
+            .ok_or(Error::MatrixInversion)?;
+
+        // information form
+        let mut info = p_inv.clone() + g_k.transpose() * w_k * g_k;
+        let mut info_x = p_inv * self.predicted.x.clone() + g_k.transpose() * w_k * y_k;
+
+        let mut rejected = Vec::<usize>::new();
+
+        while nrows - rejected.len() > ndf {
+            let mut worst = None::<(usize, f64)>;
+
+            for i in 0..nrows {
+                let w_ii = w_k[(i, i)];
+
+                if w_ii <= 0.0 || rejected.contains(&i) {
+                    continue;
+                }
+
+                let g_i = g_k.row(i).transpose();
+
+                // prediction, without this measurement
+                let info_i = info.clone() - g_i.clone() * g_i.transpose() * w_ii;
+                let info_i_inv = info_i.try_inverse().ok_or(Error::MatrixInversion)?;
+                let x_i = info_i_inv.clone() * (info_x.clone() - g_i.clone() * w_ii * y_k[i]);
+
+                let innovation = y_k[i] - g_i.dot(&x_i);
+                let variance = g_i.dot(&(info_i_inv * g_i.clone())) + 1.0 / w_ii;
+                let normalized = innovation.abs() / variance.sqrt();
+
+                if normalized > threshold && worst.is_none_or(|(_, value)| normalized > value) {
+                    worst = Some((i, normalized));
+                }
+            }
+
+            let Some((i, normalized)) = worst else {
+                break;
+            };
+
+            debug!("innovation gating: row #{i} rejected ({normalized:.3}σ)");
+
+            let g_i = g_k.row(i).transpose();
+            let w_ii = w_k[(i, i)];
+
+            info -= g_i.clone() * g_i.transpose() * w_ii;
+            info_x -= g_i * w_ii * y_k[i];
+
+            rejected.push(i);
+        }
+
+        if rejected.len() * 2 > nrows {
+            debug!(
+                "innovation gating: common mode jump ({} rows)",
+                rejected.len()
+            );
+            return Ok(Vec::new());
+        }
+
+        rejected.sort();
+        Ok(rejected)
+    }
+
+    /// Run this [Kalman] filter, returning new [KfEstimate]
+    /// and the measurements (rows) rejected by innovation gating.
     ///
     /// ## Input
     /// - f_k: Dynamics [DMatrix]
@@ -171,6 +255,7 @@ impl Kalman {
     /// - w_k: W [DMatrix]
     /// - q_k: Q [DMatrix]
     /// - y_k: Measurement [DVector]
+    /// - innovation_gate: possible normalized innovation threshold
     pub fn run(
         &mut self,
         f_k: &DMatrix<f64>,
@@ -178,7 +263,8 @@ impl Kalman {
         w_k: &DMatrix<f64>,
         q_k: &DMatrix<f64>,
         y_k: &DVector<f64>,
-    ) -> Result<KfEstimate, Error> {
+        innovation_gate: Option<f64>,
+    ) -> Result<(KfEstimate, Vec<usize>), Error> {
         let (w_rows, w_cols) = (w_k.nrows(), w_k.ncols());
         let (g_rows, g_cols) = (g_k.nrows(), g_k.ncols());
         let (f_rows, f_cols) = (f_k.nrows(), f_k.ncols());
@@ -201,6 +287,24 @@ impl Kalman {
         assert_eq!(y_rows, g_rows, "invalid Y/G dimensions!");
         assert_eq!(y_rows, w_rows, "invalid Y/W dimensions!");
 
+        let rejected = match innovation_gate {
+            Some(threshold) => self.innovation_gating(g_k, w_k, y_k, threshold)?,
+            None => Vec::new(),
+        };
+
+        // rejected measurements do not contribute
+        let (g_k, w_k, y_k) = if rejected.is_empty() {
+            (g_k.clone(), w_k.clone(), y_k.clone())
+        } else {
+            (
+                g_k.clone().remove_rows_at(&rejected),
+                w_k.clone()
+                    .remove_rows_at(&rejected)
+                    .remove_columns_at(&rejected),
+                y_k.clone().remove_rows_at(&rejected),
+            )
+        };
+
         let gt = g_k.transpose();
 
         let p_inv = self
@@ -232,7 +336,7 @@ impl Kalman {
 
         self.predicted = KfEstimate { x: x_k1, p: p_k1 };
 
-        Ok(KfEstimate { x: x_k, p: p_k })
+        Ok((KfEstimate { x: x_k, p: p_k }, rejected))
     }
 
     /// Run this [Kalman] filter, returning new [KfEstimate].
@@ -292,9 +396,9 @@ impl Kalman {
 
 #[cfg(test)]
 mod test {
-    use super::KfEstimate;
+    use super::{Kalman, KfEstimate};
 
-    use nalgebra::{DimName, OMatrix, OVector, U6};
+    use nalgebra::{DMatrix, DVector, DimName, OMatrix, OVector, U6};
 
     #[test]
     fn kf_estimate_from_static() {
@@ -321,4 +425,47 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn innovation_gating() {
+        // scalar static state, observed five times with unit variance
+        let f_k = DMatrix::<f64>::identity(1, 1);
+        let q_k = DMatrix::<f64>::zeros(1, 1);
+        let g_k = DMatrix::<f64>::from_element(5, 1, 1.0);
+        let w_k = DMatrix::<f64>::identity(5, 5);
+
+        let y_k = DVector::from_row_slice(&[10.1, 9.9, 10.05, 60.0, 10.0]);
+
+        let mut kf = Kalman::new(1);
+
+        kf.initialize(
+            &f_k,
+            q_k.clone(),
+            KfEstimate::new(
+                &DVector::from_row_slice(&[10.0]),
+                &DMatrix::from_row_slice(1, 1, &[1.0]),
+            ),
+        );
+
+        let mut gated = kf.clone();
+
+        // outlier drags the estimate
+        let (estimate, rejected) = kf.run(&f_k, &g_k, &w_k, &q_k, &y_k, None).unwrap();
+
+        assert!(rejected.is_empty());
+        assert!((estimate.x[0] - 10.0).abs() > 5.0);
+
+        // outlier is rejected
+        let (estimate, rejected) = gated.run(&f_k, &g_k, &w_k, &q_k, &y_k, Some(5.0)).unwrap();
+
+        assert_eq!(rejected, vec![3]);
+        assert!((estimate.x[0] - 10.01).abs() < 1.0E-6);
+
+        // common mode jump: nothing is rejected
+        let y_k = DVector::from_row_slice(&[60.1, 59.9, 60.05, 60.0, 60.0]);
+
+        let (_, rejected) = gated.run(&f_k, &g_k, &w_k, &q_k, &y_k, Some(5.0)).unwrap();
+
+        assert!(rejected.is_empty());
+    }
 }
@@ -1,14 +1,17 @@
-use log::{debug, error};
+use log::{debug, error, warn};
 
 #[cfg(doc)]
 use crate::prelude::TimeScale;
 
-mod dop;
 mod kalman;
 mod postfit;
 mod ppp_ar;
+mod smoothing;
 
+pub(crate) mod accuracy;
 pub(crate) mod apriori;
+pub(crate) mod dop;
+pub(crate) mod integrity;
 pub(crate) mod solutions;
 pub(crate) mod state;
 pub(crate) mod sv;
@@ -20,27 +23,34 @@ use nalgebra::{DMatrix, DVector, DimName, U3, U4};
 
 use crate::{
     candidate::differences::Differences,
+    constants::SPEED_OF_LIGHT_M_S,
     navigation::{
+        accuracy::Accuracy,
         apriori::Apriori,
         dop::DilutionOfPrecision,
+        integrity::{IntegrityStatus, ProtectionLevels, residual_test},
         kalman::{Kalman, KfEstimate},
         postfit::PostfitKf,
         ppp_ar::Solver,
+        smoothing::RtsSmoother,
+        solutions::InterSystemBias,
         state::State,
         sv::SVContribution,
+        vector::VectorContribution,
     },
     prelude::{
         Candidate,
         Config,
+        Constellation,
         Duration,
         Epoch,
         Error,
         Frame,
         // Method,
+        SV,
         UserParameters,
-        // SV,
+        Vector3,
     },
-    rtk::RTKBase,
 };
 
 /// [Navigation] Solver
@@ -54,8 +64,8 @@ pub(crate) struct Navigation {
     /// Y allocation
     y_k_vec: Vec<f64>,
 
-    /// W allocation
-    w_k_vec: Vec<f64>,
+    /// Measurement variances (m²) allocation, W being their inverse
+    r_k_vec: Vec<f64>,
 
     /// W
     w_k: DMatrix<f64>,
@@ -84,9 +94,18 @@ pub(crate) struct Navigation {
     /// Postfit (Kalman)
     postfit: Option<PostfitKf>,
 
+    /// Velocity [Kalman] filter, driven by Doppler observations.
+    /// State is the ECEF velocity and clock drift (m.s⁻¹). Its clock drift
+    /// is only reported in RTK, where the navigation filter does not resolve the clock.
+    velocity: Kalman,
+
     /// contribution allocation
     indexes: Vec<usize>,
 
+    /// True for each phase measurement row, which follows
+    /// the code row of its [Candidate] when the float ambiguities are estimated.
+    phase_rows: Vec<bool>,
+
     /// [SVContribution]s
     pub sv: Vec<SVContribution>,
 
@@ -96,8 +115,39 @@ pub(crate) struct Navigation {
     /// [DilutionOfPrecision]
     pub dop: DilutionOfPrecision,
 
+    /// [Accuracy]
+    pub accuracy: Accuracy,
+
     /// Null on first iter
     prev_epoch: Option<Epoch>,
+
+    /// Possible [RtsSmoother], recording the forward pass
+    smoother: Option<RtsSmoother>,
+
+    /// [Constellation]s being navigated, when inter-system biases are estimated.
+    /// The first one is the time reference, each following one
+    /// owns an inter-system bias state (see [Self::isb_index]).
+    constellations: Vec<Constellation>,
+
+    /// [Constellation] whose inter-system bias is absorbed by the clock, at this epoch
+    /// (see [Self::collinear_isb_election]).
+    collinear_isb: Option<Constellation>,
+
+    /// Satellites whose float phase ambiguity is estimated, in order of their states
+    /// (see [Self::ambiguity_index]).
+    ambiguities: Vec<SV>,
+
+    /// Estimated [InterSystemBias]es
+    pub inter_system_biases: Vec<InterSystemBias>,
+
+    /// Latest [ProtectionLevels], when integrity monitoring is available.
+    pub protection_levels: Option<ProtectionLevels>,
+
+    /// Satellites excluded by integrity monitoring, at latest epoch.
+    pub excluded_sv: Vec<SV>,
+
+    /// Satellites rejected by innovation gating, at latest epoch.
+    pub rejected_sv: Vec<SV>,
 }
 
 impl Navigation {
@@ -124,16 +174,31 @@ impl Navigation {
             frame,
             postfit: None,
             prev_epoch: None,
+            smoother: if cfg.solver.rts_smoothing {
+                Some(RtsSmoother::default())
+            } else {
+                None
+            },
             cfg: cfg.clone(),
             prefit: None,
             state: Default::default(),
             sv: Vec::with_capacity(8),
             kalman: Kalman::new(U4::DIM),
+            velocity: Kalman::new(U4::DIM),
             y_k_vec: Vec::with_capacity(8),
-            w_k_vec: Vec::with_capacity(8),
+            r_k_vec: Vec::with_capacity(8),
             indexes: Vec::with_capacity(8),
+            phase_rows: Vec::with_capacity(8),
+            constellations: Vec::with_capacity(4),
+            collinear_isb: None,
+            ambiguities: Vec::with_capacity(8),
+            inter_system_biases: Vec::with_capacity(4),
+            protection_levels: None,
+            excluded_sv: Vec::new(),
+            rejected_sv: Vec::new(),
             x_k: DVector::zeros(U4::DIM),
             dop: DilutionOfPrecision::default(),
+            accuracy: Accuracy::default(),
             g_k: DMatrix::<f64>::zeros(U4::DIM, U4::DIM),
             w_k: DMatrix::<f64>::zeros(U4::DIM, U4::DIM),
             p_k: DMatrix::<f64>::zeros(U4::DIM, U4::DIM),
@@ -151,6 +216,7 @@ impl Navigation {
     pub fn reset(&mut self) {
         self.clear();
         self.kalman.reset();
+        self.velocity.reset();
 
         if let Some(prefit) = &mut self.prefit {
             prefit.reset();
@@ -160,8 +226,20 @@ impl Navigation {
             postfit.reset();
         }
 
+        if let Some(smoother) = &mut self.smoother {
+            smoother.reset();
+        }
+
         self.prev_epoch = None;
+        self.constellations.clear();
+        self.collinear_isb = None;
+        self.ambiguities.clear();
+        self.inter_system_biases.clear();
+        self.protection_levels = None;
+        self.excluded_sv.clear();
+        self.rejected_sv.clear();
         self.dop = DilutionOfPrecision::default();
+        self.accuracy = Accuracy::default();
     }
 
     /// Returns clock index
@@ -169,6 +247,247 @@ impl Navigation {
         U4::DIM - 1
     }
 
+    /// Returns clock drift index. The drift state follows the clock state,
+    /// in absolute navigation only.
+    pub(crate) fn clock_drift_index() -> usize {
+        U4::DIM
+    }
+
+    /// Returns the inter-system bias state index of this [Constellation],
+    /// if it is being estimated. The reference [Constellation] has none.
+    pub(crate) fn isb_index(&self, constellation: Constellation) -> Option<usize> {
+        self.constellations
+            .iter()
+            .skip(1)
+            .position(|c| *c == constellation)
+            .map(|k| Self::clock_drift_index() + 1 + k)
+    }
+
+    /// Returns the inter-system bias column of this [Constellation], in G:
+    /// the collinear bias (if any) is not observed at this epoch.
+    fn isb_column(&self, constellation: Constellation) -> Option<usize> {
+        if self.collinear_isb == Some(constellation) {
+            None
+        } else {
+            self.isb_index(constellation)
+        }
+    }
+
+    /// When the reference [Constellation] is not proposed, the inter-system biases
+    /// are collinear with the receiver clock: the clock then absorbs the bias
+    /// of the first proposed [Constellation], which is not observed at this epoch.
+    fn collinear_isb_election(&mut self, candidates: &[Candidate]) {
+        self.collinear_isb = None;
+
+        let proposed = |constellation: Constellation| {
+            candidates
+                .iter()
+                .any(|cd| cd.sv.constellation == constellation)
+        };
+
+        let reference = match self.constellations.first() {
+            Some(reference) => *reference,
+            None => return,
+        };
+
+        if !proposed(reference) {
+            self.collinear_isb = self
+                .constellations
+                .iter()
+                .skip(1)
+                .copied()
+                .find(|constellation| proposed(*constellation));
+
+            if let Some(constellation) = self.collinear_isb {
+                debug!("{reference} not proposed: {constellation} bias absorbed by the clock");
+            }
+        }
+    }
+
+    /// True when the float phase ambiguities are estimated: in absolute navigation
+    /// with phase observations. In RTK, the double differences are formed instead.
+    fn uses_ambiguities(&self, uses_rtk: bool) -> bool {
+        !uses_rtk && self.cfg.method.uses_phase()
+    }
+
+    /// Returns the index of the first float ambiguity state,
+    /// which follows the position and temporal states.
+    fn ambiguity_offset(&self, uses_rtk: bool) -> usize {
+        if uses_rtk {
+            U4::DIM - 1
+        } else {
+            Self::clock_drift_index() + self.constellations.len().max(1)
+        }
+    }
+
+    /// Returns the float ambiguity state index of this satellite, if it is being estimated.
+    fn ambiguity_index(&self, sv: SV) -> Option<usize> {
+        self.ambiguities
+            .iter()
+            .position(|amb| *amb == sv)
+            .map(|k| self.ambiguity_offset(false) + k)
+    }
+
+    /// Returns the filter dimension
+    fn ndf(&self, uses_rtk: bool) -> usize {
+        self.ambiguity_offset(uses_rtk) + self.ambiguities.len()
+    }
+
+    /// Returns the minimal number of contributing satellites: the clock drift is not
+    /// observed by a single epoch, it is resolved by the filter dynamics,
+    /// and each phase measurement is absorbed by its float ambiguity.
+    fn min_measurements(&self, uses_rtk: bool) -> usize {
+        if uses_rtk {
+            U4::DIM
+        } else {
+            U4::DIM.max(self.ambiguity_offset(uses_rtk) - 1)
+        }
+    }
+
+    /// Returns the columns of G that are observed by current measurements.
+    /// Unobserved states (like the clock drift) are excluded from the least squares
+    /// geometry: initialization, dilution of precision and integrity monitoring.
+    fn observed_columns(g_k: &DMatrix<f64>) -> Vec<usize> {
+        (0..g_k.ncols())
+            .filter(|j| g_k.column(*j).iter().any(|g| *g != 0.0))
+            .collect()
+    }
+
+    /// Registers the [Constellation]s proposed at this epoch, when inter-system biases
+    /// are estimated. On first iteration, the most represented [Constellation]
+    /// becomes the time reference.
+    fn constellations_registration(&mut self, candidates: &[Candidate], size: usize) {
+        let mut proposed = Vec::<(Constellation, usize)>::with_capacity(4);
+
+        for cd in candidates.iter().take(size) {
+            match proposed.iter_mut().find(|(c, _)| *c == cd.sv.constellation) {
+                Some((_, count)) => *count += 1,
+                None => proposed.push((cd.sv.constellation, 1)),
+            }
+        }
+
+        if self.constellations.is_empty() {
+            // stable sort: ties are resolved in order of appearance
+            proposed.sort_by(|(_, a), (_, b)| b.cmp(a));
+        }
+
+        for (constellation, _) in proposed {
+            if !self.constellations.contains(&constellation) {
+                debug!("new constellation: {constellation}");
+                self.constellations.push(constellation);
+            }
+        }
+    }
+
+    /// Registers the float ambiguity states of this epoch, then remaps the prediction
+    /// to the new filter layout: position, clock, clock drift, inter-system biases and ambiguities.
+    /// New states are uncorrelated, with a large uncertainty: new inter-system biases,
+    /// and the ambiguity of each new phase arc (new satellite, cycle slip or data gap),
+    /// which is leveled to the code. Returns true when the ambiguity states changed.
+    fn states_registration(
+        &mut self,
+        params: UserParameters,
+        dt: Duration,
+        candidates: &[Candidate],
+        size: usize,
+        uses_rtk: bool,
+    ) -> bool {
+        let past_ambiguities = std::mem::take(&mut self.ambiguities);
+
+        let past_offset = self
+            .kalman
+            .predicted
+            .x
+            .nrows()
+            .saturating_sub(past_ambiguities.len());
+
+        let data_gap = dt.to_seconds() > self.cfg.cycle_slip.max_gap_s;
+
+        // new phase arcs, with their initial ambiguity (m)
+        let mut new_arcs = Vec::<(SV, f64)>::with_capacity(size);
+
+        if self.uses_ambiguities(uses_rtk) {
+            for cd in candidates.iter().take(size) {
+                let ambiguity_m = match cd.ppp_float_ambiguity_m(&self.cfg) {
+                    Some(ambiguity_m) => ambiguity_m,
+                    None => continue,
+                };
+
+                self.ambiguities.push(cd.sv);
+
+                if cd.cycle_slip || data_gap || !past_ambiguities.contains(&cd.sv) {
+                    debug!(
+                        "{}({}) - new phase arc: float ambiguity reset",
+                        cd.epoch, cd.sv
+                    );
+                    new_arcs.push((cd.sv, ambiguity_m));
+                }
+            }
+        }
+
+        let layout_changed = self.ambiguities != past_ambiguities || !new_arcs.is_empty();
+
+        let (offset, ndf) = (self.ambiguity_offset(uses_rtk), self.ndf(uses_rtk));
+
+        if !self.kalman.initialized {
+            self.kalman.resize_mut(ndf);
+            return layout_changed;
+        }
+
+        // past index of each state, none for new states
+        let past_index = (0..ndf)
+            .map(|i| {
+                if i < offset {
+                    return (i < past_offset).then_some(i);
+                }
+
+                let sv = self.ambiguities[i - offset];
+
+                if new_arcs.iter().any(|(new, _)| *new == sv) {
+                    None
+                } else {
+                    past_ambiguities
+                        .iter()
+                        .position(|past| *past == sv)
+                        .map(|k| past_offset + k)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut predicted = KfEstimate::zero(ndf);
+
+        for (i, past_i) in past_index.iter().enumerate() {
+            match past_i {
+                Some(past_i) => {
+                    predicted.x[i] = self.kalman.predicted.x[*past_i];
+
+                    for (j, past_j) in past_index.iter().enumerate() {
+                        if let Some(past_j) = past_j {
+                            predicted.p[(i, j)] = self.kalman.predicted.p[(*past_i, *past_j)];
+                        }
+                    }
+                },
+                None if i < offset => {
+                    predicted.p[(i, i)] = params.q_isb(Duration::ZERO);
+                },
+                None => {
+                    let sv = self.ambiguities[i - offset];
+
+                    let (_, ambiguity_m) = new_arcs
+                        .iter()
+                        .find(|(new, _)| *new == sv)
+                        .expect("internal error: unregistered phase arc");
+
+                    predicted.x[i] = *ambiguity_m;
+                    predicted.p[(i, i)] = params.q_ambiguity(Duration::ZERO);
+                },
+            }
+        }
+
+        self.kalman.predicted = predicted;
+        layout_changed
+    }
+
     /// Mutable iteration of the [Navigation] filter.
     ///
     /// ## Input
@@ -178,8 +497,9 @@ impl Navigation {
     /// - candidates: proposed [Candidate]s
     /// - size: number of proposed [Cadndidate]s
     /// - uses_rtk: true when RTK mode nav is being used
+    /// - base_position_ecef_m: reference site position at sampling [Epoch], in meters (ECEF)
     /// - double_differences: possible double [Differences]
-    pub fn solve<RTK: RTKBase>(
+    pub fn solve(
         &mut self,
         epoch: Epoch,
         params: UserParameters,
@@ -187,7 +507,7 @@ impl Navigation {
         candidates: &[Candidate],
         size: usize,
         uses_rtk: bool,
-        rtk_base: &RTK,
+        base_position_ecef_m: (f64, f64, f64),
         pivot_position_ecef_m: &Option<(f64, f64, f64)>,
         double_differences: &Option<Differences>,
     ) -> Result<(), Error> {
@@ -195,21 +515,8 @@ impl Navigation {
 
         let initial_state = initial_state.clone();
 
-        let mut ndf = U4::DIM;
-
-        if uses_rtk {
-            ndf -= 1;
-        }
-
-        self.state.resize_mut(ndf);
-        self.kalman.resize_mut(ndf);
-
-        self.f_k.resize_mut(ndf, ndf, 0.0);
-        self.q_k.resize_mut(ndf, ndf, 0.0);
-        self.x_k.resize_vertically_mut(ndf, 0.0);
-
-        for i in 0..ndf {
-            self.f_k[(i, i)] = 1.0;
+        if !uses_rtk && self.cfg.solver.inter_system_biases {
+            self.constellations_registration(candidates, size);
         }
 
         let dt = if let Some(past_epoch) = self.prev_epoch {
@@ -218,6 +525,22 @@ impl Navigation {
             Duration::ZERO
         };
 
+        let layout_changed = self.states_registration(params, dt, candidates, size, uses_rtk);
+
+        let (offset, ndf) = (self.ambiguity_offset(uses_rtk), self.ndf(uses_rtk));
+
+        self.state.resize_mut(offset);
+        self.state.resize_ambiguities_mut(self.ambiguities.len());
+
+        self.f_k = DMatrix::identity(ndf, ndf);
+        self.q_k = DMatrix::zeros(ndf, ndf);
+        self.x_k.resize_vertically_mut(ndf, 0.0);
+
+        // clock offset follows the clock drift
+        if !uses_rtk {
+            self.f_k[(Self::clock_index(), Self::clock_drift_index())] = dt.to_seconds();
+        }
+
         // if self.cfg.method == Method::PPP {
         //     if self.prefit.is_none() {
         //         self.prefit = Some(Solver::new(self.cfg.clone(), self.frame));
@@ -226,6 +549,30 @@ impl Navigation {
 
         params.q_matrix(&mut self.q_k, dt, ndf);
 
+        if !uses_rtk {
+            params.q_clock_drift_matrix(
+                &mut self.q_k,
+                dt,
+                Self::clock_index(),
+                Self::clock_drift_index(),
+            );
+        }
+
+        for i in Self::clock_drift_index() + 1..offset {
+            self.q_k[(i, i)] = params.q_isb(dt);
+        }
+
+        for i in offset..ndf {
+            self.q_k[(i, i)] = params.q_ambiguity(dt);
+        }
+
+        // prior, recorded for the backward pass, which restarts when the ambiguities change
+        let p_predicted = if self.smoother.is_some() && self.kalman.initialized && !layout_changed {
+            Some(self.kalman.predicted.p.clone())
+        } else {
+            None
+        };
+
         if let Some(prefit) = &mut self.prefit {
             let double_diff = double_differences
                 .as_ref()
@@ -241,7 +588,7 @@ impl Navigation {
                 &initial_state,
                 candidates,
                 size,
-                rtk_base,
+                base_position_ecef_m,
                 pivot_position_ecef_m,
                 double_diff,
             ) {
@@ -265,27 +612,85 @@ impl Navigation {
         //     None
         // };
 
-        if !self.kalman.initialized {
-            self.kf_initialization(
-                epoch,
-                &initial_state,
-                candidates,
-                size,
-                uses_rtk,
-                rtk_base,
-                pivot_position_ecef_m,
-                double_differences,
-            )?;
-        } else {
-            self.kf_run(
-                epoch,
-                candidates,
-                size,
-                uses_rtk,
-                rtk_base,
-                pivot_position_ecef_m,
-                double_differences,
-            )?;
+        let mut candidates = candidates.iter().take(size).cloned().collect::<Vec<_>>();
+
+        self.excluded_sv.clear();
+
+        // fault detection and exclusion loop
+        loop {
+            self.clear();
+
+            // filter is restored on each exclusion
+            let backup = (
+                self.kalman.clone(),
+                self.state.clone(),
+                self.x_k.clone(),
+                self.p_k.clone(),
+            );
+
+            self.collinear_isb_election(&candidates);
+
+            if !self.kalman.initialized {
+                self.kf_initialization(
+                    epoch,
+                    &initial_state,
+                    &candidates,
+                    candidates.len(),
+                    uses_rtk,
+                    base_position_ecef_m,
+                    pivot_position_ecef_m,
+                    double_differences,
+                )?;
+            } else {
+                self.kf_run(
+                    epoch,
+                    &candidates,
+                    candidates.len(),
+                    uses_rtk,
+                    base_position_ecef_m,
+                    pivot_position_ecef_m,
+                    double_differences,
+                )?;
+            }
+
+            let faulty = self.integrity_monitoring(epoch);
+
+            if let Ok(None) = faulty {
+                break;
+            }
+
+            (self.kalman, self.state, self.x_k, self.p_k) = backup;
+
+            match faulty {
+                Ok(Some(index)) if self.excluded_sv.len() < self.cfg.integrity.max_exclusions => {
+                    let sv = candidates[index].sv;
+                    warn!("{epoch}({sv}) - integrity fault: excluded");
+
+                    self.excluded_sv.push(sv);
+                    candidates.remove(index);
+                },
+                Ok(_) => {
+                    error!("{epoch} - integrity fault could not be excluded");
+                    return Err(Error::IntegrityFault);
+                },
+                Err(e) => return Err(e),
+            }
+        }
+
+        let size = candidates.len();
+
+        if !uses_rtk {
+            let drift_index = Self::clock_drift_index();
+
+            self.state.clock_drift_update_mut(
+                self.x_k[drift_index] / SPEED_OF_LIGHT_M_S,
+                self.p_k[(drift_index, drift_index)].sqrt() / SPEED_OF_LIGHT_M_S,
+            );
+        }
+
+        if let Err(e) = self.velocity_run(epoch, params, dt, &candidates, size, uses_rtk) {
+            debug!("{epoch} - velocity not resolved: {e}");
+            self.state.velocity_clear_mut();
         }
 
         if self.cfg.solver.postfit_denoising > 0.0 {
@@ -315,16 +720,238 @@ impl Navigation {
             }
         }
 
+        if let Some(smoother) = &mut self.smoother {
+            let mut x = DVector::<f64>::zeros(ndf);
+            let position_ecef_m = self.state.to_position_ecef_m();
+
+            for i in 0..U3::DIM {
+                x[i] = position_ecef_m[i];
+            }
+
+            if ndf > Self::clock_index() {
+                let (clock_offset_s, _) = self.state.clock_profile_s();
+                x[Self::clock_index()] = clock_offset_s * SPEED_OF_LIGHT_M_S;
+            }
+
+            for i in U4::DIM..offset {
+                x[i] = self.state.x[i] * SPEED_OF_LIGHT_M_S;
+            }
+
+            for i in offset..ndf {
+                x[i] = self.state.x_amb[i - offset];
+            }
+
+            smoother.push(epoch, x, self.p_k.clone(), p_predicted, self.f_k.clone());
+        }
+
+        self.inter_system_biases_update();
         self.prev_epoch = Some(epoch);
 
         Ok(())
     }
 
+    /// Integrity monitoring of the latest update, when enabled.
+    /// Returns the index of the [Candidate] that should be excluded, if any.
+    fn integrity_monitoring(&mut self, epoch: Epoch) -> Result<Option<usize>, Error> {
+        self.protection_levels = None;
+
+        if !self.cfg.integrity.raim {
+            return Ok(None);
+        }
+
+        let y_k = DVector::from_row_slice(&self.y_k_vec);
+        let g_k = self.g_k.select_columns(&Self::observed_columns(&self.g_k));
+
+        let (lat_rad, lon_rad) = (
+            self.state.lat_long_alt_deg_deg_km.0.to_radians(),
+            self.state.lat_long_alt_deg_deg_km.1.to_radians(),
+        );
+
+        match residual_test(&self.cfg.integrity, &g_k, &self.w_k, &y_k, lat_rad, lon_rad) {
+            IntegrityStatus::Unavailable => {
+                debug!("{epoch} - integrity monitoring unavailable: solution is unmonitored");
+                Ok(None)
+            },
+            IntegrityStatus::Nominal(levels) => {
+                debug!(
+                    "{epoch} - hpl={:.3}m vpl={:.3}m",
+                    levels.hpl_m, levels.vpl_m
+                );
+
+                self.protection_levels = Some(levels);
+                Ok(None)
+            },
+            IntegrityStatus::Fault(Some(row)) => Ok(Some(self.indexes[row])),
+            IntegrityStatus::Fault(None) => Err(Error::IntegrityFault),
+        }
+    }
+
+    /// Updates the [InterSystemBias]es from latest estimate.
+    fn inter_system_biases_update(&mut self) {
+        self.inter_system_biases.clear();
+
+        let reference = match self.constellations.first() {
+            Some(reference) => *reference,
+            None => return,
+        };
+
+        for constellation in self.constellations.iter().skip(1) {
+            let index = self
+                .isb_index(*constellation)
+                .expect("internal error: unregistered constellation");
+
+            if index >= self.state.x.nrows() || index >= self.p_k.nrows() {
+                continue;
+            }
+
+            self.inter_system_biases.push(InterSystemBias {
+                reference,
+                constellation: *constellation,
+                bias_s: self.state.x[index],
+                sigma_s: self.p_k[(index, index)].sqrt() / SPEED_OF_LIGHT_M_S,
+            });
+        }
+    }
+
+    /// Applies the latest clock, clock drift, inter-system bias
+    /// and float ambiguity estimates (m) to pending [State].
+    fn temporal_correction(&self, pending: &mut State) {
+        let offset = self.ambiguity_offset(false);
+
+        pending.temporal_correction_mut(self.x_k[Self::clock_index()]);
+
+        for i in U4::DIM..offset {
+            pending.isb_correction_mut(i, self.x_k[i]);
+        }
+
+        for i in offset..self.x_k.nrows() {
+            pending.ambiguity_correction_mut(i - offset, self.x_k[i]);
+        }
+    }
+
+    /// Forms the temporal columns of G, for this row:
+    /// receiver clock and possible inter-system bias. The clock drift is not observed.
+    fn g_temporal_contribution(g_k: &mut DMatrix<f64>, row: usize, isb_index: Option<usize>) {
+        g_k[(row, Self::clock_index())] = 1.0;
+
+        for j in U4::DIM..g_k.ncols() {
+            g_k[(row, j)] = 0.0;
+        }
+
+        if let Some(index) = isb_index {
+            g_k[(row, index)] = 1.0;
+        }
+    }
+
+    /// Runs the backward smoothing pass over the recorded forward pass,
+    /// returning smoothed position (and possible clock) estimates, in meters, for each [Epoch].
+    pub fn rts_smoothing(&self) -> Result<Vec<(Epoch, KfEstimate)>, Error> {
+        let smoother = self.smoother.as_ref().ok_or(Error::RtsSmoothingDisabled)?;
+        smoother.smooth()
+    }
+
+    /// Measurement [VectorContribution] of this [Candidate], at this position.
+    /// Code and phase rows are formed when the float ambiguities are estimated.
+    fn vector_contribution(
+        &self,
+        t: Epoch,
+        cd: &Candidate,
+        uses_rtk: bool,
+        position_m: Vector3<f64>,
+        double_differences: &Option<Differences>,
+        contribution: &mut SVContribution,
+    ) -> Option<VectorContribution> {
+        if uses_rtk {
+            let double_differences = double_differences
+                .as_ref()
+                .expect("internal error: invalid rtk measurement/post fit");
+
+            match cd.rtk_vector_contribution(t, false, &self.cfg, double_differences, contribution)
+            {
+                Ok(vec) => Some(vec),
+                Err(e) => {
+                    error!("{}({}) - rtk measurement error: {}", t, cd.sv, e);
+                    None
+                },
+            }
+        } else {
+            let two_rows = self.uses_ambiguities(uses_rtk);
+
+            match cd.ppp_vector_contribution(&self.cfg, two_rows, position_m, contribution) {
+                Ok(vec) => Some(vec),
+                Err(e) => {
+                    error!("{}({}) - ppp measurement error: {}", t, cd.sv, e);
+                    None
+                },
+            }
+        }
+    }
+
+    /// Allocates the measurement rows of this [VectorContribution], for this [Candidate]:
+    /// the code row, then the phase row when the float ambiguities are estimated.
+    fn rows_allocation(&mut self, index: usize, vec: &VectorContribution, uses_rtk: bool) {
+        self.y_k_vec.push(vec.row_1);
+        self.r_k_vec.push(vec.sigma_1.powi(2));
+        self.indexes.push(index);
+        self.phase_rows.push(false);
+
+        if self.uses_ambiguities(uses_rtk) {
+            self.y_k_vec.push(vec.row_2);
+            self.r_k_vec.push(vec.sigma_2.powi(2));
+            self.indexes.push(index);
+            self.phase_rows.push(true);
+        }
+    }
+
+    /// Forms G at this position, for the allocated measurement rows.
+    fn matrix_allocation(
+        &mut self,
+        candidates: &[Candidate],
+        position_m: Vector3<f64>,
+        uses_rtk: bool,
+        pivot_position_ecef_m: &Option<(f64, f64, f64)>,
+    ) {
+        self.g_k
+            .resize_mut(self.indexes.len(), self.ndf(uses_rtk), 0.0);
+
+        for (i, index) in self.indexes.iter().enumerate() {
+            let cd = &candidates[*index];
+
+            let (dx, dy, dz) = if uses_rtk {
+                let pivot_position_ecef_m = pivot_position_ecef_m.unwrap_or_else(|| {
+                    panic!("internal error: undefined pivot satellite position");
+                });
+
+                cd.rtk_matrix_contribution(position_m, pivot_position_ecef_m)
+            } else {
+                cd.ppp_matrix_contribution(&self.cfg, position_m)
+            };
+
+            self.g_k[(i, 0)] = dx;
+            self.g_k[(i, 1)] = dy;
+            self.g_k[(i, 2)] = dz;
+
+            if !uses_rtk {
+                let isb_index = self.isb_column(cd.sv.constellation);
+                Self::g_temporal_contribution(&mut self.g_k, i, isb_index);
+
+                // resolved ambiguities share the float ambiguity of their pivot
+                let owner = cd.ambiguity_pivot.unwrap_or(cd.sv);
+
+                if let (true, Some(index)) = (self.phase_rows[i], self.ambiguity_index(owner)) {
+                    self.g_k[(i, index)] = 1.0;
+                }
+            }
+        }
+    }
+
     fn clear(&mut self) {
         self.sv.clear();
+        self.rejected_sv.clear();
         self.indexes.clear();
+        self.phase_rows.clear();
         self.y_k_vec.clear();
-        self.w_k_vec.clear();
+        self.r_k_vec.clear();
     }
 
     /// Filter first iteration.
@@ -336,16 +963,17 @@ impl Navigation {
     /// - candidates: proposed [Candidate]s
     /// - size: number of proposed [Cadndidate]s
     /// - uses_rtk: true when RTK mode nav is being used
+    /// - base_position_ecef_m: reference site position at sampling [Epoch], in meters (ECEF)
     /// - double_differences: possible double [Differences]
     /// - fixed_ambiguities: possible fixed ambiguities
-    pub fn kf_initialization<RTK: RTKBase>(
+    pub fn kf_initialization(
         &mut self,
         t: Epoch,
         state: &State,
         candidates: &[Candidate],
         size: usize,
         uses_rtk: bool,
-        rtk_base: &RTK,
+        base_position_ecef_m: (f64, f64, f64),
         pivot_position_ecef_m: &Option<(f64, f64, f64)>,
         double_differences: &Option<Differences>,
     ) -> Result<(), Error> {
@@ -354,85 +982,40 @@ impl Navigation {
         let mut pending = state.clone();
         let mut dop = DilutionOfPrecision::default();
 
-        let (base_x0, base_y0, base_z0) = rtk_base.reference_position_ecef_m(t);
+        if !uses_rtk {
+            pending.resize_mut(self.ambiguity_offset(uses_rtk));
+            pending.resize_ambiguities_mut(self.ambiguities.len());
+        }
+
+        let (base_x0, base_y0, base_z0) = base_position_ecef_m;
 
         // measurements
-        for i in 0..size {
+        for (i, cd) in candidates.iter().take(size).enumerate() {
             let mut contrib = SVContribution::default();
 
-            contrib.sv = candidates[i].sv;
+            contrib.sv = cd.sv;
 
             let position_m = pending.to_position_ecef_m();
 
-            let vec = if uses_rtk {
-                let double_differences = double_differences
-                    .as_ref()
-                    .expect("internal error: invalid rtk measurement/post fit");
-
-                // let fixed_amb = if self.cfg.method == Method::PPP {
-                //     let fixed_ambiguities = fixed_ambiguities
-                //         .as_ref()
-                //         .expect("internal error: missing PPP prefit");
-
-                //     fixed_ambiguities.get(&candidates[i].sv)
-                // } else {
-                //     None
-                // };
-
-                match candidates[i].rtk_vector_contribution(
-                    t,
-                    false,
-                    &self.cfg,
-                    double_differences,
-                    &mut contrib,
-                ) {
-                    Ok(vec) => Some(vec),
-                    Err(e) => {
-                        error!("{}({}) - rtk measurement error: {}", t, candidates[i].sv, e);
-                        None
-                    },
-                }
-            } else {
-                match candidates[i].ppp_vector_contribution(
-                    &self.cfg,
-                    false,
-                    position_m,
-                    &mut contrib,
-                ) {
-                    Ok(vec) => Some(vec),
-                    Err(e) => {
-                        error!("{}({}) - ppp measurement error: {}", t, candidates[i].sv, e);
-                        None
-                    },
-                }
-            };
-
-            if vec.is_none() {
-                continue;
+            if let Some(vec) = self.vector_contribution(
+                t,
+                cd,
+                uses_rtk,
+                position_m,
+                double_differences,
+                &mut contrib,
+            ) {
+                self.rows_allocation(i, &vec, uses_rtk);
+                self.sv.push(contrib);
             }
-
-            let vec = vec.unwrap();
-
-            self.y_k_vec.push(vec.row_1);
-            self.w_k_vec.push(1.0); // TODO improve model
-            self.indexes.push(i);
-            self.sv.push(contrib);
         }
 
-        let y_len = self.indexes.len();
+        let ndf = self.ndf(uses_rtk);
 
-        if y_len < U4::DIM {
+        if self.sv.len() < self.min_measurements(uses_rtk) {
             return Err(Error::MatrixMinimalDimension);
         }
 
-        let mut ndf = U4::DIM;
-
-        if uses_rtk {
-            ndf -= 1;
-        }
-
-        self.g_k.resize_mut(y_len, ndf, 0.0);
-
         // run
         for ith in 0..NB_ITER {
             let y_len = self.y_k_vec.len();
@@ -441,46 +1024,45 @@ impl Navigation {
             self.w_k.resize_mut(y_len, y_len, 0.0);
 
             for i in 0..y_len {
-                self.w_k[(i, i)] = 1.0 / self.w_k_vec[i];
+                self.w_k[(i, i)] = 1.0 / self.r_k_vec[i];
             }
 
             let y_k = DVector::from_row_slice(&self.y_k_vec); // TODO malloc
             debug!("(i={ith}) Y: {y_k}");
 
             // Form G
-            for (i, index) in self.indexes.iter().enumerate() {
-                let position_m = pending.to_position_ecef_m();
-
-                let (dx, dy, dz) = if uses_rtk {
-                    let pivot_position_ecef_m = pivot_position_ecef_m.unwrap_or_else(|| {
-                        panic!("internal error: undefined pivot satellite position");
-                    });
-
-                    candidates[*index].rtk_matrix_contribution(position_m, pivot_position_ecef_m)
-                } else {
-                    candidates[*index].ppp_matrix_contribution(&self.cfg, position_m)
-                };
+            self.matrix_allocation(
+                candidates,
+                pending.to_position_ecef_m(),
+                uses_rtk,
+                pivot_position_ecef_m,
+            );
 
-                self.g_k[(i, 0)] = dx;
-                self.g_k[(i, 1)] = dy;
-                self.g_k[(i, 2)] = dz;
+            // run, on the observed states
+            let columns = Self::observed_columns(&self.g_k);
+            let g_k = self.g_k.select_columns(&columns);
 
-                if !uses_rtk {
-                    self.g_k[(i, Self::clock_index())] = 1.0;
-                }
-            }
-
-            // run
-            let gt = self.g_k.transpose();
-            let gt_g = gt.clone() * self.g_k.clone();
+            let gt = g_k.transpose();
+            let gt_g = gt.clone() * g_k.clone();
             let gt_w = gt.clone() * self.w_k.clone();
-            let gt_w_g = gt_w * self.g_k.clone();
+            let gt_w_g = gt_w * g_k.clone();
             let gt_w_g_inv = gt_w_g.try_inverse().ok_or(Error::MatrixInversion)?;
             let gt_w_g_inv_gt = gt_w_g_inv.clone() * gt.clone();
             let gt_w_g_inv_gt_w = gt_w_g_inv_gt * self.w_k.clone();
 
-            self.x_k = gt_w_g_inv_gt_w * y_k.clone();
-            self.p_k = gt_w_g_inv.clone();
+            let x_k = gt_w_g_inv_gt_w * y_k.clone();
+
+            // unobserved states keep their initial uncertainty
+            self.x_k = DVector::zeros(ndf);
+            self.p_k = DMatrix::from_diagonal(&self.q_k.diagonal());
+
+            for (i, col_i) in columns.iter().enumerate() {
+                self.x_k[*col_i] = x_k[i];
+
+                for (j, col_j) in columns.iter().enumerate() {
+                    self.p_k[(*col_i, *col_j)] = gt_w_g_inv[(i, j)];
+                }
+            }
 
             if uses_rtk {
                 let position_ecef_m = pending.to_position_ecef_m();
@@ -508,7 +1090,7 @@ impl Navigation {
                 })?;
 
             if !uses_rtk {
-                pending.temporal_correction_mut(self.x_k[3]);
+                self.temporal_correction(&mut pending);
             }
 
             let gt_g_inv = gt_g.try_inverse().ok_or(Error::MatrixInversion)?;
@@ -519,71 +1101,30 @@ impl Navigation {
             debug!("(i={ith}) {t} - pending state {pending}");
 
             // models update
+            let mut contributors = self.indexes.clone();
+            contributors.dedup();
+
             self.y_k_vec.clear();
-            self.w_k_vec.clear();
+            self.r_k_vec.clear();
+            self.indexes.clear();
+            self.phase_rows.clear();
 
-            self.indexes.retain(|i| {
-                let position_m = pending.to_position_ecef_m();
+            let position_m = pending.to_position_ecef_m();
 
+            for index in contributors {
                 let mut unused = SVContribution::default();
 
-                let vec = if uses_rtk {
-                    let double_differences = double_differences
-                        .as_ref()
-                        .expect("internal error: invalid rtk measurement/post fit");
-
-                    // let fixed_amb = if self.cfg.method == Method::PPP {
-                    //     let fixed_ambiguities = fixed_ambiguities
-                    //         .as_ref()
-                    //         .expect("internal error: missing PPP prefit");
-
-                    //     fixed_ambiguities.get(&candidates[*i].sv)
-                    // } else {
-                    //     None
-                    // };
-
-                    match candidates[*i].rtk_vector_contribution(
-                        t,
-                        false,
-                        &self.cfg,
-                        double_differences,
-                        &mut unused,
-                    ) {
-                        Ok(vec) => Some(vec),
-                        Err(e) => {
-                            error!(
-                                "{}({}) - rtk measurement error: {}",
-                                t, candidates[*i].sv, e
-                            );
-                            None
-                        },
-                    }
-                } else {
-                    match candidates[*i].ppp_vector_contribution(
-                        &self.cfg,
-                        false,
-                        position_m,
-                        &mut unused,
-                    ) {
-                        Ok(vec) => Some(vec),
-                        Err(e) => {
-                            error!(
-                                "{}({}) - ppp measurement error: {}",
-                                t, candidates[*i].sv, e
-                            );
-                            None
-                        },
-                    }
-                };
-
-                if let Some(vec) = vec {
-                    self.y_k_vec.push(vec.row_1);
-                    self.w_k_vec.push(1.0); // TODO improve model
-                    true
-                } else {
-                    false
+                if let Some(vec) = self.vector_contribution(
+                    t,
+                    &candidates[index],
+                    uses_rtk,
+                    position_m,
+                    double_differences,
+                    &mut unused,
+                ) {
+                    self.rows_allocation(index, &vec, uses_rtk);
                 }
-            });
+            }
         }
 
         // validation
@@ -594,6 +1135,7 @@ impl Navigation {
         self.kalman
             .initialize(&self.f_k, self.q_k.clone(), initial_estimate);
 
+        self.accuracy = Accuracy::new(&pending, &self.p_k);
         self.state = pending;
         self.dop = dop;
 
@@ -612,81 +1154,43 @@ impl Navigation {
     /// - candidates: proposed [Candidate]s
     /// - size: number of proposed [Cadndidate]s
     /// - uses_rtk: true when RTK mode nav is being used
+    /// - base_position_ecef_m: reference site position at sampling [Epoch], in meters (ECEF)
     /// - double_differences: possible double [Differences]
-    pub fn kf_run<RTK: RTKBase>(
+    pub fn kf_run(
         &mut self,
         t: Epoch,
         candidates: &[Candidate],
         size: usize,
         uses_rtk: bool,
-        rtk_base: &RTK,
+        base_position_ecef_m: (f64, f64, f64),
         pivot_position_ecef_m: &Option<(f64, f64, f64)>,
         double_differences: &Option<Differences>,
     ) -> Result<(), Error> {
         let mut pending = self.state.clone();
 
-        let (base_x0, base_y0, base_z0) = rtk_base.reference_position_ecef_m(t);
+        let (base_x0, base_y0, base_z0) = base_position_ecef_m;
 
         // measurement
-        for i in 0..size {
+        for (i, cd) in candidates.iter().take(size).enumerate() {
             let mut contrib = SVContribution::default();
 
-            contrib.sv = candidates[i].sv;
+            contrib.sv = cd.sv;
 
             let pos_m = pending.to_position_ecef_m();
 
-            let vec = if uses_rtk {
-                let double_differences = double_differences
-                    .as_ref()
-                    .expect("internal error: invalid rtk measurement/post fit");
-
-                // let fixed_amb = if self.cfg.method == Method::PPP {
-                //     // let fixed_ambiguities = fixed_ambiguities
-                //     //     .as_ref()
-                //     //     .expect("internal error: missing PPP prefit");
-
-                //     // fixed_ambiguities.get(&candidates[i].sv)
-                //     None
-                // } else {
-                //     None
-                // };
-
-                match candidates[i].rtk_vector_contribution(
-                    t,
-                    false,
-                    &self.cfg,
-                    double_differences,
-                    &mut contrib,
-                ) {
-                    Ok(vec) => Some(vec),
-                    Err(e) => {
-                        error!("{}({}) - rtk measurement error: {}", t, candidates[i].sv, e);
-                        None
-                    },
-                }
-            } else {
-                match candidates[i].ppp_vector_contribution(&self.cfg, false, pos_m, &mut contrib) {
-                    Ok(vec) => Some(vec),
-                    Err(e) => {
-                        error!("{}({}) - ppp measurement error: {}", t, candidates[i].sv, e);
-                        None
-                    },
-                }
-            };
-
-            if let Some(vec) = vec {
-                self.y_k_vec.push(vec.row_1);
-                self.w_k_vec.push(1.0); // TODO improve model
-                self.indexes.push(i);
+            if let Some(vec) =
+                self.vector_contribution(t, cd, uses_rtk, pos_m, double_differences, &mut contrib)
+            {
+                self.rows_allocation(i, &vec, uses_rtk);
                 self.sv.push(contrib);
             } else {
-                error!("{}({}) - cannot contribute", t, candidates[i].sv);
+                error!("{}({}) - cannot contribute", t, cd.sv);
             }
         }
 
         let y_len = self.y_k_vec.len();
 
-        if y_len < U4::DIM {
+        if self.sv.len() < self.min_measurements(uses_rtk) {
             return Err(Error::MatrixMinimalDimension);
         }
 
@@ -695,46 +1199,60 @@ impl Navigation {
 
         self.w_k.resize_mut(y_len, y_len, 0.0);
 
-        let mut ndf = U4::DIM;
-
-        if uses_rtk {
-            ndf -= 1;
-        }
-
-        self.g_k.resize_mut(y_len, ndf, 0.0);
-
         for i in 0..y_len {
-            self.w_k[(i, i)] = 1.0 / self.w_k_vec[i];
+            self.w_k[(i, i)] = 1.0 / self.r_k_vec[i];
         }
 
         // Form G
-        for (i, index) in self.indexes.iter().enumerate() {
-            let position_m = pending.to_position_ecef_m();
-
-            let (dx, dy, dz) = if uses_rtk {
-                let pivot_position_ecef_m = pivot_position_ecef_m.unwrap_or_else(|| {
-                    panic!("internal error: undefined pivot satellite position");
-                });
+        self.matrix_allocation(
+            candidates,
+            pending.to_position_ecef_m(),
+            uses_rtk,
+            pivot_position_ecef_m,
+        );
+
+        let (estimate, rejected) = self.kalman.run(
+            &self.f_k,
+            &self.g_k,
+            &self.w_k,
+            &self.q_k,
+            &y_k,
+            self.cfg.solver.innovation_gate,
+        )?;
 
-                candidates[*index].rtk_matrix_contribution(position_m, pivot_position_ecef_m)
-            } else {
-                candidates[*index].ppp_matrix_contribution(&self.cfg, position_m)
-            };
+        debug!("state correction: dx={}", estimate.x);
 
-            self.g_k[(i, 0)] = dx;
-            self.g_k[(i, 1)] = dy;
-            self.g_k[(i, 2)] = dz;
+        // rejected measurements do not contribute (descending order)
+        for row in rejected.iter().rev() {
+            let sv = candidates[self.indexes[*row]].sv;
+            warn!("{t}({sv}) - innovation outlier: rejected");
 
-            if !uses_rtk {
-                self.g_k[(i, Self::clock_index())] = 1.0;
+            if !self.rejected_sv.contains(&sv) {
+                self.rejected_sv.push(sv);
             }
-        }
 
-        let estimate = self
-            .kalman
-            .run(&self.f_k, &self.g_k, &self.w_k, &self.q_k, &y_k)?;
+            self.indexes.remove(*row);
+            self.phase_rows.remove(*row);
+            self.y_k_vec.remove(*row);
+            self.r_k_vec.remove(*row);
+        }
 
-        debug!("state correction: dx={}", estimate.x);
+        // satellites without any measurement row
+        self.sv.retain(|contrib| {
+            self.indexes
+                .iter()
+                .any(|index| candidates[*index].sv == contrib.sv)
+        });
+
+        if !rejected.is_empty() {
+            self.g_k = self.g_k.clone().remove_rows_at(&rejected);
+
+            self.w_k = self
+                .w_k
+                .clone()
+                .remove_rows_at(&rejected)
+                .remove_columns_at(&rejected);
+        }
 
         let ndf = estimate.x.nrows();
 
@@ -742,6 +1260,8 @@ impl Navigation {
             self.x_k[i] = estimate.x[i];
         }
 
+        self.p_k = estimate.p.clone();
+
         if uses_rtk {
             let position_ecef_m = pending.to_position_ecef_m();
 
@@ -766,10 +1286,12 @@ impl Navigation {
             })?;
 
         if !uses_rtk {
-            pending.temporal_correction_mut(self.x_k[3]);
+            self.temporal_correction(&mut pending);
         }
 
-        let gt_g_inv = (self.g_k.transpose() * self.g_k.clone())
+        let g_k = self.g_k.select_columns(&Self::observed_columns(&self.g_k));
+
+        let gt_g_inv = (g_k.transpose() * g_k)
             .try_inverse()
             .ok_or(Error::MatrixInversion)?;
 
@@ -779,6 +1301,7 @@ impl Navigation {
         self.state_validation(&dop)?;
 
         self.dop = dop;
+        self.accuracy = Accuracy::new(&pending, &self.p_k);
         self.state = pending.clone();
 
         debug!("{t} - new state {pending}");
@@ -787,6 +1310,109 @@ impl Navigation {
         Ok(())
     }
 
+    /// Velocity [Kalman] filter run, using Doppler observations.
+    /// Velocity estimation is opportunistic: the position solution is
+    /// preserved whether this passes or not.
+    ///
+    /// ## Input
+    /// - t: sampling [Epoch]
+    /// - params: [UserParameters]
+    /// - dt: sampling interval, as [Duration]
+    /// - candidates: proposed [Candidate]s
+    /// - size: number of proposed [Candidate]s
+    /// - uses_rtk: true when RTK mode nav is being used
+    fn velocity_run(
+        &mut self,
+        t: Epoch,
+        params: UserParameters,
+        dt: Duration,
+        candidates: &[Candidate],
+        size: usize,
+        uses_rtk: bool,
+    ) -> Result<(), Error> {
+        let position_m = self.state.to_position_ecef_m();
+
+        let mut y_k_vec = Vec::with_capacity(size);
+        let mut g_k_vec = Vec::with_capacity(size);
+        let mut w_k_vec = Vec::with_capacity(size);
+
+        for cd in candidates.iter().take(size) {
+            match cd.doppler_vector_contribution(position_m) {
+                Ok((_, range_rate_m_s)) => {
+                    let (dx, dy, dz) = cd.ppp_matrix_contribution(&self.cfg, position_m);
+
+                    let sigma_m_s = self
+                        .cfg
+                        .stochastic
+                        .doppler_sigma_m_s(cd.elevation_deg.unwrap_or(90.0));
+
+                    y_k_vec.push(range_rate_m_s);
+                    g_k_vec.push((dx, dy, dz));
+                    w_k_vec.push(1.0 / sigma_m_s.powi(2));
+                },
+                Err(e) => {
+                    debug!("{}({}) - doppler contribution: {}", t, cd.sv, e);
+                },
+            }
+        }
+
+        let y_len = y_k_vec.len();
+
+        if y_len < U4::DIM {
+            return Err(Error::MatrixMinimalDimension);
+        }
+
+        let y_k = DVector::from_row_slice(&y_k_vec);
+        let w_k = DMatrix::from_diagonal(&DVector::from_row_slice(&w_k_vec));
+
+        let mut g_k = DMatrix::<f64>::zeros(y_len, U4::DIM);
+
+        for (i, (dx, dy, dz)) in g_k_vec.iter().enumerate() {
+            g_k[(i, 0)] = *dx;
+            g_k[(i, 1)] = *dy;
+            g_k[(i, 2)] = *dz;
+            g_k[(i, Self::clock_index())] = 1.0;
+        }
+
+        let f_k = DMatrix::<f64>::identity(U4::DIM, U4::DIM);
+        let mut q_k = DMatrix::<f64>::zeros(U4::DIM, U4::DIM);
+
+        params.q_velocity_matrix(&mut q_k, dt);
+
+        let estimate = if self.velocity.initialized {
+            self.velocity.run(&f_k, &g_k, &w_k, &q_k, &y_k, None)?.0
+        } else {
+            let gt = g_k.transpose();
+            let gt_w_g_inv = (gt.clone() * w_k.clone() * g_k.clone())
+                .try_inverse()
+                .ok_or(Error::MatrixInversion)?;
+
+            let x_k = gt_w_g_inv.clone() * gt * w_k * y_k;
+            let estimate = KfEstimate::new(&x_k, &gt_w_g_inv);
+
+            self.velocity.initialize(&f_k, q_k, estimate.clone());
+            estimate
+        };
+
+        let velocity_m_s = Vector3::new(estimate.x[0], estimate.x[1], estimate.x[2]);
+
+        let clock_drift_s_s = estimate.x[Self::clock_index()] / SPEED_OF_LIGHT_M_S;
+
+        let clock_drift_sigma_s_s =
+            estimate.p[(Self::clock_index(), Self::clock_index())].sqrt() / SPEED_OF_LIGHT_M_S;
+
+        debug!("{t} - velocity: {velocity_m_s} m/s - clock drift: {clock_drift_s_s:.11E} s/s");
+
+        self.state.velocity_update_mut(velocity_m_s);
+
+        if uses_rtk {
+            self.state
+                .clock_drift_update_mut(clock_drift_s_s, clock_drift_sigma_s_s);
+        }
+
+        Ok(())
+    }
+
     /// Validate pending [State]
     fn state_validation(&self, dop: &DilutionOfPrecision) -> Result<(), Error> {
         if dop.gdop > self.cfg.solver.max_gdop {
@@ -803,8 +1429,19 @@ impl Navigation {
 
 #[cfg(test)]
 mod test {
-    use crate::navigation::{DilutionOfPrecision, Navigation, State};
-    use nalgebra::{DMatrix, DVector};
+    use crate::{
+        navigation::{DilutionOfPrecision, Navigation, State},
+        prelude::{
+            Candidate, Carrier, Config, Constellation, Duration, Epoch, Error, Method, Observation,
+            SPEED_OF_LIGHT_M_S, UserParameters, WeightingModel,
+        },
+        tests::{
+            CandidatesBuilder, ROVER_REFERENCE_COORDS_ECEF_M, earth_frame, reference_epoch,
+            rover_reference_apriori_at_ref_epoch,
+        },
+    };
+
+    use nalgebra::{DMatrix, DVector, Vector3};
 
     #[test]
     fn navigation_dimensions_clock_index() {
@@ -819,4 +1456,557 @@ mod test {
         assert_eq!(dop.gdop, (1.0_f64 + 2.0_f64 + 3.0_f64 + 4.0_f64).sqrt());
         assert_eq!(dop.tdop, 2.0);
     }
+
+    #[test]
+    fn inter_system_bias_estimation() {
+        let mut cfg = Config::default().with_navigation_method(Method::SPP);
+        cfg.solver.inter_system_biases = true;
+        cfg.solver.postfit_denoising = 0.0;
+
+        let (clock_m, isb_m) = (300.0, 15.0);
+
+        let reference_m = Vector3::new(
+            ROVER_REFERENCE_COORDS_ECEF_M.0,
+            ROVER_REFERENCE_COORDS_ECEF_M.1,
+            ROVER_REFERENCE_COORDS_ECEF_M.2,
+        );
+
+        let geometry = [
+            (Constellation::GPS, 0.0, 80.0),
+            (Constellation::GPS, 45.0, 30.0),
+            (Constellation::GPS, 135.0, 45.0),
+            (Constellation::GPS, 225.0, 20.0),
+            (Constellation::GPS, 315.0, 55.0),
+            (Constellation::Galileo, 90.0, 35.0),
+            (Constellation::Galileo, 180.0, 60.0),
+            (Constellation::Galileo, 270.0, 25.0),
+            (Constellation::Galileo, 10.0, 15.0),
+            (Constellation::Galileo, 300.0, 70.0),
+        ];
+
+        let biases_m = geometry
+            .iter()
+            .map(|(constellation, _, _)| {
+                if *constellation == Constellation::Galileo {
+                    clock_m + isb_m
+                } else {
+                    clock_m
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let initial_state = State::from_apriori(&rover_reference_apriori_at_ref_epoch()).unwrap();
+        let mut nav = Navigation::new(&cfg, earth_frame());
+
+        // GPS only, then Galileo appears, then GPS (the reference) disappears
+        for k in 0..12 {
+            let t = reference_epoch() + k as f64 * Duration::from_seconds(30.0);
+
+            let candidates = CandidatesBuilder::build_synthetic_rover_at(t, &geometry, &biases_m)
+                .into_iter()
+                .filter(|cd| match k {
+                    0..=1 => cd.sv.constellation == Constellation::GPS,
+                    8..=9 => cd.sv.constellation == Constellation::Galileo,
+                    _ => true,
+                })
+                .collect::<Vec<_>>();
+
+            nav.solve(
+                t,
+                UserParameters::default(),
+                &initial_state,
+                &candidates,
+                candidates.len(),
+                false,
+                (0.0, 0.0, 0.0),
+                &None,
+                &None,
+            )
+            .unwrap_or_else(|e| panic!("navigation failed with {e}"));
+
+            let error_m = (nav.state.to_position_ecef_m() - reference_m).norm();
+            assert!(error_m < 1.0E-2, "epoch #{k} - position error: {error_m}m");
+
+            let (clock_offset_s, _) = nav.state.clock_profile_s();
+
+            match k {
+                0..=1 => {
+                    // single (reference) constellation: no bias
+                    assert_eq!(nav.state.x.nrows(), 5, "invalid dimension");
+                    assert_eq!(nav.isb_index(Constellation::Galileo), None);
+                    assert!(nav.inter_system_biases.is_empty());
+                },
+                8..=9 => {
+                    // collinear with the clock: the bias is predicted
+                    assert_eq!(nav.collinear_isb, Some(Constellation::Galileo));
+
+                    let error_m = clock_offset_s * SPEED_OF_LIGHT_M_S - clock_m - isb_m;
+                    assert!(
+                        error_m.abs() < 1.0E-2,
+                        "epoch #{k} - clock error: {error_m}m"
+                    );
+
+                    let isb = nav.inter_system_biases[0];
+                    assert!((isb.bias_s * SPEED_OF_LIGHT_M_S - isb_m).abs() < 1.0E-2);
+                },
+                _ => {
+                    // GPS is the reference: a single bias is estimated, after the clock drift
+                    assert_eq!(nav.state.x.nrows(), 6, "invalid dimension");
+                    assert_eq!(nav.isb_index(Constellation::GPS), None);
+                    assert_eq!(nav.isb_index(Constellation::Galileo), Some(5));
+                    assert_eq!(nav.collinear_isb, None);
+
+                    // the common offset is absorbed by the clock, not the bias
+                    let error_m = clock_offset_s * SPEED_OF_LIGHT_M_S - clock_m;
+                    assert!(
+                        error_m.abs() < 1.0E-2,
+                        "epoch #{k} - clock error: {error_m}m"
+                    );
+
+                    assert_eq!(nav.inter_system_biases.len(), 1);
+
+                    let isb = nav.inter_system_biases[0];
+
+                    assert_eq!(isb.reference, Constellation::GPS);
+                    assert_eq!(isb.constellation, Constellation::Galileo);
+                    assert!(isb.sigma_s > 0.0);
+
+                    let error_m = isb.bias_s * SPEED_OF_LIGHT_M_S - isb_m;
+                    assert!(
+                        error_m.abs() < 1.0E-2,
+                        "epoch #{k} - bias error: {error_m}m"
+                    );
+                },
+            }
+        }
+
+        // reset
+        nav.reset();
+        assert!(nav.inter_system_biases.is_empty());
+        assert_eq!(nav.isb_index(Constellation::Galileo), None);
+    }
+
+    #[test]
+    fn clock_drift_estimation() {
+        let mut cfg = Config::default().with_navigation_method(Method::SPP);
+        cfg.solver.postfit_denoising = 0.0;
+
+        let geometry = [
+            (Constellation::GPS, 0.0, 80.0),
+            (Constellation::GPS, 45.0, 30.0),
+            (Constellation::GPS, 135.0, 45.0),
+            (Constellation::GPS, 225.0, 20.0),
+            (Constellation::GPS, 315.0, 55.0),
+            (Constellation::GPS, 90.0, 35.0),
+        ];
+
+        let (clock_m, drift_m_s) = (300.0, 0.5);
+
+        let initial_state = State::from_apriori(&rover_reference_apriori_at_ref_epoch()).unwrap();
+        let mut nav = Navigation::new(&cfg, earth_frame());
+
+        for k in 0..10 {
+            let t = reference_epoch() + k as f64 * Duration::from_seconds(30.0);
+            let biases_m = vec![clock_m + drift_m_s * 30.0 * k as f64; geometry.len()];
+            let candidates = CandidatesBuilder::build_synthetic_rover_at(t, &geometry, &biases_m);
+
+            nav.solve(
+                t,
+                UserParameters::default(),
+                &initial_state,
+                &candidates,
+                candidates.len(),
+                false,
+                (0.0, 0.0, 0.0),
+                &None,
+                &None,
+            )
+            .unwrap_or_else(|e| panic!("navigation failed with {e}"));
+
+            // (position, clock, clock drift) states
+            assert_eq!(nav.state.x.nrows(), 5, "invalid dimension");
+
+            // the drift is not observed on first epoch
+            if k == 0 {
+                assert_eq!(nav.state.clock_drift_s_s, 0.0);
+                assert!(nav.state.clock_drift_sigma_s_s > 1.0E-6);
+            }
+        }
+
+        let (clock_offset_s, clock_drift_s_s) = nav.state.clock_profile_s();
+
+        let expected_clock_m = clock_m + drift_m_s * 30.0 * 9.0;
+        assert!((clock_offset_s * SPEED_OF_LIGHT_M_S - expected_clock_m).abs() < 1.0E-2);
+
+        let error_m_s = clock_drift_s_s * SPEED_OF_LIGHT_M_S - drift_m_s;
+        assert!(error_m_s.abs() < 1.0E-3, "drift error: {error_m_s}m/s");
+        assert!(nav.state.clock_drift_sigma_s_s * SPEED_OF_LIGHT_M_S < 1.0);
+    }
+
+    #[test]
+    fn rts_smoothing_reset() {
+        let mut cfg = Config::default().with_navigation_method(Method::SPP);
+        cfg.solver.postfit_denoising = 0.0;
+        cfg.solver.rts_smoothing = true;
+
+        let geometry = [
+            (Constellation::GPS, 0.0, 80.0),
+            (Constellation::GPS, 45.0, 30.0),
+            (Constellation::GPS, 135.0, 45.0),
+            (Constellation::GPS, 225.0, 20.0),
+            (Constellation::GPS, 315.0, 55.0),
+        ];
+
+        let biases_m = vec![300.0; geometry.len()];
+
+        let initial_state = State::from_apriori(&rover_reference_apriori_at_ref_epoch()).unwrap();
+        let mut nav = Navigation::new(&cfg, earth_frame());
+
+        let epochs = (0..5)
+            .map(|k| reference_epoch() + k as f64 * Duration::from_seconds(30.0))
+            .collect::<Vec<_>>();
+
+        for (k, t) in epochs.iter().enumerate() {
+            // reset after the third epoch
+            if k == 3 {
+                nav.reset();
+                assert!(nav.rts_smoothing().unwrap().is_empty());
+            }
+
+            let candidates = CandidatesBuilder::build_synthetic_rover_at(*t, &geometry, &biases_m);
+
+            nav.solve(
+                *t,
+                UserParameters::default(),
+                &initial_state,
+                &candidates,
+                candidates.len(),
+                false,
+                (0.0, 0.0, 0.0),
+                &None,
+                &None,
+            )
+            .unwrap_or_else(|e| panic!("navigation failed with {e}"));
+        }
+
+        // forward pass only covers the epochs that followed the reset
+        let smoothed = nav.rts_smoothing().unwrap();
+
+        assert_eq!(
+            smoothed.iter().map(|(t, _)| *t).collect::<Vec<_>>(),
+            epochs[3..].to_vec(),
+        );
+    }
+
+    #[test]
+    fn fault_detection_and_exclusion() {
+        let mut cfg = Config::default().with_navigation_method(Method::SPP);
+        cfg.solver.postfit_denoising = 0.0;
+        cfg.integrity.raim = true;
+        cfg.stochastic.weighting = WeightingModel::Equal;
+
+        let geometry = [
+            (Constellation::GPS, 0.0, 80.0),
+            (Constellation::GPS, 45.0, 30.0),
+            (Constellation::GPS, 135.0, 45.0),
+            (Constellation::GPS, 225.0, 20.0),
+            (Constellation::GPS, 315.0, 55.0),
+            (Constellation::GPS, 90.0, 35.0),
+            (Constellation::GPS, 180.0, 60.0),
+            (Constellation::GPS, 270.0, 25.0),
+        ];
+
+        let clock_m = 300.0;
+
+        let initial_state = State::from_apriori(&rover_reference_apriori_at_ref_epoch()).unwrap();
+        let mut nav = Navigation::new(&cfg, earth_frame());
+
+        // nominal, then a faulty measurement, then a fault that cannot be excluded
+        let mut faulty_m = vec![clock_m; geometry.len()];
+        faulty_m[2] += 200.0;
+
+        let mut unresolved_m = vec![clock_m; geometry.len()];
+        unresolved_m[1] += 200.0;
+        unresolved_m[4] -= 300.0;
+
+        for (k, biases_m) in [vec![clock_m; geometry.len()], faulty_m, unresolved_m]
+            .iter()
+            .enumerate()
+        {
+            let t = reference_epoch() + k as f64 * Duration::from_seconds(30.0);
+            let candidates = CandidatesBuilder::build_synthetic_rover_at(t, &geometry, biases_m);
+
+            let solved = nav.solve(
+                t,
+                UserParameters::default(),
+                &initial_state,
+                &candidates,
+                candidates.len(),
+                false,
+                (0.0, 0.0, 0.0),
+                &None,
+                &None,
+            );
+
+            match k {
+                0 => {
+                    solved.unwrap_or_else(|e| panic!("navigation failed with {e}"));
+
+                    assert!(nav.excluded_sv.is_empty());
+
+                    let levels = nav
+                        .protection_levels
+                        .unwrap_or_else(|| panic!("missing protection levels"));
+
+                    assert!(levels.hpl_m > 0.0);
+                    assert!(levels.vpl_m > levels.hpl_m);
+                },
+                1 => {
+                    solved.unwrap_or_else(|e| panic!("navigation failed with {e}"));
+
+                    assert_eq!(nav.excluded_sv, vec![candidates[2].sv]);
+                    assert!(nav.sv.iter().all(|sv| sv.sv != candidates[2].sv));
+                    assert_eq!(nav.sv.len(), geometry.len() - 1);
+                    assert!(nav.protection_levels.is_some());
+
+                    // fault did not affect the solution
+                    let error_m = (nav.state.to_position_ecef_m()
+                        - Vector3::new(
+                            ROVER_REFERENCE_COORDS_ECEF_M.0,
+                            ROVER_REFERENCE_COORDS_ECEF_M.1,
+                            ROVER_REFERENCE_COORDS_ECEF_M.2,
+                        ))
+                    .norm();
+
+                    assert!(error_m < 1.0E-3, "position error: {error_m}m");
+                },
+                _ => {
+                    assert!(matches!(solved, Err(Error::IntegrityFault)));
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn innovation_outlier_rejection() {
+        let mut cfg = Config::default().with_navigation_method(Method::SPP);
+        cfg.solver.postfit_denoising = 0.0;
+        cfg.solver.innovation_gate = Some(5.0);
+        cfg.integrity.raim = true;
+        cfg.stochastic.weighting = WeightingModel::Equal;
+
+        let geometry = [
+            (Constellation::GPS, 0.0, 80.0),
+            (Constellation::GPS, 45.0, 30.0),
+            (Constellation::GPS, 135.0, 45.0),
+            (Constellation::GPS, 225.0, 20.0),
+            (Constellation::GPS, 315.0, 55.0),
+            (Constellation::GPS, 90.0, 35.0),
+            (Constellation::GPS, 180.0, 60.0),
+            (Constellation::GPS, 250.0, 40.0),
+        ];
+
+        let clock_m = 300.0;
+
+        let initial_state = State::from_apriori(&rover_reference_apriori_at_ref_epoch()).unwrap();
+        let mut nav = Navigation::new(&cfg, earth_frame());
+
+        // gating requires a prediction: multipath on third epoch,
+        // on a measurement that is not the last row
+        let mut candidates = Vec::new();
+
+        for k in 0..3 {
+            let t = reference_epoch() + k as f64 * Duration::from_seconds(30.0);
+
+            let mut biases_m = vec![clock_m; geometry.len()];
+
+            if k == 2 {
+                biases_m[3] += 50.0;
+            }
+
+            candidates = CandidatesBuilder::build_synthetic_rover_at(t, &geometry, &biases_m);
+
+            nav.solve(
+                t,
+                UserParameters::default(),
+                &initial_state,
+                &candidates,
+                candidates.len(),
+                false,
+                (0.0, 0.0, 0.0),
+                &None,
+                &None,
+            )
+            .unwrap_or_else(|e| panic!("navigation failed with {e}"));
+
+            if k < 2 {
+                assert!(nav.rejected_sv.is_empty());
+            }
+        }
+
+        assert_eq!(nav.rejected_sv, vec![candidates[3].sv]);
+        assert!(nav.excluded_sv.is_empty());
+
+        // remaining measurements are consistent
+        assert_eq!(nav.sv.len(), geometry.len() - 1);
+        assert!(nav.sv.iter().all(|sv| sv.sv != candidates[3].sv));
+
+        assert_eq!(nav.g_k.nrows(), geometry.len() - 1);
+        assert_eq!(nav.w_k.shape(), (nav.g_k.nrows(), nav.g_k.nrows()));
+        assert_eq!(nav.y_k_vec.len(), nav.g_k.nrows());
+
+        // integrity monitoring ran on the gated measurements
+        assert!(nav.protection_levels.is_some());
+
+        let error_m = (nav.state.to_position_ecef_m()
+            - Vector3::new(
+                ROVER_REFERENCE_COORDS_ECEF_M.0,
+                ROVER_REFERENCE_COORDS_ECEF_M.1,
+                ROVER_REFERENCE_COORDS_ECEF_M.2,
+            ))
+        .norm();
+
+        assert!(error_m < 1.0E-2, "position error: {error_m}m");
+
+        // reset
+        nav.reset();
+        assert!(nav.rejected_sv.is_empty());
+        assert!(nav.protection_levels.is_none());
+    }
+
+    /// Dual frequency code and phase [Candidate]s, with a code noise (m)
+    /// and unknown (L1, L2) phase ambiguities (cycles).
+    fn dual_frequency_candidates(
+        t: Epoch,
+        geometry: &[(Constellation, f64, f64)],
+        clock_m: f64,
+        noise_m: &[f64],
+        ambiguities: &[(f64, f64)],
+    ) -> Vec<Candidate> {
+        let biases_m = vec![0.0; geometry.len()];
+
+        CandidatesBuilder::build_synthetic_rover_at(t, geometry, &biases_m)
+            .into_iter()
+            .zip(noise_m.iter().zip(ambiguities.iter()))
+            .map(|(mut cd, (noise_m, (n_1, n_2)))| {
+                let range_m = cd.observations[0].pseudo_range_m.unwrap() + clock_m;
+
+                cd.observations = [(Carrier::L1, n_1), (Carrier::L2, n_2)]
+                    .iter()
+                    .map(|(carrier, n)| {
+                        Observation::pseudo_range(*carrier, range_m + noise_m, None)
+                            .with_ambiguous_phase_range_m(range_m - *n * carrier.wavelength())
+                    })
+                    .collect();
+
+                cd
+            })
+            .collect()
+    }
+
+    #[test]
+    fn float_ambiguity_estimation() {
+        let mut cfg = Config::default().with_navigation_method(Method::PPP);
+        cfg.solver.postfit_denoising = 0.0;
+        cfg.stochastic.weighting = WeightingModel::Equal;
+
+        let clock_m = 300.0;
+
+        let reference_m = Vector3::new(
+            ROVER_REFERENCE_COORDS_ECEF_M.0,
+            ROVER_REFERENCE_COORDS_ECEF_M.1,
+            ROVER_REFERENCE_COORDS_ECEF_M.2,
+        );
+
+        let geometry = [
+            (Constellation::GPS, 0.0, 80.0),
+            (Constellation::GPS, 45.0, 30.0),
+            (Constellation::GPS, 135.0, 45.0),
+            (Constellation::GPS, 225.0, 20.0),
+            (Constellation::GPS, 315.0, 55.0),
+            (Constellation::GPS, 90.0, 35.0),
+        ];
+
+        let mut ambiguities = vec![
+            (12.0, -7.0),
+            (-3.0, 25.0),
+            (40.0, 31.0),
+            (-18.0, -2.0),
+            (5.0, 9.0),
+            (-27.0, 14.0),
+        ];
+
+        let initial_state = State::from_apriori(&rover_reference_apriori_at_ref_epoch()).unwrap();
+        let mut nav = Navigation::new(&cfg, earth_frame());
+
+        // IF float ambiguity (m), as defined by the noise free observations
+        let float_ambiguity_m = |t: Epoch, ambiguities: &[(f64, f64)], i: usize| {
+            let noise_m = vec![0.0; geometry.len()];
+            let cd = &dual_frequency_candidates(t, &geometry, clock_m, &noise_m, ambiguities)[i];
+            cd.phase_if_combination().unwrap().value - cd.code_if_combination().unwrap().value
+        };
+
+        for k in 0..40 {
+            let t = reference_epoch() + k as f64 * Duration::from_seconds(30.0);
+
+            // zero mean code noise
+            let noise_m = (0..geometry.len())
+                .map(|i| if (i + k) % 2 == 0 { 0.5 } else { -0.5 })
+                .collect::<Vec<_>>();
+
+            // cycle slip on the first satellite
+            if k == 20 {
+                ambiguities[0].0 += 100.0;
+            }
+
+            let mut candidates =
+                dual_frequency_candidates(t, &geometry, clock_m, &noise_m, &ambiguities);
+
+            candidates[0].cycle_slip = k == 20;
+
+            nav.solve(
+                t,
+                UserParameters::default(),
+                &initial_state,
+                &candidates,
+                candidates.len(),
+                false,
+                (0.0, 0.0, 0.0),
+                &None,
+                &None,
+            )
+            .unwrap_or_else(|e| panic!("navigation failed with {e}"));
+
+            // one float ambiguity per satellite, after the temporal states
+            assert_eq!(nav.ambiguities.len(), geometry.len());
+            assert_eq!(nav.ambiguity_index(candidates[0].sv), Some(5));
+            assert_eq!(nav.state.x.nrows(), 5, "invalid dimension");
+            assert_eq!(nav.state.x_amb.nrows(), geometry.len());
+            assert_eq!(nav.sv.len(), geometry.len());
+            assert_eq!(nav.sv[0].cycle_slip, k == 20);
+
+            let error_m = (nav.state.to_position_ecef_m() - reference_m).norm();
+
+            // code level, then phase level: the slip does not corrupt the solution
+            let (max_position_m, max_ambiguity_m) = if k < 10 { (5.0, 1.0) } else { (0.5, 0.1) };
+
+            assert!(
+                error_m < max_position_m,
+                "epoch #{k} - position error: {error_m}m"
+            );
+
+            for i in 0..geometry.len() {
+                let error_m = nav.state.x_amb[i] - float_ambiguity_m(t, &ambiguities, i);
+
+                assert!(
+                    error_m.abs() < max_ambiguity_m,
+                    "epoch #{k} - sv#{i} ambiguity error: {error_m}m"
+                );
+            }
+        }
+
+        // reset
+        nav.reset();
+        assert!(nav.ambiguities.is_empty());
+    }
 }
@@ -14,8 +14,7 @@ use crate::{
         state::State,
         sv::SVContribution,
     },
-    prelude::{Candidate, Config, Duration, Epoch, Error, Frame, Method, SV},
-    rtk::RTKBase,
+    prelude::{Candidate, Config, Duration, Epoch, Error, Frame, SV},
     user::UserParameters,
 };
 
@@ -35,8 +34,8 @@ pub struct Solver {
     /// Y allocation
     y_k_vec: Vec<f64>,
 
-    /// W allocation
-    w_k_vec: Vec<f64>,
+    /// Measurement variances (m²) allocation, W being their inverse
+    r_k_vec: Vec<f64>,
 
     /// W
     w_k: DMatrix<f64>,
@@ -110,7 +109,7 @@ impl Solver {
             sv: Vec::with_capacity(8),
             kalman: Kalman::new(U8::DIM),
             y_k_vec: Vec::with_capacity(8),
-            w_k_vec: Vec::with_capacity(8),
+            r_k_vec: Vec::with_capacity(8),
             indexes: Vec::with_capacity(8),
             fixed_amb: Default::default(),
             lambda_x: DMatrix::zeros(1, U4::DIM),
@@ -137,17 +136,17 @@ impl Solver {
     /// - params: [UserParameters]
     /// - candidates: proposed [Candidate]s
     /// - size: number of proposed [Cadndidate]s
-    /// - rtk_base: custom [RTK] implementation
+    /// - base_position_ecef_m: reference site position at sampling [Epoch], in meters (ECEF)
     /// - pivot_position_ecef_m: pivot position in meters (ECEF)
     /// - double_differences: double [Differences]
-    pub fn run<RTK: RTKBase>(
+    pub fn run(
         &mut self,
         epoch: Epoch,
         params: UserParameters,
         initial_state: &State,
         candidates: &[Candidate],
         size: usize,
-        rtk_base: &RTK,
+        base_position_ecef_m: (f64, f64, f64),
         pivot_position_ecef_m: (f64, f64, f64),
         double_differences: &Differences,
     ) -> Result<(), Error> {
@@ -172,7 +171,7 @@ impl Solver {
                 candidates,
                 params,
                 size,
-                rtk_base,
+                base_position_ecef_m,
                 pivot_position_ecef_m,
                 double_differences,
             )?;
@@ -182,7 +181,7 @@ impl Solver {
                 candidates,
                 params,
                 size,
-                rtk_base,
+                base_position_ecef_m,
                 pivot_position_ecef_m,
                 double_differences,
             )?;
@@ -198,7 +197,7 @@ impl Solver {
         self.sv.clear();
         self.indexes.clear();
         self.y_k_vec.clear();
-        self.w_k_vec.clear();
+        self.r_k_vec.clear();
     }
 
     /// Filter first iteration.
@@ -209,17 +208,17 @@ impl Solver {
     /// - candidates: proposed [Candidate]s
     /// - params: [UserParameters]
     /// - size: number of proposed [Cadndidate]s
-    /// - rtk_base: custom [RTK] implementation
+    /// - base_position_ecef_m: reference site position at sampling [Epoch], in meters (ECEF)
     /// - pivot_position_ecef_m: pivot position in meters (ECEF)
     /// - double_differences: double [Differences]
-    pub fn kf_initialization<RTK: RTKBase>(
+    pub fn kf_initialization(
         &mut self,
         epoch: Epoch,
         state: &State,
         candidates: &[Candidate],
         params: UserParameters,
         size: usize,
-        rtk_base: &RTK,
+        base_position_ecef_m: (f64, f64, f64),
         pivot_position_ecef_m: (f64, f64, f64),
         double_differences: &Differences,
     ) -> Result<(), Error> {
@@ -228,7 +227,7 @@ impl Solver {
         let mut pending = state.clone();
         let mut dop = DilutionOfPrecision::default();
 
-        let (base_x0, base_y0, base_z0) = rtk_base.reference_position_ecef_m(epoch);
+        let (base_x0, base_y0, base_z0) = base_position_ecef_m;
 
         // measurements
         for i in 0..size {
@@ -246,8 +245,8 @@ impl Solver {
                 Ok(vec) => {
                     self.y_k_vec.push(vec.row_1);
                     self.y_k_vec.push(vec.row_2);
-                    self.w_k_vec.push(1.0); // TODO: improve model
-                    self.w_k_vec.push(1.0); // TODO: improve model
+                    self.r_k_vec.push(vec.sigma_1.powi(2));
+                    self.r_k_vec.push(vec.sigma_2.powi(2));
                     self.indexes.push(i);
                     self.sv.push(contrib);
                 },
@@ -274,7 +273,7 @@ impl Solver {
             self.w_k.resize_mut(y_len, y_len, 0.0);
 
             for i in 0..y_len {
-                self.w_k[(i, i)] = 1.0 / self.w_k_vec[i];
+                self.w_k[(i, i)] = 1.0 / self.r_k_vec[i];
             }
 
             let y_k = DVector::from_row_slice(&self.y_k_vec); // TODO malloc
@@ -361,7 +360,7 @@ impl Solver {
 
             // models update
             self.y_k_vec.clear();
-            self.w_k_vec.clear();
+            self.r_k_vec.clear();
 
             self.indexes.retain(|i| {
                 let mut unused = SVContribution::default();
@@ -376,8 +375,8 @@ impl Solver {
                     Ok(vec) => {
                         self.y_k_vec.push(vec.row_1);
                         self.y_k_vec.push(vec.row_2);
-                        self.w_k_vec.push(1.0); // TODO improve model
-                        self.w_k_vec.push(1.0); // TODO improve model
+                        self.r_k_vec.push(vec.sigma_1.powi(2));
+                        self.r_k_vec.push(vec.sigma_2.powi(2));
                         true
                     },
                     Err(e) => {
@@ -473,22 +472,22 @@ impl Solver {
     /// - candidates: proposed [Candidate]s
     /// - params: [UserParameters]
     /// - size: number of proposed [Cadndidate]s
-    /// - rtk_base: custom [RTK] implementation
+    /// - base_position_ecef_m: reference site position at sampling [Epoch], in meters (ECEF)
     /// - pivot_position_ecef_m: pivot position in meters (ECEF)
     /// - double_differences: double [Differences]
-    pub fn kf_run<RTK: RTKBase>(
+    pub fn kf_run(
         &mut self,
         epoch: Epoch,
         candidates: &[Candidate],
         params: UserParameters,
         size: usize,
-        rtk_base: &RTK,
+        base_position_ecef_m: (f64, f64, f64),
         pivot_position_ecef_m: (f64, f64, f64),
         double_differences: &Differences,
     ) -> Result<(), Error> {
         let mut pending = self.state.clone();
 
-        let (base_x0, base_y0, base_z0) = rtk_base.reference_position_ecef_m(epoch);
+        let (base_x0, base_y0, base_z0) = base_position_ecef_m;
 
         // measurements
         for i in 0..size {
@@ -506,7 +505,8 @@ impl Solver {
                 Ok(vec) => {
                     self.y_k_vec.push(vec.row_1);
                     self.y_k_vec.push(vec.row_2);
-                    self.w_k_vec.push(1.0); // TODO
+                    self.r_k_vec.push(vec.sigma_1.powi(2));
+                    self.r_k_vec.push(vec.sigma_2.powi(2));
                     self.sv.push(contrib);
                     self.indexes.push(i);
                 },
@@ -539,7 +539,7 @@ impl Solver {
         self.w_k.resize_mut(y_len, y_len, 0.0);
 
         for i in 0..y_len {
-            self.w_k[(i, i)] = 1.0; // TODO: improve model
+            self.w_k[(i, i)] = 1.0 / self.r_k_vec[i];
         }
 
         // form G
@@ -583,9 +583,9 @@ impl Solver {
 
         debug!("ndf(ppp)={} F(ppp)={} Q(ppp)={}", ndf, self.f_k, q_mat);
 
-        let estimate = self
+        let (estimate, _) = self
             .kalman
-            .run(&self.f_k, &self.g_k, &self.w_k, &q_mat, &y)?;
+            .run(&self.f_k, &self.g_k, &self.w_k, &q_mat, &y, None)?;
 
         let ndf = estimate.x.nrows();
 
@@ -636,7 +636,7 @@ impl Solver {
         debug!("{} - new PPP state {}", epoch, self.state);
         debug!("{} - gdop={} tdop={}", epoch, self.dop.gdop, self.dop.tdop);
 
-        if self.cfg.method == Method::PPP {
+        if self.cfg.method.uses_phase() {
             self.lambda_q.resize_mut(lambda_ndf, lambda_ndf, 0.0);
             self.lambda_x.resize_mut(lambda_ndf, 1, 0.0);
 
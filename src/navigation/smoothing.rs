@@ -0,0 +1,283 @@
+//! Forward-backward (Rauch-Tung-Striebel) smoothing
+use nalgebra::{DMatrix, DVector};
+
+use crate::{
+    navigation::kalman::KfEstimate,
+    prelude::{Epoch, Error},
+};
+
+/// Forward pass record, at one [Epoch].
+#[derive(Clone)]
+struct RtsEntry {
+    /// Sampling [Epoch]
+    epoch: Epoch,
+
+    /// Filtered state (position then possible clock, in meters)
+    x: DVector<f64>,
+
+    /// Filtered covariance
+    p: DMatrix<f64>,
+
+    /// Predicted covariance, that served as prior at this [Epoch].
+    /// Null on first iteration (or after a reset).
+    p_predicted: Option<DMatrix<f64>>,
+
+    /// Dynamics, that predicted the next [Epoch] from this one
+    f: DMatrix<f64>,
+}
+
+/// [RtsSmoother] stores the forward [Navigation](crate::navigation::Navigation) pass,
+/// then runs the backward Rauch-Tung-Striebel pass. The dynamics are mostly the identity,
+/// except for the clock offset, which follows the clock drift.
+#[derive(Clone, Default)]
+pub(crate) struct RtsSmoother {
+    /// Forward pass, in chronological order
+    entries: Vec<RtsEntry>,
+}
+
+impl RtsSmoother {
+    /// Stores new forward iteration.
+    ///
+    /// ## Input
+    /// - epoch: sampling [Epoch]
+    /// - x: filtered state (meters)
+    /// - p: filtered covariance
+    /// - p_predicted: predicted covariance, that served as prior at this [Epoch].
+    /// - f: dynamics, that predicted the next [Epoch] from this one.
+    pub fn push(
+        &mut self,
+        epoch: Epoch,
+        x: DVector<f64>,
+        p: DMatrix<f64>,
+        p_predicted: Option<DMatrix<f64>>,
+        f: DMatrix<f64>,
+    ) {
+        self.entries.push(RtsEntry {
+            epoch,
+            x,
+            p,
+            p_predicted,
+            f,
+        });
+    }
+
+    /// Clears the forward pass.
+    pub fn reset(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Runs the backward pass, returning the smoothed [KfEstimate] for each
+    /// forward iteration, in chronological order. The recursion restarts on each
+    /// filter (re)initialization or dimension change, which are not smoothed through.
+    pub fn smooth(&self) -> Result<Vec<(Epoch, KfEstimate)>, Error> {
+        let nb_entries = self.entries.len();
+        let mut smoothed = Vec::<KfEstimate>::with_capacity(nb_entries);
+
+        for (k, entry) in self.entries.iter().enumerate().rev() {
+            let next = if k + 1 < nb_entries {
+                let next = &self.entries[k + 1];
+
+                match &next.p_predicted {
+                    Some(p_predicted)
+                        if p_predicted.nrows() == entry.p.nrows()
+                            && entry.f.nrows() == entry.p.nrows() =>
+                    {
+                        smoothed
+                            .last()
+                            .map(|next_smoothed| (next_smoothed, p_predicted))
+                    },
+                    _ => None,
+                }
+            } else {
+                None
+            };
+
+            let estimate = match next {
+                Some((next_smoothed, p_predicted)) => {
+                    let p_predicted_inv = p_predicted
+                        .clone()
+                        .try_inverse()
+                        .ok_or(Error::MatrixInversion)?;
+
+                    let c = entry.p.clone() * entry.f.transpose() * p_predicted_inv;
+
+                    let x = entry.x.clone()
+                        + c.clone() * (next_smoothed.x.clone() - entry.f.clone() * entry.x.clone());
+
+                    let p = entry.p.clone()
+                        + c.clone() * (next_smoothed.p.clone() - p_predicted) * c.transpose();
+
+                    KfEstimate { x, p }
+                },
+                None => KfEstimate::new(&entry.x, &entry.p),
+            };
+
+            smoothed.push(estimate);
+        }
+
+        smoothed.reverse();
+
+        Ok(self
+            .entries
+            .iter()
+            .map(|entry| entry.epoch)
+            .zip(smoothed)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RtsSmoother;
+    use crate::prelude::{Duration, Epoch};
+
+    use nalgebra::{DMatrix, DVector};
+
+    use std::str::FromStr;
+
+    #[test]
+    fn rts_static_smoothing() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let dt = Duration::from_seconds(30.0);
+
+        // scalar static state, observed with unit variance
+        let (q, r) = (0.0, 1.0);
+        let measurements = [1.0, 3.0, 2.0, 2.0];
+
+        let mut smoother = RtsSmoother::default();
+
+        let (mut x, mut p) = (0.0, 1.0E6);
+        let mut p_predicted = None;
+
+        for (k, y) in measurements.iter().enumerate() {
+            let prior = p;
+            let gain = prior / (prior + r);
+
+            x += gain * (y - x);
+            p = (1.0 - gain) * prior;
+
+            smoother.push(
+                t0 + k as f64 * dt,
+                DVector::from_row_slice(&[x]),
+                DMatrix::from_row_slice(1, 1, &[p]),
+                p_predicted,
+                DMatrix::identity(1, 1),
+            );
+
+            p += q;
+            p_predicted = Some(DMatrix::from_row_slice(1, 1, &[p]));
+        }
+
+        let smoothed = smoother.smooth().unwrap();
+
+        assert_eq!(smoothed.len(), measurements.len());
+
+        // static state: every smoothed estimate is the final (batch) estimate
+        let (_, last) = smoothed.last().unwrap();
+
+        for (k, (t, estimate)) in smoothed.iter().enumerate() {
+            assert_eq!(*t, t0 + k as f64 * dt);
+            assert!((estimate.x[0] - 2.0).abs() < 1.0E-3);
+            assert!((estimate.p[(0, 0)] - last.p[(0, 0)]).abs() < 1.0E-3);
+        }
+
+        // first solution improved significantly
+        assert!(smoothed[0].1.p[(0, 0)] < 0.3);
+    }
+
+    #[test]
+    fn rts_segments() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let dt = Duration::from_seconds(30.0);
+
+        let mut smoother = RtsSmoother::default();
+
+        let p = DMatrix::from_row_slice(1, 1, &[1.0]);
+        let f = DMatrix::identity(1, 1);
+
+        smoother.push(
+            t0,
+            DVector::from_row_slice(&[1.0]),
+            p.clone(),
+            None,
+            f.clone(),
+        );
+
+        // filter reset
+        smoother.push(
+            t0 + dt,
+            DVector::from_row_slice(&[5.0]),
+            p.clone(),
+            None,
+            f.clone(),
+        );
+
+        let smoothed = smoother.smooth().unwrap();
+
+        assert_eq!(smoothed[0].1.x[0], 1.0);
+        assert_eq!(smoothed[1].1.x[0], 5.0);
+
+        // smoother reset
+        smoother.reset();
+        assert!(smoother.smooth().unwrap().is_empty());
+
+        smoother.push(t0 + 2.0 * dt, DVector::from_row_slice(&[3.0]), p, None, f);
+
+        let smoothed = smoother.smooth().unwrap();
+
+        assert_eq!(smoothed.len(), 1);
+        assert_eq!(smoothed[0].0, t0 + 2.0 * dt);
+        assert_eq!(smoothed[0].1.x[0], 3.0);
+    }
+
+    #[test]
+    fn rts_drifting_clock() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let dt = Duration::from_seconds(30.0);
+        let dt_s = dt.to_seconds();
+
+        // (offset, drift) clock, only the offset is observed
+        let (offset_m, drift_m_s) = (10.0, 2.0);
+
+        let f = DMatrix::from_row_slice(2, 2, &[1.0, dt_s, 0.0, 1.0]);
+        let h = DMatrix::from_row_slice(1, 2, &[1.0, 0.0]);
+        let r = 1.0E-4;
+
+        let mut smoother = RtsSmoother::default();
+
+        let mut x = DVector::<f64>::zeros(2);
+        let mut p = DMatrix::<f64>::identity(2, 2) * 1.0E6;
+        let mut p_predicted = None;
+
+        for k in 0..4 {
+            let y = offset_m + drift_m_s * k as f64 * dt_s;
+
+            let s = (h.clone() * p.clone() * h.transpose())[(0, 0)] + r;
+            let gain = p.clone() * h.transpose() / s;
+
+            x += gain.clone() * (y - (h.clone() * x.clone())[0]);
+            p = (DMatrix::identity(2, 2) - gain * h.clone()) * p;
+
+            smoother.push(
+                t0 + k as f64 * dt,
+                x.clone(),
+                p.clone(),
+                p_predicted,
+                f.clone(),
+            );
+
+            x = f.clone() * x;
+            p = f.clone() * p * f.transpose();
+            p_predicted = Some(p.clone());
+        }
+
+        let smoothed = smoother.smooth().unwrap();
+
+        // the drift is not observed on first epoch: smoothing resolves it
+        let (_, first) = &smoothed[0];
+
+        assert!((first.x[0] - offset_m).abs() < 1.0E-3, "{}", first.x);
+        assert!((first.x[1] - drift_m_s).abs() < 1.0E-3, "{}", first.x);
+        assert!(first.p[(1, 1)] < 1.0E-3);
+    }
+}
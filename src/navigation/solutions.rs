@@ -1,7 +1,12 @@
 //! PVT Solution
 use crate::{
-    navigation::{DilutionOfPrecision, State, sv::SVContribution},
-    prelude::{Epoch, TimeScale},
+    navigation::{
+        DilutionOfPrecision, State,
+        accuracy::{Accuracy, ErrorEllipse, ErrorEllipsoid},
+        integrity::ProtectionLevels,
+        sv::SVContribution,
+    },
+    prelude::{Constellation, Epoch, SV, TimeScale},
 };
 
 #[cfg(feature = "serde")]
@@ -18,12 +23,31 @@ pub enum PVTSolutionType {
 
     /// [PVTSolutionType::RTK] obtained using differential navigation technique.
     /// That means at minimum of one ground station was used to obtain
-    /// this [PVTSolution]. The clock state, drift and TDoP are not resolved in
+    /// this [PVTSolution]. The clock state and TDoP are not resolved in
     /// RTK solutions. It is normal to obtain 0 for all these fields, for every single solution,
-    /// for a session that only uses RTK.
+    /// for a session that only uses RTK. The clock drift is only resolved in RTK solutions
+    /// when Doppler observations are provided.
     RTK = 1,
 }
 
+/// [InterSystemBias] is the receiver time offset of one [Constellation],
+/// relative to the reference [Constellation] of the navigation filter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct InterSystemBias {
+    /// [Constellation] affected by this bias
+    pub constellation: Constellation,
+
+    /// Reference [Constellation], which carries the receiver clock offset.
+    pub reference: Constellation,
+
+    /// Bias (in seconds), to add to the clock offset when observing [Self::constellation].
+    pub bias_s: f64,
+
+    /// Bias uncertainty (1σ, in seconds).
+    pub sigma_s: f64,
+}
+
 /// [PVTSolution] is solved by the navigation solver from a set of measurements,
 /// using either PPP or RTK navigation technique. Depending on the technique being used,
 /// the [PVTSolution] will differ. Mainly, RTK is not able to update and resolve the clock state.
@@ -52,6 +76,19 @@ pub struct PVTSolution {
     /// Clock offset (in seconds).
     pub clock_offset_s: f64,
 
+    /// Clock offset uncertainty (1σ, in seconds).
+    /// Null in RTK solutions, where the clock state is not resolved.
+    pub clock_offset_sigma_s: f64,
+
+    /// Clock drift (in s.s⁻¹), which is the local oscillator frequency offset.
+    /// Resolved by the navigation filter, where the clock offset follows the clock drift.
+    /// In RTK, where the clock is not resolved, it is resolved from Doppler observations
+    /// when they are available.
+    pub clock_drift_s_s: f64,
+
+    /// Clock drift uncertainty (1σ, in s.s⁻¹).
+    pub clock_drift_sigma_s_s: f64,
+
     /// Space Vehicles that helped form this solution
     /// and data associated to each individual SV
     pub sv: Vec<SVContribution>,
@@ -67,6 +104,54 @@ pub struct PVTSolution {
 
     /// Temporal Dilution of Precision
     pub tdop: f64,
+
+    /// Position covariance matrix, expressed in m² (ECEF).
+    pub pos_cov_m2: [[f64; 3]; 3],
+
+    /// Position uncertainty (1σ) for each ECEF axis, in meters.
+    pub pos_sigma_m: (f64, f64, f64),
+
+    /// Position uncertainty (1σ) for each local (east, north, up) axis, in meters.
+    pub enu_sigma_m: (f64, f64, f64),
+
+    /// Circular Error Probable: radius of the horizontal circle
+    /// that contains the solution with 50% probability, in meters.
+    pub cep_m: f64,
+
+    /// Radius of the horizontal circle that contains the solution
+    /// with 95% probability, in meters.
+    pub r95_m: f64,
+
+    /// Horizontal (2D) [ErrorEllipse] (1σ).
+    pub error_ellipse: ErrorEllipse,
+
+    /// 3D [ErrorEllipsoid] (1σ).
+    pub error_ellipsoid: ErrorEllipsoid,
+
+    /// Relative baseline vector, from the reference site to the rover,
+    /// expressed in meters (ECEF). Only available in RTK solutions.
+    /// When the reference site is moving, this is the baseline at the rover [Epoch].
+    pub baseline_m: Option<(f64, f64, f64)>,
+
+    /// Baseline rate of change, expressed in meters.s⁻¹ (ECEF),
+    /// which is the rover velocity relative to the reference site.
+    /// Only available in RTK solutions, when the rover velocity was resolved.
+    pub baseline_rate_m_s: Option<(f64, f64, f64)>,
+
+    /// [InterSystemBias]es, in multi-constellation absolute navigation.
+    /// Only available when `SolverOpts::inter_system_biases` is enabled.
+    pub inter_system_biases: Vec<InterSystemBias>,
+
+    /// [ProtectionLevels], when integrity monitoring is enabled
+    /// (see `IntegrityOpts`) and enough satellites are in sight.
+    pub protection_levels: Option<ProtectionLevels>,
+
+    /// Satellites excluded by the integrity monitoring (fault detection and exclusion).
+    pub excluded_sv: Vec<SV>,
+
+    /// Satellites rejected by innovation gating (outliers),
+    /// see `SolverOpts::innovation_gate`.
+    pub rejected_sv: Vec<SV>,
 }
 
 impl PVTSolution {
@@ -75,10 +160,11 @@ impl PVTSolution {
         uses_rtk: bool,
         state: &State,
         dop: &DilutionOfPrecision,
+        accuracy: &Accuracy,
         contributions: &[SVContribution],
     ) -> Self {
         let pos_vel_ecef_m = state.to_position_velocity_ecef_m();
-        let (clock_offset_s, _) = state.clock_profile_s();
+        let (clock_offset_s, clock_drift_s_s) = state.clock_profile_s();
 
         Self {
             epoch,
@@ -92,6 +178,9 @@ impl PVTSolution {
             vdop: dop.vdop,
             hdop: dop.hdop,
             clock_offset_s,
+            clock_offset_sigma_s: accuracy.clock_offset_sigma_s,
+            clock_drift_s_s,
+            clock_drift_sigma_s_s: state.clock_drift_sigma_s_s,
             lat_long_alt_deg_deg_m: (
                 state.lat_long_alt_deg_deg_km.0,
                 state.lat_long_alt_deg_deg_km.1,
@@ -101,6 +190,166 @@ impl PVTSolution {
             timescale: state.epoch.time_scale,
             pos_m: (pos_vel_ecef_m[0], pos_vel_ecef_m[1], pos_vel_ecef_m[2]),
             vel_m_s: (pos_vel_ecef_m[3], pos_vel_ecef_m[4], pos_vel_ecef_m[5]),
+            pos_cov_m2: accuracy.pos_cov_m2.transpose().into(),
+            pos_sigma_m: accuracy.pos_sigma_m(),
+            enu_sigma_m: accuracy.enu_sigma_m(),
+            cep_m: accuracy.cep_m,
+            r95_m: accuracy.r95_m,
+            error_ellipse: accuracy.error_ellipse,
+            error_ellipsoid: accuracy.error_ellipsoid,
+            baseline_m: None,
+            baseline_rate_m_s: None,
+            inter_system_biases: Vec::new(),
+            protection_levels: None,
+            excluded_sv: Vec::new(),
+            rejected_sv: Vec::new(),
+        }
+    }
+
+    /// Updates this [PVTSolution] with smoothed estimates.
+    ///
+    /// ## Input
+    /// - state: smoothed [State]
+    /// - clock_offset_s: possible smoothed clock offset (s)
+    /// - accuracy: smoothed [Accuracy]
+    pub(crate) fn smoothing_update_mut(
+        &mut self,
+        state: &State,
+        clock_offset_s: Option<f64>,
+        accuracy: &Accuracy,
+    ) {
+        let pos_ecef_m = state.to_position_ecef_m();
+
+        // preserve the reference position
+        if let Some(baseline_m) = &mut self.baseline_m {
+            baseline_m.0 += pos_ecef_m[0] - self.pos_m.0;
+            baseline_m.1 += pos_ecef_m[1] - self.pos_m.1;
+            baseline_m.2 += pos_ecef_m[2] - self.pos_m.2;
+        }
+
+        self.pos_m = (pos_ecef_m[0], pos_ecef_m[1], pos_ecef_m[2]);
+
+        self.lat_long_alt_deg_deg_m = (
+            state.lat_long_alt_deg_deg_km.0,
+            state.lat_long_alt_deg_deg_km.1,
+            state.lat_long_alt_deg_deg_km.2 * 1.0E3,
+        );
+
+        if let Some(clock_offset_s) = clock_offset_s {
+            self.clock_offset_s = clock_offset_s;
+            self.clock_offset_sigma_s = accuracy.clock_offset_sigma_s;
         }
+
+        self.pos_cov_m2 = accuracy.pos_cov_m2.transpose().into();
+        self.pos_sigma_m = accuracy.pos_sigma_m();
+        self.enu_sigma_m = accuracy.enu_sigma_m();
+        self.cep_m = accuracy.cep_m;
+        self.r95_m = accuracy.r95_m;
+        self.error_ellipse = accuracy.error_ellipse;
+        self.error_ellipsoid = accuracy.error_ellipsoid;
+    }
+
+    /// Defines the estimated [InterSystemBias]es.
+    pub(crate) fn with_inter_system_biases(mut self, biases: &[InterSystemBias]) -> Self {
+        self.inter_system_biases = biases.to_vec();
+        self
+    }
+
+    /// Defines the integrity monitoring outcome.
+    pub(crate) fn with_integrity(
+        mut self,
+        protection_levels: Option<ProtectionLevels>,
+        excluded_sv: &[SV],
+    ) -> Self {
+        self.protection_levels = protection_levels;
+        self.excluded_sv = excluded_sv.to_vec();
+        self
+    }
+
+    /// Defines the satellites rejected by innovation gating.
+    pub(crate) fn with_rejected_sv(mut self, rejected_sv: &[SV]) -> Self {
+        self.rejected_sv = rejected_sv.to_vec();
+        self
+    }
+
+    /// Defines the relative baseline, from the reference site position
+    /// and possible velocity (moving base), both expressed at the solution [Epoch] (ECEF).
+    /// The baseline rate is only defined when the rover velocity was resolved.
+    pub(crate) fn with_baseline(
+        mut self,
+        base_position_ecef_m: (f64, f64, f64),
+        base_velocity_ecef_m_s: Option<(f64, f64, f64)>,
+        velocity_resolved: bool,
+    ) -> Self {
+        let (base_vel_x, base_vel_y, base_vel_z) = base_velocity_ecef_m_s.unwrap_or_default();
+
+        self.baseline_m = Some((
+            self.pos_m.0 - base_position_ecef_m.0,
+            self.pos_m.1 - base_position_ecef_m.1,
+            self.pos_m.2 - base_position_ecef_m.2,
+        ));
+
+        self.baseline_rate_m_s = if velocity_resolved {
+            Some((
+                self.vel_m_s.0 - base_vel_x,
+                self.vel_m_s.1 - base_vel_y,
+                self.vel_m_s.2 - base_vel_z,
+            ))
+        } else {
+            None
+        };
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        navigation::{DilutionOfPrecision, State, accuracy::Accuracy, solutions::PVTSolution},
+        prelude::Epoch,
+    };
+
+    use anise::math::Vector3;
+
+    fn solution(state: &State) -> PVTSolution {
+        PVTSolution::new(
+            Epoch::default(),
+            true,
+            state,
+            &DilutionOfPrecision::default(),
+            &Accuracy::default(),
+            &[],
+        )
+    }
+
+    #[test]
+    fn moving_base_baseline() {
+        let base_position_ecef_m = (1.0, 2.0, 3.0);
+        let base_velocity_ecef_m_s = Some((0.5, 0.0, -1.0));
+
+        let mut state = State::default();
+        state.velocity_update_mut(Vector3::new(1.5, 0.0, 1.0));
+
+        let solved = solution(&state).with_baseline(
+            base_position_ecef_m,
+            base_velocity_ecef_m_s,
+            state.velocity_m_s.is_some(),
+        );
+
+        assert_eq!(solved.baseline_m, Some((-1.0, -2.0, -3.0)));
+        assert_eq!(solved.baseline_rate_m_s, Some((1.0, 0.0, 2.0)));
+
+        // rover velocity not resolved: rate is not defined
+        state.velocity_clear_mut();
+
+        let solved = solution(&state).with_baseline(
+            base_position_ecef_m,
+            base_velocity_ecef_m_s,
+            state.velocity_m_s.is_some(),
+        );
+
+        assert_eq!(solved.baseline_m, Some((-1.0, -2.0, -3.0)));
+        assert!(solved.baseline_rate_m_s.is_none());
     }
 }
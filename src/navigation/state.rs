@@ -20,12 +20,18 @@ pub struct State {
     /// [DVector]
     pub x: DVector<f64>,
 
-    /// x_amb [DVector]
+    /// Float ambiguities (in meters), in order of [Navigation] registration
     pub x_amb: DVector<f64>,
 
+    /// Velocity (m.s⁻¹, ECEF), when resolved
+    pub velocity_m_s: Option<Vector3>,
+
     /// Clock drift (s.s⁻¹)
     pub clock_drift_s_s: f64,
 
+    /// Clock drift uncertainty (s.s⁻¹)
+    pub clock_drift_sigma_s_s: f64,
+
     /// Geodeticy position (ddeg, ddeg, km above mean sea level)
     pub lat_long_alt_deg_deg_km: (f64, f64, f64),
 }
@@ -37,7 +43,9 @@ impl Default for State {
             epoch: Default::default(),
             x_amb: Default::default(),
             x: DVector::<f64>::zeros(U4::DIM),
+            velocity_m_s: None,
             clock_drift_s_s: Default::default(),
+            clock_drift_sigma_s_s: Default::default(),
             lat_long_alt_deg_deg_km: Default::default(),
         }
     }
@@ -97,7 +105,9 @@ impl State {
         Ok(Self {
             x,
             epoch: orbit.epoch,
+            velocity_m_s: None,
             clock_drift_s_s: 0.0_f64,
+            clock_drift_sigma_s_s: 0.0_f64,
             x_amb: Default::default(),
             lat_long_alt_deg_deg_km: latlongalt,
         })
@@ -108,9 +118,19 @@ impl State {
         Vector3::new(self.x[0], self.x[1], self.x[2])
     }
 
-    /// Returns position and velocity in ECEF meters as [Vector6]
+    /// Returns position and velocity in ECEF meters as [Vector6].
+    /// Velocity is null when it has not been resolved.
     pub fn to_position_velocity_ecef_m(&self) -> Vector6 {
-        Vector6::new(self.x[0], self.x[1], self.x[2], 0.0, 0.0, 0.0)
+        let velocity_m_s = self.velocity_m_s.unwrap_or_default();
+
+        Vector6::new(
+            self.x[0],
+            self.x[1],
+            self.x[2],
+            velocity_m_s[0],
+            velocity_m_s[1],
+            velocity_m_s[2],
+        )
     }
 
     /// Returns estimated clock (offset, drift) in seconds and s.s⁻¹.
@@ -118,7 +138,7 @@ impl State {
         if self.x.nrows() > Navigation::clock_index() {
             (self.x[Navigation::clock_index()], self.clock_drift_s_s)
         } else {
-            (0.0_f64, 0.0_f64)
+            (0.0_f64, self.clock_drift_s_s)
         }
     }
 
@@ -147,22 +167,46 @@ impl State {
 
     /// Temporal [State] correction, with mutable access.
     pub fn temporal_correction_mut(&mut self, dt: f64) {
-        // let dt = (pending_t - self.epoch).to_seconds();
-        // if dt > 0.0 && ndf > clock_index {
-        //     self.clock_drift_s_s =
-        //         ((dx[clock_index] / SPEED_OF_LIGHT_M_S) - self.x[clock_index]) / dt;
-        // }
-
         self.x[Navigation::clock_index()] = dt / SPEED_OF_LIGHT_M_S;
     }
 
+    /// Inter-system bias [State] correction (in meters), with mutable access.
+    pub fn isb_correction_mut(&mut self, index: usize, bias_m: f64) {
+        self.x[index] = bias_m / SPEED_OF_LIGHT_M_S;
+    }
+
+    /// Float ambiguity [State] correction (in meters), with mutable access.
+    pub fn ambiguity_correction_mut(&mut self, index: usize, ambiguity_m: f64) {
+        self.x_amb[index] = ambiguity_m;
+    }
+
+    /// Clock drift [State] update (s.s⁻¹), with its uncertainty, with mutable access.
+    pub fn clock_drift_update_mut(&mut self, drift_s_s: f64, sigma_s_s: f64) {
+        self.clock_drift_s_s = drift_s_s;
+        self.clock_drift_sigma_s_s = sigma_s_s;
+    }
+
+    /// Velocity [State] update, with mutable access.
+    pub fn velocity_update_mut(&mut self, velocity_m_s: Vector3) {
+        self.velocity_m_s = Some(velocity_m_s);
+    }
+
+    /// Clears the velocity, when it could not be resolved, with mutable access.
+    pub fn velocity_clear_mut(&mut self) {
+        self.velocity_m_s = None;
+    }
+
     /// Temporal update
     pub fn postfit_update_mut(&mut self, frame: Frame, dx: &DVector<f64>) -> PhysicsResult<()> {
-        // TODO: velocity
+        let mut velocity_m_s = Vector3::zeros();
+
         for i in 0..U3::DIM {
             self.x[i] = dx[i];
+            velocity_m_s[i] = dx[i + U3::DIM];
         }
 
+        self.velocity_m_s = Some(velocity_m_s);
+
         let new_orbit = self.to_orbit(frame);
         self.lat_long_alt_deg_deg_km = new_orbit.latlongalt()?;
 
@@ -260,7 +304,7 @@ mod test {
                 position_velocity_m_s[5],
             ),
             (0.0, 0.0, 0.0),
-            "dynamics not modeled in yet!",
+            "initial state should be static!",
         );
 
         assert_eq!(
@@ -354,14 +398,60 @@ mod test {
             "invalid spatial state correction",
         );
 
-        let (clock_offset_s, clock_drift_s) = state.clock_profile_s();
+        let (clock_offset_s, _) = state.clock_profile_s();
 
         assert_eq!(clock_offset_s, 4.0 / SPEED_OF_LIGHT_M_S);
 
-        // assert_eq!(
-        //     clock_drift_s,
-        //     4.0 / SPEED_OF_LIGHT_M_S / Duration::from_seconds(30.0).to_seconds(),
-        //     "invalid clock drift update!"
-        // );
+        let drift_s_s = 4.0 / SPEED_OF_LIGHT_M_S / (new_t - initial_state.epoch).to_seconds();
+
+        state.clock_drift_update_mut(drift_s_s, 1.0E-12);
+
+        let (clock_offset_s, clock_drift_s) = state.clock_profile_s();
+
+        assert_eq!(
+            clock_offset_s,
+            4.0 / SPEED_OF_LIGHT_M_S,
+            "clock offset should have been preserved"
+        );
+
+        assert_eq!(
+            clock_drift_s,
+            4.0 / SPEED_OF_LIGHT_M_S / Duration::from_seconds(30.0).to_seconds(),
+            "invalid clock drift update!"
+        );
+
+        assert_eq!(state.clock_drift_sigma_s_s, 1.0E-12);
+
+        // Test velocity update
+        let mut state = initial_state.clone();
+
+        state.velocity_update_mut(Vector3::new(1.0, 2.0, 3.0));
+
+        let position_velocity_m_s = state.to_position_velocity_ecef_m();
+
+        assert_eq!(
+            (
+                position_velocity_m_s[3],
+                position_velocity_m_s[4],
+                position_velocity_m_s[5],
+            ),
+            (1.0, 2.0, 3.0),
+            "invalid velocity update",
+        );
+
+        assert_eq!(
+            state.to_position_ecef_m(),
+            initial_state.to_position_ecef_m(),
+            "velocity update should not modify the position",
+        );
+
+        // unresolved velocity is not carried over
+        state.velocity_clear_mut();
+
+        assert_eq!(state.velocity_m_s, None);
+        assert_eq!(
+            state.to_position_velocity_ecef_m(),
+            initial_state.to_position_velocity_ecef_m()
+        );
     }
 }
@@ -36,4 +36,16 @@ pub struct SVContribution {
 
     /// Offset to selected [TimeScale], expressed as [Duration] within that [TimeScale].
     pub clock_correction: Option<Duration>,
+
+    /// Code measurement uncertainty (1σ, in meters) used to weight this contribution.
+    /// Only reported when the stochastic model describes the uncertainties.
+    pub code_sigma_m: Option<f64>,
+
+    /// Phase measurement uncertainty (1σ, in meters) used to weight this contribution.
+    /// Only reported when the stochastic model describes the uncertainties.
+    pub phase_sigma_m: Option<f64>,
+
+    /// True when a cycle slip was detected at this epoch:
+    /// a new phase arc started and its ambiguities were reset.
+    pub cycle_slip: bool,
 }
@@ -6,6 +6,9 @@ pub struct VectorContribution {
     /// Row #2 contribution
     pub row_2: f64,
 
-    /// sigma
-    pub sigma: f64,
+    /// Row #1 uncertainty (1σ, in meters)
+    pub sigma_1: f64,
+
+    /// Row #2 uncertainty (1σ, in meters)
+    pub sigma_2: f64,
 }
@@ -6,7 +6,9 @@ pub trait OrbitSource {
     /// Provide [Orbit]al state that will contribute to the solving process.
     ///
     /// The state should represent the [SV] Antenna Phase Center coordinates,
-    /// at requested [Epoch], expressed in requested [Frame].
+    /// at requested [Epoch], expressed in requested [Frame]. When ANTEX is provided
+    /// (see [Solver::with_antex](crate::prelude::Solver::with_antex)), the state
+    /// should represent the center of mass instead, as SP3 data does.
     /// Any error here directly reflects on the accuracy of the solution.
     ///
     /// `GNSS-RTK` is fully synchronous and expects measurements
@@ -14,7 +16,18 @@ pub trait OrbitSource {
     /// requests will then also be in chronological order, and this is
     /// always the "latest" [Epoch] processed for this [SV].
     ///
-    /// :warning: it is mandatory to provide [Orbit]al states
-    /// and not possible to only navigate using ephemeris frames using the current version.
+    /// When no state is provided, the satellite is resolved from
+    /// the broadcast [Ephemeris](crate::prelude::Ephemeris), if any.
     fn state_at(&self, epoch: Epoch, sv: SV, fr: Frame) -> Option<Orbit>;
 }
+
+/// [NullOrbitSource] does not provide any [Orbit]al state.
+/// Use it to navigate from broadcast [Ephemeris](crate::prelude::Ephemeris) only.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct NullOrbitSource;
+
+impl OrbitSource for NullOrbitSource {
+    fn state_at(&self, _: Epoch, _: SV, _: Frame) -> Option<Orbit> {
+        None
+    }
+}
@@ -0,0 +1,12 @@
+//! Parsing helpers of the text formats (ANTEX, IONEX, NeQuick-G data)
+use crate::prelude::Error;
+
+/// Parses all floating point values of this content,
+/// or returns this [Error] on any invalid value.
+pub(crate) fn parse_values(content: &str, error: Error) -> Result<Vec<f64>, Error> {
+    content
+        .split_ascii_whitespace()
+        .map(|item| item.parse::<f64>().ok())
+        .collect::<Option<Vec<_>>>()
+        .ok_or(error)
+}
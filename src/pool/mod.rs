@@ -5,14 +5,16 @@ use std::collections::HashMap;
 use crate::{
     candidate::differences::Differences,
     constants::EARTH_ANGULAR_VEL_RAD,
+    ephemeris::BroadcastEphemeris,
     prelude::{
-        Almanac, Candidate, Config, Duration, EnvironmentalBias, Ephemeris, EphemerisSource, Epoch,
+        Almanac, Antex, Candidate, Config, Duration, EnvironmentalBias, EphemerisSource, Epoch,
         Frame, Orbit, OrbitSource, Rc, SV, SpacebornBias,
     },
-    // smoothing::Smoother,
+    smoothing::Smoother,
+    tracker::Tracker,
 };
 
-use nalgebra::{Matrix3, Vector3};
+use nalgebra::{Matrix3, Vector3, Vector6};
 
 pub mod postfit;
 pub mod prefit;
@@ -33,8 +35,12 @@ pub struct Pool<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, S
     /// Previous [Candidate]s pool
     past: Vec<Candidate>,
 
-    // /// Measurements [Smoother]
-    // smoother: Smoother,
+    /// Phase arcs [Tracker]
+    tracker: Tracker,
+
+    /// Measurements [Smoother]
+    smoother: Smoother,
+
     /// [OrbitSource]
     orb_source: Rc<ORB>,
 
@@ -47,14 +53,23 @@ pub struct Pool<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, S
     /// Spaceborn biases
     space_bias: Rc<SB>,
 
-    /// [Ephemeris] Buffer
-    eph_buffer: HashMap<SV, Ephemeris>,
+    /// [BroadcastEphemeris] Buffer
+    eph_buffer: HashMap<SV, BroadcastEphemeris>,
 
     /// Runtime selected pivot position (ECEF, meters)
     pub pivot_position_ecef_m: Option<(f64, f64, f64)>,
 
     /// Single [Differences]
     single_differences: Differences,
+
+    /// Possible [Antex] antenna models
+    antex: Option<Rc<Antex>>,
+
+    /// Antenna Reference Point (ENU, m) of the observing receiver
+    arp_enu: Option<(f64, f64, f64)>,
+
+    /// Antenna model of the observing receiver (antenna and radome types)
+    rx_antenna: Option<String>,
 }
 
 fn orbit_rotation(t: Epoch, dt: Duration, orbit: &Orbit, modeling: bool, frame: Frame) -> Orbit {
@@ -68,12 +83,20 @@ fn orbit_rotation(t: Epoch, dt: Duration, orbit: &Orbit, modeling: bool, frame:
 
     let state_m = orbit.to_cartesian_pos_vel() * 1.0E3;
     let position_m = Vector3::new(state_m[0], state_m[1], state_m[2]);
-    let position = dcm3 * position_m;
+    let velocity_m_s = Vector3::new(state_m[3], state_m[4], state_m[5]);
 
-    Orbit::from_position(
-        position[0] / 1.0E3,
-        position[1] / 1.0E3,
-        position[2] / 1.0E3,
+    let position = dcm3 * position_m;
+    let velocity = dcm3 * velocity_m_s;
+
+    Orbit::from_cartesian_pos_vel(
+        Vector6::new(
+            position[0] / 1.0E3,
+            position[1] / 1.0E3,
+            position[2] / 1.0E3,
+            velocity[0] / 1.0E3,
+            velocity[1] / 1.0E3,
+            velocity[2] / 1.0E3,
+        ),
         t,
         frame,
     )
@@ -92,7 +115,11 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
         env_bias: Rc<EB>,
         space_bias: Rc<SB>,
     ) -> Self {
-        // let smoother = Smoother::new(cfg.code_smoothing);
+        let smoother = Smoother::new(
+            cfg.code_smoothing,
+            cfg.cycle_slip.max_gap_s,
+            cfg.cycle_slip.code_carrier_threshold_m,
+        );
 
         Self {
             cfg,
@@ -101,20 +128,80 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
             orb_source,
             env_bias,
             space_bias,
-            // smoother,
+            smoother,
             almanac,
+            tracker: Default::default(),
             pivot_position_ecef_m: None,
             past: Vec::with_capacity(8),
             inner: Vec::with_capacity(8),
             eph_buffer: HashMap::with_capacity(8),
             single_differences: Default::default(),
+            antex: None,
+            arp_enu: None,
+            rx_antenna: None,
         }
     }
 
+    /// Marks this [Pool] as observing the rover antenna.
+    pub fn with_rover_antenna(mut self) -> Self {
+        self.arp_enu = self.cfg.arp_enu;
+        self.rx_antenna = self.cfg.rx_antenna.clone();
+        self
+    }
+
+    /// Defines the antenna of the observing receiver: Antenna Reference Point
+    /// (ENU, m) and model (antenna and radome types).
+    pub fn set_receiver_antenna(
+        &mut self,
+        arp_enu: Option<(f64, f64, f64)>,
+        rx_antenna: Option<String>,
+    ) {
+        self.arp_enu = arp_enu;
+        self.rx_antenna = rx_antenna;
+    }
+
+    /// Defines the [Antex] antenna models.
+    pub fn set_antex(&mut self, antex: Rc<Antex>) {
+        self.antex = Some(antex);
+    }
+
+    /// Allocate new [Pool] that shares the same setup and data sources.
+    pub fn new_like(&self) -> Self {
+        let mut pool = Self::allocate(
+            self.almanac.clone(),
+            self.cfg.clone(),
+            self.earth_cef,
+            self.eph_source.clone(),
+            self.orb_source.clone(),
+            self.env_bias.clone(),
+            self.space_bias.clone(),
+        );
+
+        pool.antex = self.antex.clone();
+        pool
+    }
+
+    /// Allocate new [Pool] like [Self::new_like], that holds a copy
+    /// of the current [Candidate]s.
+    pub fn copy_candidates(&self) -> Self {
+        let mut pool = self.new_like();
+        pool.inner = self.inner.clone();
+        pool
+    }
+
     /// Prepare for new epoch
     pub fn new_epoch(&mut self, candidates: &[Candidate]) {
-        self.inner = candidates.to_vec();
+        // past orbital states are used to determine velocities
+        self.past = std::mem::replace(&mut self.inner, candidates.to_vec());
+        self.past.retain(|cd| cd.orbit.is_some());
         self.pivot_position_ecef_m = None;
+        self.single_differences = Default::default();
+    }
+
+    /// Resets the phase arcs tracking and the code smoothing
+    pub fn reset_tracking(&mut self) {
+        self.tracker.reset();
+        self.smoother.reset();
     }
 
     /// Returns total number of [Candidate]s
@@ -164,12 +251,26 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
                         );
 
                         cd.orbit = Some(orbit);
+                        cd.precise_orbit = true;
                         determined = true;
+
+                        // broadcast clock and group delay no longer apply
+                        if self
+                            .eph_buffer
+                            .get(&cd.sv)
+                            .is_some_and(|eph| eph.is_usable(cd.epoch))
+                        {
+                            Self::spaceborn_biases(&self.cfg, self.space_bias.as_ref(), cd, None);
+                        }
                     }
 
                     // indirect state determination
                     if !determined {
-                        if let Some(eph) = &self.eph_buffer.get(&cd.sv) {
+                        if let Some(eph) = self
+                            .eph_buffer
+                            .get(&cd.sv)
+                            .filter(|eph| eph.is_usable(cd.tx_epoch))
+                        {
                             if let Some(state) = eph.resolve_state(cd.tx_epoch, self.earth_cef) {
                                 let state = orbit_rotation(
                                     cd.epoch,
@@ -180,6 +281,7 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
                                 );
 
                                 cd.orbit = Some(state);
+                                cd.precise_orbit = false;
                                 determined = true;
                             }
                         }
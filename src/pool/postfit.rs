@@ -9,6 +9,7 @@ use crate::{
     prelude::{
         // Almanac,
         Candidate,
+        Constellation,
         EnvironmentalBias,
         EphemerisSource,
         Error,
@@ -18,15 +19,16 @@ use crate::{
         SpacebornBias,
         Vector3,
     },
+    tides::solid_tides_displacement_m,
 };
 
 use std::cmp::Ordering;
 
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 
 use hifitime::Unit;
 
-use anise::errors::AlmanacResult;
+use anise::{constants::frames::MOON_J2000, errors::AlmanacResult};
 
 impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: SpacebornBias>
     Pool<EPH, ORB, EB, SB>
@@ -36,41 +38,174 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
         self.post_fit_attitudes(name, state);
         self.post_fit_velocities(name);
         self.post_fit_eclipse(name);
+
+        if self.cfg.modeling.phase_windup {
+            self.post_fit_phase_windup(state)?;
+        }
+
+        if self.cfg.modeling.solid_tides || self.cfg.modeling.ocean_loading {
+            self.post_fit_site_displacement(name, state)?;
+        }
+
+        if self.antex.is_some() || self.arp_enu.is_some() {
+            self.post_fit_antennas(name, state)?;
+        }
+
+        if self.cfg.code_smoothing > 0 {
+            self.post_fit_code_smoothing();
+        }
+
         self.post_fit_biases(name, state);
 
         Ok(())
     }
 
-    // /// Post fit phase windup correction
-    // fn post_fit_phase_windup(&mut self, almanac: &Almanac, _state: &State) -> AlmanacResult<()> {
-    //     let epoch = self.inner[0].epoch;
-
-    //     let earth_sun = almanac.transform(SUN_J2000, EARTH_J2000, epoch, None)?;
-
-    //     let r_sun = Vector3::new(
-    //         earth_sun.radius_km.x * 1.0E3,
-    //         earth_sun.radius_km.y * 1.0E3,
-    //         earth_sun.radius_km.z * 1.0E3,
-    //     );
-
-    //     // for cd in self.inner.iter_mut() {
-    //     // let prev_correction = self
-    //     //     .past
-    //     //     .iter()
-    //     //     .filter_map(|past| {
-    //     //         if past.sv == cd.sv {
-    //     //             Some(past.windup)
-    //     //         } else {
-    //     //             None
-    //     //         }
-    //     //     })
-    //     //     .reduce(|k, _| k);
-
-    //     // cd.phase_windup_correction(state, r_sun, prev_correction);
-    //     // }
-
-    //     Ok(())
-    // }
+    /// Post fit phase windup correction
+    fn post_fit_phase_windup(&mut self, state: &State) -> AlmanacResult<()> {
+        let epoch = match self.inner.first() {
+            Some(cd) => cd.epoch,
+            None => return Ok(()),
+        };
+
+        let earth_sun = self
+            .almanac
+            .transform(SUN_J2000, self.earth_cef, epoch, None)?;
+
+        let r_sun = Vector3::new(
+            earth_sun.radius_km.x * 1.0E3,
+            earth_sun.radius_km.y * 1.0E3,
+            earth_sun.radius_km.z * 1.0E3,
+        );
+
+        for cd in self.inner.iter_mut() {
+            let past = self.past.iter().find(|past| past.sv == cd.sv);
+
+            cd.phase_windup_correction(state, r_sun, past);
+
+            debug!("{}({}) - windup={:.3} cycles", cd.epoch, cd.sv, cd.windup);
+        }
+
+        Ok(())
+    }
+
+    /// Post fit antenna phase center corrections
+    fn post_fit_antennas(&mut self, name: &str, state: &State) -> AlmanacResult<()> {
+        let epoch = match self.inner.first() {
+            Some(cd) => cd.epoch,
+            None => return Ok(()),
+        };
+
+        let earth_sun = self
+            .almanac
+            .transform(SUN_J2000, self.earth_cef, epoch, None)?;
+
+        let r_sun = earth_sun.radius_km * 1.0E3;
+
+        let arp_enu = self.arp_enu;
+
+        let rx_antenna = self.rx_antenna.as_ref().and_then(|model| {
+            let antenna = self
+                .antex
+                .as_ref()
+                .and_then(|antex| antex.receiver_antenna(model));
+
+            if antenna.is_none() {
+                debug!(
+                    "{} {} - antenna \"{}\" is not described",
+                    epoch, name, model
+                );
+            }
+
+            antenna
+        });
+
+        for cd in self.inner.iter_mut() {
+            let sv_antenna = self
+                .antex
+                .as_ref()
+                .and_then(|antex| antex.satellite_antenna(cd.sv, cd.epoch));
+
+            let past = self.past.iter().find(|past| past.sv == cd.sv);
+            let attitude = cd.satellite_attitude(r_sun, past);
+
+            if attitude.is_some() {
+                cd.yaw_rad = attitude.and_then(|attitude| attitude.yaw_rad);
+            }
+
+            cd.antenna_correction(state, arp_enu, rx_antenna, sv_antenna, attitude);
+
+            debug!(
+                "{}({}) {} - antenna phase center corrected",
+                cd.epoch, cd.sv, name
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Post fit receiver site displacement: solid Earth tides and ocean tide loading
+    fn post_fit_site_displacement(&mut self, name: &str, state: &State) -> AlmanacResult<()> {
+        let epoch = match self.inner.first() {
+            Some(cd) => cd.epoch,
+            None => return Ok(()),
+        };
+
+        let mut displacement_m = Vector3::zeros();
+
+        if self.cfg.modeling.solid_tides {
+            let earth_sun = self
+                .almanac
+                .transform(SUN_J2000, self.earth_cef, epoch, None)?;
+
+            let earth_moon = self
+                .almanac
+                .transform(MOON_J2000, self.earth_cef, epoch, None)?;
+
+            let solid_tides_m = solid_tides_displacement_m(
+                epoch,
+                state.to_position_ecef_m(),
+                earth_sun.radius_km * 1.0E3,
+                earth_moon.radius_km * 1.0E3,
+                self.cfg.modeling.mean_tide,
+            );
+
+            debug!("{} {} - solid tides={}m", epoch, name, solid_tides_m);
+            displacement_m += solid_tides_m;
+        }
+
+        if self.cfg.modeling.ocean_loading {
+            if let Some(ocean_loading) = &self.cfg.ocean_loading {
+                let (east_m, north_m, up_m) = ocean_loading.displacement_enu_m(epoch);
+
+                let (lat_deg, long_deg, _) = state.lat_long_alt_deg_deg_km;
+                let (lat_rad, long_rad) = (lat_deg.to_radians(), long_deg.to_radians());
+
+                let (sin_lat, cos_lat) = lat_rad.sin_cos();
+                let (sin_long, cos_long) = long_rad.sin_cos();
+
+                let ocean_loading_m = Vector3::new(
+                    -sin_long * east_m - sin_lat * cos_long * north_m + cos_lat * cos_long * up_m,
+                    cos_long * east_m - sin_lat * sin_long * north_m + cos_lat * sin_long * up_m,
+                    cos_lat * north_m + sin_lat * up_m,
+                );
+
+                debug!(
+                    "{} {} - ocean loading (enu)=({:.4}, {:.4}, {:.4})m",
+                    epoch, name, east_m, north_m, up_m
+                );
+
+                displacement_m += ocean_loading_m;
+            } else {
+                warn!("{} {} - missing ocean loading coefficients", epoch, name);
+            }
+        }
+
+        for cd in self.inner.iter_mut() {
+            cd.site_displacement_m = displacement_m;
+        }
+
+        Ok(())
+    }
 
     /// Apply Attitudes Post fit
     fn post_fit_attitudes(&mut self, name: &str, state: &State) {
@@ -131,66 +266,79 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
         // let w_e = EARTH_SEMI_MAJOR_AXIS_WGS84;
 
         for cd in self.inner.iter_mut() {
-            let time_of_flight = cd.signal_time_of_flight();
-
             if let Some(sv_orbit) = &mut cd.orbit {
-                if let Some(past_elected) = self.past.iter().find(|elected| elected.sv == cd.sv) {
-                    let pos_vel_km_s = sv_orbit.to_cartesian_pos_vel();
-                    let past_time_of_flight = past_elected.signal_time_of_flight();
-
-                    let dt_s = (time_of_flight - past_time_of_flight).to_seconds();
-                    let past_orbit = past_elected.orbit.unwrap();
-                    let pos_vel_z1_km_s = past_orbit.to_cartesian_pos_vel();
-
-                    let vel_km_s = (
-                        (pos_vel_km_s[0] - pos_vel_z1_km_s[0]) / dt_s,
-                        (pos_vel_km_s[1] - pos_vel_z1_km_s[1]) / dt_s,
-                        (pos_vel_km_s[2] - pos_vel_z1_km_s[2]) / dt_s,
-                    );
+                let pos_vel_km_s = sv_orbit.to_cartesian_pos_vel();
 
-                    debug!(
-                        "{}({}) {} inst. velocity {:?} km/s",
-                        cd.epoch, cd.sv, name, vel_km_s
-                    );
+                let mut vel_km_s = (pos_vel_km_s[3], pos_vel_km_s[4], pos_vel_km_s[5]);
+
+                // velocity not provided by the orbital source: finite differences
+                if sv_orbit.velocity_km_s.norm() == 0.0 {
+                    let past_orbit = self
+                        .past
+                        .iter()
+                        .find(|past| past.sv == cd.sv)
+                        .and_then(|past| Some((past.tx_epoch, past.orbit?)));
+
+                    if let Some((past_tx_epoch, past_orbit)) = past_orbit {
+                        let dt_s = (cd.tx_epoch - past_tx_epoch).to_seconds();
 
-                    *sv_orbit = sv_orbit
-                        .with_velocity_km_s(Vector3::new(vel_km_s.0, vel_km_s.1, vel_km_s.2));
+                        if dt_s <= 0.0 {
+                            continue;
+                        }
+
+                        let pos_vel_z1_km_s = past_orbit.to_cartesian_pos_vel();
+
+                        vel_km_s = (
+                            (pos_vel_km_s[0] - pos_vel_z1_km_s[0]) / dt_s,
+                            (pos_vel_km_s[1] - pos_vel_z1_km_s[1]) / dt_s,
+                            (pos_vel_km_s[2] - pos_vel_z1_km_s[2]) / dt_s,
+                        );
 
-                    if self.cfg.modeling.relativistic_clock_bias
-                        && cd.clock_corr.needs_relativistic_correction
-                    {
-                        let pos_m = (
-                            pos_vel_km_s[0] * 1.0E3,
-                            pos_vel_km_s[1] * 1.0E3,
-                            pos_vel_km_s[2] * 1.0E3,
+                        debug!(
+                            "{}({}) {} inst. velocity {:?} km/s",
+                            cd.epoch, cd.sv, name, vel_km_s
                         );
 
-                        let vel_m_s = (vel_km_s.0 * 1.0E3, vel_km_s.1 * 1.0E3, vel_km_s.2 * 1.0E3);
+                        *sv_orbit = sv_orbit
+                            .with_velocity_km_s(Vector3::new(vel_km_s.0, vel_km_s.1, vel_km_s.2));
+                    } else {
+                        continue;
+                    }
+                }
 
-                        let r_v_sat =
-                            pos_m.0 * vel_m_s.0 + pos_m.1 * vel_m_s.1 + pos_m.2 * vel_m_s.2;
+                if self.cfg.modeling.relativistic_clock_bias
+                    && cd.clock_corr.needs_relativistic_correction
+                {
+                    let pos_m = (
+                        pos_vel_km_s[0] * 1.0E3,
+                        pos_vel_km_s[1] * 1.0E3,
+                        pos_vel_km_s[2] * 1.0E3,
+                    );
 
-                        let bias =
-                            -2.0 * r_v_sat / SPEED_OF_LIGHT_M_S / SPEED_OF_LIGHT_M_S * Unit::Second;
+                    let vel_m_s = (vel_km_s.0 * 1.0E3, vel_km_s.1 * 1.0E3, vel_km_s.2 * 1.0E3);
 
-                        // let ea_deg = sv_orbit.ea_deg().map_err(Error::Physics)?;
+                    let r_v_sat = pos_m.0 * vel_m_s.0 + pos_m.1 * vel_m_s.1 + pos_m.2 * vel_m_s.2;
 
-                        // let ea_rad = ea_deg.to_radians();
-                        // let gm = (w_e * mu).sqrt();
-                        // let ecc = sv_orbit.ecc().map_err(Error::Physics)?;
+                    let bias =
+                        -2.0 * r_v_sat / SPEED_OF_LIGHT_M_S / SPEED_OF_LIGHT_M_S * Unit::Second;
 
-                        // let bias = -2.0_f64 * ecc * ea_rad.sin() * gm
-                        //     / SPEED_OF_LIGHT_M_S
-                        //     / SPEED_OF_LIGHT_M_S
-                        //     * Unit::Second;
+                    // let ea_deg = sv_orbit.ea_deg().map_err(Error::Physics)?;
 
-                        debug!(
-                            "{}({}) {} : relativistic clock bias: {}",
-                            cd.epoch, cd.sv, name, bias
-                        );
-                        // clock_corr.duration += bias;
-                    } //clockbias
-                } //velocity
+                    // let ea_rad = ea_deg.to_radians();
+                    // let gm = (w_e * mu).sqrt();
+                    // let ecc = sv_orbit.ecc().map_err(Error::Physics)?;
+
+                    // let bias = -2.0_f64 * ecc * ea_rad.sin() * gm
+                    //     / SPEED_OF_LIGHT_M_S
+                    //     / SPEED_OF_LIGHT_M_S
+                    //     * Unit::Second;
+
+                    debug!(
+                        "{}({}) {} : relativistic clock bias: {}",
+                        cd.epoch, cd.sv, name, bias
+                    );
+                    // clock_corr.duration += bias;
+                } //clockbias
             }
         }
     }
@@ -219,47 +367,12 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
         });
     }
 
-    // fn post_fit_code_smoothing(&mut self) {
-    //     for cd in self.inner.iter_mut() {
-    //         for sv_observ in cd.observations.iter_mut() {
-    //             if sv_observ.carrier.is_l1() {
-    //                 let lambda_1 = sv_observ.carrier.wavelength();
-    //                 let n_1 = sv_observ.ambiguity.unwrap_or_default();
-    //                 if n_1 != 0.0 {
-    //                     if let Some(c_n) = &mut sv_observ.pseudo_range_m {
-    //                         if let Some(l_n) = sv_observ.phase_range_m {
-    //                             *c_n = self.smoother.smoothing(
-    //                                 sv_observ.carrier,
-    //                                 cd.sv,
-    //                                 *c_n,
-    //                                 n_1,
-    //                                 lambda_1,
-    //                                 l_n,
-    //                             );
-    //                         }
-    //                     }
-    //                 }
-    //             } else {
-    //                 let lambda_j = sv_observ.carrier.wavelength();
-    //                 let n_j = sv_observ.ambiguity.unwrap_or_default();
-    //                 if n_j != 0.0 {
-    //                     if let Some(c_n) = &mut sv_observ.pseudo_range_m {
-    //                         if let Some(l_n) = sv_observ.phase_range_m {
-    //                             *c_n = self.smoother.smoothing(
-    //                                 sv_observ.carrier,
-    //                                 cd.sv,
-    //                                 *c_n,
-    //                                 n_j,
-    //                                 lambda_j,
-    //                                 l_n,
-    //                             );
-    //                         }
-    //                     }
-    //                 }
-    //             }
-    //         }
-    //     }
-    // }
+    /// Carrier smoothing of the pseudo ranges
+    fn post_fit_code_smoothing(&mut self) {
+        for cd in self.inner.iter_mut() {
+            self.smoother.smoothing(cd);
+        }
+    }
 
     fn post_fit_biases(&mut self, name: &str, state: &State) {
         let rcvr_position_ecef_m = state.to_position_ecef_m();
@@ -303,33 +416,60 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
 
                 cd.relativistic_path_range = dr;
             }
-
-            if self.cfg.modeling.phase_windup {
-                // TODO
-            }
         }
     }
 
     /// Runs a postfit SD algorithm, between seld and rhs.
+    /// Glonass satellites are differenced against the Glonass pivot, if any.
     /// NB: only shared measurements are preserved
-    pub fn post_fit_sd(&mut self, pivot: &Candidate) -> Result<(), Error> {
-        for cd in self.inner.iter() {
-            if cd.sv != pivot.sv {
-                self.single_differences
-                    .insert(cd.sv, cd.single_difference(pivot));
+    pub fn post_fit_sd(
+        &mut self,
+        pivot: &Candidate,
+        glonass_pivot: Option<&Candidate>,
+    ) -> Result<(), Error> {
+        let glonass_pivot_sv = glonass_pivot.map(|cd| cd.sv);
+
+        let glonass_pivot_position_ecef_m = glonass_pivot.and_then(|cd| cd.orbit).map(|orbit| {
+            let pos_vel = orbit.to_cartesian_pos_vel() * 1.0E3;
+            (pos_vel[0], pos_vel[1], pos_vel[2])
+        });
+
+        for cd in self.inner.iter_mut() {
+            if cd.sv == pivot.sv || Some(cd.sv) == glonass_pivot_sv {
+                continue;
             }
+
+            let sd = match glonass_pivot {
+                Some(glonass_pivot) if cd.sv.constellation == Constellation::Glonass => {
+                    cd.pivot_position_ecef_m = glonass_pivot_position_ecef_m;
+                    cd.single_difference(glonass_pivot)
+                },
+                _ => cd.single_difference(pivot),
+            };
+
+            self.single_differences.insert(cd.sv, sd);
         }
 
-        // drop pivot
-        self.retain(|cd| cd.sv != pivot.sv);
+        // drop pivots
+        self.retain(|cd| cd.sv != pivot.sv && Some(cd.sv) != glonass_pivot_sv);
 
         Ok(())
     }
 
-    /// Select a pivot measurement
-    fn post_fit_sd_pivot_election(&self) -> Option<Candidate> {
+    /// Elects the pivot satellites: the main pivot and a possible Glonass pivot,
+    /// because Glonass FDMA signals cannot be differenced against other constellations.
+    fn post_fit_sd_pivots_election(&self) -> Option<(Candidate, Option<Candidate>)> {
+        match self.post_fit_sd_pivot_election(false) {
+            Some(pivot) => Some((pivot, self.post_fit_sd_pivot_election(true))),
+            None => Some((self.post_fit_sd_pivot_election(true)?, None)),
+        }
+    }
+
+    /// Select a pivot measurement, among Glonass satellites or other constellations.
+    fn post_fit_sd_pivot_election(&self, glonass: bool) -> Option<Candidate> {
         self.inner
             .iter()
+            .filter(|cd| (cd.sv.constellation == Constellation::Glonass) == glonass)
             .max_by(|meas_a, meas_b| {
                 if meas_a.elevation_deg > meas_b.elevation_deg {
                     Ordering::Greater
@@ -344,23 +484,28 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
     /// returning DD'ed measurements.
     pub fn rtk_post_fit(&mut self, base: &mut Self) -> Result<Differences, Error> {
         // run SD algorithm on both sites
-        let mob_pivot = self.post_fit_sd_pivot_election();
+        let mob_pivots = self.post_fit_sd_pivots_election();
 
-        if mob_pivot.is_none() {
+        if mob_pivots.is_none() {
             error!("rover failed to elect pivot satellite");
             return Err(Error::SdPivotSatellite);
         }
 
-        let mob_pivot = mob_pivot.unwrap();
+        let (mob_pivot, mob_glonass_pivot) = mob_pivots.unwrap();
 
-        let base_pivot = base.post_fit_sd_pivot_election();
+        let base_pivots = base.post_fit_sd_pivots_election();
 
-        if base_pivot.is_none() {
+        if base_pivots.is_none() {
             error!("base failed to elect pivot satellite");
             return Err(Error::SdPivotSatellite);
         }
 
-        let base_pivot = base_pivot.unwrap();
+        let (base_pivot, _) = base_pivots.unwrap();
+
+        // Glonass satellites are not differenced, unless both sites share the Glonass pivot
+        let base_glonass_pivot = mob_glonass_pivot
+            .as_ref()
+            .and_then(|pivot| base.inner.iter().find(|cd| cd.sv == pivot.sv).cloned());
 
         if base_pivot.sv != mob_pivot.sv {
             error!(
@@ -376,8 +521,8 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
             mob_pivot.epoch, mob_pivot.sv
         );
 
-        self.post_fit_sd(&mob_pivot)?;
-        base.post_fit_sd(&base_pivot)?;
+        self.post_fit_sd(&mob_pivot, mob_glonass_pivot.as_ref())?;
+        base.post_fit_sd(&base_pivot, base_glonass_pivot.as_ref())?;
 
         let pos_vel = mob_pivot
             .orbit
@@ -389,13 +534,206 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
         self.pivot_position_ecef_m = Some((pos_vel[0], pos_vel[1], pos_vel[2]));
 
         // DD
-        let double_diff = self
+        let mut double_diff = self
             .single_differences
             .double_difference(&base.single_differences);
 
-        // remove pivot from both sites
-        self.retain_mut(|cd| cd.sv != mob_pivot.sv);
-        base.retain_mut(|cd| cd.sv != mob_pivot.sv);
+        double_diff.ifb_correction_mut(self.cfg.glonass_code_ifb_m);
+
+        // remove pivots from both sites
+        let glonass_pivot_sv = mob_glonass_pivot.map(|cd| cd.sv);
+
+        self.retain_mut(|cd| cd.sv != mob_pivot.sv && Some(cd.sv) != glonass_pivot_sv);
+        base.retain_mut(|cd| cd.sv != mob_pivot.sv && Some(cd.sv) != glonass_pivot_sv);
+
+        for (sat, dd) in double_diff.inner.iter() {
+            debug!("{}({}) - DD={}", mob_pivot.epoch, sat, dd);
+        }
+
+        Ok(double_diff)
+    }
+
+    /// Runs the special post-fit prior absolute triple frequency navigation, resolving
+    /// the between satellite ambiguities: each satellite is differenced against the pivot
+    /// satellite of its constellation, and its ambiguities are only fixed when the
+    /// validated cascade passes. Resolved satellites share the float ambiguity of their pivot.
+    pub fn ppp_post_fit(&mut self, name: &str) {
+        let max_fraction_cycles = self.cfg.solver.max_ambiguity_fraction_cycles;
+
+        // highest elevation pivot, per constellation
+        let mut pivots = Vec::<Candidate>::with_capacity(4);
+
+        for cd in self.inner.iter().filter(|cd| cd.ppp_tf_compatible()) {
+            match pivots
+                .iter_mut()
+                .find(|pivot| pivot.sv.constellation == cd.sv.constellation)
+            {
+                Some(pivot) => {
+                    if cd.elevation_deg > pivot.elevation_deg {
+                        *pivot = cd.clone();
+                    }
+                },
+                None => pivots.push(cd.clone()),
+            }
+        }
+
+        for cd in self.inner.iter_mut() {
+            let pivot = match pivots
+                .iter()
+                .find(|pivot| pivot.sv.constellation == cd.sv.constellation)
+            {
+                Some(pivot) if pivot.sv != cd.sv => pivot,
+                _ => continue,
+            };
+
+            let sd = cd.single_difference(pivot);
+
+            match sd.cascaded_ambiguities(max_fraction_cycles) {
+                Some(ambiguities) => {
+                    debug!(
+                        "{}({}) {} - ambiguities resolved against {}",
+                        cd.epoch, cd.sv, name, pivot.sv
+                    );
+
+                    cd.resolved_ambiguities_mut(pivot.sv, &sd, &ambiguities);
+                },
+                None => {
+                    debug!(
+                        "{}({}) {} - ambiguities not resolved",
+                        cd.epoch, cd.sv, name
+                    );
+                },
+            }
+        }
+    }
+
+    /// Runs the special post-fit prior network RTK solving, where self is considered rover
+    /// and each reference site is described by its [Pool] and reference position (ECEF, m).
+    /// Double differences are formed against each site that observes the rover pivot
+    /// satellite, translated to the network reference position, then averaged with
+    /// weights that decrease with the baseline length. Sites that cannot contribute are dropped.
+    pub fn rtk_network_post_fit(
+        &mut self,
+        bases: &mut [(&mut Self, (f64, f64, f64))],
+        network_reference_ecef_m: (f64, f64, f64),
+        rover_position_ecef_m: Vector3<f64>,
+    ) -> Result<Differences, Error> {
+        let mob_pivots = self.post_fit_sd_pivots_election();
+
+        if mob_pivots.is_none() {
+            error!("rover failed to elect pivot satellite");
+            return Err(Error::SdPivotSatellite);
+        }
+
+        let (mob_pivot, mob_glonass_pivot) = mob_pivots.unwrap();
+
+        debug!(
+            "{} - using {} as pivot satellite",
+            mob_pivot.epoch, mob_pivot.sv
+        );
+
+        self.post_fit_sd(&mob_pivot, mob_glonass_pivot.as_ref())?;
+
+        let glonass_pivot_sv = mob_glonass_pivot.as_ref().map(|cd| cd.sv);
+
+        let pos_vel = mob_pivot
+            .orbit
+            .as_ref()
+            .expect("internal error: undefined pivot sat state")
+            .to_cartesian_pos_vel()
+            * 1.0E3;
+
+        self.pivot_position_ecef_m = Some((pos_vel[0], pos_vel[1], pos_vel[2]));
+
+        let pivot_position_m = Vector3::new(pos_vel[0], pos_vel[1], pos_vel[2]);
+
+        let reference_m = Vector3::new(
+            network_reference_ecef_m.0,
+            network_reference_ecef_m.1,
+            network_reference_ecef_m.2,
+        );
+
+        let mut weighted = Vec::with_capacity(bases.len());
+
+        for (base, base_position_ecef_m) in bases.iter_mut() {
+            let base_position_m = Vector3::new(
+                base_position_ecef_m.0,
+                base_position_ecef_m.1,
+                base_position_ecef_m.2,
+            );
+
+            let base_pivot = base.inner.iter().find(|cd| cd.sv == mob_pivot.sv).cloned();
+
+            if base_pivot.is_none() {
+                warn!(
+                    "{}({}) - pivot sat not observed by reference site {:?}: dropped",
+                    mob_pivot.epoch, mob_pivot.sv, base_position_ecef_m
+                );
+                continue;
+            }
+
+            let base_glonass_pivot =
+                glonass_pivot_sv.and_then(|sv| base.inner.iter().find(|cd| cd.sv == sv).cloned());
+
+            if let Err(e) = base.post_fit_sd(&base_pivot.unwrap(), base_glonass_pivot.as_ref()) {
+                warn!(
+                    "{} - reference site {:?} single difference failed with {}: dropped",
+                    mob_pivot.epoch, base_position_ecef_m, e
+                );
+                continue;
+            }
+
+            let mut double_diff = self
+                .single_differences
+                .double_difference(&base.single_differences);
+
+            double_diff.ifb_correction_mut(self.cfg.glonass_code_ifb_m);
+
+            base.retain_mut(|cd| cd.sv != mob_pivot.sv && Some(cd.sv) != glonass_pivot_sv);
+
+            // translate to network reference position
+            for (sat, dd) in double_diff.inner.iter_mut() {
+                let candidate = self.inner.iter().find(|cd| cd.sv == *sat);
+
+                if let Some(orbit) = candidate.and_then(|cd| cd.orbit) {
+                    let pos_vel = orbit.to_cartesian_pos_vel() * 1.0E3;
+                    let sat_position_m = Vector3::new(pos_vel[0], pos_vel[1], pos_vel[2]);
+
+                    let pivot_position_m = match candidate.and_then(|cd| cd.pivot_position_ecef_m) {
+                        Some((x, y, z)) => Vector3::new(x, y, z),
+                        None => pivot_position_m,
+                    };
+
+                    let sd_base_m = (sat_position_m - base_position_m).norm()
+                        - (pivot_position_m - base_position_m).norm();
+
+                    let sd_reference_m = (sat_position_m - reference_m).norm()
+                        - (pivot_position_m - reference_m).norm();
+
+                    dd.geometric_shift_mut(sd_base_m - sd_reference_m);
+                }
+            }
+
+            let baseline_km = (rover_position_ecef_m - base_position_m).norm() / 1.0E3;
+            let weight = reference_site_weight(baseline_km);
+
+            debug!(
+                "{} - reference site {:?} baseline={:.3}km weight={:.3}",
+                mob_pivot.epoch, base_position_ecef_m, baseline_km, weight
+            );
+
+            weighted.push((double_diff, weight));
+        }
+
+        if weighted.is_empty() {
+            error!(
+                "{}({}) - no usable reference site observes the pivot sat",
+                mob_pivot.epoch, mob_pivot.sv
+            );
+            return Err(Error::NoCommonPivot);
+        }
+
+        let double_diff = Differences::weighted_mean(&weighted);
 
         for (sat, dd) in double_diff.inner.iter() {
             debug!("{}({}) - DD={}", mob_pivot.epoch, sat, dd);
@@ -404,3 +742,28 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
         Ok(double_diff)
     }
 }
+
+/// Weight of one reference site in the network solution, inversely
+/// proportional to its baseline length (km) to the rover.
+fn reference_site_weight(baseline_km: f64) -> f64 {
+    1.0 / baseline_km.max(1.0E-3)
+}
+
+#[cfg(test)]
+mod test {
+    use super::reference_site_weight;
+
+    #[test]
+    fn network_reference_site_weight() {
+        // closest site prevails
+        assert!(reference_site_weight(5.0) > reference_site_weight(20.0));
+        assert_eq!(
+            reference_site_weight(20.0) / reference_site_weight(5.0),
+            0.25
+        );
+
+        // co-located site remains finite
+        assert_eq!(reference_site_weight(0.0), 1.0E3);
+        assert_eq!(reference_site_weight(1.0E-6), 1.0E3);
+    }
+}
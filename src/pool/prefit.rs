@@ -1,10 +1,14 @@
 use crate::{
+    ephemeris::BroadcastEphemeris,
     pool::Pool,
-    prelude::{EnvironmentalBias, EphemerisSource, Method, OrbitSource, SpacebornBias},
+    prelude::{
+        Candidate, Config, Constellation, EnvironmentalBias, EphemerisSource, Method, OrbitSource,
+        SpacebornBias,
+    },
     time::AbsoluteTime,
 };
 
-use log::{debug, error};
+use log::{debug, error, warn};
 
 impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: SpacebornBias>
     Pool<EPH, ORB, EB, SB>
@@ -39,6 +43,11 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
         // temporal corrections if needed
         self.pre_fit_time_corrections(absolute_time);
 
+        // phase arcs tracking
+        if self.cfg.cycle_slip.detection {
+            self.pre_fit_cycle_slips(name);
+        }
+
         // prefit biases
         self.pre_fit_biases();
     }
@@ -47,8 +56,22 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
     fn pre_fit_ephemeris_update(&mut self) {
         for cd in self.inner.iter() {
             // update attempt
-            if let Some(data) = self.eph_source.ephemeris_data(cd.epoch, cd.sv) {
-                self.eph_buffer.insert(cd.sv, data);
+            let data = if cd.sv.constellation == Constellation::Glonass {
+                self.eph_source
+                    .glonass_ephemeris_data(cd.epoch, cd.sv)
+                    .map(BroadcastEphemeris::Glonass)
+            } else {
+                self.eph_source
+                    .ephemeris_data(cd.epoch, cd.sv)
+                    .map(BroadcastEphemeris::Kepler)
+            };
+
+            if let Some(data) = data {
+                if data.is_usable(cd.epoch) {
+                    self.eph_buffer.insert(cd.sv, data);
+                } else {
+                    debug!("{}({}) - ephemeris disregarded", cd.epoch, cd.sv);
+                }
             }
         }
     }
@@ -97,6 +120,14 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
                     false
                 }
             },
+            Method::PPP_TF => {
+                if cd.ppp_tf_compatible() {
+                    true
+                } else {
+                    debug!("{}({}) {} missing third frequency", cd.epoch, cd.sv, name);
+                    false
+                }
+            },
         });
     }
 
@@ -111,24 +142,66 @@ impl<EPH: EphemerisSource, ORB: OrbitSource, EB: EnvironmentalBias, SB: Spacebor
         }
     }
 
-    /// Calculates the pre fit biases to compensate
-    fn pre_fit_biases(&mut self) {
+    /// Tracks the phase arcs and detects the cycle slips.
+    fn pre_fit_cycle_slips(&mut self, name: &str) {
         for cd in self.inner.iter_mut() {
-            let rtm = cd.to_partial_bias_runtime();
+            cd.cycle_slip = self.tracker.track(&self.cfg.cycle_slip, cd);
 
-            if self.cfg.modeling.sv_clock_bias {
-                cd.clock_corr = self.space_bias.clock_bias(&rtm);
-                debug!(
-                    "{}({}) - sat clock correction = {}",
-                    cd.epoch, cd.sv, cd.clock_corr.duration
+            if cd.cycle_slip {
+                warn!(
+                    "{}({}) {} - cycle slip: new phase arc",
+                    cd.epoch, cd.sv, name
                 );
             }
+        }
+    }
 
-            if self.cfg.modeling.sv_total_group_delay {
-                let tgd = self.space_bias.group_delay(&rtm);
-                debug!("{}({}) - tgd = {}", cd.epoch, cd.sv, tgd);
-                cd.tgd = tgd;
-            }
+    /// Calculates the pre fit biases to compensate.
+    /// Satellites described by broadcast [Ephemeris] use the broadcast clock and group delay,
+    /// until the [OrbitSource] describes them (see [Self::orbital_states_fit]).
+    fn pre_fit_biases(&mut self) {
+        for cd in self.inner.iter_mut() {
+            let eph = self
+                .eph_buffer
+                .get(&cd.sv)
+                .filter(|eph| eph.is_usable(cd.epoch));
+
+            Self::spaceborn_biases(&self.cfg, self.space_bias.as_ref(), cd, eph);
+        }
+    }
+
+    /// Defines the satellite clock correction and group delay of this [Candidate],
+    /// from the [BroadcastEphemeris] if any, otherwise from the [SpacebornBias].
+    pub(crate) fn spaceborn_biases(
+        cfg: &Config,
+        space_bias: &SB,
+        cd: &mut Candidate,
+        eph: Option<&BroadcastEphemeris>,
+    ) {
+        let rtm = cd.to_partial_bias_runtime();
+
+        if cfg.modeling.sv_clock_bias {
+            let broadcast_corr = eph.and_then(|eph| eph.clock_correction(cd.epoch));
+
+            cd.clock_corr = match broadcast_corr {
+                Some(clock_corr) => clock_corr,
+                None => space_bias.clock_bias(&rtm),
+            };
+
+            debug!(
+                "{}({}) - sat clock correction = {}",
+                cd.epoch, cd.sv, cd.clock_corr.duration
+            );
+        }
+
+        if cfg.modeling.sv_total_group_delay {
+            let tgd = match eph {
+                Some(eph) => eph.group_delay(),
+                None => space_bias.group_delay(&rtm),
+            };
+
+            debug!("{}({}) - tgd = {}", cd.epoch, cd.sv, tgd);
+            cd.tgd = tgd;
         }
     }
 }
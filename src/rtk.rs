@@ -1,5 +1,8 @@
 use crate::prelude::{Candidate, Epoch};
 
+#[cfg(doc)]
+use crate::prelude::{Antex, Config};
+
 /// Any [RTKBase] provides remote data by implementing the [RemoteSource] trait.
 pub trait RTKBase {
     /// Provide a meaningful name of each reference station.
@@ -24,6 +27,32 @@ pub trait RTKBase {
     /// This information is mandatory. Any error on this value will automatically
     /// decrease the accuracy of the solution.
     fn reference_position_ecef_m(&self, epoch: Epoch) -> (f64, f64, f64);
+
+    /// Provide the velocity of the base station (in m.s⁻¹, ECEF), at sampling [Epoch].
+    /// Returning a value enables the moving-base mode: the base position and
+    /// its observations are extrapolated from their sampling [Epoch] to the rover [Epoch],
+    /// and the base velocity is used when reporting the baseline rate.
+    /// Observations should then provide Doppler shifts, otherwise they cannot be
+    /// extrapolated and are discarded.
+    /// Static base stations should not implement this method.
+    fn reference_velocity_ecef_m_s(&self, _epoch: Epoch) -> Option<(f64, f64, f64)> {
+        None
+    }
+
+    /// Provide the antenna model of the reference station (antenna and radome types,
+    /// as named by the IGS, for example "TRM59800.00     NONE"), like [Config::rx_antenna]
+    /// for the rover. Its phase center is then compensated with the [Antex] models.
+    /// By default, it is not compensated: this is only valid for short baselines
+    /// between identical antennas, whose phase centers cancel out in the differences.
+    fn antenna_model(&self) -> Option<String> {
+        None
+    }
+
+    /// Provide the Antenna Reference Point (ENU, m) of the reference station,
+    /// from its reference position, like [Config::arp_enu] for the rover.
+    fn arp_enu_m(&self) -> Option<(f64, f64, f64)> {
+        None
+    }
 }
 
 pub(crate) struct NullRTK {}
@@ -0,0 +1,331 @@
+//! Session (batch) post-processing
+use log::{error, info};
+
+use std::iter::Peekable;
+
+use crate::{
+    ephemeris::EphemerisSource,
+    navigation::solutions::PVTSolution,
+    orbit::OrbitSource,
+    prelude::{
+        Candidate, Duration, EnvironmentalBias, Epoch, Error, SpacebornBias, UserParameters,
+    },
+    rtk::RTKBase,
+    solver::Solver,
+    time::AbsoluteTime,
+};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// [SessionSummary] describes the outcome of a complete
+/// observation session.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SessionSummary {
+    /// First [Epoch] of the session
+    pub first_epoch: Option<Epoch>,
+
+    /// Last [Epoch] of the session
+    pub last_epoch: Option<Epoch>,
+
+    /// Total number of epochs proposed
+    pub epochs: usize,
+
+    /// Number of [PVTSolution]s resolved
+    pub solutions: usize,
+
+    /// Number of failed epochs
+    pub failures: usize,
+
+    /// Mean position of all [PVTSolution]s, in meters (ECEF).
+    pub mean_position_ecef_m: Option<(f64, f64, f64)>,
+
+    /// Mean GDOP of all [PVTSolution]s.
+    pub mean_gdop: Option<f64>,
+}
+
+impl SessionSummary {
+    /// Returns the ratio of epochs that were resolved, in percent.
+    pub fn availability_percent(&self) -> f64 {
+        if self.epochs == 0 {
+            0.0
+        } else {
+            self.solutions as f64 * 100.0 / self.epochs as f64
+        }
+    }
+
+    /// Returns total session [Duration].
+    pub fn duration(&self) -> Duration {
+        match (self.first_epoch, self.last_epoch) {
+            (Some(first), Some(last)) => last - first,
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Creates a new [SessionSummary] from the session results.
+    fn new(epochs: &[Epoch], solutions: &[PVTSolution], failures: usize) -> Self {
+        let mut summary = Self {
+            failures,
+            epochs: epochs.len(),
+            solutions: solutions.len(),
+            first_epoch: epochs.iter().min().copied(),
+            last_epoch: epochs.iter().max().copied(),
+            ..Default::default()
+        };
+
+        if !solutions.is_empty() {
+            let n = solutions.len() as f64;
+
+            let (x, y, z) = solutions.iter().fold((0.0, 0.0, 0.0), |acc, pvt| {
+                (
+                    acc.0 + pvt.pos_m.0,
+                    acc.1 + pvt.pos_m.1,
+                    acc.2 + pvt.pos_m.2,
+                )
+            });
+
+            summary.mean_position_ecef_m = Some((x / n, y / n, z / n));
+            summary.mean_gdop = Some(solutions.iter().map(|pvt| pvt.gdop).sum::<f64>() / n);
+        }
+
+        summary
+    }
+}
+
+/// [SessionReport] gathers all results of a complete observation session.
+#[derive(Debug, Default)]
+pub struct SessionReport {
+    /// All [PVTSolution]s, in chronological order.
+    pub solutions: Vec<PVTSolution>,
+
+    /// Failed epochs, with their [Error], in chronological order.
+    pub failures: Vec<(Epoch, Error)>,
+
+    /// Smoothed [PVTSolution]s, in chronological order.
+    /// Only available when `SolverOpts::rts_smoothing` is enabled.
+    pub smoothed: Vec<PVTSolution>,
+
+    /// [SessionSummary]
+    pub summary: SessionSummary,
+}
+
+/// Reference site described by a stream of observations, at a fixed position.
+struct StreamBase {
+    /// Reference position (ECEF, m)
+    position_ecef_m: (f64, f64, f64),
+
+    /// Observations aligned to current [Epoch]
+    observations: Vec<Candidate>,
+}
+
+impl RTKBase for StreamBase {
+    fn name(&self) -> String {
+        "base".to_string()
+    }
+
+    fn new_epoch(&mut self, _: Epoch) {}
+
+    fn observe(&self, _: Epoch) -> Vec<Candidate> {
+        self.observations.clone()
+    }
+
+    fn reference_position_ecef_m(&self, _: Epoch) -> (f64, f64, f64) {
+        self.position_ecef_m
+    }
+}
+
+/// Consumes the base stream up to rover [Epoch], returning the aligned observations, if any.
+/// Base observations that are more recent are preserved for later epochs.
+fn aligned_base_observations<B: Iterator<Item = (Epoch, Vec<Candidate>)>>(
+    base: &mut Peekable<B>,
+    epoch: Epoch,
+) -> Option<Vec<Candidate>> {
+    while let Some((base_epoch, _)) = base.peek() {
+        if *base_epoch < epoch {
+            let _ = base.next();
+        } else if *base_epoch == epoch {
+            return base.next().map(|(_, observations)| observations);
+        } else {
+            return None;
+        }
+    }
+
+    None
+}
+
+impl<
+    EPH: EphemerisSource,
+    ORB: OrbitSource,
+    EB: EnvironmentalBias,
+    SB: SpacebornBias,
+    TIM: AbsoluteTime,
+> Solver<EPH, ORB, EB, SB, TIM>
+{
+    /// Runs the backward smoothing pass at the end of the session, when enabled.
+    fn session_smoothing(&self, report: &mut SessionReport) {
+        if !self.cfg.solver.rts_smoothing {
+            return;
+        }
+
+        match self.rts_smoothing(&report.solutions) {
+            Ok(smoothed) => report.smoothed = smoothed,
+            Err(e) => error!("session smoothing: {e}"),
+        }
+    }
+
+    /// Post processes a complete observation session using PPP technique,
+    /// see [Self::ppp]. Each epoch is processed in chronological order,
+    /// failures do not interrupt the session.
+    ///
+    /// ## Input
+    /// - params: [UserParameters]
+    /// - rover: session, as an iterator of [Epoch]s and proposed [Candidate]s.
+    ///
+    /// ## Output
+    /// - [SessionReport]
+    pub fn ppp_session<I: IntoIterator<Item = (Epoch, Vec<Candidate>)>>(
+        &mut self,
+        params: UserParameters,
+        rover: I,
+    ) -> SessionReport {
+        let mut epochs = Vec::new();
+        let mut report = SessionReport::default();
+
+        for (epoch, candidates) in rover {
+            epochs.push(epoch);
+
+            match self.ppp(epoch, params, &candidates) {
+                Ok(solution) => report.solutions.push(solution),
+                Err(e) => {
+                    error!("{epoch} - session: {e}");
+                    report.failures.push((epoch, e));
+                },
+            }
+        }
+
+        self.session_smoothing(&mut report);
+
+        report.summary = SessionSummary::new(&epochs, &report.solutions, report.failures.len());
+        info!("session summary: {:?}", report.summary);
+
+        report
+    }
+
+    /// Post processes a complete observation session using RTK technique,
+    /// see [Self::rtk]. The base stream is aligned to the rover: base observations
+    /// must share the rover sampling [Epoch]s, rover epochs without base observations
+    /// are reported as failures. Each epoch is processed in chronological order,
+    /// failures do not interrupt the session.
+    ///
+    /// ## Input
+    /// - params: [UserParameters]
+    /// - rover: rover session, as an iterator of [Epoch]s and proposed [Candidate]s.
+    /// - base: base session, as an iterator of [Epoch]s and [Candidate]s.
+    /// - base_position_ecef_m: static reference position of the base, in meters (ECEF).
+    ///
+    /// ## Output
+    /// - [SessionReport]
+    pub fn rtk_session<
+        I: IntoIterator<Item = (Epoch, Vec<Candidate>)>,
+        B: IntoIterator<Item = (Epoch, Vec<Candidate>)>,
+    >(
+        &mut self,
+        params: UserParameters,
+        rover: I,
+        base: B,
+        base_position_ecef_m: (f64, f64, f64),
+    ) -> SessionReport {
+        let mut epochs = Vec::new();
+        let mut report = SessionReport::default();
+
+        let mut base = base.into_iter().peekable();
+
+        let mut rtk_base = StreamBase {
+            position_ecef_m: base_position_ecef_m,
+            observations: Vec::new(),
+        };
+
+        for (epoch, candidates) in rover {
+            epochs.push(epoch);
+
+            let status = match aligned_base_observations(&mut base, epoch) {
+                Some(observations) => {
+                    rtk_base.observations = observations;
+                    self.rtk(epoch, params, &candidates, &rtk_base)
+                },
+                None => Err(Error::MissingRemoteRTKObservations),
+            };
+
+            match status {
+                Ok(solution) => report.solutions.push(solution),
+                Err(e) => {
+                    error!("{epoch} - session: {e}");
+                    report.failures.push((epoch, e));
+                },
+            }
+        }
+
+        self.session_smoothing(&mut report);
+
+        report.summary = SessionSummary::new(&epochs, &report.solutions, report.failures.len());
+        info!("session summary: {:?}", report.summary);
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SessionSummary, aligned_base_observations};
+    use crate::prelude::{Candidate, Duration, Epoch, SV};
+
+    use std::str::FromStr;
+
+    #[test]
+    fn base_stream_alignment() {
+        let g01 = SV::from_str("G01").unwrap();
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let dt = Duration::from_seconds(30.0);
+
+        let base = [t0, t0 + dt, t0 + 3.0 * dt]
+            .iter()
+            .map(|t| (*t, vec![Candidate::new(g01, *t, vec![])]))
+            .collect::<Vec<_>>();
+
+        let mut base = base.into_iter().peekable();
+
+        // rover is late: first base epoch is dropped
+        let observations = aligned_base_observations(&mut base, t0 + dt).unwrap();
+        assert_eq!(observations[0].epoch, t0 + dt);
+
+        // base gap
+        assert!(aligned_base_observations(&mut base, t0 + 2.0 * dt).is_none());
+
+        // base is preserved
+        let observations = aligned_base_observations(&mut base, t0 + 3.0 * dt).unwrap();
+        assert_eq!(observations[0].epoch, t0 + 3.0 * dt);
+
+        // end of stream
+        assert!(aligned_base_observations(&mut base, t0 + 4.0 * dt).is_none());
+    }
+
+    #[test]
+    fn session_summary() {
+        let summary = SessionSummary::new(&[], &[], 0);
+
+        assert_eq!(summary.epochs, 0);
+        assert_eq!(summary.availability_percent(), 0.0);
+        assert_eq!(summary.duration(), Duration::ZERO);
+        assert!(summary.mean_position_ecef_m.is_none());
+
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let t1 = Epoch::from_str("2020-06-25T00:15:00 GPST").unwrap();
+
+        let summary = SessionSummary::new(&[t0, t1], &[], 2);
+
+        assert_eq!(summary.failures, 2);
+        assert_eq!(summary.availability_percent(), 0.0);
+        assert_eq!(summary.duration(), t1 - t0);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::prelude::{Carrier, SV};
+//! Carrier smoothing of the pseudo ranges
+use crate::prelude::{Candidate, Carrier, Epoch, SV};
 use std::collections::HashMap;
 
 use log::debug;
@@ -11,83 +12,289 @@ struct Key {
     carrier: Carrier,
 }
 
-struct MovingAverage {
-    pub n: usize,
-    pub size: usize,
-    pub c_n: f64,
-    pub l_n: f64,
+/// Hatch filter of a single signal
+struct HatchFilter {
+    /// Number of smoothed epochs, up to the window length
+    n: usize,
+
+    /// Latest [Epoch]
+    epoch: Epoch,
+
+    /// True when the phase input is divergence free
+    divergence_free: bool,
+
+    /// Latest phase input, in meters
+    phase_m: f64,
+
+    /// Latest smoothed pseudo range, in meters
+    smoothed_m: f64,
 }
 
-impl MovingAverage {
-    pub fn new(size: usize) -> Self {
+impl HatchFilter {
+    fn new(epoch: Epoch, divergence_free: bool, code_m: f64, phase_m: f64) -> Self {
         Self {
-            size,
-            n: 0,
-            c_n: 0.0,
-            l_n: 0.0,
+            n: 1,
+            epoch,
+            divergence_free,
+            phase_m,
+            smoothed_m: code_m,
         }
     }
 
-    pub fn add(&mut self, c_n: f64, l_n: f64) -> f64 {
-        self.n += 1;
-        let alpha = if self.n < self.size {
-            1.0 / self.n as f64
-        } else {
-            1.0 / self.size as f64
-        };
-
-        let l_k = l_n - self.l_n;
-        let c_k = alpha * c_n + (1.0 - alpha) * (self.c_n + l_k);
-        debug!(
-            "alpha={} | c_n={} | c_k={} | err={}",
-            alpha,
-            c_n,
-            c_k,
-            (c_n - c_k).abs()
-        );
-
-        self.l_n = l_n;
-
-        if self.n == 1 {
-            self.c_n = c_n;
-        } else {
-            self.c_n = c_k;
-        }
+    /// Deviation of the pseudo range from its carrier smoothed prediction, in meters
+    fn code_carrier_jump_m(&self, code_m: f64, phase_m: f64) -> f64 {
+        code_m - (self.smoothed_m + phase_m - self.phase_m)
+    }
+
+    fn add(&mut self, win_len: usize, epoch: Epoch, code_m: f64, phase_m: f64) -> f64 {
+        self.n = (self.n + 1).min(win_len);
+
+        let n = self.n as f64;
 
-        c_k
+        self.smoothed_m = code_m / n + (n - 1.0) / n * (self.smoothed_m + phase_m - self.phase_m);
+        self.phase_m = phase_m;
+        self.epoch = epoch;
+
+        self.smoothed_m
     }
 }
 
+/// Carrier [Smoother] of the pseudo ranges (Hatch filter).
+/// When dual frequency phase observations are available, the phase input is made
+/// divergence free: its ionospheric variation matches the code variation.
+/// Otherwise, the single frequency filter is used, which diverges with the ionosphere
+/// variation over the window.
 pub struct Smoother {
+    /// Window length
     win_len: usize,
-    inner: HashMap<Key, MovingAverage>,
+
+    /// Maximal data gap, in seconds
+    max_gap_s: f64,
+
+    /// Maximal code-carrier jump, in meters
+    max_jump_m: f64,
+
+    /// [HatchFilter] of each signal
+    inner: HashMap<Key, HatchFilter>,
 }
 
 impl Smoother {
-    pub fn new(win_len: usize) -> Self {
+    pub fn new(win_len: usize, max_gap_s: f64, max_jump_m: f64) -> Self {
         Self {
             win_len,
+            max_gap_s,
+            max_jump_m,
             inner: HashMap::with_capacity(8),
         }
     }
 
-    pub fn smoothing(
-        &mut self,
-        carrier: Carrier,
-        sv: SV,
-        c_n: f64,
-        n_1: f64,
-        lambda_1: f64,
-        l_n: f64,
-    ) -> f64 {
-        let k = Key { sv, carrier };
-        if let Some(mov) = self.inner.get_mut(&k) {
-            mov.add(c_n, l_n - n_1 * lambda_1)
-        } else {
-            let mut mov = MovingAverage::new(self.win_len);
-            let c_k = mov.add(c_n, l_n - n_1 * lambda_1);
-            self.inner.insert(k.clone(), mov);
-            c_k
+    /// Resets all filters
+    pub fn reset(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Smoothes the pseudo ranges of this [Candidate], that need
+    /// to be observed with a phase range on the same frequency.
+    /// Filters are reset on cycle slips and data gaps. Each filter is also reset
+    /// when the pseudo range jumps away from its prediction, which catches
+    /// the cycle slips that were not detected (or when detection is disabled).
+    pub fn smoothing(&mut self, cd: &mut Candidate) {
+        if cd.cycle_slip {
+            debug!("{}({}) - cycle slip: smoothing reset", cd.epoch, cd.sv);
+            self.inner.retain(|k, _| k.sv != cd.sv);
+        }
+
+        // ionosphere variation (scaled to the reference frequency)
+        let iono_m = cd.phase_gf_combination().map(|gf| {
+            let gamma = (gf.rhs.frequency_hz() / gf.lhs.frequency_hz()).powi(2);
+            (gf.rhs.frequency_hz(), gf.value / (gamma - 1.0))
+        });
+
+        for ob in cd.observations.iter_mut() {
+            let (code_m, phase_m) = match (ob.pseudo_range_m, ob.phase_range_m) {
+                (Some(code_m), Some(phase_m)) => (code_m, phase_m),
+                _ => continue,
+            };
+
+            // phase wind up correction
+            let phase_m = phase_m - cd.windup * ob.carrier.wavelength();
+
+            let divergence_free = iono_m.is_some();
+
+            let phase_m = match iono_m {
+                Some((f1_hz, iono_m)) => {
+                    phase_m + 2.0 * (f1_hz / ob.carrier.frequency_hz()).powi(2) * iono_m
+                },
+                None => phase_m,
+            };
+
+            let key = Key {
+                sv: cd.sv,
+                carrier: ob.carrier,
+            };
+
+            let smoothed_m = match self.inner.get_mut(&key) {
+                Some(filter)
+                    if filter.divergence_free == divergence_free
+                        && cd.epoch > filter.epoch
+                        && (cd.epoch - filter.epoch).to_seconds() <= self.max_gap_s =>
+                {
+                    let jump_m = filter.code_carrier_jump_m(code_m, phase_m);
+
+                    if jump_m.abs() > self.max_jump_m {
+                        debug!(
+                            "{}({}) - {} code-carrier jump of {:.3}m: smoothing reset",
+                            cd.epoch, cd.sv, ob.carrier, jump_m
+                        );
+
+                        *filter = HatchFilter::new(cd.epoch, divergence_free, code_m, phase_m);
+                        code_m
+                    } else {
+                        filter.add(self.win_len, cd.epoch, code_m, phase_m)
+                    }
+                },
+                _ => {
+                    let filter = HatchFilter::new(cd.epoch, divergence_free, code_m, phase_m);
+                    self.inner.insert(key, filter);
+                    code_m
+                },
+            };
+
+            ob.pseudo_range_m = Some(smoothed_m);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Smoother;
+
+    use crate::prelude::{Candidate, Carrier, Constellation, Duration, Epoch, Observation, SV};
+
+    use std::str::FromStr;
+
+    /// [Candidate] observed with alternating code noise (in meters)
+    /// and increasing ionosphere delay (in meters, on the L1 frequency)
+    fn candidate(
+        t0: Epoch,
+        k: usize,
+        carriers: &[Carrier],
+        noise_m: f64,
+        iono_m: f64,
+    ) -> Candidate {
+        let range_m = 20.0E6 + 100.0 * k as f64;
+        let noise_m = noise_m * (-1.0_f64).powi(k as i32);
+
+        let observations = carriers
+            .iter()
+            .map(|carrier| {
+                let iono_m = iono_m * (Carrier::L1.frequency_hz() / carrier.frequency_hz()).powi(2);
+
+                Observation::pseudo_range(*carrier, range_m + iono_m + noise_m, None)
+                    .with_ambiguous_phase_range_m(range_m - iono_m + 1000.0)
+            })
+            .collect();
+
+        Candidate::new(
+            SV::new(Constellation::GPS, 1),
+            t0 + Duration::from_seconds(k as f64),
+            observations,
+        )
+    }
+
+    fn l1_error_m(cd: &Candidate, k: usize, iono_m: f64) -> f64 {
+        let range_m = 20.0E6 + 100.0 * k as f64;
+        cd.observations[0].pseudo_range_m.unwrap() - range_m - iono_m
+    }
+
+    #[test]
+    fn hatch_smoothing() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let mut smoother = Smoother::new(100, 60.0, 10.0);
+
+        for k in 0..200 {
+            let mut cd = candidate(t0, k, &[Carrier::L1], 1.0, 0.0);
+            smoother.smoothing(&mut cd);
+
+            let err_m = l1_error_m(&cd, k, 0.0);
+
+            if k == 0 {
+                assert_eq!(err_m.abs(), 1.0);
+            } else if k > 100 {
+                assert!(err_m.abs() < 0.02, "@{k}: {err_m}");
+            }
+        }
+
+        // cycle slip: reset
+        let mut cd = candidate(t0, 200, &[Carrier::L1], 1.0, 0.0);
+        cd.cycle_slip = true;
+        smoother.smoothing(&mut cd);
+        assert_eq!(l1_error_m(&cd, 200, 0.0), 1.0);
+
+        // data gap: reset
+        let mut cd = candidate(t0, 300, &[Carrier::L1], 1.0, 0.0);
+        smoother.smoothing(&mut cd);
+        assert_eq!(l1_error_m(&cd, 300, 0.0), 1.0);
+    }
+
+    #[test]
+    fn undetected_cycle_slip() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let mut smoother = Smoother::new(100, 60.0, 10.0);
+
+        for k in 0..200 {
+            let mut cd = candidate(t0, k, &[Carrier::L1], 1.0, 0.0);
+
+            // undetected slip of 100 cycles
+            if k >= 150 {
+                for ob in cd.observations.iter_mut() {
+                    if let Some(phase_m) = ob.phase_range_m.as_mut() {
+                        *phase_m += 100.0 * Carrier::L1.wavelength();
+                    }
+                }
+            }
+
+            smoother.smoothing(&mut cd);
+
+            let err_m = l1_error_m(&cd, k, 0.0);
+
+            if k == 150 {
+                // reset: raw pseudo range
+                assert_eq!(err_m.abs(), 1.0);
+            } else if k > 150 {
+                // smoothing restarted, not biased by the slip (19m)
+                assert!(err_m.abs() <= 1.0, "@{k}: {err_m}");
+            } else if k > 100 {
+                assert!(err_m.abs() < 0.02, "@{k}: {err_m}");
+            }
+        }
+    }
+
+    #[test]
+    fn divergence_free_smoothing() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+
+        let mut single = Smoother::new(100, 60.0, 10.0);
+        let mut dual = Smoother::new(100, 60.0, 10.0);
+
+        for k in 0..200 {
+            // ionosphere delay increases by 1cm/s
+            let iono_m = 0.01 * k as f64;
+
+            let mut cd = candidate(t0, k, &[Carrier::L1], 0.0, iono_m);
+            single.smoothing(&mut cd);
+            let single_err_m = l1_error_m(&cd, k, iono_m);
+
+            let mut cd = candidate(t0, k, &[Carrier::L1, Carrier::L2], 0.0, iono_m);
+            dual.smoothing(&mut cd);
+            let dual_err_m = l1_error_m(&cd, k, iono_m);
+
+            assert!(dual_err_m.abs() < 1.0E-6, "@{k}: {dual_err_m}");
+
+            if k > 100 {
+                // code-carrier divergence
+                assert!(single_err_m.abs() > 0.5, "@{k}: {single_err_m}");
+            }
         }
     }
 }
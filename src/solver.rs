@@ -1,4 +1,6 @@
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+
+use std::collections::HashMap;
 
 use anise::{
     math::Vector3,
@@ -6,14 +8,20 @@ use anise::{
 };
 
 use crate::{
+    attitude::{AttitudeSolution, Baseline, attitude_fit},
     bancroft::Bancroft,
     candidate::Candidate,
     cfg::Config,
     ephemeris::EphemerisSource,
-    navigation::{Navigation, apriori::Apriori, solutions::PVTSolution, state::State},
+    navigation::{
+        Navigation, accuracy::Accuracy, apriori::Apriori, solutions::PVTSolution, state::State,
+    },
     orbit::OrbitSource,
     pool::Pool,
-    prelude::{EnvironmentalBias, Epoch, Error, Rc, SpacebornBias, UserParameters},
+    prelude::{
+        Antex, EnvironmentalBias, Epoch, Error, Method, Rc, SPEED_OF_LIGHT_M_S, SpacebornBias,
+        UserParameters,
+    },
     rtk::{NullRTK, RTKBase},
     time::AbsoluteTime,
 };
@@ -21,8 +29,9 @@ use crate::{
 /// [Solver] to resolve [PVTSolution]s.
 ///
 /// ## Generics:
-/// - EPH: [EphemerisSource] custom data source. Curreuntly unused: limited to [ORB] only!
-/// - ORB: [OrbitSource], custom Orbit data source.
+/// - EPH: [EphemerisSource] custom data source, used for satellites that [ORB] does not describe.
+/// - ORB: [OrbitSource], custom Orbit data source. Use [NullOrbitSource](crate::prelude::NullOrbitSource)
+///   to navigate from broadcast [Ephemeris](crate::prelude::Ephemeris) only.
 /// - EB: [EnvironmentalBias] implementation using either intenral or custom external model.
 /// - SB: [SpacebornBias] external information provider.
 /// - TIM: [AbsoluteTime] source for correct absolute time management.
@@ -42,8 +51,11 @@ pub struct Solver<
     /// Rover pool
     rover_pool: Pool<EPH, ORB, EB, SB>,
 
-    /// Base pool
-    base_pool: Pool<EPH, ORB, EB, SB>,
+    /// Base pools, one per reference site
+    base_pools: Vec<Pool<EPH, ORB, EB, SB>>,
+
+    /// Attitude pools: primary then secondary antenna
+    attitude_pools: Vec<Pool<EPH, ORB, EB, SB>>,
 
     /// PPP prefit
 
@@ -72,8 +84,8 @@ impl<
     /// - almanac: provided valid [Almanac]
     /// - earth_cef: [Frame] that must be an ECEF
     /// - cfg: solver [Config]uration
-    /// - eph_source: custom [EphemerisSource], unavailable right now,
-    /// tie to null.
+    /// - eph_source: custom [EphemerisSource], that provides broadcast
+    /// frames for satellites that the [OrbitSource] does not describe.
     /// - orbit_source: custom [OrbitSource] implementation,
     /// wrapped in a Rc<RefCell<>> which allows the solver
     /// and the orbital provider to live in the same thread.
@@ -81,6 +93,9 @@ impl<
     /// - bias: [Bias] model implementation
     /// - state_ecef_m: provide initial state as ECEF 3D coordinates,
     /// otherwise we will have to figure them.
+    ///
+    /// ## Output
+    /// - [Solver], or [Error::Config] when the [Config] is not valid.
     pub fn new(
         almanac: Almanac,
         earth_cef: Frame,
@@ -91,7 +106,9 @@ impl<
         environmental_biases: Rc<EB>,
         absolute_time: TIM,
         state_ecef_m: Option<(f64, f64, f64)>,
-    ) -> Self {
+    ) -> Result<Self, Error> {
+        cfg.validate()?;
+
         let initial_ecef_m = match state_ecef_m {
             Some((x0, y0, z0)) => Some(Vector3::new(x0, y0, z0)),
             _ => None,
@@ -107,7 +124,8 @@ impl<
             orbit_source.clone(),
             environmental_biases.clone(),
             spaceborn_biases.clone(),
-        );
+        )
+        .with_rover_antenna();
 
         let base_pool = Pool::allocate(
             almanac,
@@ -119,15 +137,16 @@ impl<
             spaceborn_biases,
         );
 
-        Self {
+        Ok(Self {
             earth_cef,
             navigation,
             rover_pool,
-            base_pool,
+            base_pools: vec![base_pool],
+            attitude_pools: Vec::with_capacity(2),
             absolute_time,
             initial_ecef_m,
             cfg: cfg.clone(),
-        }
+        })
     }
 
     /// Creates a new [Solver] to operate without a priori knowledge
@@ -142,7 +161,7 @@ impl<
         spaceborn_biases: Rc<SB>,
         environmental_biases: Rc<EB>,
         absolute_time: TIM,
-    ) -> Self {
+    ) -> Result<Self, Error> {
         Self::new(
             almanac,
             earth_cef,
@@ -156,6 +175,34 @@ impl<
         )
     }
 
+    /// Provides [Antex] antenna models, to compensate the phase center offsets
+    /// and variations of the satellite antennas, of the rover antenna
+    /// selected by [Config::rx_antenna], and of the reference station antennas
+    /// selected by [RTKBase::antenna_model]. Satellite antennas are only
+    /// compensated for [OrbitSource] states, that should then refer to the
+    /// center of mass (for example, SP3 data).
+    pub fn with_antex(mut self, antex: Antex) -> Self {
+        if let Some(model) = self
+            .cfg
+            .rx_antenna
+            .as_ref()
+            .filter(|model| antex.receiver_antenna(model).is_none())
+        {
+            warn!("rx antenna \"{model}\" is not described by ANTEX");
+        }
+
+        let antex = Rc::new(antex);
+
+        for pool in std::iter::once(&mut self.rover_pool)
+            .chain(self.base_pools.iter_mut())
+            .chain(self.attitude_pools.iter_mut())
+        {
+            pool.set_antex(antex.clone());
+        }
+
+        self
+    }
+
     /// [PVTSolution] solving attempt using PPP technique (no reference).
     /// Unlike RTK resolution attempt, PPP solving will resolve both
     /// the position and the local clock state, but it is
@@ -176,8 +223,7 @@ impl<
         params: UserParameters,
         candidates: &[Candidate],
     ) -> Result<PVTSolution, Error> {
-        let null_rtk = NullRTK {};
-        let solution = self.solve(epoch, params, candidates, &null_rtk, false)?;
+        let solution = self.solve::<NullRTK>(epoch, params, candidates, &[])?;
         Ok(solution)
     }
 
@@ -211,19 +257,52 @@ impl<
         candidates: &[Candidate],
         rtk_base: &RTK,
     ) -> Result<PVTSolution, Error> {
-        self.solve(epoch, params, candidates, rtk_base, true)
+        self.solve(epoch, params, candidates, std::slice::from_ref(rtk_base))
     }
 
-    /// [PVTSolution] solving attempt.
+    /// [PVTSolution] solving attempt using network RTK technique: several remote
+    /// reference sites that implement [RTKBase] are used at once.
+    /// Double differences are formed against each reference site that
+    /// agrees with the rover, then weighted by baseline length (closest sites
+    /// contribute the most). Reference sites that drop out (no observations,
+    /// pivot satellite not in sight..) are simply discarded for this [Epoch],
+    /// the resolution attempt proceeds as long as one of them remains.
+    ///
+    /// The first reference site defines the network reference position, it
+    /// should remain the same for the whole session.
+    ///
+    /// ## Input
+    /// - epoch: [Epoch] of measurement from the rover reported by the rover.
+    /// - params: rover [UserParameters]
+    /// - candidates: rover measurements, wrapped as [Candidate]s.
+    /// - rtk_bases: remote reference sites that implement [RTKBase].
+    ///
+    /// ## Output
+    /// - [PVTSolution].
+    pub fn rtk_network<RTK: RTKBase>(
+        &mut self,
+        epoch: Epoch,
+        params: UserParameters,
+        candidates: &[Candidate],
+        rtk_bases: &[RTK],
+    ) -> Result<PVTSolution, Error> {
+        if rtk_bases.is_empty() {
+            return Err(Error::MissingRemoteRTKObservations);
+        }
+
+        self.solve(epoch, params, candidates, rtk_bases)
+    }
+
+    /// [PVTSolution] solving attempt. RTK is used when at least
+    /// one remote site is proposed.
     fn solve<RTK: RTKBase>(
         &mut self,
         epoch: Epoch,
         params: UserParameters,
         pool: &[Candidate],
-        rtk_base: &RTK,
-        uses_rtk: bool,
+        rtk_bases: &[RTK],
     ) -> Result<PVTSolution, Error> {
-        let rtk_base_name = rtk_base.name();
+        let uses_rtk = !rtk_bases.is_empty();
         let min_required = self.min_sv_required(uses_rtk);
 
         if pool.len() < min_required {
@@ -233,16 +312,76 @@ impl<
 
         self.rover_pool.new_epoch(pool);
 
-        if uses_rtk {
-            let observations = rtk_base.observe(epoch);
+        while self.base_pools.len() < rtk_bases.len() {
+            let base_pool = self.rover_pool.new_like();
+            self.base_pools.push(base_pool);
+        }
+
+        // reference sites that may contribute
+        let mut active_bases = Vec::with_capacity(rtk_bases.len());
+
+        // reference sites position and possible velocity, at rover epoch
+        let mut base_states = Vec::with_capacity(rtk_bases.len());
+
+        for (index, rtk_base) in rtk_bases.iter().enumerate() {
+            let rtk_base_name = rtk_base.name();
+            let mut observations = rtk_base.observe(epoch);
+
+            let base_epoch = observations
+                .iter()
+                .map(|cd| cd.epoch)
+                .max()
+                .unwrap_or(epoch);
+
+            let mut position_ecef_m = rtk_base.reference_position_ecef_m(base_epoch);
+            let velocity_ecef_m_s = rtk_base.reference_velocity_ecef_m_s(base_epoch);
+
+            if let Some((vel_x_m_s, vel_y_m_s, vel_z_m_s)) = velocity_ecef_m_s {
+                // moving base: synchronize to the rover
+                let dt_s = (epoch - base_epoch).to_seconds();
+
+                position_ecef_m.0 += vel_x_m_s * dt_s;
+                position_ecef_m.1 += vel_y_m_s * dt_s;
+                position_ecef_m.2 += vel_z_m_s * dt_s;
+
+                observations.retain_mut(|cd| match cd.doppler_extrapolation_mut(epoch) {
+                    Ok(_) => true,
+                    Err(e) => {
+                        warn!(
+                            "{epoch}({}) - {rtk_base_name} extrapolation error: {e}",
+                            cd.sv
+                        );
+                        false
+                    },
+                });
+
+                debug!("{epoch} - {rtk_base_name} extrapolated by {dt_s:.3}s");
+            }
+
+            base_states.push((position_ecef_m, velocity_ecef_m_s));
+
+            if observations.is_empty() {
+                warn!("{epoch} - remote {rtk_base_name} reference did not observe: dropped");
+                continue;
+            }
+
             info!("{epoch} - using remote {rtk_base_name} reference");
-            self.base_pool.new_epoch(&observations);
+
+            self.base_pools[index]
+                .set_receiver_antenna(rtk_base.arp_enu_m(), rtk_base.antenna_model());
+
+            self.base_pools[index].new_epoch(&observations);
+            active_bases.push(index);
+        }
+
+        if uses_rtk && active_bases.is_empty() {
+            return Err(Error::MissingRemoteRTKObservations);
         }
 
         self.rover_pool.pre_fit("rover", &self.absolute_time);
 
-        if uses_rtk {
-            self.base_pool.pre_fit(&rtk_base_name, &self.absolute_time);
+        for index in active_bases.iter() {
+            self.base_pools[*index].pre_fit(&rtk_bases[*index].name(), &self.absolute_time);
         }
 
         if self.rover_pool.len() < min_required {
@@ -266,8 +405,8 @@ impl<
 
         self.rover_pool.orbital_states_fit("rover");
 
-        if uses_rtk {
-            self.base_pool.orbital_states_fit(&rtk_base_name);
+        for index in active_bases.iter() {
+            self.base_pools[*index].orbital_states_fit(&rtk_bases[*index].name());
         }
 
         // current state
@@ -310,15 +449,44 @@ impl<
 
         let double_differences = if uses_rtk {
             // base post-fit
-            self.base_pool
-                .post_fit(&rtk_base_name, &state)
-                .map_err(|e| {
-                    error!("{epoch} {rtk_base_name} postfit error: {e}");
-                    Error::PostfitPrenav
-                })?;
+            active_bases.retain(|index| {
+                let rtk_base_name = rtk_bases[*index].name();
+
+                match self.base_pools[*index].post_fit(&rtk_base_name, &state) {
+                    Ok(_) => true,
+                    Err(e) => {
+                        error!("{epoch} {rtk_base_name} postfit error: {e}");
+                        false
+                    },
+                }
+            });
+
+            if active_bases.is_empty() {
+                return Err(Error::PostfitPrenav);
+            }
 
             // special RTK post-fit
-            match self.rover_pool.rtk_post_fit(&mut self.base_pool) {
+            let double_differences = if rtk_bases.len() == 1 {
+                self.rover_pool.rtk_post_fit(&mut self.base_pools[0])
+            } else {
+                let (network_reference_ecef_m, _) = base_states[0];
+
+                let mut sites = self
+                    .base_pools
+                    .iter_mut()
+                    .enumerate()
+                    .filter(|(index, _)| active_bases.contains(index))
+                    .map(|(index, pool)| (pool, base_states[index].0))
+                    .collect::<Vec<_>>();
+
+                self.rover_pool.rtk_network_post_fit(
+                    &mut sites,
+                    network_reference_ecef_m,
+                    state.to_position_ecef_m(),
+                )
+            };
+
+            match double_differences {
                 Ok(double_differences) => Some(double_differences),
                 Err(e) => {
                     error!("{epoch} - rtk post-fit error: {e}");
@@ -326,9 +494,17 @@ impl<
                 },
             }
         } else {
+            if self.cfg.method == Method::PPP_TF && self.cfg.solver.ppp_ambiguity_resolution {
+                self.rover_pool.ppp_post_fit("rover");
+            }
+
             None
         };
 
+        // the first reference site defines the network reference position
+        let (base_position_ecef_m, base_velocity_ecef_m_s) =
+            base_states.first().copied().unwrap_or_default();
+
         let pool_size = self.rover_pool.len();
 
         if pool_size < min_required {
@@ -343,7 +519,7 @@ impl<
             self.rover_pool.candidates(),
             pool_size,
             uses_rtk,
-            rtk_base,
+            base_position_ecef_m,
             &self.rover_pool.pivot_position_ecef_m,
             &double_differences,
         ) {
@@ -362,8 +538,24 @@ impl<
             uses_rtk,
             &self.navigation.state,
             &self.navigation.dop,
+            &self.navigation.accuracy,
             &self.navigation.sv,
-        );
+        )
+        .with_integrity(
+            self.navigation.protection_levels,
+            &self.navigation.excluded_sv,
+        )
+        .with_rejected_sv(&self.navigation.rejected_sv);
+
+        let solution = if uses_rtk {
+            solution.with_baseline(
+                base_position_ecef_m,
+                base_velocity_ecef_m_s,
+                self.navigation.state.velocity_m_s.is_some(),
+            )
+        } else {
+            solution.with_inter_system_biases(&self.navigation.inter_system_biases)
+        };
 
         // Special "open loop" option
         if self.cfg.solver.open_loop {
@@ -373,9 +565,251 @@ impl<
         Ok(solution)
     }
 
+    /// [AttitudeSolution] solving attempt, for a rigid body equipped with
+    /// two or three synchronized antennas. The lever arm of each secondary antenna,
+    /// from the primary antenna, is described in [Config] and used as constraint.
+    /// A short baseline is resolved between the primary antenna and each secondary antenna,
+    /// from double differenced phase observations (ambiguities are fixed on a single epoch),
+    /// then the heading, pitch and roll angles are fitted to the lever arms.
+    ///
+    /// The primary antenna position should be resolved beforehand, with [Self::ppp] or [Self::rtk]
+    /// at the same [Epoch]: it is only used to determine the lines of sight,
+    /// so this does not modify the navigation filter.
+    ///
+    /// ## Input
+    /// - epoch: [Epoch] of measurement
+    /// - primary: primary antenna measurements, wrapped as [Candidate]s.
+    /// - secondaries: measurements of each secondary antenna,
+    ///   in the order of the lever arms described in [Config].
+    ///
+    /// ## Output
+    /// - [AttitudeSolution].
+    pub fn attitude(
+        &mut self,
+        epoch: Epoch,
+        primary: &[Candidate],
+        secondaries: &[&[Candidate]],
+    ) -> Result<AttitudeSolution, Error> {
+        let lever_arms_m = self
+            .cfg
+            .attitude
+            .lever_arms_m
+            .iter()
+            .map(|(forward, right, down)| Vector3::new(*forward, *right, *down))
+            .collect::<Vec<_>>();
+
+        if secondaries.is_empty() || secondaries.len() != lever_arms_m.len() {
+            error!("{epoch} - attitude requires one lever arm per secondary antenna");
+            return Err(Error::InvalidLeverArms);
+        }
+
+        if !self.navigation.is_initialized() {
+            return Err(Error::UninitializedFilter);
+        }
+
+        let state = self.navigation.state.with_epoch(epoch);
+        let position_ecef_m = state.to_position_ecef_m();
+
+        let (lat_rad, long_rad) = (
+            state.lat_long_alt_deg_deg_km.0.to_radians(),
+            state.lat_long_alt_deg_deg_km.1.to_radians(),
+        );
+
+        // one pool for the primary antenna, then one per secondary antenna
+        while self.attitude_pools.len() < 1 + secondaries.len() {
+            let pool = self.rover_pool.new_like();
+            self.attitude_pools.push(pool);
+        }
+
+        let (primary_pool, secondary_pools) = self.attitude_pools.split_at_mut(1);
+        let primary_pool = &mut primary_pool[0];
+
+        primary_pool.new_epoch(primary);
+        primary_pool.pre_fit("primary", &self.absolute_time);
+        primary_pool.orbital_states_fit("primary");
+
+        primary_pool.post_fit("primary", &state).map_err(|e| {
+            error!("{epoch} primary postfit error {e}");
+            Error::PostfitPrenav
+        })?;
+
+        let mut baselines = Vec::with_capacity(secondaries.len());
+
+        for (index, ((secondary, lever_arm_m), secondary_pool)) in secondaries
+            .iter()
+            .zip(lever_arms_m.iter())
+            .zip(secondary_pools.iter_mut())
+            .enumerate()
+        {
+            let name = format!("antenna #{}", index + 1);
+
+            secondary_pool.new_epoch(secondary);
+            secondary_pool.pre_fit(&name, &self.absolute_time);
+            secondary_pool.orbital_states_fit(&name);
+
+            secondary_pool.post_fit(&name, &state).map_err(|e| {
+                error!("{epoch} {name} postfit error {e}");
+                Error::PostfitPrenav
+            })?;
+
+            // DD modifies both pools: each secondary antenna uses a copy of the primary antenna
+            let mut primary_pool = primary_pool.copy_candidates();
+
+            // secondary antenna is the rover, so DD resolve primary to secondary baseline
+            let double_differences = secondary_pool.rtk_post_fit(&mut primary_pool)?;
+
+            let pivot_position_ecef_m = secondary_pool
+                .pivot_position_ecef_m
+                .ok_or(Error::SdPivotSatellite)?;
+
+            let (mut g, mut phase_m, mut lambda_m) = (vec![], vec![], vec![]);
+
+            for cd in secondary_pool.candidates() {
+                let phase = double_differences.difference(cd.sv).and_then(|dd| dd.phase);
+
+                if let Some((carrier, dd_m)) = phase {
+                    let (dx, dy, dz) =
+                        cd.rtk_matrix_contribution(position_ecef_m, pivot_position_ecef_m);
+
+                    g.push(Vector3::new(dx, dy, dz));
+                    phase_m.push(dd_m);
+                    lambda_m.push(carrier.wavelength());
+                }
+            }
+
+            let baseline = Baseline::resolve(
+                &g,
+                &phase_m,
+                &lambda_m,
+                lever_arm_m.norm(),
+                self.cfg.attitude.max_length_error_m,
+            )
+            .map_err(|e| {
+                error!("{epoch} {name} baseline error: {e}");
+                e
+            })?;
+
+            let baseline = baseline.to_enu(lat_rad, long_rad);
+
+            debug!("{epoch} - {name} baseline (enu): {}", baseline.vector_m);
+            baselines.push(baseline);
+        }
+
+        let (angles_rad, q) = attitude_fit(&baselines, &lever_arms_m)?;
+        let resolves_roll = q.nrows() > 2;
+
+        let solution = AttitudeSolution {
+            epoch,
+            heading_deg: angles_rad[0].to_degrees(),
+            heading_sigma_deg: q[(0, 0)].sqrt().to_degrees(),
+            pitch_deg: angles_rad[1].to_degrees(),
+            pitch_sigma_deg: q[(1, 1)].sqrt().to_degrees(),
+            roll_deg: if resolves_roll {
+                Some(angles_rad[2].to_degrees())
+            } else {
+                None
+            },
+            roll_sigma_deg: if resolves_roll {
+                Some(q[(2, 2)].sqrt().to_degrees())
+            } else {
+                None
+            },
+            baselines_enu_m: baselines
+                .iter()
+                .map(|baseline| {
+                    (
+                        baseline.vector_m[0],
+                        baseline.vector_m[1],
+                        baseline.vector_m[2],
+                    )
+                })
+                .collect(),
+        };
+
+        info!(
+            "{epoch} - attitude: heading={:.3}° pitch={:.3}°",
+            solution.heading_deg, solution.pitch_deg
+        );
+
+        Ok(solution)
+    }
+
+    /// Forward-backward (Rauch-Tung-Striebel) smoothing of a post-processed session.
+    /// The forward pass is recorded by the navigation filter when
+    /// `SolverOpts::rts_smoothing` is enabled in the [Config]: simply
+    /// process your session, then propose all forward [PVTSolution]s.
+    /// Each solution is then updated with the smoothed position (possible clock, clock drift
+    /// and inter-system biases) and its reduced covariance, which makes the early session solutions usable.
+    ///
+    /// ## Input
+    /// - solutions: forward [PVTSolution]s, resolved by this [Solver].
+    ///
+    /// ## Output
+    /// - smoothed [PVTSolution]s, in the same order.
+    pub fn rts_smoothing(&self, solutions: &[PVTSolution]) -> Result<Vec<PVTSolution>, Error> {
+        let smoothed = self
+            .navigation
+            .rts_smoothing()?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        let mut ret = Vec::with_capacity(solutions.len());
+
+        for solution in solutions.iter() {
+            let mut solution = solution.clone();
+
+            if let Some(estimate) = smoothed.get(&solution.epoch) {
+                let x0_y0_z0_m = Vector3::new(estimate.x[0], estimate.x[1], estimate.x[2]);
+                let apriori = Apriori::from_ecef_m(x0_y0_z0_m, solution.epoch, self.earth_cef);
+
+                let state = State::from_apriori(&apriori).map_err(Error::Physics)?;
+                let accuracy = Accuracy::new(&state, &estimate.p);
+
+                let clock_offset_s = if estimate.x.nrows() > Navigation::clock_index() {
+                    Some(estimate.x[Navigation::clock_index()] / SPEED_OF_LIGHT_M_S)
+                } else {
+                    None
+                };
+
+                solution.smoothing_update_mut(&state, clock_offset_s, &accuracy);
+
+                let drift_index = Navigation::clock_drift_index();
+
+                if drift_index < estimate.x.nrows() {
+                    solution.clock_drift_s_s = estimate.x[drift_index] / SPEED_OF_LIGHT_M_S;
+                    solution.clock_drift_sigma_s_s =
+                        estimate.p[(drift_index, drift_index)].sqrt() / SPEED_OF_LIGHT_M_S;
+                }
+
+                // inter-system bias states follow the clock drift state, in order of registration
+                for (k, isb) in solution.inter_system_biases.iter_mut().enumerate() {
+                    let index = drift_index + 1 + k;
+
+                    if index < estimate.x.nrows() {
+                        isb.bias_s = estimate.x[index] / SPEED_OF_LIGHT_M_S;
+                        isb.sigma_s = estimate.p[(index, index)].sqrt() / SPEED_OF_LIGHT_M_S;
+                    }
+                }
+            } else {
+                warn!("{} - no smoothed estimate", solution.epoch);
+            }
+
+            ret.push(solution);
+        }
+
+        Ok(ret)
+    }
+
     /// Reset this [Solver].
     pub fn reset(&mut self) {
         self.navigation.reset();
+
+        for pool in std::iter::once(&mut self.rover_pool)
+            .chain(self.base_pools.iter_mut())
+            .chain(self.attitude_pools.iter_mut())
+        {
+            pool.reset_tracking();
+        }
     }
 
     /// Returns minimal requirement for current preset
@@ -393,3 +827,190 @@ impl<
         min_sv
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use nalgebra::Vector3;
+
+    use crate::{
+        attitude::body_to_enu,
+        navigation::dop::DilutionOfPrecision,
+        prelude::{
+            ClockProfile, Config, ConfigError, Epoch, Error, Method, Solver, UserParameters,
+            UserProfile,
+        },
+        tests::{
+            CandidatesBuilder, OrbitsData, ROVER_REFERENCE_COORDS_ECEF_M, TestEnvironment,
+            TestSpacebornBiases, almanac, earth_frame, ephemeris::NullEph, init_logger,
+            time::NullTime,
+        },
+    };
+
+    #[test]
+    fn attitude_pool_per_secondary() {
+        init_logger();
+
+        let mut cfg = Config::default().with_navigation_method(Method::CPP);
+        cfg.attitude.lever_arms_m = vec![(1.0, 0.0, 0.0), (0.0, 1.0, 0.0)];
+
+        let params = UserParameters::new(UserProfile::Static, ClockProfile::Quartz);
+
+        let earth_frame = earth_frame();
+        let rtk_base = CandidatesBuilder::build_rtk_base();
+
+        let mut solver = Solver::new(
+            almanac(),
+            earth_frame,
+            cfg,
+            NullEph {}.into(),
+            OrbitsData::new(earth_frame).into(),
+            TestSpacebornBiases::build().into(),
+            TestEnvironment::new().into(),
+            NullTime {},
+            Some(ROVER_REFERENCE_COORDS_ECEF_M),
+        )
+        .unwrap_or_else(|e| panic!("failed to deploy solver: {e}"));
+
+        for epoch_str in ["2020-06-25T00:00:00 GPST", "2020-06-25T00:15:00 GPST"] {
+            let t_gpst = Epoch::from_str(epoch_str).unwrap();
+
+            let primary = CandidatesBuilder::build_rover_at(t_gpst);
+            assert!(!primary.is_empty(), "{epoch_str} - no measurements");
+
+            solver
+                .rtk(t_gpst, params, &primary, &rtk_base)
+                .unwrap_or_else(|e| panic!("{epoch_str} - rtk failed with {e}"));
+
+            // the second secondary antenna misses one satellite
+            let missing_sv = primary[0].sv;
+            let secondary = primary
+                .iter()
+                .filter(|cd| cd.sv != missing_sv)
+                .cloned()
+                .collect::<Vec<_>>();
+
+            // zero length baselines do not match the lever arms:
+            // only the pools management is verified here.
+            let _ = solver.attitude(t_gpst, &primary, &[&primary, &secondary]);
+
+            assert_eq!(solver.attitude_pools.len(), 3, "{epoch_str}");
+
+            let primary_pool = &solver.attitude_pools[0];
+            assert!(!primary_pool.candidates().is_empty(), "{epoch_str}");
+
+            // pivot satellite is only dropped from the copy of the primary antenna
+            assert!(
+                primary_pool.len() > solver.attitude_pools[1].len(),
+                "{epoch_str} - primary antenna modified by the double differences"
+            );
+
+            assert!(
+                solver.attitude_pools[2]
+                    .candidates()
+                    .iter()
+                    .all(|cd| cd.sv != missing_sv),
+                "{epoch_str} - secondary antennas share a pool"
+            );
+
+            // synthetic secondary antennas, displaced by the rotated lever arms
+            let (heading_deg, pitch_deg) = (30.0_f64, 5.0_f64);
+
+            let state = &solver.navigation.state;
+            let position_ecef_m = state.to_position_ecef_m();
+
+            let enu_ecef = DilutionOfPrecision::enu_rotation(
+                state.lat_long_alt_deg_deg_km.0.to_radians(),
+                state.lat_long_alt_deg_deg_km.1.to_radians(),
+            );
+
+            let body_enu = body_to_enu(heading_deg.to_radians(), pitch_deg.to_radians(), 0.0);
+
+            // resolved satellite states, from the primary antenna pool
+            let orbits = solver.attitude_pools[0]
+                .candidates()
+                .iter()
+                .filter_map(|cd| Some((cd.sv, cd.orbit?)))
+                .collect::<Vec<_>>();
+
+            let secondaries = [(1.0, 0.0, 0.0), (0.0, 1.0, 0.0)]
+                .iter()
+                .map(|(forward, right, down)| {
+                    let lever_arm_ecef_m =
+                        enu_ecef * body_enu * Vector3::new(*forward, *right, *down);
+
+                    let antenna_ecef_m = position_ecef_m + lever_arm_ecef_m;
+
+                    primary
+                        .iter()
+                        .filter_map(|cd| {
+                            let (_, orbit) = orbits.iter().find(|(sv, _)| *sv == cd.sv)?;
+                            let pos_vel_m = orbit.to_cartesian_pos_vel() * 1.0E3;
+                            let sat_ecef_m = Vector3::new(pos_vel_m[0], pos_vel_m[1], pos_vel_m[2]);
+
+                            let delta_m = (sat_ecef_m - antenna_ecef_m).norm()
+                                - (sat_ecef_m - position_ecef_m).norm();
+
+                            let mut cd = cd.clone();
+
+                            for obs in cd.observations.iter_mut() {
+                                if let Some(range_m) = obs.pseudo_range_m.as_mut() {
+                                    *range_m += delta_m;
+                                }
+                                if let Some(range_m) = obs.phase_range_m.as_mut() {
+                                    *range_m += delta_m;
+                                }
+                            }
+
+                            Some(cd)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            let solution = solver
+                .attitude(t_gpst, &primary, &[&secondaries[0], &secondaries[1]])
+                .unwrap_or_else(|e| panic!("{epoch_str} - attitude failed with {e}"));
+
+            assert!(
+                (solution.heading_deg - heading_deg).abs() < 1.0E-2,
+                "{epoch_str} - heading error: {}",
+                solution.heading_deg
+            );
+
+            assert!(
+                (solution.pitch_deg - pitch_deg).abs() < 1.0E-2,
+                "{epoch_str} - pitch error: {}",
+                solution.pitch_deg
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_stochastic_model() {
+        init_logger();
+
+        let mut cfg = Config::default().with_navigation_method(Method::SPP);
+        cfg.stochastic.code_sigma_m = 0.0;
+
+        let earth_frame = earth_frame();
+
+        let solver = Solver::new(
+            almanac(),
+            earth_frame,
+            cfg,
+            NullEph {}.into(),
+            OrbitsData::new(earth_frame).into(),
+            TestSpacebornBiases::build().into(),
+            TestEnvironment::new().into(),
+            NullTime {},
+            Some(ROVER_REFERENCE_COORDS_ECEF_M),
+        );
+
+        assert!(matches!(
+            solver,
+            Err(Error::Config(ConfigError::InvalidStochasticModel))
+        ));
+    }
+}
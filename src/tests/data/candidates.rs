@@ -1,10 +1,13 @@
 use std::str::FromStr;
 
+use nalgebra::Vector3;
+
 use crate::{
-    prelude::{Candidate, Carrier, Epoch, Observation, RTKBase, SV},
+    prelude::{Candidate, Carrier, Constellation, Epoch, Observation, Orbit, RTKBase, SV},
     tests::{
-        BASE_REFERENCE_COORDS_ECEF_M,
+        BASE_REFERENCE_COORDS_ECEF_M, ROVER_REFERENCE_COORDS_ECEF_M,
         data::{E01, E03, E05, E09, E13, E15, E24, E31},
+        earth_frame,
     },
 };
 
@@ -35,6 +38,61 @@ impl RTKBase for RTKStation {
 pub struct CandidatesBuilder {}
 
 impl CandidatesBuilder {
+    /// Builds synthetic rover [Candidate]s, seen from the reference position,
+    /// with (constellation, azimuth, elevation) geometry and one bias per pseudo range (m).
+    pub fn build_synthetic_rover_at(
+        epoch: Epoch,
+        geometry: &[(Constellation, f64, f64)],
+        biases_m: &[f64],
+    ) -> Vec<Candidate> {
+        let frame = earth_frame();
+
+        let rx = Vector3::new(
+            ROVER_REFERENCE_COORDS_ECEF_M.0,
+            ROVER_REFERENCE_COORDS_ECEF_M.1,
+            ROVER_REFERENCE_COORDS_ECEF_M.2,
+        );
+
+        // local frame
+        let up = rx.normalize();
+        let east = Vector3::new(-rx[1], rx[0], 0.0).normalize();
+        let north = up.cross(&east);
+
+        geometry
+            .iter()
+            .zip(biases_m.iter())
+            .enumerate()
+            .map(|(prn, ((constellation, azim_deg, elev_deg), bias_m))| {
+                let (azim_rad, elev_rad) = (azim_deg.to_radians(), elev_deg.to_radians());
+
+                let los = east * elev_rad.cos() * azim_rad.sin()
+                    + north * elev_rad.cos() * azim_rad.cos()
+                    + up * elev_rad.sin();
+
+                let sv_m = rx + los * 20_000.0E3;
+                let pr_m = (sv_m - rx).norm() + bias_m;
+
+                let mut cd = Candidate::new(
+                    SV::new(*constellation, prn as u8 + 1),
+                    epoch,
+                    vec![Observation::pseudo_range(Carrier::L1, pr_m, None)],
+                );
+
+                cd.set_orbit(Orbit::from_position(
+                    sv_m[0] / 1.0E3,
+                    sv_m[1] / 1.0E3,
+                    sv_m[2] / 1.0E3,
+                    epoch,
+                    frame,
+                ));
+
+                cd.elevation_deg = Some(*elev_deg);
+                cd.azimuth_deg = Some(*azim_deg);
+                cd
+            })
+            .collect()
+    }
+
     pub fn build_rover_at(epoch: Epoch) -> Vec<Candidate> {
         Self::build_rover_data()
             .into_iter()
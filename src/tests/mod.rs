@@ -24,8 +24,9 @@ mod rtk2cpp;
 mod rtk_cpp;
 mod rtk_ppp;
 mod rtk_spp;
+mod session;
 mod spp;
-mod time;
+pub mod time;
 
 pub use data::*;
 
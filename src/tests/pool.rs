@@ -2,7 +2,10 @@ use crate::{
     constants::SPEED_OF_LIGHT_M_S,
     navigation::{apriori::Apriori, state::State},
     pool::Pool,
-    prelude::{Almanac, Carrier, Config, Epoch, Frame, Method, TimeScale},
+    prelude::{
+        Almanac, Candidate, Carrier, Config, Constellation, Ephemeris, EphemerisSource, Epoch,
+        Frame, Method, Observation, Orbit, OrbitSource, SV, TimeScale,
+    },
     tests::{
         E01, E03, E05, OrbitsData, TestEnvironment, TestSpacebornBiases, data::CandidatesBuilder,
         ephemeris::NullEph, init_logger, time::NullTime,
@@ -11,7 +14,7 @@ use crate::{
 
 use rstest::*;
 
-use std::rc::Rc;
+use std::{cell::RefCell, rc::Rc};
 
 use hifitime::Duration;
 
@@ -764,3 +767,114 @@ fn rtk_ppp_pool_fit() {
     assert!(e01_passed, "E01 test failed");
     assert!(e03_passed, "E03 test failed");
 }
+
+/// Broadcasts a single GPS [Ephemeris] frame.
+struct SingleEph {
+    eph: Ephemeris,
+}
+
+impl EphemerisSource for SingleEph {
+    fn ephemeris_data(&self, _: Epoch, sv: SV) -> Option<Ephemeris> {
+        if sv == self.eph.sv {
+            Some(self.eph)
+        } else {
+            None
+        }
+    }
+}
+
+/// Records the [OrbitSource] requests, answered from the broadcast frame.
+struct RecordingOrbits {
+    eph: Ephemeris,
+    requests: RefCell<Vec<Epoch>>,
+}
+
+impl OrbitSource for RecordingOrbits {
+    fn state_at(&self, epoch: Epoch, _: SV, frame: Frame) -> Option<Orbit> {
+        self.requests.borrow_mut().push(epoch);
+        self.eph.resolve_state(epoch, frame)
+    }
+}
+
+#[test]
+fn orbit_source_chronological_requests() {
+    init_logger();
+
+    let earth_frame = build_earth_frame();
+    let toe = Epoch::from_str("2020-06-25T02:00:00 GPST").unwrap();
+
+    let eph = Ephemeris {
+        sv: SV::new(Constellation::GPS, 1),
+        toe,
+        toc: toe,
+        semi_major_axis_m: 5153.6_f64.powi(2),
+        eccentricity: 0.0111,
+        m0_rad: 0.85,
+        i0_rad: 0.97,
+        idot_rad_s: -2.1E-10,
+        dn_rad: 4.5E-9,
+        omega0_rad: -1.92,
+        omega_rad: 0.73,
+        omega_dot_rad_s: -8.1E-9,
+        cus_cuc_rad: (6.2E-6, -1.3E-6),
+        cis_cic_rad: (1.1E-7, -5.2E-8),
+        crs_crc_m: (-24.5, 283.0),
+        clock_bias_s: -1.2E-4,
+        clock_drift_s_s: -9.0E-12,
+        clock_drift_rate_s_s2: 0.0,
+        tgd_s: 5.1E-9,
+        health: 0,
+    };
+
+    let orbits = Rc::new(RecordingOrbits {
+        eph,
+        requests: RefCell::new(Vec::new()),
+    });
+
+    let mut pool = Pool::allocate(
+        build_almanac(),
+        Config::default().with_navigation_method(Method::SPP),
+        earth_frame,
+        Rc::new(SingleEph { eph }),
+        orbits.clone(),
+        Rc::new(TestEnvironment::new()),
+        Rc::new(TestSpacebornBiases::build()),
+    );
+
+    for k in 0..3 {
+        let t = toe + k as f64 * Duration::from_seconds(30.0);
+
+        let candidates = vec![Candidate::new(
+            eph.sv,
+            t,
+            vec![Observation::pseudo_range(Carrier::L1, 22_000.0E3, None)],
+        )];
+
+        pool.new_epoch(&candidates);
+        pool.pre_fit("rover", &NullTime {});
+        pool.orbital_states_fit("rover");
+
+        assert_eq!(
+            pool.candidates().len(),
+            1,
+            "{t} - pool fit dropped some data!"
+        );
+
+        let cd = &pool.candidates()[0];
+        assert!(cd.precise_orbit);
+
+        // orbit source prevails over the broadcast clock
+        assert_eq!(cd.clock_corr.drift_s_s, 0.0);
+    }
+
+    // one request per epoch, in chronological order
+    let requests = orbits.requests.borrow();
+    assert_eq!(requests.len(), 3);
+
+    for pair in requests.windows(2) {
+        assert!(
+            pair[0] < pair[1],
+            "non chronological requests: {requests:?}"
+        );
+    }
+}
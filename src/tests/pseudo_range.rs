@@ -1,4 +1,7 @@
-use crate::prelude::{Candidate, Carrier, Epoch, Observation, SV};
+use crate::{
+    candidate::combination::triple_if_coefficients,
+    prelude::{Candidate, Carrier, Constellation, Epoch, Observation, SV},
+};
 
 #[test]
 fn best_snr_pseudorange() {
@@ -152,3 +155,81 @@ fn l1_l5_code_if() {
 
     assert_eq!(c_if.value, (f1pow * 64.0 - f2pow * 128.0) / (f1pow - f2pow));
 }
+
+#[test]
+fn triple_frequency_code_if() {
+    for (c_1, c_j, c_k) in [
+        (Carrier::L1, Carrier::L2, Carrier::L5),
+        (Carrier::L1, Carrier::E5a, Carrier::E5b),
+    ] {
+        let (a_1, a_j, a_k) = triple_if_coefficients(c_1, c_j, c_k);
+
+        // geometry preserving
+        assert!((a_1 + a_j + a_k - 1.0).abs() < 1.0E-9);
+
+        // ionosphere free
+        let f_1 = c_1.frequency_hz();
+        let iono = a_1
+            + a_j * (f_1 / c_j.frequency_hz()).powi(2)
+            + a_k * (f_1 / c_k.frequency_hz()).powi(2);
+
+        assert!(iono.abs() < 1.0E-9);
+
+        // less noisy than the dual frequency IF combination
+        let (f1pow, fjpow) = (f_1.powi(2), c_j.frequency_hz().powi(2));
+        let dual_noise = (f1pow.powi(2) + fjpow.powi(2)).sqrt() / (f1pow - fjpow).abs();
+        let triple_noise = (a_1.powi(2) + a_j.powi(2) + a_k.powi(2)).sqrt();
+
+        assert!(triple_noise < dual_noise);
+    }
+
+    let obs = vec![
+        Observation::pseudo_range(Carrier::E5b, 30.0, None),
+        Observation::pseudo_range(Carrier::L1, 10.0, None),
+        Observation::pseudo_range(Carrier::E5a, 20.0, None),
+    ];
+
+    let cd = Candidate::new(SV::new(Constellation::Galileo, 1), Epoch::default(), obs);
+
+    assert!(cd.has_triple_pseudo_range());
+
+    let c_if = cd.code_tf_if_combination().unwrap();
+    assert_eq!(c_if.carriers, (Carrier::L1, Carrier::E5a, Carrier::E5b));
+
+    let (a_1, a_j, a_k) = triple_if_coefficients(Carrier::L1, Carrier::E5a, Carrier::E5b);
+    assert_eq!(c_if.value, a_1 * 10.0 + a_j * 20.0 + a_k * 30.0);
+
+    // E1/E5a/E5a5b: AltBOC is too close to E5a
+    let obs = vec![
+        Observation::pseudo_range(Carrier::L1, 10.0, None),
+        Observation::pseudo_range(Carrier::E5a, 20.0, None),
+        Observation::pseudo_range(Carrier::E5a5b, 30.0, None),
+    ];
+
+    let cd = Candidate::new(SV::new(Constellation::Galileo, 1), Epoch::default(), obs);
+
+    assert!(!cd.has_triple_pseudo_range());
+    assert!(cd.code_tf_if_combination().is_none());
+}
+
+#[test]
+fn constellation_code_if_carriers() {
+    // generic designations of shared frequencies
+    let obs = vec![
+        Observation::pseudo_range(Carrier::L1, 10.0, None),
+        Observation::pseudo_range(Carrier::L5, 20.0, None),
+    ];
+
+    for (constellation, (c_1, c_j)) in [
+        (Constellation::GPS, (Carrier::L1, Carrier::L5)),
+        (Constellation::Galileo, (Carrier::L1, Carrier::E5a)),
+        (Constellation::BeiDou, (Carrier::B1C, Carrier::B2a)),
+    ] {
+        let cd = Candidate::new(SV::new(constellation, 1), Epoch::default(), obs.clone());
+
+        let c_if = cd.code_if_combination().unwrap();
+
+        assert_eq!(c_if.rhs, c_1, "{constellation}");
+        assert_eq!(c_if.lhs, c_j, "{constellation}");
+    }
+}
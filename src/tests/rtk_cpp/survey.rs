@@ -62,7 +62,8 @@ fn static_rtk_cpp() {
         space_biases.into(),
         environment.into(),
         null_time,
-    );
+    )
+    .unwrap_or_else(|e| panic!("failed to deploy solver: {e}"));
 
     for (nth, epoch_str) in [
         "2020-06-25T00:00:00 GPST",
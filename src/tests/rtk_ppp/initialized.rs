@@ -59,7 +59,8 @@ fn static_rtk_ppp() {
         environment.into(),
         null_time,
         Some(ROVER_REFERENCE_COORDS_ECEF_M),
-    );
+    )
+    .unwrap_or_else(|e| panic!("failed to deploy solver: {e}"));
 
     for (nth, epoch_str) in [
         "2020-06-25T00:00:00 GPST",
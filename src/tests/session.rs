@@ -0,0 +1,141 @@
+use rstest::*;
+use std::str::FromStr;
+
+use crate::{
+    prelude::{
+        Almanac, ClockProfile, Config, Epoch, Frame, Method, Solver, UserParameters, UserProfile,
+    },
+    tests::{
+        BASE_REFERENCE_COORDS_ECEF_M, CandidatesBuilder, MAX_SPP_X_ERROR_M, MAX_SPP_Y_ERROR_M,
+        MAX_SPP_Z_ERROR_M, OrbitsData, ROVER_REFERENCE_COORDS_ECEF_M, TestEnvironment,
+        TestSpacebornBiases, ephemeris::NullEph, init_logger, time::NullTime,
+    },
+};
+
+#[fixture]
+fn build_almanac() -> Almanac {
+    use crate::tests::almanac;
+    almanac()
+}
+
+#[fixture]
+fn build_earth_frame() -> Frame {
+    use crate::tests::earth_frame;
+    earth_frame()
+}
+
+#[test]
+fn static_spp_session() {
+    init_logger();
+
+    let cfg = Config::default().with_navigation_method(Method::SPP);
+
+    let default_params = UserParameters::new(UserProfile::Static, ClockProfile::Quartz);
+
+    let almanac = build_almanac();
+    let earth_frame = build_earth_frame();
+
+    let orbits_data = OrbitsData::new(earth_frame);
+
+    let mut solver = Solver::new(
+        almanac,
+        earth_frame,
+        cfg,
+        NullEph {}.into(),
+        orbits_data.into(),
+        TestSpacebornBiases::build().into(),
+        TestEnvironment::new().into(),
+        NullTime {},
+        Some(ROVER_REFERENCE_COORDS_ECEF_M),
+    )
+    .unwrap_or_else(|e| panic!("failed to deploy solver: {e}"));
+
+    let session = [
+        "2020-06-25T00:00:00 GPST",
+        "2020-06-25T00:15:00 GPST",
+        "2020-06-25T00:30:00 GPST",
+        "2020-06-25T00:45:00 GPST",
+    ]
+    .iter()
+    .map(|epoch_str| {
+        let t_gpst = Epoch::from_str(epoch_str).unwrap();
+        (t_gpst, CandidatesBuilder::build_rover_at(t_gpst))
+    });
+
+    let report = solver.ppp_session(default_params, session);
+
+    assert!(
+        report.failures.is_empty(),
+        "session failures: {:?}",
+        report.failures
+    );
+
+    assert_eq!(report.solutions.len(), 4);
+    assert_eq!(report.summary.epochs, 4);
+    assert_eq!(report.summary.solutions, 4);
+    assert_eq!(report.summary.availability_percent(), 100.0);
+
+    let (mean_x_m, mean_y_m, mean_z_m) = report.summary.mean_position_ecef_m.unwrap();
+    let (expected_x_m, expected_y_m, expected_z_m) = ROVER_REFERENCE_COORDS_ECEF_M;
+
+    assert!((mean_x_m - expected_x_m).abs() < MAX_SPP_X_ERROR_M);
+    assert!((mean_y_m - expected_y_m).abs() < MAX_SPP_Y_ERROR_M);
+    assert!((mean_z_m - expected_z_m).abs() < MAX_SPP_Z_ERROR_M);
+}
+
+#[test]
+fn static_rtk_spp_session() {
+    init_logger();
+
+    let cfg = Config::default().with_navigation_method(Method::SPP);
+
+    let almanac = build_almanac();
+    let earth_frame = build_earth_frame();
+
+    let orbits_data = OrbitsData::new(earth_frame);
+
+    let mut solver = Solver::new(
+        almanac,
+        earth_frame,
+        cfg,
+        NullEph {}.into(),
+        orbits_data.into(),
+        TestSpacebornBiases::build().into(),
+        TestEnvironment::new().into(),
+        NullTime {},
+        Some(ROVER_REFERENCE_COORDS_ECEF_M),
+    )
+    .unwrap_or_else(|e| panic!("failed to deploy solver: {e}"));
+
+    let epochs = ["2020-06-25T00:00:00 GPST", "2020-06-25T00:15:00 GPST"]
+        .iter()
+        .map(|epoch_str| Epoch::from_str(epoch_str).unwrap())
+        .collect::<Vec<_>>();
+
+    let rover = epochs
+        .iter()
+        .map(|t| (*t, CandidatesBuilder::build_rover_at(*t)));
+
+    // base stream is missing the last epoch
+    let base = epochs
+        .iter()
+        .take(1)
+        .map(|t| (*t, CandidatesBuilder::build_base_at(*t)));
+
+    let report = solver.rtk_session(
+        UserParameters::default(),
+        rover,
+        base,
+        BASE_REFERENCE_COORDS_ECEF_M,
+    );
+
+    assert_eq!(report.solutions.len(), 1);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].0, epochs[1]);
+
+    assert_eq!(report.summary.epochs, 2);
+    assert_eq!(report.summary.duration(), epochs[1] - epochs[0]);
+    assert_eq!(report.summary.availability_percent(), 50.0);
+
+    assert!(report.solutions[0].baseline_m.is_some());
+}
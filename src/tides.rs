@@ -1,173 +1,391 @@
+//! Solid Earth tides (IERS Conventions 2010, chapter 7.1.1)
 use crate::{
-    prelude::{Almanac, Epoch, Error, Vector3},
-    constants::{
-        EARTH_EQUATORIAL_RADIUS_M,
-        LOVE_DEGREE2,
-        SHIDA_DEGREE2,
-        EARTH_GRAVITATION_MU_M3_S2,
-        SUN_GRAVITATION_MU_M3_S2,
-        MOON_GRAVITATION_MU_M3_S2,
-    },
+    constants::{EARTH_EQUATORIAL_RADIUS_M, LOVE_DEGREE2, SHIDA_DEGREE2},
+    prelude::{Epoch, Vector3},
 };
 
-use anise::{
-    constants::{
-        frames::{EARTH_J2000, MOON_J2000, SUN_J2000},
-    },
-};
+use std::f64::consts::PI;
+
+/// Sun to Earth mass ratio
+const SUN_EARTH_MASS_RATIO: f64 = 332_946.048_2;
+
+/// Moon to Earth mass ratio
+const MOON_EARTH_MASS_RATIO: f64 = 0.012_300_037_1;
+
+/// Love degree^3 term
+const LOVE_DEGREE3: f64 = 0.292;
+
+/// Shida degree^3 term
+const SHIDA_DEGREE3: f64 = 0.015;
+
+/// Frequency dependent corrections of the diurnal band (IERS Table 7.3a):
+/// multipliers of (s, h, p, N', ps), then the in-phase and out-of-phase radial
+/// corrections, and the in-phase and out-of-phase transverse corrections (mm).
+const DIURNAL_CORRECTIONS: [[f64; 9]; 31] = [
+    [-3.0, 0.0, 2.0, 0.0, 0.0, -0.01, 0.0, 0.0, 0.0],
+    [-3.0, 2.0, 0.0, 0.0, 0.0, -0.01, 0.0, 0.0, 0.0],
+    [-2.0, 0.0, 1.0, -1.0, 0.0, -0.02, 0.0, 0.0, 0.0],
+    [-2.0, 0.0, 1.0, 0.0, 0.0, -0.08, 0.0, -0.01, 0.01],
+    [-2.0, 2.0, -1.0, 0.0, 0.0, -0.02, 0.0, 0.0, 0.0],
+    [-1.0, 0.0, 0.0, -1.0, 0.0, -0.10, 0.0, 0.0, 0.0],
+    [-1.0, 0.0, 0.0, 0.0, 0.0, -0.51, 0.0, -0.02, 0.03],
+    [-1.0, 2.0, 0.0, 0.0, 0.0, 0.01, 0.0, 0.0, 0.0],
+    [0.0, -2.0, 1.0, 0.0, 0.0, 0.01, 0.0, 0.0, 0.0],
+    [0.0, 0.0, -1.0, 0.0, 0.0, 0.02, 0.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0, 0.0, 0.06, 0.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 1.0, 0.0, 0.01, 0.0, 0.0, 0.0],
+    [0.0, 2.0, -1.0, 0.0, 0.0, 0.01, 0.0, 0.0, 0.0],
+    [1.0, -3.0, 0.0, 0.0, 1.0, -0.06, 0.0, 0.0, 0.0],
+    [1.0, -2.0, 0.0, -1.0, 0.0, 0.01, 0.0, 0.0, 0.0],
+    [1.0, -2.0, 0.0, 0.0, 0.0, -1.23, -0.07, 0.06, 0.01],
+    [1.0, -1.0, 0.0, 0.0, -1.0, 0.02, 0.0, 0.0, 0.0],
+    [1.0, -1.0, 0.0, 0.0, 1.0, 0.04, 0.0, 0.0, 0.0],
+    [1.0, 0.0, 0.0, -1.0, 0.0, -0.22, 0.01, 0.01, 0.0],
+    [1.0, 0.0, 0.0, 0.0, 0.0, 12.00, -0.80, -0.67, -0.03],
+    [1.0, 0.0, 0.0, 1.0, 0.0, 1.73, -0.12, -0.10, 0.0],
+    [1.0, 0.0, 0.0, 2.0, 0.0, -0.04, 0.0, 0.0, 0.0],
+    [1.0, 1.0, 0.0, 0.0, -1.0, -0.50, -0.01, 0.03, 0.0],
+    [1.0, 1.0, 0.0, 0.0, 1.0, 0.01, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 1.0, -1.0, -0.01, 0.0, 0.0, 0.0],
+    [1.0, 2.0, -2.0, 0.0, 0.0, -0.01, 0.0, 0.0, 0.0],
+    [1.0, 2.0, 0.0, 0.0, 0.0, -0.11, 0.01, 0.01, 0.0],
+    [2.0, -2.0, 1.0, 0.0, 0.0, -0.01, 0.0, 0.0, 0.0],
+    [2.0, 0.0, -1.0, 0.0, 0.0, -0.02, 0.0, 0.0, 0.0],
+    [3.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+];
+
+/// Frequency dependent corrections of the long period band (IERS Table 7.3b):
+/// multipliers of (s, h, p, N', ps), then the in-phase radial and transverse
+/// corrections, and the out-of-phase radial and transverse corrections (mm).
+const LONG_PERIOD_CORRECTIONS: [[f64; 9]; 5] = [
+    [0.0, 0.0, 0.0, 1.0, 0.0, 0.47, 0.23, 0.16, 0.07],
+    [0.0, 2.0, 0.0, 0.0, 0.0, -0.20, -0.12, -0.11, -0.05],
+    [1.0, 0.0, -1.0, 0.0, 0.0, -0.11, -0.08, -0.09, -0.04],
+    [2.0, 0.0, 0.0, 0.0, 0.0, -0.13, -0.11, -0.15, -0.07],
+    [2.0, 0.0, 0.0, 1.0, 0.0, -0.05, -0.05, -0.06, -0.03],
+];
+
+/// Fundamental arguments (in degrees) of the frequency dependent corrections
+struct Arguments {
+    /// Mean lunar time, without its Greenwich hour angle
+    tau: f64,
+    /// (s, h, p, N', ps) Doodson arguments
+    doodson: [f64; 5],
+}
+
+impl Arguments {
+    fn new(epoch: Epoch) -> Self {
+        // Julian centuries (TT) and hours of day (UTC)
+        let t = (epoch.to_jde_tt_days() - 2_451_545.0) / 36525.0;
+        let fhr = epoch.to_mjd_utc_days().fract() * 24.0;
+
+        let s = 218.31664563 + (481267.88194 + (-0.0014663889 + 0.00000185139 * t) * t) * t;
+
+        let tau =
+            280.4606184 + (36000.7700536 + (0.00038793 - 0.0000000258 * t) * t) * t + fhr * 15.0
+                - s;
+
+        let pr = (1.396971278 + (0.000308889 + (0.000000021 + 0.000000007 * t) * t) * t) * t;
+
+        let h = 280.46645
+            + (36000.7697489 + (0.00030322222 + (0.000000020 - 0.00000000654 * t) * t) * t) * t;
+
+        let p = 83.35324312
+            + (4069.01363525 + (-0.01032172222 + (-0.0000124991 + 0.00000005263 * t) * t) * t) * t;
+
+        let zns = 234.95544499
+            + (1934.13626197 + (-0.00207561111 + (-0.00000213944 + 0.00000001650 * t) * t) * t) * t;
+
+        let ps = 282.93734098
+            + (1.71945766667 + (0.00045688889 + (-0.00000001778 - 0.00000000334 * t) * t) * t) * t;
+
+        Self {
+            tau,
+            doodson: [s + pr, h, p, zns, ps],
+        }
+    }
+
+    /// Returns the argument (in radians) of this constituent
+    fn theta_rad(&self, tau: f64, multipliers: &[f64]) -> f64 {
+        let theta = self
+            .doodson
+            .iter()
+            .zip(multipliers)
+            .fold(tau * self.tau, |theta, (arg, k)| theta + k * arg);
+
+        theta.to_radians()
+    }
+}
+
+/// Geocentric latitude and longitude of a station
+struct Site {
+    sin_phi: f64,
+    cos_phi: f64,
+    sin_lambda: f64,
+    cos_lambda: f64,
+}
+
+impl Site {
+    fn new(position_ecef_m: Vector3<f64>) -> Self {
+        let r_m = position_ecef_m.norm();
+        let rho_m = (position_ecef_m[0].powi(2) + position_ecef_m[1].powi(2)).sqrt();
+
+        Self {
+            sin_phi: position_ecef_m[2] / r_m,
+            cos_phi: rho_m / r_m,
+            sin_lambda: position_ecef_m[1] / rho_m,
+            cos_lambda: position_ecef_m[0] / rho_m,
+        }
+    }
+
+    /// Converts a local (radial, east, north) displacement to ECEF
+    fn to_ecef(&self, dr: f64, de: f64, dn: f64) -> Vector3<f64> {
+        Vector3::new(
+            dr * self.cos_lambda * self.cos_phi
+                - de * self.sin_lambda
+                - dn * self.sin_phi * self.cos_lambda,
+            dr * self.sin_lambda * self.cos_phi + de * self.cos_lambda
+                - dn * self.sin_phi * self.sin_lambda,
+            dr * self.sin_phi + dn * self.cos_phi,
+        )
+    }
+}
+
+/// Tide generating body
+struct Body {
+    /// Position (ECEF, m)
+    r_m: Vector3<f64>,
+
+    /// Degree 2 scaling factor
+    fac2: f64,
+}
+
+impl Body {
+    fn new(r_m: Vector3<f64>, mass_ratio: f64) -> Self {
+        let fac2 = mass_ratio
+            * EARTH_EQUATORIAL_RADIUS_M
+            * (EARTH_EQUATORIAL_RADIUS_M / r_m.norm()).powi(3);
+        Self { r_m, fac2 }
+    }
+
+    /// Returns fac2 * X_i * X_j / |X|²
+    fn term(&self, i: usize, j: usize) -> f64 {
+        self.fac2 * self.r_m[i] * self.r_m[j] / self.r_m.norm_squared()
+    }
+}
+
+/// Returns the frequency dependent corrections (step 2) of the diurnal
+/// and long period bands, as a (radial, east, north) displacement (m).
+fn frequency_dependent_displacement_m(site: &Site, epoch: Epoch) -> (f64, f64, f64) {
+    let args = Arguments::new(epoch);
+
+    let (sin_phi, cos_phi) = (site.sin_phi, site.cos_phi);
+    let lambda = site.sin_lambda.atan2(site.cos_lambda);
+
+    let (mut dr, mut de, mut dn) = (0.0, 0.0, 0.0);
+
+    for row in DIURNAL_CORRECTIONS.iter() {
+        let (sin_theta, cos_theta) = (args.theta_rad(1.0, &row[..5]) + lambda).sin_cos();
+
+        dr += 2.0 * sin_phi * cos_phi * (row[5] * sin_theta + row[6] * cos_theta);
+        dn += (cos_phi.powi(2) - sin_phi.powi(2)) * (row[7] * sin_theta + row[8] * cos_theta);
+        de += sin_phi * (row[7] * cos_theta - row[8] * sin_theta);
+    }
 
-/// Calculates local crust displacement [Vector3] (all axis),
-/// at specified position position expressed in meters (ECEF).
+    for row in LONG_PERIOD_CORRECTIONS.iter() {
+        let (sin_theta, cos_theta) = args.theta_rad(0.0, &row[..5]).sin_cos();
+
+        dr += (3.0 * sin_phi.powi(2) - 1.0) / 2.0 * (row[5] * cos_theta + row[7] * sin_theta);
+        dn += 2.0 * cos_phi * sin_phi * (row[6] * cos_theta + row[8] * sin_theta);
+    }
+
+    (dr * 1.0E-3, de * 1.0E-3, dn * 1.0E-3)
+}
+
+/// Returns the displacement (ECEF, m) of a station due to the solid Earth tides,
+/// in the conventional tide free system. This is the in-phase degree 2 (latitude dependent)
+/// and degree 3 displacement, completed by the out-of-phase and the l(1) corrections
+/// of the degree 2 diurnal and semi-diurnal bands, and by the frequency dependent
+/// corrections (step 2) of the diurnal and long period bands.
 ///
 /// ## Input
-/// - epoch: [Epoch] of displacement calculation
-/// - almanac: [Almanac]
-/// - position_ecef_m: position as [Vector3] ECEF (meters)
-/// - latitude_rad: latitude (in radians) of location
-/// at which the displacement is to be calculated.
-pub fn solid_body_tidal_displacement(
+/// - epoch: [Epoch] of the displacement
+/// - position_ecef_m: station position (ECEF, m)
+/// - sun_ecef_m: Sun position (ECEF, m)
+/// - moon_ecef_m: Moon position (ECEF, m)
+/// - mean_tide: removes the permanent tide from the displacement, for positions
+///   expressed in the mean tide system.
+pub(crate) fn solid_tides_displacement_m(
     epoch: Epoch,
-    almanac: &Almanac,
     position_ecef_m: Vector3<f64>,
-    latitude_rad: f64,
-) -> Result<Vector3<f64>, Error> {
-
-     let r_earth_moon_unit = almanac
-        .unit_vector(
-            EARTH_J2000,
-            MOON_J2000,
-            epoch,
-            None,
-        )
-        .map_err(|e| Error::Almanac(e))?
-        * 1.0E3;
-    
-     let r_earth_sun_unit = almanac
-        .unit_vector(
-            SUN_J2000,
-            EARTH_J2000,
-            epoch,
-            None,
-        )
-        .map_err(|e| Error::Almanac(e))?
-        * 1.0E3;
-    
-    let position_ecef_mag = position_ecef_m.magnitude();
-    let position_ecef_unit = position_ecef_m / position_ecef_mag;
-
-    let r_earth_moon_unit_mag = r_earth_moon_unit.magnitude();
-    let r_earth_sun_unit_mag = r_earth_moon_unit.magnitude();
-
-    let moon_num = MOON_GRAVITATION_MU_M3_S2
-        * EARTH_EQUATORIAL_RADIUS_M.powi(4);
-    
-    let sun_num = SUN_GRAVITATION_MU_M3_S2
-        * EARTH_EQUATORIAL_RADIUS_M.powi(4);
-   
-    let moon_denom = EARTH_GRAVITATION_MU_M3_S2 *
-        r_earth_moon_unit_mag.powi(3);
-
-    let sun_denom = EARTH_GRAVITATION_MU_M3_S2
-        * r_earth_sun_unit_mag.powi(3);
-    
-    let r_earth_moon_unit_dot = r_earth_moon_unit
-        .dot(&position_ecef_unit);
-
-    let r_earth_sun_unit_dot = r_earth_sun_unit
-        .dot(&position_ecef_unit);
-
-    let h2 = LOVE_DEGREE2
-        - 0.0006
-        * ((3.0 * latitude_rad.sin().powi(2) - 1.0)  /2.0);
-
-    let l2 = SHIDA_DEGREE2
-        + 0.0002 
-        * ((3.0 * latitude_rad.sin().powi(2) - 1.0) / 2.0);
-
-    let moon_lhs = h2
-        * position_ecef_unit
-        * (3.0 / 2.0 * r_earth_moon_unit_dot.powi(2) - 0.5);
-
-    let sun_lhs = h2
-        * position_ecef_unit
-        * (3.0 / 2.0 * r_earth_sun_unit_dot.powi(2) - 0.5);
-
-    let moon_rhs = 3.0 * l2
-        * r_earth_moon_unit_dot
-        * (r_earth_moon_unit - r_earth_moon_unit_dot * position_ecef_unit);
-    
-    let sun_rhs = 3.0 * l2
-        * r_earth_sun_unit_dot
-        * (r_earth_sun_unit - r_earth_sun_unit_dot * position_ecef_unit);
-    
-    let mut dr = moon_num / moon_denom * (moon_lhs + moon_rhs);
-    
-    // dr += sun_num / sun_denom * (sun_lhs + sun_rhs);
-
-    Ok(dr)
+    sun_ecef_m: Vector3<f64>,
+    moon_ecef_m: Vector3<f64>,
+    mean_tide: bool,
+) -> Vector3<f64> {
+    let site = Site::new(position_ecef_m);
+    let r_sta_m = position_ecef_m.norm();
+    let e_sta = position_ecef_m / r_sta_m;
+
+    let p2_phi = 1.0 - 1.5 * site.cos_phi.powi(2);
+    let h2 = LOVE_DEGREE2 - 0.0006 * p2_phi;
+    let l2 = SHIDA_DEGREE2 + 0.0002 * p2_phi;
+
+    let (h3, l3) = (LOVE_DEGREE3, SHIDA_DEGREE3);
+
+    let bodies = [
+        Body::new(sun_ecef_m, SUN_EARTH_MASS_RATIO),
+        Body::new(moon_ecef_m, MOON_EARTH_MASS_RATIO),
+    ];
+
+    let mut dx = Vector3::zeros();
+
+    // in-phase degree 2 and 3
+    for body in bodies.iter() {
+        let r_m = body.r_m.norm();
+        let e_body = body.r_m / r_m;
+        let scalar = e_sta.dot(&e_body);
+
+        let p2 = 3.0 * (h2 / 2.0 - l2) * scalar.powi(2) - h2 / 2.0;
+        let x2 = 3.0 * l2 * scalar;
+
+        let p3 = 2.5 * (h3 - 3.0 * l3) * scalar.powi(3) + 1.5 * (l3 - h3) * scalar;
+        let x3 = 1.5 * l3 * (5.0 * scalar.powi(2) - 1.0);
+
+        let fac3 = body.fac2 * EARTH_EQUATORIAL_RADIUS_M / r_m;
+
+        dx += body.fac2 * (e_body * x2 + e_sta * p2) + fac3 * (e_body * x3 + e_sta * p3);
+    }
+
+    let (sin_phi, cos_phi) = (site.sin_phi, site.cos_phi);
+    let (sin_la, cos_la) = (site.sin_lambda, site.cos_lambda);
+
+    let cos_2phi = cos_phi.powi(2) - sin_phi.powi(2);
+    let cos_2la = cos_la.powi(2) - sin_la.powi(2);
+    let sin_2la = 2.0 * cos_la * sin_la;
+
+    let (mut dr, mut de, mut dn) = (0.0, 0.0, 0.0);
+
+    for body in bodies.iter() {
+        // diurnal band
+        let diurnal_sin = body.term(2, 0) * sin_la - body.term(2, 1) * cos_la;
+        let diurnal_cos = body.term(2, 0) * cos_la + body.term(2, 1) * sin_la;
+
+        // semi-diurnal band
+        let semi_sin =
+            (body.term(0, 0) - body.term(1, 1)) * sin_2la - 2.0 * body.term(0, 1) * cos_2la;
+        let semi_cos =
+            (body.term(0, 0) - body.term(1, 1)) * cos_2la + 2.0 * body.term(0, 1) * sin_2la;
+
+        // out-of-phase corrections (anelasticity)
+        dr += -3.0 * -0.0025 * sin_phi * cos_phi * diurnal_sin;
+        dn += -3.0 * -0.0007 * cos_2phi * diurnal_sin;
+        de += -3.0 * -0.0007 * sin_phi * diurnal_cos;
+
+        dr += -0.75 * -0.0022 * cos_phi.powi(2) * semi_sin;
+        dn += 1.5 * -0.0007 * sin_phi * cos_phi * semi_sin;
+        de += -1.5 * -0.0007 * cos_phi * semi_cos;
+
+        // l(1) corrections
+        dn += 3.0 * -0.0012 * sin_phi.powi(2) * diurnal_cos;
+        de += 3.0 * 0.0012 * sin_phi * cos_2phi * diurnal_sin;
+
+        dn += 3.0 * -0.0024 / 2.0 * sin_phi * cos_phi * semi_cos;
+        de += 3.0 * -0.0024 / 2.0 * sin_phi.powi(2) * cos_phi * semi_sin;
+    }
+
+    dx += site.to_ecef(dr, de, dn);
+
+    let (dr, de, dn) = frequency_dependent_displacement_m(&site, epoch);
+    dx += site.to_ecef(dr, de, dn);
+
+    if mean_tide {
+        let scale = -(5.0 / 4.0 / PI).sqrt() * 0.3146;
+
+        let dr = scale * LOVE_DEGREE2 * (1.5 * sin_phi.powi(2) - 0.5);
+        let dn = scale * SHIDA_DEGREE2 * 3.0 * cos_phi * sin_phi;
+
+        dx -= site.to_ecef(dr, 0.0, dn);
+    }
+
+    dx
 }
 
 #[cfg(test)]
 mod test {
-    use super::*;
-
-    use crate::{
-        tides::solid_body_tidal_displacement,
-        prelude::{
-            Vector3,
-            Almanac,
-            Epoch,
-            Duration,
-        },
-        tests::init_logger,
-    };
-
-    use hifitime::{
-        TimeSeries,
-        Unit,
-    };
-
-    use log::info;
-
-    use rstest::*;
-
-    #[fixture]
-    fn build_almanac() -> Almanac {
-        use crate::tests::almanac;
-        almanac()
-    }
+    use super::solid_tides_displacement_m;
+    use crate::prelude::{Epoch, Vector3};
+
+    /// Astronomical unit (m)
+    const AU_M: f64 = 1.495_978_707E11;
+
+    /// Mean Earth-Moon distance (m)
+    const EARTH_MOON_M: f64 = 3.844E8;
 
     #[test]
-    fn solid_crust_tidal_deformation() {
-        init_logger();
+    fn solid_tides_displacement() {
+        let t = Epoch::from_gregorian_utc_at_midnight(2020, 6, 25);
+        let position_ecef_m = Vector3::new(6_378_137.0, 0.0, 0.0);
 
-        let position_ecef_m = Vector3::<f64>::new(
-            4696989.6880,
-            723994.1970,
-            4239678.3040,
+        // Sun and Moon at the zenith: maximal uplift
+        let zenith = solid_tides_displacement_m(
+            t,
+            position_ecef_m,
+            Vector3::new(AU_M, 0.0, 0.0),
+            Vector3::new(EARTH_MOON_M, 0.0, 0.0),
+            false,
         );
 
-        let latitude_rad = 48.855338_f64.to_radians();
-    
-        let almanac = build_almanac();
+        assert!(zenith[0] > 0.25 && zenith[0] < 0.5, "dr={}", zenith[0]);
+        assert!(zenith[1].abs() < 1.0E-3 && zenith[2].abs() < 1.0E-3);
 
-        let t0 = Epoch::from_gregorian_utc_at_midnight(2000, 1, 1);
+        // Sun and Moon at the horizon: depression
+        let horizon = solid_tides_displacement_m(
+            t,
+            position_ecef_m,
+            Vector3::new(0.0, AU_M, 0.0),
+            Vector3::new(0.0, EARTH_MOON_M, 0.0),
+            false,
+        );
 
-        let t1 = t0 + 24.0 * Unit::Day;
-        let dt = Duration::from_seconds(30.0 * 60.0);
+        assert!(horizon[0] < -0.1 && horizon[0] > -0.3, "dr={}", horizon[0]);
 
-        for epoch in TimeSeries::inclusive(t0, t0, dt).into_iter() { 
-            
-            let dr = solid_body_tidal_displacement(
-                epoch,
-                &almanac,
-                position_ecef_m,
-                latitude_rad,
-            ).unwrap_or_else(|e| {
-                panic!("failed to calculate tidal displacement");
-            });
+        // daily signal: ~30cm and more
+        assert!(zenith[0] - horizon[0] > 0.3);
 
-            info!("tidal displacement dr={}m", dr);
-        }
+        // permanent tide, on the equator: -0.1206 * P2(0) = +6cm (radial)
+        let mean_tide = solid_tides_displacement_m(
+            t,
+            position_ecef_m,
+            Vector3::new(AU_M, 0.0, 0.0),
+            Vector3::new(EARTH_MOON_M, 0.0, 0.0),
+            true,
+        );
+
+        let permanent_m = zenith[0] - mean_tide[0];
+        assert!((permanent_m - 0.0603).abs() < 1.0E-3, "{permanent_m}");
+    }
+
+    #[test]
+    fn dehanttideinel_reference() {
+        // test case of the IERS DEHANTTIDEINEL routine
+        let t = Epoch::from_gregorian_utc_at_midnight(2009, 4, 13);
+
+        let dx = solid_tides_displacement_m(
+            t,
+            Vector3::new(4_075_578.385, 931_852.890, 4_801_570.154),
+            Vector3::new(137_859_926_952.015, 54_228_127_881.435, 23_509_422_341.696),
+            Vector3::new(
+                -179_996_231.920342,
+                -312_468_450.131567,
+                -169_288_918.592160,
+            ),
+            false,
+        );
+
+        let expected = Vector3::new(0.077_004_203_571, 0.063_040_563_218, 0.055_165_681_526);
+
+        let err_m = (dx - expected).norm();
+        assert!(err_m < 1.0E-6, "{dx} ({err_m}m)");
     }
 }
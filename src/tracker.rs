@@ -1,79 +1,347 @@
+//! Phase arcs tracking and cycle slip detection
 use std::collections::HashMap;
-use crate::prelude::{SV, Epoch};
 
-#[derive(Debug, Clone)]
-struct InnerData {
-    t: Epoch,
-    gf: f64,
-    mw: f64,
+use log::debug;
+
+use crate::prelude::{Candidate, Carrier, CycleSlipOpts, Epoch, SV};
+
+/// Tracking of a single phase signal
+#[derive(Debug, Clone, Copy)]
+struct SignalTracker {
+    /// [Carrier] signal
+    carrier: Carrier,
+
+    /// Latest (ambiguous) phase range, in meters
+    phase_range_m: f64,
+
+    /// Latest Doppler shift, in Hz
+    doppler_hz: Option<f64>,
 }
 
+/// Continuous phase arc of a satellite
 #[derive(Debug, Clone)]
-struct SVTracker {
-    buffer: Vec<InnerData>,
+struct PhaseArc {
+    /// Latest update [Epoch]
+    epoch: Epoch,
+
+    /// Latest geometry free phase combination, in meters
+    gf_m: Option<f64>,
+
+    /// Melbourne-Wübbena average over the arc, in wide lane cycles
+    mw_mean_cycles: f64,
+
+    /// Number of averaged Melbourne-Wübbena values
+    mw_count: usize,
+
+    /// Tracked signals
+    signals: Vec<SignalTracker>,
 }
 
-impl SVTracker {
-    fn new() -> Self {
+impl PhaseArc {
+    fn new(epoch: Epoch) -> Self {
         Self {
-            buffer: Vec::with_capacity(64),
+            epoch,
+            gf_m: None,
+            mw_mean_cycles: 0.0,
+            mw_count: 0,
+            signals: Vec::with_capacity(4),
         }
     }
-    fn mean(&self) -> InnerData {
-        let (mut gf, mut mw) = (0.0_f64, 0.0_f64);
-        for i in 0..self.buffer.len() {
-            gf += self.buffer[i].gf;
-            mw += self.buffer[i].mw;
-        }
-        InnerData {
-            t: self.buffer[self.buffer.len()-1].t,
-            gf: gf / self.buffer.len() as f64,
-            mw: mw / self.buffer.len() as f64,
-        }
+
+    /// Starts a new arc: the phase ambiguities are lost.
+    fn reset(&mut self) {
+        self.gf_m = None;
+        self.mw_count = 0;
+        self.mw_mean_cycles = 0.0;
+        self.signals.clear();
     }
-    fn stddev(&self) -> InnerData {
-        let mean = self.mean();
-        let (mut gf, mut mw) = (0.0_f64, 0.0_f64);
-        for i in 0..self.buffer.len() {
-            gf += (self.buffer[i].gf - mean.gf).powi(2);
-            mw += (self.buffer[i].mw - mean.mw).powi(2);
+
+    /// Returns true if this [Candidate] is not consistent with the current arc.
+    ///
+    /// ## Input
+    /// - opts: [CycleSlipOpts]
+    /// - cd: [Candidate]
+    /// - dt_s: elapsed time since the latest update, in seconds
+    /// - gf_m: geometry free phase combination, in meters
+    /// - mw_cycles: Melbourne-Wübbena combination, in wide lane cycles
+    fn cycle_slip(
+        &self,
+        opts: &CycleSlipOpts,
+        cd: &Candidate,
+        dt_s: f64,
+        gf_m: Option<f64>,
+        mw_cycles: Option<f64>,
+    ) -> bool {
+        if let (Some(gf_m), Some(past_gf_m)) = (gf_m, self.gf_m) {
+            if (gf_m - past_gf_m).abs() > opts.gf_threshold_m {
+                debug!(
+                    "{}({}) - gf jump: {:.3}m",
+                    cd.epoch,
+                    cd.sv,
+                    gf_m - past_gf_m
+                );
+                return true;
+            }
         }
-        InnerData {
-            t: self.buffer[self.buffer.len()-1].t,
-            gf: gf / self.buffer.len() as f64,
-            mw: mw / self.buffer.len() as f64,
+
+        if let Some(mw_cycles) = mw_cycles {
+            if self.mw_count > 0
+                && (mw_cycles - self.mw_mean_cycles).abs() > opts.mw_threshold_cycles
+            {
+                debug!(
+                    "{}({}) - mw jump: {:.3} cycles",
+                    cd.epoch,
+                    cd.sv,
+                    mw_cycles - self.mw_mean_cycles
+                );
+                return true;
+            }
+        }
+
+        for ob in cd.observations.iter() {
+            let (phase_range_m, doppler_hz) = match (ob.phase_range_m, ob.doppler) {
+                (Some(phase_range_m), Some(doppler_hz)) => (phase_range_m, doppler_hz),
+                _ => continue,
+            };
+
+            let past = self
+                .signals
+                .iter()
+                .filter(|signal| signal.carrier == ob.carrier)
+                .find_map(|signal| Some((signal.phase_range_m, signal.doppler_hz?)));
+
+            if let Some((past_phase_range_m, past_doppler_hz)) = past {
+                let lambda = ob.carrier.wavelength();
+
+                // a positive Doppler shift means a decreasing range
+                let predicted_m = -lambda * (doppler_hz + past_doppler_hz) / 2.0 * dt_s;
+                let deviation_cycles = (phase_range_m - past_phase_range_m - predicted_m) / lambda;
+
+                if deviation_cycles.abs() > opts.doppler_threshold_cycles {
+                    debug!(
+                        "{}({}) - {} doppler deviation: {:.3} cycles",
+                        cd.epoch, cd.sv, ob.carrier, deviation_cycles
+                    );
+                    return true;
+                }
+            }
         }
+
+        false
     }
-    fn update(&mut self, v: InnerData, win_len: usize) {
-        self.buffer.push(v);
-        if self.buffer.len() > win_len {
-            self.buffer.remove(0);
+
+    /// Updates this arc with this [Candidate].
+    fn update(&mut self, cd: &Candidate, gf_m: Option<f64>, mw_cycles: Option<f64>) {
+        self.epoch = cd.epoch;
+        self.gf_m = gf_m;
+
+        if let Some(mw_cycles) = mw_cycles {
+            self.mw_count += 1;
+            self.mw_mean_cycles += (mw_cycles - self.mw_mean_cycles) / self.mw_count as f64;
         }
+
+        self.signals = cd
+            .observations
+            .iter()
+            .filter_map(|ob| {
+                Some(SignalTracker {
+                    carrier: ob.carrier,
+                    phase_range_m: ob.phase_range_m?,
+                    doppler_hz: ob.doppler,
+                })
+            })
+            .collect();
     }
 }
 
-/// Signal tracker
-#[derive(Debug, Clone)]
-pub struct Tracker {
-    win_len: usize,
-    sv_tracker: HashMap<SV, SVTracker>,
+/// Phase arcs [Tracker]. Each satellite is tracked continuously, to detect cycle slips
+/// by combining geometry free jumps, Melbourne-Wübbena jumps and
+/// Doppler predicted phase variations. The phase observations (and their possible
+/// ambiguities) are not modified: the navigation filter estimates one float ambiguity
+/// per arc, which restarts on each detected cycle slip.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Tracker {
+    /// [PhaseArc] of each satellite
+    arcs: HashMap<SV, PhaseArc>,
 }
 
 impl Tracker {
-    pub fn new(win_len: usize) -> Self {
-        Self {
-            win_len,
-            sv_tracker: HashMap::with_capacity(32),
+    /// Resets all phase arcs
+    pub fn reset(&mut self) {
+        self.arcs.clear();
+    }
+
+    /// Tracks the phase observations of this [Candidate].
+    /// Returns true when a cycle slip was detected, which started a new arc.
+    pub fn track(&mut self, opts: &CycleSlipOpts, cd: &Candidate) -> bool {
+        if !cd.observations.iter().any(|ob| ob.phase_range_m.is_some()) {
+            self.arcs.remove(&cd.sv);
+            return false;
+        }
+
+        let gf_m = cd.phase_gf_combination().map(|comb| comb.value);
+        let mw_cycles = cd.mw_combination().map(|comb| comb.value / comb.lambda);
+
+        let arc = self
+            .arcs
+            .entry(cd.sv)
+            .or_insert_with(|| PhaseArc::new(cd.epoch));
+
+        let mut cycle_slip = false;
+
+        if !arc.signals.is_empty() {
+            let dt_s = (cd.epoch - arc.epoch).to_seconds();
+
+            if dt_s <= 0.0 || dt_s > opts.max_gap_s {
+                debug!("{}({}) - data gap: new phase arc", cd.epoch, cd.sv);
+                arc.reset();
+            } else if arc.cycle_slip(opts, cd, dt_s, gf_m, mw_cycles) {
+                cycle_slip = true;
+                arc.reset();
+            }
+        }
+
+        arc.update(cd, gf_m, mw_cycles);
+        cycle_slip
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Tracker;
+
+    use crate::prelude::{
+        Candidate, Carrier, Constellation, CycleSlipOpts, Duration, Epoch, Observation, SV,
+    };
+
+    use std::str::FromStr;
+
+    /// Range rate, in m.s⁻¹
+    const RANGE_RATE_M_S: f64 = 500.0;
+
+    /// Dual frequency [Candidate], with possible phase jumps (in cycles)
+    fn candidate(t0: Epoch, dt_s: f64, slips: (f64, f64), doppler: bool) -> Candidate {
+        let range_m = 20.0E6 + RANGE_RATE_M_S * dt_s;
+
+        let observations = [
+            (Carrier::L1, 1000.3, slips.0),
+            (Carrier::L2, -2000.7, slips.1),
+        ]
+        .iter()
+        .map(|(carrier, offset_m, slip)| {
+            let lambda = carrier.wavelength();
+
+            let ob = Observation::pseudo_range(*carrier, range_m, None)
+                .with_ambiguous_phase_range_m(range_m + offset_m + slip * lambda);
+
+            if doppler {
+                ob.with_doppler(-RANGE_RATE_M_S / lambda)
+            } else {
+                ob
+            }
+        })
+        .collect();
+
+        Candidate::new(
+            SV::new(Constellation::GPS, 1),
+            t0 + Duration::from_seconds(dt_s),
+            observations,
+        )
+    }
+
+    #[test]
+    fn cycle_slip_detection() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let opts = CycleSlipOpts::default();
+
+        let mut tracker = Tracker::default();
+
+        // continuous arc
+        for i in 0..5 {
+            let cd = candidate(t0, i as f64 * 30.0, (0.0, 0.0), true);
+            assert!(!tracker.track(&opts, &cd), "false alarm @{i}");
+        }
+
+        // single cycle slip: gf jump
+        let cd = candidate(t0, 150.0, (1.0, 0.0), true);
+        assert!(tracker.track(&opts, &cd));
+
+        let cd = candidate(t0, 180.0, (1.0, 0.0), true);
+        assert!(!tracker.track(&opts, &cd));
+
+        // geometry free cycle slip (77 λ1 = 60 λ2): mw jump
+        let cd = candidate(t0, 210.0, (78.0, 60.0), false);
+        assert!(tracker.track(&opts, &cd));
+
+        // data gap: new arc, not a cycle slip
+        let cd = candidate(t0, 600.0, (0.0, 0.0), true);
+        assert!(!tracker.track(&opts, &cd));
+
+        // equal cycle slips, not seen by the mw, seen by the gf and the doppler
+        let cd = candidate(t0, 630.0, (10.0, 10.0), true);
+        assert!(tracker.track(&opts, &cd));
+    }
+
+    #[test]
+    fn doppler_cycle_slip_detection() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let opts = CycleSlipOpts::default();
+
+        let mut tracker = Tracker::default();
+
+        // single frequency: only the doppler may detect
+        let single = |dt_s: f64, slip: f64| {
+            let mut cd = candidate(t0, dt_s, (slip, 0.0), true);
+            cd.observations.truncate(1);
+            cd
+        };
+
+        for i in 0..3 {
+            let cd = single(i as f64 * 30.0, 0.0);
+            assert!(!tracker.track(&opts, &cd));
         }
+
+        let cd = single(90.0, 3.0);
+        assert!(!tracker.track(&opts, &cd), "below threshold");
+
+        let cd = single(120.0, 10.0);
+        assert!(tracker.track(&opts, &cd));
     }
-    pub fn update(&mut self, sv: SV, t: Epoch, gf: f64, mw: f64) {
-        if let Some(tracker) = self.sv_tracker.get_mut(&sv) {
-            let new = InnerData { t, gf, mw };
-            tracker.update(new, self.win_len);
-        } else {
-            let mut tracker = SVTracker::new();
-            tracker.update(InnerData { t, gf, mw }, self.win_len);
-            self.sv_tracker.insert(sv, tracker);
+
+    #[test]
+    fn user_ambiguities() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let opts = CycleSlipOpts::default();
+
+        let mut tracker = Tracker::default();
+
+        let known = |dt_s: f64, slips: (f64, f64)| {
+            let mut cd = candidate(t0, dt_s, slips, true);
+
+            for ob in cd.observations.iter_mut() {
+                *ob = ob.with_ambiguity(12.0);
+            }
+
+            cd
+        };
+
+        // user ambiguities are preserved, even when a new arc starts
+        for (dt_s, slips, cycle_slip) in [
+            (0.0, (0.0, 0.0), false),
+            (30.0, (0.0, 0.0), false),
+            (60.0, (1.0, 0.0), true),
+        ] {
+            let cd = known(dt_s, slips);
+            assert_eq!(tracker.track(&opts, &cd), cycle_slip);
+
+            for ob in cd.observations.iter() {
+                assert_eq!(ob.ambiguity, Some(12.0));
+            }
         }
+
+        // raw phase observations remain ambiguous
+        let cd = candidate(t0, 0.0, (0.0, 0.0), true);
+        assert!(!Tracker::default().track(&opts, &cd));
+        assert!(cd.observations.iter().all(|ob| ob.ambiguity.is_none()));
     }
 }
@@ -23,6 +23,20 @@ const fn default_clock_drift_psd() -> f64 {
     ClockProfile::Quartz.drift_psd()
 }
 
+/// Default inter-system bias PSD
+const fn default_isb_psd() -> f64 {
+    1.0E-7
+}
+
+/// Initial inter-system bias uncertainty (1σ, in meters)
+const ISB_INITIAL_SIGMA_M: f64 = 100.0;
+
+/// Initial clock drift uncertainty (1σ, in s.s⁻¹)
+const CLOCK_DRIFT_INITIAL_SIGMA_S_S: f64 = 1.0E-5;
+
+/// Initial float ambiguity uncertainty (1σ, in meters)
+const AMBIGUITY_INITIAL_SIGMA_M: f64 = 60.0;
+
 /// [UserProfile] can be used to generate a set of [UserParameters] easily.
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -176,14 +190,20 @@ pub struct UserParameters {
     /// Clock drift PSD
     #[cfg_attr(feature = "serde", serde(default = "default_clock_drift_psd"))]
     pub clock_drift_psd: f64,
+
+    /// Inter-system bias PSD in m².s⁻¹. Inter-system biases are modeled
+    /// as random walks: they are hardware dependent and vary very slowly.
+    /// Only used when [SolverOpts::inter_system_biases](crate::cfg::SolverOpts) is enabled.
+    #[cfg_attr(feature = "serde", serde(default = "default_isb_psd"))]
+    pub isb_psd: f64,
 }
 
 impl std::fmt::Display for UserParameters {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
-            "a={}m².s⁻¹, offset={}s, drift={}s.s⁻¹",
-            self.accel_psd, self.clock_psd, self.clock_drift_psd
+            "a={}m².s⁻¹, offset={}s, drift={}s.s⁻¹, isb={}m².s⁻¹",
+            self.accel_psd, self.clock_psd, self.clock_drift_psd, self.isb_psd
         )
     }
 }
@@ -194,6 +214,7 @@ impl Default for UserParameters {
             accel_psd: default_accel_psd(),
             clock_psd: default_clock_psd(),
             clock_drift_psd: default_clock_drift_psd(),
+            isb_psd: default_isb_psd(),
         }
     }
 }
@@ -205,10 +226,11 @@ impl UserParameters {
             accel_psd: user_profile.psd(),
             clock_psd: clock_profile.bias_psd(),
             clock_drift_psd: clock_profile.drift_psd(),
+            isb_psd: default_isb_psd(),
         }
     }
 
-    /// Parametrization of the coveriance [DMatrix]
+    /// Parametrization of the covariance [DMatrix]
     pub(crate) fn q_matrix(&self, q_mat: &mut DMatrix<f64>, dt: Duration, ndf: usize) {
         assert!(ndf > 2, "Q cov: minimal dimension");
 
@@ -232,4 +254,67 @@ impl UserParameters {
             }
         }
     }
+
+    /// Parametrization of the clock drift state (m.s⁻¹) covariance, and of its correlation
+    /// with the clock offset state (m), over this sampling interval (two states clock model).
+    /// On first iteration, this is the initial drift uncertainty.
+    pub(crate) fn q_clock_drift_matrix(
+        &self,
+        q_mat: &mut DMatrix<f64>,
+        dt: Duration,
+        clock_index: usize,
+        drift_index: usize,
+    ) {
+        let dt_s = dt.to_seconds();
+        let c2 = SPEED_OF_LIGHT_M_S.powi(2);
+
+        let (q_drift, q_cross) = if dt == Duration::ZERO {
+            (
+                (CLOCK_DRIFT_INITIAL_SIGMA_S_S * SPEED_OF_LIGHT_M_S).powi(2),
+                0.0,
+            )
+        } else {
+            (
+                c2 * self.clock_drift_psd * dt_s,
+                c2 * self.clock_drift_psd * dt_s.powi(2) / 2.0,
+            )
+        };
+
+        q_mat[(drift_index, drift_index)] = q_drift;
+        q_mat[(clock_index, drift_index)] = q_cross;
+        q_mat[(drift_index, clock_index)] = q_cross;
+    }
+
+    /// Process noise of each inter-system bias state (m²), over this sampling interval.
+    /// On first iteration, this is the initial uncertainty.
+    pub(crate) fn q_isb(&self, dt: Duration) -> f64 {
+        if dt == Duration::ZERO {
+            ISB_INITIAL_SIGMA_M.powi(2)
+        } else {
+            self.isb_psd * dt.to_seconds()
+        }
+    }
+
+    /// Process noise of each float ambiguity state (m²), over this sampling interval:
+    /// ambiguities are constant over their phase arc.
+    /// On first iteration, this is the initial uncertainty.
+    pub(crate) fn q_ambiguity(&self, dt: Duration) -> f64 {
+        if dt == Duration::ZERO {
+            AMBIGUITY_INITIAL_SIGMA_M.powi(2)
+        } else {
+            0.0
+        }
+    }
+
+    /// Parametrization of the velocity filter covariance [DMatrix],
+    /// where the state is the ECEF velocity and the clock drift (m.s⁻¹).
+    pub(crate) fn q_velocity_matrix(&self, q_mat: &mut DMatrix<f64>, dt: Duration) {
+        let dt_s = dt.to_seconds();
+
+        for i in 0..=2 {
+            q_mat[(i, i)] = self.accel_psd * dt_s;
+        }
+
+        q_mat[(3, 3)] = SPEED_OF_LIGHT_M_S.powi(2) * self.clock_drift_psd * dt_s;
+    }
 }